@@ -0,0 +1,62 @@
+//! Compares the time and allocations of parsing owned and borrowed torrent files.
+//!
+//! Run with `cargo bench --bench parse`.
+
+use hightorrent::{TorrentFile, TorrentFileRef};
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+/// Counts allocations made through the global allocator.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const ITERATIONS: u32 = 1000;
+
+fn bench<F: Fn()>(name: &str, f: F) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let elapsed = start.elapsed() / ITERATIONS;
+    let allocations = (ALLOCATIONS.load(Ordering::Relaxed) - allocations) / ITERATIONS as usize;
+    let bytes = (ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes) / ITERATIONS as usize;
+    println!("{name:<40} {elapsed:>12.2?} {allocations:>8} allocs {bytes:>10} bytes");
+}
+
+fn main() {
+    for fixture in [
+        "tests/bittorrent-v1-emma-goldman.torrent",
+        "tests/bittorrent-v2-test.torrent",
+        "tests/bittorrent-v2-hybrid-test.torrent",
+    ] {
+        let slice = std::fs::read(fixture).unwrap();
+        println!("{fixture} ({} bytes)", slice.len());
+        bench("  TorrentFile::from_slice", || {
+            TorrentFile::from_slice(&slice).unwrap();
+        });
+        bench("  TorrentFileRef::from_slice", || {
+            TorrentFileRef::from_slice(&slice).unwrap();
+        });
+    }
+}
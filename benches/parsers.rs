@@ -0,0 +1,63 @@
+//! Measures the parsers called when filtering and importing torrent lists.
+//!
+//! Run with `cargo bench --bench parsers`, and with `--features faster-hex` to compare the SIMD
+//! validation of infohashes.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use hightorrent::{InfoHash, MagnetLink, MagnetParseOptions, TorrentFile};
+
+fn infohash(c: &mut Criterion) {
+    let mut group = c.benchmark_group("InfoHash::new");
+    for (name, hash) in [
+        ("v1", "C811B41641A09D192B8ED81B14064FFF55D85CE3"),
+        (
+            "v2",
+            "caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e",
+        ),
+        (
+            "hybrid",
+            "631a31dd0a46257d5078c0dee4e66e26f73e42ac:d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb",
+        ),
+        ("base32", "ZAI3ILCAUGOA3GFYQKOBOBU55AMXMDG6"),
+        ("invalid", "c811b41641a09d192b8ed81b14064fff55d85cez"),
+    ] {
+        group.bench_function(name, |b| b.iter(|| InfoHash::new(black_box(hash))));
+    }
+    group.finish();
+}
+
+fn magnet(c: &mut Criterion) {
+    let options = MagnetParseOptions {
+        require_name: false,
+        ..Default::default()
+    };
+    let mut group = c.benchmark_group("MagnetLink::new");
+    for fixture in [
+        "tests/bittorrent-v1-emma-goldman.magnet",
+        "tests/bittorrent-v2-hybrid-test.magnet",
+    ] {
+        let magnet = std::fs::read_to_string(fixture).unwrap();
+        group.bench_function(fixture, |b| {
+            b.iter(|| MagnetLink::new_with(black_box(magnet.trim()), options).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn torrent_file(c: &mut Criterion) {
+    let mut group = c.benchmark_group("TorrentFile::from_slice");
+    for fixture in [
+        "tests/bittorrent-v1-emma-goldman.torrent",
+        "tests/bittorrent-v2-test.torrent",
+        "tests/bittorrent-v2-hybrid-test.torrent",
+    ] {
+        let slice = std::fs::read(fixture).unwrap();
+        group.bench_function(fixture, |b| {
+            b.iter(|| TorrentFile::from_slice(black_box(&slice)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, infohash, magnet, torrent_file);
+criterion_main!(benches);
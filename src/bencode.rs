@@ -0,0 +1,230 @@
+//! Minimal bencode scanner, locating values in a byte slice without decoding them.
+
+/// An open list or dict, while scanning a bencoded value.
+enum Container {
+    List,
+    /// A dict, tracking whether a key or a value is expected next
+    Dict {
+        expects_key: bool,
+    },
+}
+
+/// Returns the offset right after the bencoded value starting at `start`.
+///
+/// Nested lists and dicts are scanned iteratively, so deeply nested data cannot overflow
+/// the stack. Dict keys must be byte strings, but their order is not checked.
+pub(crate) fn value_end(s: &[u8], start: usize) -> Result<usize, String> {
+    scan(s, start, &mut 0)
+}
+
+/// Returns the maximum nesting depth of lists and dicts in the bencoded value at the start
+/// of `s`, without recursion. A value which is not a list or dict has a depth of 0.
+///
+/// Malformed data is scanned up to the first error, so that the depth of truncated data is
+/// known before handing it to a recursive decoder.
+pub(crate) fn nesting_depth(s: &[u8]) -> usize {
+    let mut depth = 0;
+    let _ = scan(s, 0, &mut depth);
+    depth
+}
+
+/// Scans the bencoded value starting at `start`, returning the offset right after it. The
+/// maximum nesting depth reached is stored in `depth`, even when scanning fails.
+fn scan(s: &[u8], start: usize, depth: &mut usize) -> Result<usize, String> {
+    let mut containers: Vec<Container> = Vec::new();
+    let mut pos = start;
+
+    loop {
+        let c = *s.get(pos).ok_or("unexpected end of data")?;
+        let expects_key = matches!(
+            containers.last(),
+            Some(Container::Dict { expects_key: true })
+        );
+
+        match c {
+            b'e' if !containers.is_empty() => {
+                if matches!(
+                    containers.pop(),
+                    Some(Container::Dict { expects_key: false })
+                ) {
+                    return Err(format!("dict key without value at offset {pos}"));
+                }
+                pos += 1;
+            }
+            _ if expects_key && !c.is_ascii_digit() => {
+                return Err(format!("dict key at offset {pos} is not a byte string"));
+            }
+            b'i' => {
+                let end = find(s, pos + 1, b'e')?;
+                parse_int(&s[pos + 1..end])
+                    .ok_or_else(|| format!("invalid integer at offset {pos}"))?;
+                pos = end + 1;
+            }
+            b'0'..=b'9' => {
+                let colon = find(s, pos, b':')?;
+                let len: usize = std::str::from_utf8(&s[pos..colon])
+                    .ok()
+                    .and_then(|len| len.parse().ok())
+                    .ok_or_else(|| format!("invalid string length at offset {pos}"))?;
+                pos = (colon + 1)
+                    .checked_add(len)
+                    .filter(|end| *end <= s.len())
+                    .ok_or("unexpected end of data")?;
+            }
+            b'l' => {
+                containers.push(Container::List);
+                *depth = (*depth).max(containers.len());
+                pos += 1;
+                continue;
+            }
+            b'd' => {
+                containers.push(Container::Dict { expects_key: true });
+                *depth = (*depth).max(containers.len());
+                pos += 1;
+                continue;
+            }
+            _ => return Err(format!("unexpected byte at offset {pos}")),
+        }
+
+        // A complete value was read: after a key comes a value, and after a value a key
+        match containers.last_mut() {
+            None => return Ok(pos),
+            Some(Container::Dict { expects_key }) => *expects_key = !*expects_key,
+            Some(Container::List) => {}
+        }
+    }
+}
+
+fn find(s: &[u8], from: usize, byte: u8) -> Result<usize, String> {
+    s.get(from..)
+        .and_then(|rest| rest.iter().position(|b| *b == byte))
+        .map(|i| from + i)
+        .ok_or_else(|| "unexpected end of data".to_string())
+}
+
+fn parse_int(digits: &[u8]) -> Option<i64> {
+    std::str::from_utf8(digits).ok()?.parse().ok()
+}
+
+/// A bencoded dict, as a borrowed slice of its entries.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct DictRef<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> DictRef<'a> {
+    /// Reads the dict at the start of `s`, returning it with the offset right after it.
+    pub(crate) fn parse(s: &'a [u8]) -> Result<(DictRef<'a>, usize), String> {
+        if s.first() != Some(&b'd') {
+            return Err("not a bencoded dict".to_string());
+        }
+        let end = value_end(s, 0)?;
+        Ok((DictRef { bytes: &s[..end] }, end))
+    }
+
+    /// Returns the raw bytes of the dict, including its delimiters.
+    pub(crate) fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns the raw bencoded value for a given key.
+    pub(crate) fn get(&self, key: &[u8]) -> Option<&'a [u8]> {
+        self.entries().find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+
+    /// Returns the dict for a given key.
+    pub(crate) fn get_dict(&self, key: &[u8]) -> Option<DictRef<'a>> {
+        self.get(key)
+            .filter(|v| v.first() == Some(&b'd'))
+            .map(|bytes| DictRef { bytes })
+    }
+
+    /// Returns the byte string for a given key.
+    pub(crate) fn get_bytes(&self, key: &[u8]) -> Option<&'a [u8]> {
+        self.get(key).and_then(as_bytes)
+    }
+
+    /// Returns the integer for a given key.
+    pub(crate) fn get_int(&self, key: &[u8]) -> Option<i64> {
+        self.get(key)
+            .filter(|v| v.first() == Some(&b'i'))
+            .and_then(|v| parse_int(&v[1..v.len() - 1]))
+    }
+
+    /// Iterates over the keys and raw values of the dict. The dict was validated when
+    /// parsed, so iterating cannot fail.
+    pub(crate) fn entries(&self) -> impl Iterator<Item = (&'a [u8], &'a [u8])> {
+        let bytes = self.bytes;
+        let mut pos = 1;
+        std::iter::from_fn(move || {
+            if bytes.get(pos) == Some(&b'e') || pos >= bytes.len() {
+                return None;
+            }
+            let key_end = value_end(bytes, pos).ok()?;
+            let value_end = value_end(bytes, key_end).ok()?;
+            let key = as_bytes(&bytes[pos..key_end])?;
+            let value = &bytes[key_end..value_end];
+            pos = value_end;
+            Some((key, value))
+        })
+    }
+}
+
+/// Returns the content of a raw bencoded byte string.
+pub(crate) fn as_bytes(value: &[u8]) -> Option<&[u8]> {
+    let colon = value.iter().position(|b| *b == b':')?;
+    value.get(colon + 1..)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_value_end() {
+        assert_eq!(value_end(b"i42e", 0), Ok(4));
+        assert_eq!(value_end(b"4:spamrest", 0), Ok(6));
+        assert_eq!(value_end(b"l4:spami-3ee", 0), Ok(12));
+        assert_eq!(value_end(b"d3:keyld1:ai0eeee", 0), Ok(17));
+        assert_eq!(value_end(b"xxi1e", 2), Ok(5));
+
+        assert!(value_end(b"i42", 0).is_err());
+        assert!(value_end(b"ifooe", 0).is_err());
+        assert!(value_end(b"10:short", 0).is_err());
+        assert!(value_end(b"di1ei2ee", 0).is_err());
+        assert!(value_end(b"d3:keye", 0).is_err());
+        assert!(value_end(b"x", 0).is_err());
+    }
+
+    #[test]
+    fn deep_nesting_does_not_overflow() {
+        let depth = 1_000_000;
+        let mut data = vec![b'l'; depth];
+        data.extend(std::iter::repeat(b'e').take(depth));
+        assert_eq!(value_end(&data, 0), Ok(data.len()));
+        assert_eq!(nesting_depth(&data), depth);
+        assert_eq!(nesting_depth(&data[..depth]), depth);
+    }
+
+    #[test]
+    fn computes_nesting_depth() {
+        assert_eq!(nesting_depth(b"i42e"), 0);
+        assert_eq!(nesting_depth(b"le"), 1);
+        assert_eq!(nesting_depth(b"d1:ald2:ll4:lllleee1:bi1ee"), 3);
+        assert_eq!(nesting_depth(b"lllxlllll"), 3);
+    }
+
+    #[test]
+    fn reads_dict() {
+        let data = b"d3:bari-7e3:food1:xi1ee4:name4:testeEXTRA";
+        let (dict, end) = DictRef::parse(data).unwrap();
+        assert_eq!(end, data.len() - 5);
+        assert_eq!(dict.get_int(b"bar"), Some(-7));
+        assert_eq!(dict.get_bytes(b"name"), Some(&b"test"[..]));
+        assert_eq!(dict.get_dict(b"foo").unwrap().get_int(b"x"), Some(1));
+        assert!(dict.get_dict(b"name").is_none());
+        assert!(dict.get(b"missing").is_none());
+        assert_eq!(dict.entries().count(), 3);
+        assert!(DictRef::parse(b"l1:ae").is_err());
+    }
+}
@@ -0,0 +1,201 @@
+use std::str::FromStr;
+
+/// Error occurred during parsing a [`Category`](crate::category::Category).
+#[derive(Clone, Debug, PartialEq)]
+pub enum CategoryError {
+    Empty,
+    InvalidSegment { segment: String },
+}
+
+impl std::fmt::Display for CategoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CategoryError::Empty => write!(f, "Empty category"),
+            CategoryError::InvalidSegment { segment } => {
+                write!(f, "Invalid category segment: {segment:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CategoryError {}
+
+/// A hierarchical torrent category, such as `linux/distros/debian`.
+///
+/// Unlike tags, a torrent has at most one category, which may be nested in parent categories
+/// separated by `/`, as done by qBittorrent subcategories. Categories are normalized when
+/// parsed, so that clients which format them differently agree on the same category:
+///
+/// - whitespace around each segment is trimmed
+/// - empty segments (leading, trailing or repeated `/`) are dropped
+///
+/// Parsing fails when no segment is left, or when a segment is `.` or `..`, because clients
+/// may use categories as download subdirectories. Letter case is preserved, like in qBittorrent.
+///
+/// ```
+/// use hightorrent::Category;
+///
+/// let category = Category::new(" linux//distros/debian/ ").unwrap();
+/// assert_eq!(category.as_str(), "linux/distros/debian");
+/// assert_eq!(category.name(), "debian");
+/// assert!(Category::new("linux").unwrap().contains(&category));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct Category(String);
+
+impl Category {
+    /// The separator between a parent category and its subcategories.
+    pub const SEPARATOR: char = '/';
+
+    /// Parses and normalizes a category.
+    pub fn new(category: &str) -> Result<Category, CategoryError> {
+        let segments: Vec<&str> = category
+            .split(Category::SEPARATOR)
+            .map(str::trim)
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        if segments.is_empty() {
+            return Err(CategoryError::Empty);
+        }
+        if let Some(segment) = segments.iter().find(|s| **s == "." || **s == "..") {
+            return Err(CategoryError::InvalidSegment {
+                segment: segment.to_string(),
+            });
+        }
+
+        Ok(Category(segments.join("/")))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the segments of the category, from the top-level category to the subcategory.
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.0.split(Category::SEPARATOR)
+    }
+
+    /// Returns the last segment of the category, without its parents.
+    pub fn name(&self) -> &str {
+        self.segments().last().unwrap()
+    }
+
+    /// Returns the parent category, or None for a top-level category.
+    pub fn parent(&self) -> Option<Category> {
+        self.0
+            .rsplit_once(Category::SEPARATOR)
+            .map(|(parent, _)| Category(parent.to_string()))
+    }
+
+    /// Returns whether `other` is the same category, or one of its subcategories.
+    pub fn contains(&self, other: &Category) -> bool {
+        match other.0.strip_prefix(self.as_str()) {
+            Some(rest) => rest.is_empty() || rest.starts_with(Category::SEPARATOR),
+            None => false,
+        }
+    }
+}
+
+impl std::fmt::Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A Category is described by its normalized string.
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Category {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Category".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "minLength": 1,
+        })
+    }
+}
+
+impl FromStr for Category {
+    type Err = CategoryError;
+
+    fn from_str(s: &str) -> Result<Category, Self::Err> {
+        Category::new(s)
+    }
+}
+
+impl TryFrom<&str> for Category {
+    type Error = CategoryError;
+
+    fn try_from(s: &str) -> Result<Category, Self::Error> {
+        Category::new(s)
+    }
+}
+
+impl TryFrom<String> for Category {
+    type Error = CategoryError;
+
+    fn try_from(s: String) -> Result<Category, Self::Error> {
+        Category::new(&s)
+    }
+}
+
+impl From<Category> for String {
+    fn from(category: Category) -> String {
+        category.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_categories() {
+        assert_eq!(Category::new("books").unwrap().as_str(), "books");
+        assert_eq!(
+            Category::new("/Linux / Distros//").unwrap().as_str(),
+            "Linux/Distros"
+        );
+        assert_eq!(Category::new(" / "), Err(CategoryError::Empty));
+        assert_eq!(
+            Category::new("linux/../etc"),
+            Err(CategoryError::InvalidSegment {
+                segment: "..".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn navigates_hierarchy() {
+        let category = Category::new("linux/distros/debian").unwrap();
+        assert_eq!(
+            category.segments().collect::<Vec<_>>(),
+            ["linux", "distros", "debian"]
+        );
+        assert_eq!(category.name(), "debian");
+        assert_eq!(
+            category.parent(),
+            Some(Category::new("linux/distros").unwrap())
+        );
+        assert_eq!(Category::new("linux").unwrap().parent(), None);
+
+        assert!(category.contains(&category));
+        assert!(Category::new("linux").unwrap().contains(&category));
+        assert!(!Category::new("lin").unwrap().contains(&category));
+        assert!(!category.contains(&Category::new("linux").unwrap()));
+    }
+
+    #[test]
+    fn serde_validates_category() {
+        let category: Category = serde_json::from_str("\"linux//distros\"").unwrap();
+        assert_eq!(category.as_str(), "linux/distros");
+        assert_eq!(
+            serde_json::to_string(&category).unwrap(),
+            "\"linux/distros\""
+        );
+        assert!(serde_json::from_str::<Category>("\"..\"").is_err());
+    }
+}
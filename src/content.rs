@@ -0,0 +1,656 @@
+use bt_bencode::Value as BencodeValue;
+
+use std::path::PathBuf;
+
+use crate::torrent::format_size;
+use crate::torrent_file::bencode_i64;
+
+/// Names which cannot be used as file names on Windows, with or without extension.
+const WINDOWS_RESERVED: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Error occurred during parsing a [`SafePath`](crate::content::SafePath).
+#[derive(Clone, Debug, PartialEq)]
+pub enum SafePathError {
+    Empty,
+    InvalidComponent { component: String },
+    ReservedName { component: String },
+}
+
+impl std::fmt::Display for SafePathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SafePathError::Empty => write!(f, "Empty path"),
+            SafePathError::InvalidComponent { component } => {
+                write!(f, "Invalid path component: {component:?}")
+            }
+            SafePathError::ReservedName { component } => {
+                write!(f, "Reserved file name on Windows: {component}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SafePathError {}
+
+/// A relative file path which is safe to join to a download directory.
+///
+/// Paths in torrents are lists of components chosen by the torrent author, and could be used to
+/// write files outside of the download directory. A SafePath guarantees that none of its
+/// components:
+///
+/// - is empty, `.` or `..`
+/// - contains a `/` or `\` separator, or a NUL character
+/// - is a reserved file name on Windows (eg. `CON` or `aux.txt`)
+///
+/// A SafePath can be built by rejecting unsafe paths with [`SafePath::new`](crate::content::SafePath::new),
+/// or by sanitizing them with [`SafePath::sanitize`](crate::content::SafePath::sanitize).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(try_from = "Vec<String>", into = "Vec<String>")]
+pub struct SafePath(Vec<String>);
+
+impl SafePath {
+    /// Builds a SafePath from its components, failing if any of them is unsafe.
+    pub fn new<I, S>(components: I) -> Result<SafePath, SafePathError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let components: Vec<String> = components.into_iter().map(Into::into).collect();
+        if components.is_empty() {
+            return Err(SafePathError::Empty);
+        }
+
+        for component in &components {
+            if component.is_empty()
+                || component == "."
+                || component == ".."
+                || component.contains(['/', '\\', '\0'])
+            {
+                return Err(SafePathError::InvalidComponent {
+                    component: component.to_string(),
+                });
+            }
+
+            if is_reserved(component) {
+                return Err(SafePathError::ReservedName {
+                    component: component.to_string(),
+                });
+            }
+        }
+
+        Ok(SafePath(components))
+    }
+
+    /// Builds a SafePath from its components, sanitizing unsafe ones: empty, `.` and `..`
+    /// components are dropped, separators and NUL characters are replaced with `_`, and
+    /// reserved names are suffixed with `_`. If no component is left, the path is `_`.
+    pub fn sanitize<I, S>(components: I) -> SafePath
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut sanitized: Vec<String> = components
+            .into_iter()
+            .map(|c| c.into().replace(['/', '\\', '\0'], "_"))
+            .filter(|c| !c.is_empty() && c != "." && c != "..")
+            .map(|c| if is_reserved(&c) { format!("{c}_") } else { c })
+            .collect();
+
+        if sanitized.is_empty() {
+            sanitized.push("_".to_string());
+        }
+
+        SafePath(sanitized)
+    }
+
+    /// Returns the components of the path.
+    pub fn components(&self) -> &[String] {
+        &self.0
+    }
+
+    /// Returns the path as a relative [`PathBuf`](std::path::PathBuf) for the current platform.
+    pub fn to_path_buf(&self) -> PathBuf {
+        self.0.iter().collect()
+    }
+}
+
+impl std::fmt::Display for SafePath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.join("/"))
+    }
+}
+
+impl TryFrom<Vec<String>> for SafePath {
+    type Error = SafePathError;
+
+    fn try_from(components: Vec<String>) -> Result<SafePath, SafePathError> {
+        SafePath::new(components)
+    }
+}
+
+impl From<SafePath> for Vec<String> {
+    fn from(path: SafePath) -> Vec<String> {
+        path.0
+    }
+}
+
+fn is_reserved(component: &str) -> bool {
+    let stem = component.split('.').next().unwrap_or(component);
+    WINDOWS_RESERVED
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(stem))
+}
+
+/// BEP-0047 file attributes, parsed from the `attr` string of a file entry.
+///
+/// Attributes can be combined like bit flags:
+///
+/// ```
+/// use hightorrent::FileAttributes;
+///
+/// let attr = FileAttributes::EXECUTABLE | FileAttributes::HIDDEN;
+/// assert!(attr.is_executable());
+/// assert!(attr.contains(FileAttributes::HIDDEN));
+/// assert!(!attr.is_symlink());
+/// assert_eq!(attr, FileAttributes::from_attr(b"xh"));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct FileAttributes(u8);
+
+impl FileAttributes {
+    /// No attributes.
+    pub const NONE: FileAttributes = FileAttributes(0);
+    /// Padding file (`p`), which is not meant to be stored on disk.
+    pub const PADDING: FileAttributes = FileAttributes(1);
+    /// Symbolic link (`l`), pointing to the file's `symlink path`.
+    pub const SYMLINK: FileAttributes = FileAttributes(1 << 1);
+    /// Executable file (`x`).
+    pub const EXECUTABLE: FileAttributes = FileAttributes(1 << 2);
+    /// Hidden file (`h`).
+    pub const HIDDEN: FileAttributes = FileAttributes(1 << 3);
+
+    /// Parses a BEP-0047 `attr` string. Unknown attributes are ignored.
+    pub fn from_attr(attr: &[u8]) -> FileAttributes {
+        attr.iter().fold(FileAttributes::NONE, |acc, c| match c {
+            b'p' => acc | FileAttributes::PADDING,
+            b'l' => acc | FileAttributes::SYMLINK,
+            b'x' => acc | FileAttributes::EXECUTABLE,
+            b'h' => acc | FileAttributes::HIDDEN,
+            _ => acc,
+        })
+    }
+
+    /// Returns true when all the attributes in `other` are set.
+    pub fn contains(&self, other: FileAttributes) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Sets all the attributes in `other`.
+    pub fn insert(&mut self, other: FileAttributes) {
+        self.0 |= other.0;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn is_padding(&self) -> bool {
+        self.contains(FileAttributes::PADDING)
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        self.contains(FileAttributes::SYMLINK)
+    }
+
+    pub fn is_executable(&self) -> bool {
+        self.contains(FileAttributes::EXECUTABLE)
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.contains(FileAttributes::HIDDEN)
+    }
+}
+
+impl std::ops::BitOr for FileAttributes {
+    type Output = FileAttributes;
+
+    fn bitor(self, other: FileAttributes) -> FileAttributes {
+        FileAttributes(self.0 | other.0)
+    }
+}
+
+impl std::fmt::Display for FileAttributes {
+    /// Formats the attributes as a BEP-0047 `attr` string.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (flag, c) in [
+            (FileAttributes::PADDING, 'p'),
+            (FileAttributes::SYMLINK, 'l'),
+            (FileAttributes::EXECUTABLE, 'x'),
+            (FileAttributes::HIDDEN, 'h'),
+        ] {
+            if self.contains(flag) {
+                write!(f, "{c}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A file contained in a [`TorrentFile`](crate::torrent_file::TorrentFile), as returned by
+/// [`TorrentFile::files`](crate::torrent_file::TorrentFile::files).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TorrentContent {
+    /// The sanitized path of the file, starting with the torrent name for multi-file torrents
+    pub path: SafePath,
+    /// The size of the file, in bytes
+    pub size: u64,
+    /// The BEP-0047 attributes of the file
+    pub attributes: FileAttributes,
+    /// The sanitized BEP-0047 `symlink path`, relative to the torrent root, for symbolic links
+    pub symlink_target: Option<PathBuf>,
+}
+
+impl TorrentContent {
+    /// Returns true for BEP-0047 padding files, which are only listed by
+    /// [`TorrentFile::files_with_padding`](crate::torrent_file::TorrentFile::files_with_padding).
+    pub fn is_padding(&self) -> bool {
+        self.attributes.is_padding()
+    }
+}
+
+/// A file or directory in the hierarchical view of a torrent, as returned by
+/// [`TorrentFile::file_tree_view`](crate::torrent_file::TorrentFile::file_tree_view).
+///
+/// Files have no children, and the size of a directory is the total size of the files it
+/// contains, saturating at `u64::MAX`. Children are listed in the order of the torrent files. The Display implementation
+/// draws the tree like the `tree` command, with human-readable sizes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DirNode {
+    /// The name of the file or directory, without its parents
+    pub name: String,
+    /// The size of the file, or the total size of the directory, in bytes
+    pub size: u64,
+    /// The files and subdirectories of a directory
+    pub children: Vec<DirNode>,
+}
+
+impl DirNode {
+    /// Builds the tree of files from their paths. Multi-file torrents have a root directory
+    /// named after the torrent, which is returned, while single-file torrents are a single file.
+    pub(crate) fn from_files(name: &str, files: &[TorrentContent]) -> DirNode {
+        let mut root = DirNode::new(name);
+        for file in files {
+            root.insert(file.path.components(), file.size);
+        }
+        if root.children.len() == 1 {
+            root.children.remove(0)
+        } else {
+            root
+        }
+    }
+
+    fn new(name: &str) -> DirNode {
+        DirNode {
+            name: name.to_string(),
+            size: 0,
+            children: Vec::new(),
+        }
+    }
+
+    fn insert(&mut self, components: &[String], size: u64) {
+        let (first, rest) = match components.split_first() {
+            Some(split) => split,
+            None => return,
+        };
+        self.size = self.size.saturating_add(size);
+        let child = match self.children.iter().position(|c| c.name == *first) {
+            Some(pos) => &mut self.children[pos],
+            None => {
+                self.children.push(DirNode::new(first));
+                self.children.last_mut().unwrap()
+            }
+        };
+        if rest.is_empty() {
+            child.size = child.size.saturating_add(size);
+        } else {
+            child.insert(rest, size);
+        }
+    }
+
+    /// Returns true for files, which have no children.
+    pub fn is_file(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    fn fmt_children(&self, f: &mut std::fmt::Formatter<'_>, prefix: &str) -> std::fmt::Result {
+        for (i, child) in self.children.iter().enumerate() {
+            let last = i == self.children.len() - 1;
+            let (branch, indent) = if last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            writeln!(f, "{prefix}{branch}{}", child.label())?;
+            child.fmt_children(f, &format!("{prefix}{indent}"))?;
+        }
+        Ok(())
+    }
+
+    fn label(&self) -> String {
+        let size = format_size(i64::try_from(self.size).unwrap_or(i64::MAX));
+        format!("{} ({size})", self.name)
+    }
+}
+
+impl std::fmt::Display for DirNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.label())?;
+        self.fmt_children(f, "")
+    }
+}
+
+/// A file described by an info dict, with its raw path components.
+pub(crate) struct RawFile<'a> {
+    pub(crate) path: Vec<&'a [u8]>,
+    pub(crate) length: Option<i64>,
+    pub(crate) attributes: FileAttributes,
+    pub(crate) symlink_path: Option<Vec<&'a [u8]>>,
+    // Bittorrent v2 merkle root of the file, absent for v1 files
+    pub(crate) pieces_root: Option<&'a [u8]>,
+}
+
+impl RawFile<'_> {
+    /// Returns the path components, with invalid UTF-8 sequences replaced.
+    pub(crate) fn lossy_components(&self) -> Vec<String> {
+        self.path
+            .iter()
+            .map(|c| String::from_utf8_lossy(c).to_string())
+            .collect()
+    }
+
+    pub(crate) fn display_path(&self) -> String {
+        self.lossy_components().join("/")
+    }
+
+    /// Returns the sanitized symlink target, if the file is a symbolic link.
+    pub(crate) fn symlink_target(&self) -> Option<PathBuf> {
+        if !self.attributes.is_symlink() {
+            return None;
+        }
+        let components = self.symlink_path.as_ref()?;
+        let components = components.iter().map(|c| String::from_utf8_lossy(c));
+        Some(SafePath::sanitize(components).to_path_buf())
+    }
+}
+
+/// Reads the BEP-0047 attributes and symlink path of a file dict.
+fn file_extensions(file: &BencodeValue) -> (FileAttributes, Option<Vec<&[u8]>>) {
+    let attributes = file
+        .get("attr")
+        .and_then(|a| a.as_byte_str())
+        .map(|a| FileAttributes::from_attr(a))
+        .unwrap_or_default();
+    let symlink_path = file.get("symlink path").and_then(|p| p.as_list()).map(|p| {
+        p.iter()
+            .filter_map(|c| c.as_byte_str().map(|c| c.as_slice()))
+            .collect()
+    });
+    (attributes, symlink_path)
+}
+
+/// Lists the files of a Bittorrent v1 info dict. Paths of malformed entries are pushed
+/// to `malformed`.
+pub(crate) fn v1_files<'a>(
+    name: &'a str,
+    length: Option<u64>,
+    files: Option<&'a Vec<BencodeValue>>,
+    malformed: &mut Vec<String>,
+) -> Vec<RawFile<'a>> {
+    if let Some(length) = length {
+        return vec![RawFile {
+            path: vec![name.as_bytes()],
+            length: i64::try_from(length).ok(),
+            attributes: FileAttributes::NONE,
+            symlink_path: None,
+            pieces_root: None,
+        }];
+    }
+
+    let mut list = Vec::new();
+    for file in files.into_iter().flatten() {
+        let path: Option<Vec<&[u8]>> = file.get("path").and_then(|p| p.as_list()).map(|p| {
+            p.iter()
+                .filter_map(|c| c.as_byte_str().map(|c| c.as_slice()))
+                .collect()
+        });
+        let (attributes, symlink_path) = file_extensions(file);
+
+        match path {
+            Some(path) if !path.is_empty() => {
+                let mut full_path = vec![name.as_bytes()];
+                full_path.extend(path);
+                list.push(RawFile {
+                    path: full_path,
+                    length: file.get("length").and_then(bencode_i64),
+                    attributes,
+                    symlink_path,
+                    pieces_root: None,
+                });
+            }
+            _ => malformed.push(name.to_string()),
+        }
+    }
+    list
+}
+
+/// Lists the files of a Bittorrent v2 file tree. Files are dicts with an empty key.
+/// Paths of malformed entries are pushed to `malformed`.
+pub(crate) fn v2_files<'a>(
+    name: &'a str,
+    tree: &'a BencodeValue,
+    malformed: &mut Vec<String>,
+) -> Vec<RawFile<'a>> {
+    fn walk<'a>(
+        node: &'a BencodeValue,
+        path: &mut Vec<&'a [u8]>,
+        list: &mut Vec<RawFile<'a>>,
+        malformed: &mut Vec<String>,
+    ) {
+        let dict = match node.as_dict() {
+            Some(dict) => dict,
+            None => {
+                let path: Vec<_> = path.iter().map(|c| String::from_utf8_lossy(c)).collect();
+                malformed.push(path.join("/"));
+                return;
+            }
+        };
+
+        for (key, child) in dict {
+            if key.is_empty() {
+                let (attributes, symlink_path) = file_extensions(child);
+                list.push(RawFile {
+                    path: path.clone(),
+                    length: child.get("length").and_then(bencode_i64),
+                    attributes,
+                    symlink_path,
+                    pieces_root: child
+                        .get("pieces root")
+                        .and_then(|r| r.as_byte_str())
+                        .map(|r| r.as_slice()),
+                });
+            } else {
+                path.push(key.as_slice());
+                walk(child, path, list, malformed);
+                path.pop();
+            }
+        }
+    }
+
+    let mut list = Vec::new();
+    let mut path = Vec::new();
+    walk(tree, &mut path, &mut list, malformed);
+
+    // Single-file v2 torrents have the torrent name as the only entry in the file tree,
+    // while multi-file torrents have the name as an implicit top-level directory
+    let single = list.len() == 1 && list[0].path == [name.as_bytes()];
+    if !single {
+        for file in list.iter_mut() {
+            file.path.insert(0, name.as_bytes());
+        }
+    }
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content(path: &[&str], size: u64) -> TorrentContent {
+        TorrentContent {
+            path: SafePath::new(path.iter().copied()).unwrap(),
+            size,
+            attributes: FileAttributes::default(),
+            symlink_target: None,
+        }
+    }
+
+    #[test]
+    fn builds_file_tree_view() {
+        let files = [
+            content(&["album", "cd1", "01.flac"], 1024),
+            content(&["album", "cover.jpg"], 100),
+            content(&["album", "cd1", "02.flac"], 2048),
+        ];
+        let tree = DirNode::from_files("album", &files);
+        assert_eq!(tree.name, "album");
+        assert_eq!(tree.size, 3172);
+        assert_eq!(tree.children.len(), 2);
+        assert_eq!(tree.children[0].size, 3072);
+        assert!(tree.children[1].is_file());
+        assert_eq!(
+            tree.to_string(),
+            "album (3.1 KiB)\n\
+             ├── cd1 (3.0 KiB)\n\
+             │   ├── 01.flac (1.0 KiB)\n\
+             │   └── 02.flac (2.0 KiB)\n\
+             └── cover.jpg (100 B)\n"
+        );
+
+        let single = DirNode::from_files("file.iso", &[content(&["file.iso"], 5)]);
+        assert!(single.is_file());
+        assert_eq!(single.to_string(), "file.iso (5 B)\n");
+
+        let huge = [
+            content(&["huge", "a"], i64::MAX as u64),
+            content(&["huge", "b"], i64::MAX as u64),
+            content(&["huge", "c"], i64::MAX as u64),
+        ];
+        let tree = DirNode::from_files("huge", &huge);
+        assert_eq!(tree.size, u64::MAX);
+        assert!(tree.to_string().starts_with("huge ("));
+    }
+
+    #[test]
+    fn safe_path_accepts_normal_paths() {
+        let path = SafePath::new(["Music", "album", "01 - track.flac"]).unwrap();
+        assert_eq!(path.components().len(), 3);
+        assert_eq!(path.to_string(), "Music/album/01 - track.flac");
+        assert_eq!(
+            path.to_path_buf(),
+            PathBuf::from("Music").join("album").join("01 - track.flac")
+        );
+        assert!(SafePath::new(["console.log", "CONTRIBUTING"]).is_ok());
+    }
+
+    #[test]
+    fn safe_path_rejects_unsafe_paths() {
+        assert_eq!(
+            SafePath::new(Vec::<String>::new()).unwrap_err(),
+            SafePathError::Empty
+        );
+        for component in ["", ".", "..", "a/b", "a\\b", "a\0b"] {
+            assert_eq!(
+                SafePath::new(["dir", component]).unwrap_err(),
+                SafePathError::InvalidComponent {
+                    component: component.to_string()
+                }
+            );
+        }
+        for component in ["CON", "aux.txt", "Lpt1.tar.gz"] {
+            assert_eq!(
+                SafePath::new([component]).unwrap_err(),
+                SafePathError::ReservedName {
+                    component: component.to_string()
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn safe_path_sanitizes_unsafe_paths() {
+        assert_eq!(
+            SafePath::sanitize(["..", "etc", ".", "", "pass/wd"]).to_string(),
+            "etc/pass_wd"
+        );
+        assert_eq!(
+            SafePath::sanitize(["dir", "nul.txt", "a\\b\0"]).to_string(),
+            "dir/nul.txt_/a_b_"
+        );
+        assert_eq!(SafePath::sanitize(["..", ".."]).to_string(), "_");
+    }
+
+    #[test]
+    fn file_attributes() {
+        let attr = FileAttributes::from_attr(b"hxlp?");
+        assert!(attr.is_padding() && attr.is_symlink() && attr.is_executable() && attr.is_hidden());
+        assert_eq!(attr.to_string(), "plxh");
+        assert!(FileAttributes::from_attr(b"").is_empty());
+
+        let mut attr = FileAttributes::default();
+        attr.insert(FileAttributes::SYMLINK);
+        assert!(attr.is_symlink());
+        assert!(!attr.contains(FileAttributes::SYMLINK | FileAttributes::HIDDEN));
+    }
+
+    #[test]
+    fn symlink_target_is_sanitized() {
+        let mut file = std::collections::BTreeMap::new();
+        file.insert("attr".into(), BencodeValue::from("l"));
+        file.insert("length".into(), BencodeValue::from(0));
+        file.insert("path".into(), BencodeValue::List(vec!["link".into()]));
+        file.insert(
+            "symlink path".into(),
+            BencodeValue::List(vec![
+                "..".into(),
+                "..".into(),
+                "etc".into(),
+                "passwd".into(),
+            ]),
+        );
+        let files = vec![BencodeValue::Dict(file)];
+
+        let list = v1_files("torrent", None, Some(&files), &mut Vec::new());
+        assert_eq!(list.len(), 1);
+        assert!(list[0].attributes.is_symlink());
+        assert_eq!(
+            list[0].symlink_target(),
+            Some(PathBuf::from("etc").join("passwd"))
+        );
+    }
+
+    #[test]
+    fn safe_path_serde() {
+        let path = SafePath::new(["a", "b"]).unwrap();
+        let json = serde_json::to_string(&path).unwrap();
+        assert_eq!(json, r#"["a","b"]"#);
+        assert_eq!(serde_json::from_str::<SafePath>(&json).unwrap(), path);
+        assert!(serde_json::from_str::<SafePath>(r#"["..","b"]"#).is_err());
+    }
+}
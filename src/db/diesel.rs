@@ -0,0 +1,45 @@
+use ::diesel::backend::Backend;
+use ::diesel::deserialize::{self, FromSql};
+use ::diesel::pg::Pg;
+use ::diesel::serialize::{self, IsNull, Output, ToSql};
+use ::diesel::sql_types::Text;
+use ::diesel::sqlite::Sqlite;
+
+use std::io::Write;
+
+use crate::{InfoHash, MagnetLink, TorrentID};
+
+/// Implements diesel traits for a type stored as text, on the SQLite and PostgreSQL backends.
+macro_rules! impl_text_type {
+    ($type:ty, |$value:ident| $to_string:expr, $parse:expr) => {
+        impl ToSql<Text, Sqlite> for $type {
+            fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+                let $value = self;
+                out.set_value($to_string);
+                Ok(IsNull::No)
+            }
+        }
+
+        impl ToSql<Text, Pg> for $type {
+            fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+                let $value = self;
+                out.write_all($to_string.as_bytes())?;
+                Ok(IsNull::No)
+            }
+        }
+
+        impl<DB: Backend> FromSql<Text, DB> for $type
+        where
+            String: FromSql<Text, DB>,
+        {
+            fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
+                let s = <String as FromSql<Text, DB>>::from_sql(bytes)?;
+                Ok($parse(&s)?)
+            }
+        }
+    };
+}
+
+impl_text_type!(TorrentID, |id| id.as_str().to_string(), TorrentID::new);
+impl_text_type!(InfoHash, |hash| hash.to_full_string(), InfoHash::new);
+impl_text_type!(MagnetLink, |magnet| magnet.to_string(), MagnetLink::new);
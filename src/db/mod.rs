@@ -0,0 +1,15 @@
+//! Database integrations, implementing column types for the crate's identifiers.
+//!
+//! Identifiers are stored in their string form: [`InfoHash::to_full_string`](crate::hash::InfoHash::to_full_string)
+//! for [`InfoHash`](crate::hash::InfoHash), so that hybrid infohashes keep both digests, and the
+//! [`Display`](std::fmt::Display) form for the other types. Torrent files are stored as their
+//! original bencoded bytes, as returned by [`TorrentFile::as_bytes`](crate::torrent_file::TorrentFile::as_bytes).
+
+#[cfg(feature = "sea_orm")]
+mod sea_orm;
+
+#[cfg(feature = "sqlx")]
+mod sqlx;
+
+#[cfg(feature = "diesel")]
+mod diesel;
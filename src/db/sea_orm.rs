@@ -0,0 +1,211 @@
+use ::sea_orm::sea_query::{ArrayType, ColumnType, Nullable, StringLen, ValueType, ValueTypeErr};
+use ::sea_orm::{ColIdx, DbErr, QueryResult, TryFromU64, TryGetError, TryGetable, Value};
+
+use crate::{InfoHash, MagnetLink, SingleTarget, TorrentFile, TorrentID};
+
+/// Implements sea-orm column traits for a type stored as a string.
+macro_rules! impl_string_column {
+    ($type:ty, $column_type:expr, |$value:ident| $to_string:expr, $parse:expr) => {
+        impl From<$type> for Value {
+            fn from($value: $type) -> Value {
+                Value::String(Some(Box::new($to_string)))
+            }
+        }
+
+        impl TryGetable for $type {
+            fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+                let s = String::try_get_by(res, index)?;
+                $parse(&s).map_err(|e| {
+                    TryGetError::DbErr(DbErr::TryIntoErr {
+                        from: "String",
+                        into: stringify!($type),
+                        source: Box::new(e),
+                    })
+                })
+            }
+        }
+
+        impl ValueType for $type {
+            fn try_from(v: Value) -> Result<Self, ValueTypeErr> {
+                match v {
+                    Value::String(Some(s)) => $parse(s.as_str()).map_err(|_| ValueTypeErr),
+                    _ => Err(ValueTypeErr),
+                }
+            }
+
+            fn type_name() -> String {
+                stringify!($type).to_string()
+            }
+
+            fn array_type() -> ArrayType {
+                ArrayType::String
+            }
+
+            fn column_type() -> ColumnType {
+                $column_type
+            }
+        }
+
+        impl Nullable for $type {
+            fn null() -> Value {
+                Value::String(None)
+            }
+        }
+
+        impl TryFromU64 for $type {
+            fn try_from_u64(_: u64) -> Result<Self, DbErr> {
+                Err(DbErr::ConvertFromU64(stringify!($type)))
+            }
+        }
+    };
+}
+
+impl_string_column!(
+    TorrentID,
+    ColumnType::String(StringLen::N(40)),
+    |id| id.as_str().to_string(),
+    TorrentID::new
+);
+
+// Hybrid infohashes are stored as "v1:v2", 40 + 1 + 64 characters
+impl_string_column!(
+    InfoHash,
+    ColumnType::String(StringLen::N(105)),
+    |hash| hash.to_full_string(),
+    InfoHash::new
+);
+
+impl_string_column!(
+    SingleTarget,
+    ColumnType::String(StringLen::N(64)),
+    |target| target.as_str().to_string(),
+    SingleTarget::new
+);
+
+impl_string_column!(
+    MagnetLink,
+    ColumnType::Text,
+    |magnet| magnet.to_string(),
+    MagnetLink::new
+);
+
+// Torrent files are stored as their original bencoded bytes, not as an internal encoding,
+// so that stored torrents can be read back by other tools. Blob maps to bytea on PostgreSQL.
+impl From<TorrentFile> for Value {
+    fn from(torrent: TorrentFile) -> Value {
+        Value::Bytes(Some(Box::new(torrent.as_bytes().to_vec())))
+    }
+}
+
+impl TryGetable for TorrentFile {
+    fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+        let bytes = Vec::<u8>::try_get_by(res, index)?;
+        TorrentFile::from_slice(&bytes).map_err(|e| {
+            TryGetError::DbErr(DbErr::TryIntoErr {
+                from: "Vec<u8>",
+                into: "TorrentFile",
+                source: Box::new(e),
+            })
+        })
+    }
+}
+
+impl ValueType for TorrentFile {
+    fn try_from(v: Value) -> Result<Self, ValueTypeErr> {
+        match v {
+            Value::Bytes(Some(bytes)) => TorrentFile::from_slice(&bytes).map_err(|_| ValueTypeErr),
+            _ => Err(ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        "TorrentFile".to_string()
+    }
+
+    fn array_type() -> ArrayType {
+        ArrayType::Bytes
+    }
+
+    fn column_type() -> ColumnType {
+        ColumnType::Blob
+    }
+}
+
+impl Nullable for TorrentFile {
+    fn null() -> Value {
+        Value::Bytes(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HexDigest;
+
+    fn roundtrip<T: ValueType + Clone + Into<Value>>(t: &T) -> T {
+        <T as ValueType>::try_from(t.clone().into()).unwrap()
+    }
+
+    #[test]
+    fn infohash_column() {
+        let hybrid = InfoHash::Hybrid((
+            HexDigest::new("631a31dd0a46257d5078c0dee4e66e26f73e42ac").unwrap(),
+            HexDigest::new("d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb")
+                .unwrap(),
+        ));
+        assert_eq!(
+            Value::from(hybrid.clone()),
+            Value::String(Some(Box::new("631a31dd0a46257d5078c0dee4e66e26f73e42ac:d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb".to_string())))
+        );
+        assert_eq!(roundtrip(&hybrid), hybrid);
+
+        let v1 = InfoHash::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap();
+        assert_eq!(roundtrip(&v1), v1);
+
+        assert_eq!(<InfoHash as Nullable>::null(), Value::String(None));
+        assert!(<InfoHash as ValueType>::try_from(Value::String(None)).is_err());
+        assert!(<InfoHash as ValueType>::try_from(Value::from("invalid")).is_err());
+    }
+
+    #[test]
+    fn identifier_columns() {
+        let id = TorrentID::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap();
+        assert_eq!(roundtrip(&id), id);
+
+        let target =
+            SingleTarget::new("caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e")
+                .unwrap();
+        assert_eq!(roundtrip(&target), target);
+        assert_eq!(
+            <SingleTarget as ValueType>::column_type(),
+            ColumnType::String(StringLen::N(64))
+        );
+
+        let magnet = MagnetLink::new(
+            &std::fs::read_to_string("tests/bittorrent-v1-emma-goldman.magnet").unwrap(),
+        )
+        .unwrap();
+        assert!(roundtrip(&magnet).exact_eq(&magnet));
+    }
+
+    #[test]
+    fn torrent_file_column() {
+        let slice = std::fs::read("tests/bittorrent-v2-hybrid-test.torrent").unwrap();
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+
+        // Stored bytes are the original torrent file
+        assert_eq!(
+            Value::from(torrent.clone()),
+            Value::Bytes(Some(Box::new(slice.clone())))
+        );
+        assert_eq!(roundtrip(&torrent).as_bytes(), torrent.as_bytes());
+        assert_eq!(<TorrentFile as ValueType>::column_type(), ColumnType::Blob);
+
+        assert_eq!(<TorrentFile as Nullable>::null(), Value::Bytes(None));
+        assert!(<TorrentFile as ValueType>::try_from(Value::Bytes(None)).is_err());
+        assert!(
+            <TorrentFile as ValueType>::try_from(Value::Bytes(Some(Box::new(b"invalid".to_vec()))))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use ::sqlx::encode::IsNull;
+use ::sqlx::error::BoxDynError;
+use ::sqlx::{Database, Decode, Encode, Type};
+
+use crate::{InfoHash, MagnetLink, TorrentFile, TorrentID};
+
+/// Implements sqlx traits for a type stored as text, for any database supporting strings.
+macro_rules! impl_text_type {
+    ($type:ty, |$value:ident| $to_string:expr, $parse:expr) => {
+        impl<DB: Database> Type<DB> for $type
+        where
+            String: Type<DB>,
+        {
+            fn type_info() -> DB::TypeInfo {
+                <String as Type<DB>>::type_info()
+            }
+
+            fn compatible(ty: &DB::TypeInfo) -> bool {
+                <String as Type<DB>>::compatible(ty)
+            }
+        }
+
+        impl<'q, DB: Database> Encode<'q, DB> for $type
+        where
+            String: Encode<'q, DB>,
+        {
+            fn encode_by_ref(
+                &self,
+                buf: &mut <DB as Database>::ArgumentBuffer<'q>,
+            ) -> Result<IsNull, BoxDynError> {
+                let $value = self;
+                <String as Encode<'q, DB>>::encode($to_string, buf)
+            }
+        }
+
+        impl<'r, DB: Database> Decode<'r, DB> for $type
+        where
+            &'r str: Decode<'r, DB>,
+        {
+            fn decode(value: <DB as Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+                let s = <&'r str as Decode<'r, DB>>::decode(value)?;
+                Ok($parse(s)?)
+            }
+        }
+    };
+}
+
+impl_text_type!(TorrentID, |id| id.as_str().to_string(), TorrentID::new);
+impl_text_type!(InfoHash, |hash| hash.to_full_string(), InfoHash::new);
+impl_text_type!(MagnetLink, |magnet| magnet.to_string(), MagnetLink::new);
+
+// Torrent files are stored as their original bencoded bytes
+impl<DB: Database> Type<DB> for TorrentFile
+where
+    Vec<u8>: Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <Vec<u8> as Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <Vec<u8> as Type<DB>>::compatible(ty)
+    }
+}
+
+impl<'q, DB: Database> Encode<'q, DB> for TorrentFile
+where
+    Vec<u8>: Encode<'q, DB>,
+{
+    fn encode_by_ref(
+        &self,
+        buf: &mut <DB as Database>::ArgumentBuffer<'q>,
+    ) -> Result<IsNull, BoxDynError> {
+        <Vec<u8> as Encode<'q, DB>>::encode(self.as_bytes().to_vec(), buf)
+    }
+}
+
+impl<'r, DB: Database> Decode<'r, DB> for TorrentFile
+where
+    &'r [u8]: Decode<'r, DB>,
+{
+    fn decode(value: <DB as Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        let bytes = <&'r [u8] as Decode<'r, DB>>::decode(value)?;
+        Ok(TorrentFile::from_slice(bytes)?)
+    }
+}
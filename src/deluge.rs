@@ -0,0 +1,206 @@
+use crate::torrent::tracker_sources;
+use crate::{
+    InfoHash, InfoHashError, Progress, Timestamp, ToTorrent, Torrent, TorrentLimits, Tracker,
+    TrackerError, TryIntoTracker,
+};
+
+/// A torrent, as returned by the Deluge JSON-RPC API (`core.get_torrents_status` or
+/// `web.update_ui`).
+///
+/// Only the fields required to build a [`Torrent`](crate::torrent::Torrent) are deserialized,
+/// so make sure to request at least `hash` and `name`. The infohash is validated during
+/// deserialization.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "RawDelugeTorrent")]
+pub struct DelugeTorrent {
+    pub hash: InfoHash,
+    pub name: String,
+    pub save_path: String,
+    /// Unix timestamp (in seconds) when the torrent was added
+    pub time_added: i64,
+    /// Unix timestamp (in seconds) when the torrent was completed, or 0
+    pub completed_time: i64,
+    /// Progress percentage (0-100)
+    pub progress: f64,
+    pub total_size: i64,
+    pub total_uploaded: Option<u64>,
+    /// Total bytes downloaded, including previous sessions (`all_time_download`)
+    pub all_time_download: Option<u64>,
+    pub state: String,
+    /// Label set by the Label plugin, if enabled
+    pub label: Option<String>,
+    pub trackers: Vec<DelugeTracker>,
+    /// The limits from the `max_download_speed`, `max_upload_speed` and `stop_ratio` fields,
+    /// with the `stop_at_ratio` flag
+    pub limits: TorrentLimits,
+}
+
+#[derive(Deserialize)]
+struct RawDelugeTorrent {
+    hash: String,
+    name: String,
+    #[serde(default)]
+    save_path: String,
+    #[serde(default)]
+    time_added: f64,
+    #[serde(default)]
+    completed_time: i64,
+    #[serde(default)]
+    progress: f64,
+    #[serde(default)]
+    total_size: i64,
+    #[serde(default)]
+    total_uploaded: Option<u64>,
+    #[serde(default)]
+    all_time_download: Option<u64>,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    label: Option<String>,
+    #[serde(default)]
+    trackers: Vec<DelugeTracker>,
+    #[serde(default)]
+    max_download_speed: Option<f64>,
+    #[serde(default)]
+    max_upload_speed: Option<f64>,
+    #[serde(default)]
+    stop_at_ratio: bool,
+    #[serde(default)]
+    stop_ratio: f64,
+}
+
+impl TryFrom<RawDelugeTorrent> for DelugeTorrent {
+    type Error = InfoHashError;
+
+    fn try_from(raw: RawDelugeTorrent) -> Result<DelugeTorrent, InfoHashError> {
+        Ok(DelugeTorrent {
+            hash: InfoHash::new(&raw.hash)?,
+            name: raw.name,
+            save_path: raw.save_path,
+            // Deluge reports sub-second precision, which is not needed here
+            time_added: raw.time_added as i64,
+            completed_time: raw.completed_time,
+            progress: raw.progress,
+            total_size: raw.total_size,
+            total_uploaded: raw.total_uploaded,
+            all_time_download: raw.all_time_download,
+            state: raw.state,
+            label: raw.label.filter(|label| !label.is_empty()),
+            trackers: raw.trackers,
+            limits: TorrentLimits::from_deluge(
+                raw.max_download_speed.unwrap_or(-1.0),
+                raw.max_upload_speed.unwrap_or(-1.0),
+                raw.stop_at_ratio,
+                raw.stop_ratio,
+            ),
+        })
+    }
+}
+
+impl ToTorrent for DelugeTorrent {
+    fn to_torrent(&self) -> Torrent {
+        Torrent {
+            name: self.name.clone(),
+            path: self.save_path.clone(),
+            date_start: Timestamp::from_secs_opt(self.time_added),
+            date_end: Timestamp::from_secs_opt(self.completed_time),
+            progress: Progress::from_fraction(
+                self.progress / 100.0,
+                u64::try_from(self.total_size).unwrap_or(0),
+            ),
+            size: self.total_size,
+            uploaded: self.total_uploaded,
+            downloaded: self.all_time_download,
+            state: self.state.clone(),
+            tags: self.label.iter().cloned().collect(),
+            category: None,
+            sources: tracker_sources(
+                self.trackers
+                    .iter()
+                    .filter_map(|t| t.try_into_tracker().ok()),
+            ),
+            limits: self.limits,
+            id: self.hash.id(),
+            hash: self.hash.clone(),
+        }
+    }
+}
+
+/// A tracker, as returned in the `trackers` field of the Deluge JSON-RPC API.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct DelugeTracker {
+    pub url: String,
+    #[serde(default)]
+    pub tier: i64,
+}
+
+impl TryIntoTracker for DelugeTracker {
+    fn try_into_tracker(&self) -> Result<Tracker, TrackerError> {
+        Tracker::new(&self.url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PeerSource;
+
+    #[test]
+    fn can_convert_torrent() {
+        let json = r#"{
+            "hash": "c811b41641a09d192b8ed81b14064fff55d85ce3",
+            "name": "Goldman, Emma - Essential Works of Anarchism",
+            "save_path": "/home/deluge/Downloads",
+            "time_added": 1603243361.6541,
+            "completed_time": 1603243400,
+            "progress": 100.0,
+            "total_size": 4726342,
+            "state": "Seeding",
+            "label": "books",
+            "max_download_speed": -1,
+            "max_upload_speed": 50.5,
+            "stop_at_ratio": true,
+            "stop_ratio": 1.5,
+            "trackers": [{"url": "udp://tracker.opentrackr.org:1337/announce", "tier": 0}]
+        }"#;
+        let deluge: DelugeTorrent = serde_json::from_str(json).unwrap();
+        assert!(deluge.trackers[0].try_into_tracker().is_ok());
+
+        let torrent = deluge.to_torrent();
+        assert_eq!(
+            torrent.id.as_str(),
+            "c811b41641a09d192b8ed81b14064fff55d85ce3"
+        );
+        assert_eq!(torrent.date_start, Timestamp::from_secs_opt(1603243361));
+        assert!(torrent.progress.is_complete());
+        assert_eq!(torrent.progress.bytes_done, 4726342);
+        assert_eq!(torrent.state, "Seeding");
+        assert_eq!(
+            torrent.sources,
+            vec![PeerSource::new("udp://tracker.opentrackr.org:1337/announce").unwrap()]
+        );
+        assert_eq!(torrent.tags, vec!["books"]);
+        assert_eq!(torrent.limits.download_limit, None);
+        assert_eq!(torrent.limits.upload_limit, Some(51712));
+        assert_eq!(torrent.limits.ratio_limit, Some(1.5));
+    }
+
+    #[test]
+    fn negative_size_is_unknown() {
+        let json = r#"{"hash": "c811b41641a09d192b8ed81b14064fff55d85ce3", "name": "test", "progress": 99.6, "total_size": -1}"#;
+        let torrent = serde_json::from_str::<DelugeTorrent>(json)
+            .unwrap()
+            .to_torrent();
+        assert_eq!(torrent.progress, Progress::from_percent(99));
+    }
+
+    #[test]
+    fn ignores_empty_label() {
+        let json =
+            r#"{"hash": "c811b41641a09d192b8ed81b14064fff55d85ce3", "name": "test", "label": ""}"#;
+        let torrent = serde_json::from_str::<DelugeTorrent>(json)
+            .unwrap()
+            .to_torrent();
+        assert!(torrent.tags.is_empty());
+    }
+}
@@ -0,0 +1,163 @@
+use crate::{InfoHash, MagnetLink, MagnetParseOptions, TorrentFile, TorrentID};
+
+/// The kind of torrent found by [`detect`](crate::detect::detect), with its parsed value.
+#[derive(Clone, Debug)]
+pub enum Detected {
+    /// A magnet URI (`magnet:?xt=...`)
+    Magnet(Box<MagnetLink>),
+    /// The bencoded content of a `.torrent` file
+    TorrentBytes(Box<TorrentFile>),
+    /// A v2 infohash (64 hexadecimal characters), a base32-encoded v1 infohash (32 characters
+    /// which are not all hexadecimal), or a hybrid infohash in its `v1:v2` form
+    InfoHashString(InfoHash),
+    /// 40 hexadecimal characters, which may be a v1 infohash or the
+    /// [`TorrentID`](crate::id::TorrentID) of a v2 torrent
+    TorrentIDString(TorrentID),
+    /// The input is not a torrent, or could not be parsed
+    Unknown,
+}
+
+impl Detected {
+    /// Returns the [`TorrentID`](crate::id::TorrentID) of the detected torrent.
+    pub fn id(&self) -> Option<TorrentID> {
+        match self {
+            Detected::Magnet(magnet) => Some(magnet.id()),
+            Detected::TorrentBytes(torrent) => Some(torrent.id()),
+            Detected::InfoHashString(hash) => Some(hash.id()),
+            Detected::TorrentIDString(id) => Some(*id),
+            Detected::Unknown => None,
+        }
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Detected::Unknown)
+    }
+}
+
+/// Sniffs user input, such as a clipboard paste or a dropped file, and parses it with the
+/// matching parser.
+///
+/// Bytes starting with a bencoded dict are parsed as a
+/// [`TorrentFile`](crate::torrent_file::TorrentFile). Otherwise, the input is read as text,
+/// ignoring surrounding whitespace, and parsed as a
+/// [`MagnetLink`](crate::magnet::MagnetLink) when it starts with `magnet:`, or as an
+/// [`InfoHash`](crate::hash::InfoHash) or [`TorrentID`](crate::id::TorrentID).
+///
+/// A string of 40 hexadecimal characters is detected as a TorrentID, because it cannot be told
+/// apart from a truncated v2 infohash. Use a [`SingleTarget`](crate::target::SingleTarget) to
+/// find the matching torrent in a [`TorrentList`](crate::list::TorrentList).
+///
+/// MagnetLinks are parsed even without a name, and invalid input is reported as
+/// [`Detected::Unknown`](crate::detect::Detected::Unknown).
+///
+/// ```
+/// use hightorrent::{detect, Detected};
+///
+/// let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+/// assert!(matches!(detect(&slice), Detected::TorrentBytes(_)));
+///
+/// let magnet = "magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3\n";
+/// assert!(matches!(detect(magnet), Detected::Magnet(_)));
+///
+/// let id = " C811B41641A09D192B8ED81B14064FFF55D85CE3 ";
+/// assert_eq!(detect(id).id().unwrap().as_str(), "c811b41641a09d192b8ed81b14064fff55d85ce3");
+/// assert!(detect("hello world").is_unknown());
+/// ```
+pub fn detect<T: AsRef<[u8]>>(input: T) -> Detected {
+    let input = input.as_ref();
+
+    // A hex digest may also start with a 'd', so this is only a hint
+    if input.first() == Some(&b'd') {
+        if let Ok(torrent) = TorrentFile::from_slice(input) {
+            return Detected::TorrentBytes(Box::new(torrent));
+        }
+    }
+
+    let text = match std::str::from_utf8(input) {
+        Ok(text) => text.trim(),
+        Err(_) => return Detected::Unknown,
+    };
+
+    let scheme = text.get(..7).unwrap_or("");
+    if scheme.eq_ignore_ascii_case("magnet:") {
+        let options = MagnetParseOptions {
+            require_name: false,
+            ..MagnetParseOptions::default()
+        };
+        return match MagnetLink::new_with(text, options) {
+            Ok(magnet) => Detected::Magnet(Box::new(magnet)),
+            Err(_) => Detected::Unknown,
+        };
+    }
+
+    if text.len() == TorrentID::LEN {
+        if let Ok(id) = TorrentID::new(text) {
+            return Detected::TorrentIDString(id);
+        }
+    }
+
+    // Truncated hex digests may also be valid base32
+    let hash = if text.len() == 32 && !text.bytes().all(|c| c.is_ascii_hexdigit()) {
+        InfoHash::from_base32(text)
+    } else {
+        InfoHash::new(text)
+    };
+
+    match hash {
+        Ok(hash) => Detected::InfoHashString(hash),
+        Err(_) => Detected::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V1: &str = "631a31dd0a46257d5078c0dee4e66e26f73e42ac";
+    const V2: &str = "d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb";
+
+    #[test]
+    fn detects_torrents_and_magnets() {
+        let slice = std::fs::read("tests/bittorrent-v2-hybrid-test.torrent").unwrap();
+        let detected = detect(&slice);
+        assert!(matches!(detected, Detected::TorrentBytes(_)));
+        assert_eq!(detected.id().unwrap().as_str(), &V2[..40]);
+
+        let magnet = std::fs::read_to_string("tests/bittorrent-v2-hybrid-test.magnet").unwrap();
+        let detected = detect(format!("  {}\r\n", magnet.trim()));
+        assert!(matches!(detected, Detected::Magnet(_)));
+        assert_eq!(detected.id().unwrap().as_str(), &V2[..40]);
+        assert!(
+            detect("MAGNET:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3")
+                .id()
+                .is_some()
+        );
+        assert!(detect("magnet:?dn=nohash").is_unknown());
+
+        // Truncated torrent file
+        assert!(detect(&slice[..100]).is_unknown());
+        assert!(detect([0xff, 0xfe]).is_unknown());
+        assert!(detect("").is_unknown());
+    }
+
+    #[test]
+    fn detects_hash_strings() {
+        assert!(matches!(detect(V1), Detected::TorrentIDString(_)));
+        // Starts with a 'd' like a bencoded dict
+        assert!(matches!(detect(&V2[..40]), Detected::TorrentIDString(_)));
+        match detect(V2) {
+            Detected::InfoHashString(hash) => assert!(hash.is_v2()),
+            detected => panic!("unexpected {detected:?}"),
+        }
+        match detect(format!("{V1}:{V2}")) {
+            Detected::InfoHashString(hash) => assert!(hash.is_hybrid()),
+            detected => panic!("unexpected {detected:?}"),
+        }
+        assert!(matches!(
+            detect("ZAI3ILCAUGOA3GFYQKOBOBU55AMXMDG6"),
+            Detected::InfoHashString(_)
+        ));
+        assert!(detect(&V1[..39]).is_unknown());
+        assert!(detect(&V1[..32]).is_unknown());
+    }
+}
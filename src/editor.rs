@@ -0,0 +1,329 @@
+use bt_bencode::{ByteString, Value as BencodeValue};
+use url::Url;
+
+use std::collections::BTreeMap;
+
+use crate::{InfoHash, TorrentFile, TorrentFileError, Tracker, TrackerTiers};
+
+/// Error occurred while editing a torrent with a [`TorrentEditor`](crate::editor::TorrentEditor).
+#[derive(Clone, Debug, PartialEq)]
+pub enum TorrentEditError {
+    /// The edit would change the info dict, and therefore the infohash of the torrent.
+    InfoDictChange,
+    /// The edited torrent could not be parsed again.
+    InvalidTorrent { source: TorrentFileError },
+}
+
+impl std::fmt::Display for TorrentEditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TorrentEditError::InfoDictChange => {
+                write!(f, "Editing the info dict would change the infohash")
+            }
+            TorrentEditError::InvalidTorrent { source } => {
+                write!(f, "Invalid edited torrent: {source}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TorrentEditError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TorrentEditError::InvalidTorrent { source } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<TorrentFileError> for TorrentEditError {
+    fn from(e: TorrentFileError) -> TorrentEditError {
+        TorrentEditError::InvalidTorrent { source: e }
+    }
+}
+
+/// Edits the metadata of a [`TorrentFile`](crate::torrent_file::TorrentFile) outside of its info
+/// dict, such as trackers, web seeds or comment.
+///
+/// The info dict is kept as its original bytes, so the edited torrent always has the same
+/// infohash. Edits which would change the info dict are refused with
+/// [`TorrentEditError::InfoDictChange`](crate::editor::TorrentEditError::InfoDictChange).
+///
+/// ```
+/// use hightorrent::{TorrentEditor, TorrentFile, Tracker};
+///
+/// let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+/// let torrent = TorrentFile::from_slice(&slice).unwrap();
+///
+/// let mut editor = TorrentEditor::new(&torrent);
+/// editor.set_announce(&Tracker::new("https://tracker.example/announce").unwrap());
+/// editor.set_comment(Some("Rewritten"));
+/// let edited = editor.build().unwrap();
+///
+/// assert_eq!(edited.hash(), torrent.hash());
+/// assert_eq!(edited.comment(), Some("Rewritten"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct TorrentEditor {
+    fields: BTreeMap<ByteString, BencodeValue>,
+    info: Vec<u8>,
+    hash: InfoHash,
+}
+
+impl TorrentEditor {
+    pub fn new(torrent: &TorrentFile) -> TorrentEditor {
+        // The torrent was already parsed, so it is a valid bencoded dict
+        let mut fields = match bt_bencode::from_slice(torrent.as_bytes()) {
+            Ok(BencodeValue::Dict(fields)) => fields,
+            _ => unreachable!("a TorrentFile is a bencoded dict"),
+        };
+        fields.remove(b"info".as_slice());
+
+        TorrentEditor {
+            fields,
+            info: torrent.info_bytes().to_vec(),
+            hash: InfoHash::from(torrent),
+        }
+    }
+
+    /// Replaces all trackers with a single `announce` tracker.
+    pub fn set_announce(&mut self, tracker: &Tracker) {
+        self.set_tracker_tiers(&TrackerTiers::from_tiers(vec![vec![tracker.clone()]]));
+    }
+
+    /// Replaces all trackers with BEP-0012 tiers. The first tracker is also used as the
+    /// `announce` tracker, for clients which do not support tiers.
+    pub fn set_tracker_tiers(&mut self, tiers: &TrackerTiers) {
+        self.fields.remove(b"announce".as_slice());
+        self.fields.remove(b"announce-list".as_slice());
+
+        if let Some(first) = tiers.iter().next() {
+            self.insert("announce", BencodeValue::from(first.url()));
+            let list = tiers
+                .tiers()
+                .iter()
+                .map(|tier| BencodeValue::List(tier.iter().map(|t| t.url().into()).collect()))
+                .collect();
+            self.insert("announce-list", BencodeValue::List(list));
+        }
+    }
+
+    /// Replaces a tracker wherever it appears, keeping its position in the tiers. Trackers are
+    /// compared by their [`normalized`](crate::tracker::Tracker::normalized) URL. Returns the
+    /// number of replaced URLs.
+    pub fn replace_tracker(&mut self, from: &Tracker, to: &Tracker) -> usize {
+        let mut replaced = 0;
+        let mut replace = |value: &mut BencodeValue| {
+            let matches = value
+                .as_str()
+                .and_then(|url| Tracker::new(url).ok())
+                .map_or(false, |tracker| tracker == *from);
+            if matches {
+                *value = BencodeValue::from(to.url());
+                replaced += 1;
+            }
+        };
+
+        if let Some(announce) = self.fields.get_mut(b"announce".as_slice()) {
+            replace(announce);
+        }
+        if let Some(BencodeValue::List(tiers)) = self.fields.get_mut(b"announce-list".as_slice()) {
+            for tier in tiers.iter_mut().filter_map(|tier| tier.as_list_mut()) {
+                tier.iter_mut().for_each(&mut replace);
+            }
+        }
+        replaced
+    }
+
+    /// Sets or removes the free-form comment.
+    pub fn set_comment(&mut self, comment: Option<&str>) {
+        match comment {
+            Some(comment) => self.insert("comment", BencodeValue::from(comment)),
+            None => {
+                self.fields.remove(b"comment".as_slice());
+            }
+        }
+    }
+
+    /// Replaces the BEP-0019 web seeds. An empty list removes the `url-list` field.
+    pub fn set_web_seeds(&mut self, web_seeds: &[Url]) {
+        if web_seeds.is_empty() {
+            self.fields.remove(b"url-list".as_slice());
+        } else {
+            let list = web_seeds.iter().map(|url| url.as_str().into()).collect();
+            self.insert("url-list", BencodeValue::List(list));
+        }
+    }
+
+    /// Replaces the BEP-0017 HTTP seeds. An empty list removes the `httpseeds` field.
+    pub fn set_http_seeds(&mut self, http_seeds: &[Url]) {
+        if http_seeds.is_empty() {
+            self.fields.remove(b"httpseeds".as_slice());
+        } else {
+            let list = http_seeds.iter().map(|url| url.as_str().into()).collect();
+            self.insert("httpseeds", BencodeValue::List(list));
+        }
+    }
+
+    /// Replaces the BEP-0005 DHT nodes, as `(host, port)` pairs. An empty list removes the
+    /// `nodes` field.
+    pub fn set_dht_nodes(&mut self, nodes: &[(String, u16)]) {
+        if nodes.is_empty() {
+            self.fields.remove(b"nodes".as_slice());
+        } else {
+            let list = nodes
+                .iter()
+                .map(|(host, port)| {
+                    BencodeValue::List(vec![
+                        host.as_str().into(),
+                        BencodeValue::Int((*port).into()),
+                    ])
+                })
+                .collect();
+            self.insert("nodes", BencodeValue::List(list));
+        }
+    }
+
+    /// Sets any field outside of the info dict, such as a private `x_cross_seed` field.
+    pub fn set_field(&mut self, key: &str, value: BencodeValue) -> Result<(), TorrentEditError> {
+        if key == "info" {
+            return Err(TorrentEditError::InfoDictChange);
+        }
+        self.insert(key, value);
+        Ok(())
+    }
+
+    /// Removes any field outside of the info dict, returning its previous value.
+    pub fn remove_field(&mut self, key: &str) -> Result<Option<BencodeValue>, TorrentEditError> {
+        if key == "info" {
+            return Err(TorrentEditError::InfoDictChange);
+        }
+        Ok(self.fields.remove(key.as_bytes()))
+    }
+
+    fn insert(&mut self, key: &str, value: BencodeValue) {
+        self.fields.insert(ByteString::from(key), value);
+    }
+
+    /// Returns the bencoded bytes of the edited torrent.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![b'd'];
+        let mut info_written = false;
+        for (key, value) in &self.fields {
+            // Keys are sorted, with the original info dict at its place
+            if !info_written && key.as_slice() > b"info".as_slice() {
+                write_info(&mut bytes, &self.info);
+                info_written = true;
+            }
+            // Values were decoded from bencode or built by the editor, so they can be encoded
+            bytes.extend(bt_bencode::to_vec(key).unwrap());
+            bytes.extend(bt_bencode::to_vec(value).unwrap());
+        }
+        if !info_written {
+            write_info(&mut bytes, &self.info);
+        }
+        bytes.push(b'e');
+        bytes
+    }
+
+    /// Parses the edited torrent, which has the same infohash as the original torrent.
+    pub fn build(&self) -> Result<TorrentFile, TorrentEditError> {
+        let torrent = TorrentFile::from_slice(&self.to_bytes())?;
+        if InfoHash::from(&torrent) != self.hash {
+            return Err(TorrentEditError::InfoDictChange);
+        }
+        Ok(torrent)
+    }
+}
+
+fn write_info(bytes: &mut Vec<u8>, info: &[u8]) {
+    bytes.extend_from_slice(b"4:info");
+    bytes.extend_from_slice(info);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load() -> TorrentFile {
+        let slice = std::fs::read("tests/bittorrent-v2-hybrid-test.torrent").unwrap();
+        TorrentFile::from_slice(&slice).unwrap()
+    }
+
+    #[test]
+    fn unchanged_torrent_roundtrips() {
+        let torrent = load();
+        let editor = TorrentEditor::new(&torrent);
+        assert_eq!(editor.to_bytes(), torrent.as_bytes());
+    }
+
+    #[test]
+    fn edits_trackers() {
+        let torrent = load();
+        let first = Tracker::new("udp://tracker.example:6969").unwrap();
+        let second = Tracker::new("https://other.example/announce").unwrap();
+        let mut editor = TorrentEditor::new(&torrent);
+        editor.set_tracker_tiers(&TrackerTiers::from_tiers(vec![
+            vec![first.clone()],
+            vec![second.clone(), first.clone()],
+        ]));
+
+        let replacement = Tracker::new("udp://new.example:1337").unwrap();
+        let same = Tracker::new("udp://TRACKER.example:6969/").unwrap();
+        assert_eq!(editor.replace_tracker(&same, &replacement), 3);
+
+        let edited = editor.build().unwrap();
+        assert_eq!(edited.hash(), torrent.hash());
+        assert_eq!(
+            edited.tracker_tiers().tiers(),
+            &[vec![replacement.clone()], vec![second, replacement]]
+        );
+    }
+
+    #[test]
+    fn edits_metadata() {
+        let torrent = load();
+        let seed = Url::parse("https://seed.example/files/").unwrap();
+        let mut editor = TorrentEditor::new(&torrent);
+        editor.set_comment(Some("comment"));
+        editor.set_web_seeds(std::slice::from_ref(&seed));
+        editor.set_http_seeds(std::slice::from_ref(&seed));
+        editor.set_dht_nodes(&[("dht.example".to_string(), 6881)]);
+        editor
+            .set_field("x_cross_seed", BencodeValue::from("abc"))
+            .unwrap();
+        assert!(editor.remove_field("created by").unwrap().is_some());
+
+        let edited = editor.build().unwrap();
+        assert_eq!(edited.hash(), torrent.hash());
+        assert_eq!(edited.comment(), Some("comment"));
+        assert_eq!(edited.web_seeds(), std::slice::from_ref(&seed));
+        assert_eq!(edited.http_seeds(), &[seed]);
+        assert_eq!(edited.dht_nodes(), vec![("dht.example".to_string(), 6881)]);
+        assert_eq!(edited.created_by(), None);
+        assert_eq!(edited.decoded().extra_str("x_cross_seed"), Some("abc"));
+
+        editor.set_comment(None);
+        editor.set_web_seeds(&[]);
+        editor.set_http_seeds(&[]);
+        editor.set_dht_nodes(&[]);
+        let edited = editor.build().unwrap();
+        assert_eq!(edited.comment(), None);
+        assert!(edited.web_seeds().is_empty());
+        assert!(edited.http_seeds().is_empty());
+        assert!(edited.dht_nodes().is_empty());
+    }
+
+    #[test]
+    fn refuses_info_edits() {
+        let mut editor = TorrentEditor::new(&load());
+        assert_eq!(
+            editor.set_field("info", BencodeValue::from("nope")),
+            Err(TorrentEditError::InfoDictChange)
+        );
+        assert_eq!(
+            editor.remove_field("info"),
+            Err(TorrentEditError::InfoDictChange)
+        );
+    }
+}
@@ -0,0 +1,488 @@
+//! Import and export of [`TorrentList`](crate::list::TorrentList)s to interchange formats.
+
+use std::str::FromStr;
+
+use crate::{
+    Category, InfoHash, PeerSource, Progress, Timestamp, Torrent, TorrentLimits, TorrentList,
+};
+
+/// Error occurred while importing a [`TorrentList`](crate::list::TorrentList).
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExportError {
+    // serde_json::Error is not PartialEq so we store error as String
+    InvalidJson { reason: String },
+    InvalidCsv { line: usize, reason: String },
+}
+
+impl std::fmt::Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::InvalidJson { reason } => write!(f, "Invalid JSON: {reason}"),
+            ExportError::InvalidCsv { line, reason } => {
+                write!(f, "Invalid CSV on line {line}: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Error> for ExportError {
+    fn from(e: serde_json::Error) -> ExportError {
+        ExportError::InvalidJson {
+            reason: e.to_string(),
+        }
+    }
+}
+
+/// The columns of the CSV export, in order.
+const CSV_HEADER: [&str; 14] = [
+    "hash",
+    "name",
+    "path",
+    "size",
+    "bytes_done",
+    "bytes_total",
+    "date_start",
+    "date_end",
+    "uploaded",
+    "downloaded",
+    "state",
+    "tags",
+    "category",
+    "sources",
+];
+
+impl TorrentList {
+    /// Exports the TorrentList as indented JSON, which can be imported again with
+    /// [`TorrentList::from_json`](crate::list::TorrentList::from_json).
+    #[cfg(feature = "json")]
+    pub fn to_json_pretty(&self) -> String {
+        // Serializing a TorrentList to a String cannot fail
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Imports a TorrentList from JSON, as exported by
+    /// [`TorrentList::to_json_pretty`](crate::list::TorrentList::to_json_pretty).
+    #[cfg(feature = "json")]
+    pub fn from_json(s: &str) -> Result<TorrentList, ExportError> {
+        Ok(serde_json::from_str(s)?)
+    }
+
+    /// Exports the TorrentList as CSV ([RFC 4180](https://www.rfc-editor.org/rfc/rfc4180)),
+    /// with a header line.
+    ///
+    /// Hybrid infohashes are exported with both digests, tags are separated by commas, and
+    /// peer sources by spaces. Unknown dates and counters are empty.
+    pub fn to_csv(&self) -> String {
+        let mut out = csv_line(CSV_HEADER.iter().map(|h| h.to_string()));
+        for t in self.iter() {
+            let opt = |v: Option<u64>| v.map(|v| v.to_string()).unwrap_or_default();
+            let date =
+                |d: Option<Timestamp>| d.map(|d| d.as_secs().to_string()).unwrap_or_default();
+            out.push_str(&csv_line([
+                t.hash.to_full_string(),
+                t.name.clone(),
+                t.path.clone(),
+                t.size.to_string(),
+                t.progress.bytes_done.to_string(),
+                t.progress.bytes_total.to_string(),
+                date(t.date_start),
+                date(t.date_end),
+                opt(t.uploaded),
+                opt(t.downloaded),
+                t.state.clone(),
+                t.tags.join(","),
+                t.category
+                    .as_ref()
+                    .map(|c| c.to_string())
+                    .unwrap_or_default(),
+                t.sources
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" "),
+            ]));
+        }
+        out
+    }
+
+    /// Imports a TorrentList from CSV, as exported by
+    /// [`TorrentList::to_csv`](crate::list::TorrentList::to_csv). Columns are matched by
+    /// their header, and only the `hash` column is required.
+    pub fn from_csv(s: &str) -> Result<TorrentList, ExportError> {
+        let mut records = parse_csv(s)?.into_iter();
+        let header = match records.next() {
+            Some((_, header)) => header,
+            None => return Ok(TorrentList::new()),
+        };
+        let column = |name: &str| header.iter().position(|h| h == name);
+        let hash_column = column("hash").ok_or(ExportError::InvalidCsv {
+            line: 1,
+            reason: "missing hash column".to_string(),
+        })?;
+
+        let mut list = TorrentList::new();
+        for (line, record) in records {
+            let err = |reason: String| ExportError::InvalidCsv { line, reason };
+            let field = |name: &str| {
+                column(name)
+                    .and_then(|i| record.get(i))
+                    .map(|f| f.as_str())
+                    .unwrap_or("")
+            };
+            let number = |name: &str| -> Result<Option<u64>, ExportError> {
+                match field(name) {
+                    "" => Ok(None),
+                    value => value
+                        .parse()
+                        .map(Some)
+                        .map_err(|_| err(format!("invalid {name}: {value}"))),
+                }
+            };
+            let date = |name: &str| -> Result<Option<Timestamp>, ExportError> {
+                match field(name) {
+                    "" => Ok(None),
+                    value => value
+                        .parse()
+                        .map(Timestamp::from_secs_opt)
+                        .map_err(|_| err(format!("invalid {name}: {value}"))),
+                }
+            };
+
+            let hash = record.get(hash_column).map(|h| h.as_str()).unwrap_or("");
+            let hash = InfoHash::new(hash).map_err(|e| err(e.to_string()))?;
+            let size = match field("size") {
+                "" => 0,
+                value => value
+                    .parse()
+                    .map_err(|_| err(format!("invalid size: {value}")))?,
+            };
+            let sources = field("sources")
+                .split(' ')
+                .filter(|s| !s.is_empty())
+                .map(PeerSource::from_str)
+                .collect::<Result<Vec<PeerSource>, _>>()
+                .map_err(|e| err(e.to_string()))?;
+
+            list.push(Torrent {
+                name: field("name").to_string(),
+                path: field("path").to_string(),
+                date_start: date("date_start")?,
+                date_end: date("date_end")?,
+                progress: Progress::new(
+                    number("bytes_done")?.unwrap_or(0),
+                    number("bytes_total")?.unwrap_or(0),
+                ),
+                size,
+                uploaded: number("uploaded")?,
+                downloaded: number("downloaded")?,
+                state: field("state").to_string(),
+                tags: field("tags")
+                    .split(',')
+                    .filter(|t| !t.is_empty())
+                    .map(String::from)
+                    .collect(),
+                category: match field("category") {
+                    "" => None,
+                    value => Some(Category::new(value).map_err(|e| err(e.to_string()))?),
+                },
+                sources,
+                limits: TorrentLimits::default(),
+                id: hash.id(),
+                hash,
+            });
+        }
+        Ok(list)
+    }
+}
+
+/// Formats a CSV record, quoting fields when needed.
+fn csv_line<I: IntoIterator<Item = String>>(fields: I) -> String {
+    let fields: Vec<String> = fields
+        .into_iter()
+        .map(|field| {
+            if field.contains(['"', ',', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+        .collect();
+    format!("{}\r\n", fields.join(","))
+}
+
+/// Parses CSV records, along with the line number they start on. Empty lines are skipped.
+fn parse_csv(s: &str) -> Result<Vec<(usize, Vec<String>)>, ExportError> {
+    let mut records = Vec::new();
+    let mut record: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                if record.len() > 1 || !record[0].is_empty() {
+                    records.push((record_line, std::mem::take(&mut record)));
+                }
+                record.clear();
+                line += 1;
+                record_line = line;
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if quoted {
+        return Err(ExportError::InvalidCsv {
+            line: record_line,
+            reason: "unterminated quoted field".to_string(),
+        });
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((record_line, record));
+    }
+    Ok(records)
+}
+
+#[cfg(all(feature = "json", feature = "transmission"))]
+mod transmission {
+    use serde_json::{json, Value};
+
+    use super::ExportError;
+    use crate::{PeerSource, ToTorrent, Torrent, TorrentList, TransmissionTorrent};
+
+    /// Guesses the numeric Transmission status of a torrent from its state and progress, as
+    /// parsed by [`TransmissionStatus`](crate::transmission::TransmissionStatus).
+    fn transmission_status(torrent: &Torrent) -> i64 {
+        match torrent.state.to_lowercase().as_str() {
+            "stopped" | "paused" => 0,
+            "check-wait" => 1,
+            "check" => 2,
+            "download-wait" => 3,
+            "seed-wait" => 5,
+            _ if torrent.progress.is_complete() => 6,
+            _ => 4,
+        }
+    }
+
+    impl TorrentList {
+        /// Exports the TorrentList as a JSON array of torrents, with the fields of the
+        /// Transmission RPC `torrent-get` method, so that it can be read by tools built for
+        /// Transmission.
+        pub fn to_transmission_json(&self) -> String {
+            let torrents: Vec<Value> = self
+                .iter()
+                .map(|t| {
+                    json!({
+                        // Transmission identifies hybrid torrents by their infohash v1
+                        "hashString": t.hash.v1().unwrap_or(t.hash.as_str()).as_str(),
+                        "name": t.name,
+                        "downloadDir": t.path,
+                        "addedDate": t.date_start.map(|d| d.as_secs()).unwrap_or(0),
+                        "doneDate": t.date_end.map(|d| d.as_secs()).unwrap_or(0),
+                        "percentDone": t.progress.percent() / 100.0,
+                        "totalSize": t.size,
+                        "uploadedEver": t.uploaded.unwrap_or(0),
+                        "downloadedEver": t.downloaded.unwrap_or(0),
+                        "status": transmission_status(t),
+                        "labels": t.tags,
+                        "trackers": t.sources.iter().filter_map(|s| match s {
+                            PeerSource::Tracker(tracker) => Some(tracker.url()),
+                            _ => None,
+                        }).enumerate().map(|(i, url)| json!({
+                            "announce": url,
+                            "id": i,
+                            "tier": i,
+                        })).collect::<Vec<Value>>(),
+                    })
+                })
+                .collect();
+            // Serializing a Value to a String cannot fail
+            serde_json::to_string_pretty(&torrents).unwrap()
+        }
+
+        /// Imports a TorrentList from the torrents returned by the Transmission RPC
+        /// `torrent-get` method. Accepts the full RPC response, its `arguments` object,
+        /// or the array of torrents.
+        ///
+        /// Transmission does not report infohash v2, so hybrid torrents are imported with
+        /// their infohash v1 only.
+        pub fn from_transmission_json(s: &str) -> Result<TorrentList, ExportError> {
+            let mut value: Value = serde_json::from_str(s)?;
+            if let Some(arguments) = value.get_mut("arguments") {
+                value = arguments.take();
+            }
+            if let Some(torrents) = value.get_mut("torrents") {
+                value = torrents.take();
+            }
+
+            let torrents: Vec<TransmissionTorrent> = serde_json::from_value(value)?;
+            Ok(torrents.iter().map(|t| t.to_torrent()).collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Tracker;
+
+    fn list() -> TorrentList {
+        let hybrid = InfoHash::new("631a31dd0a46257d5078c0dee4e66e26f73e42ac")
+            .unwrap()
+            .hybrid(
+                &InfoHash::new("d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb")
+                    .unwrap(),
+            )
+            .unwrap();
+        let tracker = Tracker::new("udp://tracker.opentrackr.org:1337/announce").unwrap();
+
+        TorrentList::from_vec(vec![
+            Torrent::builder()
+                .hash(hybrid)
+                .name("bittorrent-v1-v2-hybrid-test, \"quoted\"\nname")
+                .path("/data/torrents")
+                .size(1024)
+                .progress(Progress::new(1024, 1024))
+                .date_start(Timestamp::from_secs(1591173906))
+                .uploaded(2048)
+                .downloaded(1024)
+                .state("seeding")
+                .tags(vec!["demoscene".to_string(), "amiga".to_string()])
+                .category(Category::new("demos/amiga").unwrap())
+                .sources(vec![PeerSource::DHT, tracker.to_peer_source()])
+                .build()
+                .unwrap(),
+            Torrent::builder()
+                .hash(InfoHash::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap())
+                .name("Goldman, Emma - Essential Works of Anarchism")
+                .build()
+                .unwrap(),
+        ])
+    }
+
+    fn assert_same(left: &TorrentList, right: &TorrentList) {
+        assert_eq!(left.len(), right.len());
+        for (l, r) in left.iter().zip(right.iter()) {
+            assert_eq!(l.hash, r.hash);
+            assert_eq!(l.id, r.id);
+            assert_eq!(l.name, r.name);
+            assert_eq!(l.path, r.path);
+            assert_eq!(l.size, r.size);
+            assert_eq!(l.progress, r.progress);
+            assert_eq!(l.date_start, r.date_start);
+            assert_eq!(l.date_end, r.date_end);
+            assert_eq!(l.uploaded, r.uploaded);
+            assert_eq!(l.downloaded, r.downloaded);
+            assert_eq!(l.state, r.state);
+            assert_eq!(l.tags, r.tags);
+            assert_eq!(l.category, r.category);
+            assert_eq!(l.sources, r.sources);
+        }
+    }
+
+    #[test]
+    fn csv_roundtrip() {
+        let csv = list().to_csv();
+        assert!(csv.starts_with("hash,name,path,size,"));
+        assert!(csv.contains("\"bittorrent-v1-v2-hybrid-test, \"\"quoted\"\"\nname\""));
+        assert!(csv.contains(",\"demoscene,amiga\",demos/amiga,dht udp://"));
+        assert_same(&TorrentList::from_csv(&csv).unwrap(), &list());
+    }
+
+    #[test]
+    fn csv_errors() {
+        assert!(TorrentList::from_csv("").unwrap().is_empty());
+        assert_eq!(
+            TorrentList::from_csv("name\ntest\n").unwrap_err(),
+            ExportError::InvalidCsv {
+                line: 1,
+                reason: "missing hash column".to_string()
+            }
+        );
+        assert!(matches!(
+            TorrentList::from_csv("hash,name\n\"multi\nline\",test\ninvalid,test\n").unwrap_err(),
+            ExportError::InvalidCsv { line: 2, .. }
+        ));
+        assert!(matches!(
+            TorrentList::from_csv("hash,size\nc811b41641a09d192b8ed81b14064fff55d85ce3,big\n")
+                .unwrap_err(),
+            ExportError::InvalidCsv { line: 2, .. }
+        ));
+        assert!(TorrentList::from_csv("hash\n\"unterminated\n").is_err());
+
+        // Only the hash column is required
+        let list =
+            TorrentList::from_csv("hash\r\nc811b41641a09d192b8ed81b14064fff55d85ce3\r\n").unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.iter().next().unwrap().uploaded, None);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_roundtrip() {
+        let json = list().to_json_pretty();
+        assert!(json.contains("\n  "));
+        assert_same(&TorrentList::from_json(&json).unwrap(), &list());
+        assert!(matches!(
+            TorrentList::from_json("{").unwrap_err(),
+            ExportError::InvalidJson { .. }
+        ));
+    }
+
+    #[cfg(feature = "transmission")]
+    #[cfg(feature = "json")]
+    #[test]
+    fn transmission_roundtrip() {
+        let json = list().to_transmission_json();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value[0]["hashString"],
+            "631a31dd0a46257d5078c0dee4e66e26f73e42ac"
+        );
+        assert_eq!(value[0]["status"], 6);
+        assert_eq!(value[1]["status"], 4);
+
+        let imported = TorrentList::from_transmission_json(&json).unwrap();
+        assert_eq!(imported.len(), 2);
+        let first = imported.iter().next().unwrap();
+        assert!(first.hash.is_v1());
+        assert_eq!(first.name, list().iter().next().unwrap().name);
+        assert_eq!(first.uploaded, Some(2048));
+        assert_eq!(first.sources.len(), 1);
+
+        let response = format!(r#"{{"arguments": {{"torrents": {json}}}, "result": "success"}}"#);
+        assert_eq!(
+            TorrentList::from_transmission_json(&response)
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Entry points for fuzz targets, which check that parsers are stable.
+//!
+//! Each function accepts any input. When the input can be parsed, it is serialized and parsed
+//! again, and the function panics if the second parse fails or differs from the first. The
+//! fuzz targets in the `fuzz` directory of the repository call these functions with
+//! [cargo-fuzz](https://github.com/rust-fuzz/cargo-fuzz).
+
+use crate::{InfoHash, MagnetLink, PeerAddr, TorrentEditor, TorrentFile, TorrentFileRef};
+
+/// Checks that a parsed [`MagnetLink`](crate::magnet::MagnetLink) is formatted as a magnet URI
+/// which is parsed to the same MagnetLink.
+pub fn roundtrip_magnet(s: &str) {
+    let magnet = match MagnetLink::new(s) {
+        Ok(magnet) => magnet,
+        Err(_) => return,
+    };
+
+    let formatted = magnet.to_string();
+    let reparsed = MagnetLink::new(&formatted)
+        .unwrap_or_else(|e| panic!("formatted magnet {formatted:?} cannot be parsed: {e}"));
+    assert_eq!(magnet.hash(), reparsed.hash());
+    assert_eq!(magnet.name(), reparsed.name());
+    assert_eq!(magnet.trackers(), reparsed.trackers());
+    assert_eq!(magnet.web_seeds(), reparsed.web_seeds());
+    assert_eq!(magnet.acceptable_sources(), reparsed.acceptable_sources());
+    assert_eq!(magnet.peers(), reparsed.peers());
+    assert_eq!(formatted, reparsed.to_string());
+}
+
+/// Checks that a parsed [`TorrentFile`](crate::torrent_file::TorrentFile) is parsed again from its
+/// bytes and from its info dict with the same infohash, including when borrowed as a
+/// [`TorrentFileRef`](crate::torrent_file_ref::TorrentFileRef) or rewritten by a
+/// [`TorrentEditor`](crate::editor::TorrentEditor).
+pub fn roundtrip_torrent(bytes: &[u8]) {
+    let torrent = match TorrentFile::from_slice(bytes) {
+        Ok(torrent) => torrent,
+        Err(_) => return,
+    };
+
+    let reparsed = TorrentFile::from_slice(torrent.as_bytes()).unwrap();
+    assert_eq!(torrent.hash(), reparsed.hash());
+    assert_eq!(torrent.name(), reparsed.name());
+
+    let hash = InfoHash::from_info_dict(torrent.info_bytes(), None).unwrap();
+    assert_eq!(torrent.hash(), hash.as_str());
+
+    // The borrowed parser is more lenient, but must agree on the infohash
+    let borrowed = TorrentFileRef::from_slice(bytes).unwrap();
+    assert_eq!(torrent.hash(), borrowed.hash());
+
+    let edited = TorrentEditor::new(&torrent).build().unwrap();
+    assert_eq!(torrent.hash(), edited.hash());
+}
+
+/// Checks that a parsed [`InfoHash`](crate::hash::InfoHash) is formatted as a string which is
+/// parsed to the same InfoHash.
+pub fn roundtrip_infohash(s: &str) {
+    let hash = match InfoHash::new(s) {
+        Ok(hash) => hash,
+        Err(_) => return,
+    };
+
+    let formatted = hash.to_full_string();
+    let reparsed = InfoHash::new(&formatted)
+        .unwrap_or_else(|e| panic!("formatted infohash {formatted:?} cannot be parsed: {e}"));
+    assert_eq!(hash, reparsed);
+    assert_eq!(hash.id(), reparsed.id());
+}
+
+/// Checks that compact IPv4 and IPv6 peers decoded by
+/// [`PeerAddr::compact_v4`](crate::peer::PeerAddr::compact_v4) and
+/// [`PeerAddr::compact_v6`](crate::peer::PeerAddr::compact_v6) are encoded to the same bytes.
+pub fn roundtrip_compact_peers(bytes: &[u8]) {
+    if let Ok(peers) = PeerAddr::compact_v4(bytes) {
+        assert_eq!(peers.len(), bytes.len() / PeerAddr::COMPACT_V4_LEN);
+        let peers: Vec<_> = peers.collect();
+        assert_eq!(PeerAddr::encode_compact_v4(&peers), bytes);
+    }
+    if let Ok(peers) = PeerAddr::compact_v6(bytes) {
+        assert_eq!(peers.len(), bytes.len() / PeerAddr::COMPACT_V6_LEN);
+        let peers: Vec<_> = peers.collect();
+        assert_eq!(PeerAddr::encode_compact_v6(&peers), bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_inputs_roundtrip() {
+        for path in [
+            "tests/bittorrent-v1-emma-goldman.magnet",
+            "tests/bittorrent-v2-hybrid-test.magnet",
+        ] {
+            roundtrip_magnet(std::fs::read_to_string(path).unwrap().trim());
+        }
+        for path in [
+            "tests/bittorrent-v1-emma-goldman.torrent",
+            "tests/bittorrent-v2-test.torrent",
+            "tests/bittorrent-v2-hybrid-test.torrent",
+        ] {
+            roundtrip_torrent(&std::fs::read(path).unwrap());
+        }
+        roundtrip_infohash("c811b41641a09d192b8ed81b14064fff55d85ce3");
+        roundtrip_infohash("ZAI3ILCAUGOA3GFYQKOBOBU55AMXMDG6");
+        roundtrip_compact_peers(&[0xff; 36]);
+    }
+
+    #[test]
+    fn invalid_inputs_are_ignored() {
+        roundtrip_magnet("magnet:?dn=nohash");
+        roundtrip_torrent(b"d4:infoi1ee");
+        roundtrip_infohash("nothex");
+        roundtrip_compact_peers(b"abcde");
+    }
+
+    #[test]
+    fn deeply_nested_inputs_do_not_overflow() {
+        // Found by fuzzing: a truncated deep list in a field decoded as a bencode value
+        let mut truncated = b"d13:announce-list".to_vec();
+        truncated.extend(std::iter::repeat(b'l').take(1_000_000));
+        roundtrip_torrent(&truncated);
+
+        let mut terminated = truncated.clone();
+        terminated.extend(std::iter::repeat(b'e').take(1_000_001));
+        roundtrip_torrent(&terminated);
+    }
+}
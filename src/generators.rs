@@ -0,0 +1,226 @@
+//! Generation of structured inputs for fuzzing and property-based testing.
+//!
+//! With the `arbitrary` feature, [`arbitrary::Arbitrary`] is implemented for
+//! [`InfoHash`](crate::hash::InfoHash), [`TorrentID`](crate::id::TorrentID),
+//! [`MagnetLink`](crate::magnet::MagnetLink) and
+//! [`DecodedTorrent`](crate::torrent_file::DecodedTorrent), for fuzz targets. With the `proptest`
+//! feature, [`proptest::arbitrary::Arbitrary`] is implemented for the same types.
+//!
+//! Generated infohashes are real digests, and magnet links are built from a generated query which
+//! is then parsed. Generated torrents can always be parsed, but their names are arbitrary so they
+//! may not pass [`TorrentFile::validate`](crate::torrent_file::TorrentFile::validate).
+
+use bt_bencode::{ByteString, Value as BencodeValue};
+
+use std::collections::HashMap;
+
+use crate::magnet::encode_param;
+use crate::torrent_file::{DecodedInfo, DecodedTorrent};
+use crate::{InfoHash, MagnetLink, TorrentID};
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Builds an infohash from generated digests, the variant being chosen by `kind`.
+fn infohash(kind: u8, v1: &[u8; 20], v2: &[u8; 32]) -> InfoHash {
+    match kind % 3 {
+        0 => InfoHash::new(&to_hex(v1)).unwrap(),
+        1 => InfoHash::new(&to_hex(v2)).unwrap(),
+        _ => InfoHash::new(&format!("{}:{}", to_hex(v1), to_hex(v2))).unwrap(),
+    }
+}
+
+/// Builds a magnet link from its generated parts. Trackers are given by their port.
+fn magnet(hash: &InfoHash, name: &str, trackers: &[u16]) -> MagnetLink {
+    let mut query = Vec::new();
+    if let Some(v1) = hash.v1() {
+        query.push(format!("xt=urn:btih:{v1}"));
+    }
+    if let Some(v2) = hash.v2() {
+        query.push(format!("xt=urn:btmh:1220{v2}"));
+    }
+    // Some builds require a name, so it is never empty
+    let name = if name.is_empty() { "torrent" } else { name };
+    query.push(format!("dn={}", encode_param(name)));
+    for (i, port) in trackers.iter().enumerate() {
+        let url = format!("udp://tracker{i}.example:{}/announce", port.max(&1));
+        query.push(format!("tr={}", encode_param(&url)));
+    }
+
+    let s = format!("magnet:?{}", query.join("&"));
+    // The query only contains valid parts
+    MagnetLink::new(&s).unwrap()
+}
+
+/// Builds a v1 torrent from its generated parts: a single file when there is one length, or
+/// a directory of files named after their index.
+fn decoded_torrent(
+    name: String,
+    lengths: &[u32],
+    piece_length: u32,
+    announce: Option<u16>,
+    comment: Option<String>,
+) -> DecodedTorrent {
+    // Power of two between 16KiB and 4MiB
+    let piece_length = 1u64 << (14 + piece_length % 9);
+    let total: u64 = lengths.iter().map(|l| *l as u64).sum();
+    let pieces = (total + piece_length - 1) / piece_length;
+
+    let mut extra = HashMap::new();
+    extra.insert("piece length".to_string(), BencodeValue::from(piece_length));
+    extra.insert(
+        "pieces".to_string(),
+        BencodeValue::ByteStr(ByteString::from(vec![0; pieces as usize * 20])),
+    );
+
+    let (length, files) = match lengths {
+        [length] => (Some(*length as u64), None),
+        lengths => {
+            let files = lengths
+                .iter()
+                .enumerate()
+                .map(|(i, length)| {
+                    let mut file = std::collections::BTreeMap::new();
+                    file.insert(ByteString::from("length"), BencodeValue::from(*length));
+                    file.insert(
+                        ByteString::from("path"),
+                        BencodeValue::List(vec![BencodeValue::from(format!("{i}.bin"))]),
+                    );
+                    BencodeValue::Dict(file)
+                })
+                .collect();
+            (None, Some(files))
+        }
+    };
+
+    DecodedTorrent {
+        info: DecodedInfo {
+            version: None,
+            name,
+            length,
+            files,
+            file_tree: None,
+            extra,
+        },
+        announce: announce.map(|port| {
+            ByteString::from(format!("udp://tracker.example:{}/announce", port.max(1)))
+        }),
+        announce_list: None,
+        url_list: None,
+        httpseeds: None,
+        nodes: None,
+        comment: comment.map(|c| BencodeValue::from(c.as_str())),
+        creation_date: None,
+        created_by: None,
+        encoding: None,
+        extra: HashMap::new(),
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod impl_arbitrary {
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    use super::*;
+
+    impl<'a> Arbitrary<'a> for InfoHash {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<InfoHash> {
+            Ok(infohash(u.arbitrary()?, &u.arbitrary()?, &u.arbitrary()?))
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for TorrentID {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<TorrentID> {
+            Ok(TorrentID::from_infohash(&u.arbitrary()?))
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for MagnetLink {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<MagnetLink> {
+            let hash: InfoHash = u.arbitrary()?;
+            let name: String = u.arbitrary()?;
+            let trackers: Vec<u16> = u.arbitrary_iter()?.take(4).collect::<Result<_>>()?;
+            Ok(magnet(&hash, &name, &trackers))
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for DecodedTorrent {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<DecodedTorrent> {
+            let name: String = u.arbitrary()?;
+            let mut lengths: Vec<u32> = u.arbitrary_iter()?.take(8).collect::<Result<_>>()?;
+            if lengths.is_empty() {
+                lengths.push(u.arbitrary()?);
+            }
+            Ok(decoded_torrent(
+                name,
+                &lengths,
+                u.arbitrary()?,
+                u.arbitrary()?,
+                u.arbitrary()?,
+            ))
+        }
+    }
+}
+
+#[cfg(feature = "proptest")]
+mod impl_proptest {
+    use proptest::arbitrary::{any, Arbitrary};
+    use proptest::collection::vec;
+    use proptest::option;
+    use proptest::strategy::{BoxedStrategy, Strategy};
+
+    use super::*;
+
+    impl Arbitrary for InfoHash {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<InfoHash>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            (any::<u8>(), any::<[u8; 20]>(), any::<[u8; 32]>())
+                .prop_map(|(kind, v1, v2)| infohash(kind, &v1, &v2))
+                .boxed()
+        }
+    }
+
+    impl Arbitrary for TorrentID {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<TorrentID>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            any::<InfoHash>()
+                .prop_map(|hash| TorrentID::from_infohash(&hash))
+                .boxed()
+        }
+    }
+
+    impl Arbitrary for MagnetLink {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<MagnetLink>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            (any::<InfoHash>(), any::<String>(), vec(any::<u16>(), 0..4))
+                .prop_map(|(hash, name, trackers)| magnet(&hash, &name, &trackers))
+                .boxed()
+        }
+    }
+
+    impl Arbitrary for DecodedTorrent {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<DecodedTorrent>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            (
+                any::<String>(),
+                vec(any::<u32>(), 1..8),
+                any::<u32>(),
+                option::of(any::<u16>()),
+                option::of(any::<String>()),
+            )
+                .prop_map(|(name, lengths, piece_length, announce, comment)| {
+                    decoded_torrent(name, &lengths, piece_length, announce, comment)
+                })
+                .boxed()
+        }
+    }
+}
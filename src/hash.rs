@@ -2,7 +2,9 @@ use serde::{Deserialize, Serialize};
 
 use std::str::FromStr;
 
-use crate::TorrentID;
+use crate::bencode::DictRef;
+use crate::torrent_file::hash_info_dict;
+use crate::{TorrentFileError, TorrentID};
 
 /// Error occurred during parsing a [`InfoHash`](crate::hash::InfoHash).
 #[derive(Clone, Debug, PartialEq)]
@@ -40,17 +42,276 @@ impl std::fmt::Display for InfoHashError {
 
 impl std::error::Error for InfoHashError {}
 
+/// A binary digest of exactly `N` bytes, stored inline and rendered as lowercase hexadecimal.
+///
+/// The digests of an [`InfoHash`](crate::hash::InfoHash) are HexDigests, so that an InfoHash
+/// does not allocate: a v1 (sha1) digest is a `HexDigest<20>` and a v2 (sha256) digest is a
+/// `HexDigest<32>`. Their hexadecimal form is rendered on demand as a
+/// [`HexString`](crate::hash::HexString), so digests of more than 32 bytes are not supported.
+///
+/// ```
+/// use hightorrent::{HexDigest, InfoHash};
+///
+/// let digest = HexDigest::<20>::new("C811B41641A09D192B8ED81B14064FFF55D85CE3").unwrap();
+/// assert_eq!(digest.to_hex(), "c811b41641a09d192b8ed81b14064fff55d85ce3");
+/// assert_eq!(digest.as_bytes()[0], 0xc8);
+/// assert_eq!(InfoHash::V1(digest), InfoHash::new(&digest.to_hex()).unwrap());
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HexDigest<const N: usize>([u8; N]);
+
+impl<const N: usize> HexDigest<N> {
+    /// Parses a hexadecimal digest of `2 * N` characters, in any case.
+    pub fn new(hex: &str) -> Result<HexDigest<N>, InfoHashError> {
+        if !is_hex(hex) {
+            return Err(InfoHashError::InvalidChars {
+                hash: hex.to_string(),
+            });
+        }
+        if hex.len() != N * 2 {
+            return Err(InfoHashError::InvalidLength {
+                hash: hex.to_ascii_lowercase(),
+                len: hex.len(),
+            });
+        }
+
+        let mut digest = [0; N];
+        for (byte, pair) in digest.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            *byte = (hex_value(pair[0]) << 4) | hex_value(pair[1]);
+        }
+        Ok(HexDigest(digest))
+    }
+
+    /// Wraps the raw bytes of a digest.
+    pub fn from_bytes(bytes: [u8; N]) -> HexDigest<N> {
+        HexDigest(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; N] {
+        &self.0
+    }
+
+    /// Renders the digest as a lowercase hexadecimal string, without allocating.
+    pub fn to_hex(&self) -> HexString {
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
+        let mut hex = HexString {
+            buf: [0; HexString::CAPACITY],
+            len: (N * 2) as u8,
+        };
+        for (pair, byte) in hex.buf.chunks_mut(2).zip(self.0.iter()) {
+            pair[0] = DIGITS[(byte >> 4) as usize];
+            pair[1] = DIGITS[(byte & 0xf) as usize];
+        }
+        hex
+    }
+}
+
+/// Returns the value of an hexadecimal character, which was already validated.
+fn hex_value(c: u8) -> u8 {
+    match c {
+        b'0'..=b'9' => c - b'0',
+        b'a'..=b'f' => c - b'a' + 10,
+        _ => c - b'A' + 10,
+    }
+}
+
+impl<const N: usize> std::fmt::Debug for HexDigest<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.to_hex().as_str())
+    }
+}
+
+impl<const N: usize> std::fmt::Display for HexDigest<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+impl<const N: usize> PartialEq<str> for HexDigest<N> {
+    fn eq(&self, other: &str) -> bool {
+        self.to_hex() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for HexDigest<N> {
+    fn eq(&self, other: &&str) -> bool {
+        self.to_hex() == *other
+    }
+}
+
+impl<const N: usize> Serialize for HexDigest<N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.to_hex().as_str())
+    }
+}
+
+/// A HexDigest is described by its lowercase hex string.
+#[cfg(feature = "schemars")]
+impl<const N: usize> schemars::JsonSchema for HexDigest<N> {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        format!("HexDigest{N}").into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "pattern": format!("^[0-9a-f]{{{}}}$", N * 2),
+        })
+    }
+}
+
+/// The lowercase hexadecimal form of a [`HexDigest`](crate::hash::HexDigest), rendered on the
+/// stack. It dereferences to [`str`], and compares equal to strings with the same contents.
+///
+/// ```
+/// use hightorrent::InfoHash;
+///
+/// let hash = InfoHash::new("C811B41641A09D192B8ED81B14064FFF55D85CE3").unwrap();
+/// let hex = hash.as_str();
+/// assert_eq!(hex, "c811b41641a09d192b8ed81b14064fff55d85ce3");
+/// assert!(hex.starts_with("c811"));
+/// let owned: String = hex.into();
+/// ```
+#[derive(Clone, Copy)]
+pub struct HexString {
+    buf: [u8; HexString::CAPACITY],
+    len: u8,
+}
+
+impl HexString {
+    /// Length of the longest supported digest (sha256).
+    const CAPACITY: usize = 64;
+
+    pub fn as_str(&self) -> &str {
+        // The buffer is filled with hex digits up to len
+        std::str::from_utf8(&self.buf[..self.len as usize]).unwrap()
+    }
+}
+
+impl std::ops::Deref for HexString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for HexString {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl std::fmt::Debug for HexString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+impl std::fmt::Display for HexString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq for HexString {
+    fn eq(&self, other: &HexString) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for HexString {}
+
+impl std::hash::Hash for HexString {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl PartialEq<str> for HexString {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for HexString {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for HexString {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<HexString> for str {
+    fn eq(&self, other: &HexString) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl PartialEq<HexString> for &str {
+    fn eq(&self, other: &HexString) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl PartialEq<HexString> for String {
+    fn eq(&self, other: &HexString) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl From<HexString> for String {
+    fn from(hex: HexString) -> String {
+        hex.as_str().to_string()
+    }
+}
+
 /// A torrent's infohash, represented by a stringy lowercase hexadecimal digest.
 ///
 /// The [`InfoHash`] can be either
 /// a Bittorrent v1 info hash (40 chars sha1) or Bittorrent v2 info hash (64 chars sha256). In both cases, the hash
 /// is guaranteed to be a valid sha1/sha256 lowercase hex digest and not a random string.
 /// Alternatively, the Hybrid variant holds both v1 and v2 lowercase hex digests.
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+///
+/// The digests are [`HexDigest`](crate::hash::HexDigest)s holding the raw sha1/sha256 bytes
+/// inline, so an InfoHash takes 53 bytes and never allocates. It used to take 48 bytes, plus
+/// one heap allocation per hex digest (40 bytes for v1 and 64 bytes for v2, before allocator
+/// overhead): a v1 infohash now takes 53 bytes instead of 88, and a hybrid infohash 53 bytes
+/// instead of 152. Cloning an InfoHash is a copy, which matters when filtering large
+/// [`TorrentList`](crate::list::TorrentList)s. The hexadecimal form is rendered on demand as a
+/// [`HexString`](crate::hash::HexString).
+///
+/// InfoHash implements [`Eq`], [`Hash`](std::hash::Hash) and [`Ord`] so it can be used as a key in
+/// a [`HashMap`](std::collections::HashMap) or [`BTreeMap`](std::collections::BTreeMap).
+/// Equality is strict: a hybrid infohash is not equal to its v1 or v2 component. To find out
+/// whether two infohashes designate the same torrent, use
+/// [`InfoHash::matches`](crate::hash::InfoHash::matches) instead.
+/// Infohashes are ordered by variant first (V1, then V2, then Hybrid), then by digest.
+/// Hybrid infohashes are compared by their v1 digest, then by their v2 digest.
+///
+/// InfoHash is serialized as an externally tagged enum (`{"V1": "..."}`), and its digests are
+/// validated when deserialized. For a plain string representation, use
+/// [`CompactInfoHash`](crate::hash::CompactInfoHash) instead.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "TaggedInfoHash")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow),
+    diesel(sql_type = diesel::sql_types::Text)
+)]
 pub enum InfoHash {
-    V1(String),
-    V2(String),
-    Hybrid((String, String)),
+    V1(HexDigest<20>),
+    V2(HexDigest<32>),
+    Hybrid((HexDigest<20>, HexDigest<32>)),
 }
 
 impl InfoHash {
@@ -59,41 +320,92 @@ impl InfoHash {
     /// Will fail if the string contains non-hexadecimal characters, or if its length is not
     /// exactly 40 or 64 characters. This method should only be used for generating an actual InfoHash.
     ///
+    /// A hybrid infohash can be parsed from its `v1:v2` form, as produced by
+    /// [`InfoHash::to_full_string`](crate::hash::InfoHash::to_full_string).
+    ///
     /// If you want to match a torrent by user-submitted input, where a Bittorrent v1 digest and a
     /// truncated Bittorrent v2 are ambiguous, you should use [SingleTarget](crate::target::SingleTarget)
     /// instead. If you want to unambiguously designate a Torrent by a 40 characters identifier,
     /// you should use [`TorrentID`](crate::id::TorrentID) instead.
     pub fn new(hash: &str) -> Result<InfoHash, InfoHashError> {
-        if !hash.as_bytes().iter().all(|b| b.is_ascii_hexdigit()) {
-            return Err(InfoHashError::InvalidChars {
+        if let Some((v1, v2)) = hash.split_once(':') {
+            return match InfoHash::new(v1)? {
+                v1 @ InfoHash::V1(_) => v1.hybrid(&InfoHash::new(v2)?),
+                _ => Err(InfoHashError::InvalidChars {
+                    hash: hash.to_string(),
+                }),
+            };
+        }
+
+        match hash.len() {
+            40 => HexDigest::new(hash).map(InfoHash::V1),
+            64 => HexDigest::new(hash).map(InfoHash::V2),
+            _ if !is_hex(hash) => Err(InfoHashError::InvalidChars {
                 hash: hash.to_string(),
-            });
+            }),
+            len => Err(InfoHashError::InvalidLength {
+                hash: hash.to_ascii_lowercase(),
+                len,
+            }),
         }
+    }
 
-        let hash = hash.to_lowercase();
-        let len = hash.len();
+    /// Decodes a 32 characters base32-encoded infohash v1, as found in older magnet links.
+    ///
+    /// This is not done by [`InfoHash::new`](crate::hash::InfoHash::new), because some hex
+    /// strings are also valid base32.
+    pub(crate) fn from_base32(hash: &str) -> Result<InfoHash, InfoHashError> {
+        if hash.len() != 32 {
+            return Err(InfoHashError::InvalidLength {
+                hash: hash.to_ascii_lowercase(),
+                len: hash.len(),
+            });
+        }
 
-        if len == 40 {
-            Ok(InfoHash::V1(hash))
-        } else if len == 64 {
-            Ok(InfoHash::V2(hash))
-        } else {
-            Err(InfoHashError::InvalidLength {
+        match decode_base32(hash) {
+            Some(digest) => Ok(InfoHash::V1(HexDigest::from_bytes(digest))),
+            None => Err(InfoHashError::InvalidChars {
                 hash: hash.to_string(),
-                len,
-            })
+            }),
         }
     }
 
+    /// Computes the InfoHash of a raw bencoded info dict, such as received from the DHT or over
+    /// [BEP-0009](https://www.bittorrent.org/beps/bep_0009.html), without decoding the whole dict.
+    ///
+    /// The torrent version is read from the `meta version` field, unless a `version_hint` is
+    /// given. Hinting version 1 for a hybrid info dict only computes its v1 infohash.
+    ///
+    /// ```
+    /// use hightorrent::{InfoHash, TorrentFile};
+    ///
+    /// let slice = std::fs::read("tests/bittorrent-v2-hybrid-test.torrent").unwrap();
+    /// let torrent = TorrentFile::from_slice(&slice).unwrap();
+    ///
+    /// let hash = InfoHash::from_info_dict(torrent.info_bytes(), None).unwrap();
+    /// assert_eq!(hash.as_str(), torrent.hash());
+    /// let v1 = InfoHash::from_info_dict(torrent.info_bytes(), Some(1)).unwrap();
+    /// assert_eq!(v1.as_str(), hash.v1().unwrap());
+    /// ```
+    pub fn from_info_dict(
+        bytes: &[u8],
+        version_hint: Option<u64>,
+    ) -> Result<InfoHash, TorrentFileError> {
+        let not_a_torrent = |reason: String| TorrentFileError::NotATorrent { reason };
+        let (info, end) = DictRef::parse(bytes).map_err(not_a_torrent)?;
+        if end != bytes.len() {
+            return Err(not_a_torrent("trailing data".to_string()));
+        }
+        hash_info_dict(info, version_hint)
+    }
+
     /// Takes the current infohash and hybrids it with a second infohash.
     /// Returns an error if the two hash types are identical.
     pub fn hybrid(&self, with: &InfoHash) -> Result<InfoHash, InfoHashError> {
         match (&self, &with) {
-            (&InfoHash::V1(hash1), &InfoHash::V2(hash2)) => {
-                Ok(InfoHash::Hybrid((hash1.to_string(), hash2.to_string())))
-            }
-            (&InfoHash::V2(hash2), &InfoHash::V1(hash1)) => {
-                Ok(InfoHash::Hybrid((hash1.to_string(), hash2.to_string())))
+            (&InfoHash::V1(hash1), &InfoHash::V2(hash2))
+            | (&InfoHash::V2(hash2), &InfoHash::V1(hash1)) => {
+                Ok(InfoHash::Hybrid((*hash1, *hash2)))
             }
             (&InfoHash::V1(_), &InfoHash::V1(_)) => Err(InfoHashError::FailedHybrid {
                 hashtype: "V1".to_string(),
@@ -105,13 +417,24 @@ impl InfoHash {
         }
     }
 
-    /// Returns a stringy representation of the infohash. In case of an hybrid infohash, the v2
-    /// hash is used.
-    pub fn as_str(&self) -> &str {
+    /// Returns a stringy representation of the infohash, rendered without allocating. In case
+    /// of an hybrid infohash, the v2 hash is used.
+    pub fn as_str(&self) -> HexString {
+        match &self {
+            Self::V1(h1) => h1.to_hex(),
+            Self::V2(h2) => h2.to_hex(),
+            Self::Hybrid((_h1, h2)) => h2.to_hex(),
+        }
+    }
+
+    /// Returns a lossless string representation of the infohash, which can be parsed again
+    /// with [`InfoHash::new`](crate::hash::InfoHash::new). Unlike
+    /// [`as_str`](crate::hash::InfoHash::as_str), hybrid infohashes are represented with both
+    /// digests, in the `v1:v2` form.
+    pub fn to_full_string(&self) -> String {
         match &self {
-            Self::V1(s) => s,
-            Self::V2(s) => s,
-            Self::Hybrid((_h1, h2)) => h2,
+            Self::Hybrid((h1, h2)) => format!("{h1}:{h2}"),
+            _ => self.as_str().to_string(),
         }
     }
 
@@ -120,6 +443,90 @@ impl InfoHash {
     pub fn id(&self) -> TorrentID {
         TorrentID::from_infohash(self)
     }
+
+    /// Returns the infohash v1 digest, for v1 and hybrid infohashes.
+    pub fn v1(&self) -> Option<HexString> {
+        match &self {
+            Self::V1(h1) | Self::Hybrid((h1, _)) => Some(h1.to_hex()),
+            Self::V2(_) => None,
+        }
+    }
+
+    /// Returns the infohash v2 digest, for v2 and hybrid infohashes.
+    pub fn v2(&self) -> Option<HexString> {
+        match &self {
+            Self::V2(h2) | Self::Hybrid((_, h2)) => Some(h2.to_hex()),
+            Self::V1(_) => None,
+        }
+    }
+
+    /// Returns true for a v1-only infohash.
+    pub fn is_v1(&self) -> bool {
+        matches!(self, Self::V1(_))
+    }
+
+    /// Returns true for a v2-only infohash.
+    pub fn is_v2(&self) -> bool {
+        matches!(self, Self::V2(_))
+    }
+
+    /// Returns true for a hybrid infohash.
+    pub fn is_hybrid(&self) -> bool {
+        matches!(self, Self::Hybrid(_))
+    }
+
+    /// Returns whether two infohashes designate the same torrent.
+    ///
+    /// Unlike strict equality, a hybrid infohash matches a v1 or v2 infohash equal to one of
+    /// its components. The two infohashes must share at least one hash type, and all digests
+    /// of the shared hash types must be identical. For this reason, a v1 infohash never matches
+    /// a v2 infohash, and two hybrid infohashes with the same v1 digest but different v2
+    /// digests do not match.
+    pub fn matches(&self, other: &InfoHash) -> bool {
+        let v1 = match (self.v1(), other.v1()) {
+            (Some(a), Some(b)) => Some(a == b),
+            _ => None,
+        };
+        let v2 = match (self.v2(), other.v2()) {
+            (Some(a), Some(b)) => Some(a == b),
+            _ => None,
+        };
+
+        match (v1, v2) {
+            (None, None) => false,
+            (v1, v2) => v1.unwrap_or(true) && v2.unwrap_or(true),
+        }
+    }
+}
+
+/// Decodes a 32 characters RFC 4648 base32 string (case-insensitive, no padding) into
+/// a 20 bytes sha1 digest.
+fn decode_base32(s: &str) -> Option<[u8; 20]> {
+    let mut digest = [0u8; 20];
+    let mut buffer: u64 = 0;
+    let mut bits = 0;
+    let mut pos = 0;
+
+    for c in s.bytes() {
+        let value = match c.to_ascii_uppercase() {
+            c @ b'A'..=b'Z' => c - b'A',
+            c @ b'2'..=b'7' => c - b'2' + 26,
+            _ => return None,
+        };
+        buffer = (buffer << 5) | value as u64;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            digest[pos] = (buffer >> bits) as u8;
+            pos += 1;
+        }
+    }
+
+    if pos == 20 {
+        Some(digest)
+    } else {
+        None
+    }
 }
 
 impl std::fmt::Display for InfoHash {
@@ -136,6 +543,125 @@ impl FromStr for InfoHash {
     }
 }
 
+impl TryFrom<&str> for InfoHash {
+    type Error = InfoHashError;
+
+    fn try_from(s: &str) -> Result<InfoHash, Self::Error> {
+        InfoHash::new(s)
+    }
+}
+
+/// The serialized form of an [`InfoHash`](crate::hash::InfoHash), whose digests are validated
+/// before building the InfoHash.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename = "InfoHash")]
+enum TaggedInfoHash {
+    V1(String),
+    V2(String),
+    Hybrid((String, String)),
+}
+
+impl TryFrom<TaggedInfoHash> for InfoHash {
+    type Error = InfoHashError;
+
+    fn try_from(tagged: TaggedInfoHash) -> Result<InfoHash, InfoHashError> {
+        // Only hex digests of the expected length are accepted, not base32 or hybrid strings
+        let digest = |hash: String, len: usize| {
+            if hash.len() != len {
+                return Err(InfoHashError::InvalidLength {
+                    len: hash.len(),
+                    hash,
+                });
+            }
+            InfoHash::new(&hash)
+        };
+
+        match tagged {
+            TaggedInfoHash::V1(hash) => digest(hash, 40),
+            TaggedInfoHash::V2(hash) => digest(hash, 64),
+            TaggedInfoHash::Hybrid((v1, v2)) => digest(v1, 40)?.hybrid(&digest(v2, 64)?),
+        }
+    }
+}
+
+/// Returns whether the string only contains hexadecimal characters, in any case.
+///
+/// With the `faster-hex` feature, the string is validated with SIMD instructions when available.
+#[cfg(not(feature = "faster-hex"))]
+fn is_hex(hash: &str) -> bool {
+    hash.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+#[cfg(feature = "faster-hex")]
+fn is_hex(hash: &str) -> bool {
+    faster_hex::hex_check(hash.as_bytes())
+}
+
+/// An [`InfoHash`](crate::hash::InfoHash) serialized as a plain string, for APIs and databases.
+///
+/// The string is the one produced by [`InfoHash::to_full_string`](crate::hash::InfoHash::to_full_string):
+/// the lowercase hex digest for v1 and v2 infohashes, and `v1:v2` for hybrid infohashes. It is
+/// parsed with [`InfoHash::new`](crate::hash::InfoHash::new) when deserialized.
+///
+/// To migrate data serialized in the default tagged format, deserialize it as an
+/// [`InfoHash`](crate::hash::InfoHash) and serialize it again as a CompactInfoHash.
+///
+/// ```
+/// use hightorrent::{CompactInfoHash, InfoHash};
+///
+/// let hash = InfoHash::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap();
+/// let json = serde_json::to_string(&CompactInfoHash(hash.clone())).unwrap();
+/// assert_eq!(json, "\"c811b41641a09d192b8ed81b14064fff55d85ce3\"");
+///
+/// let compact: CompactInfoHash = serde_json::from_str(&json).unwrap();
+/// assert_eq!(InfoHash::from(compact), hash);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct CompactInfoHash(pub InfoHash);
+
+impl From<InfoHash> for CompactInfoHash {
+    fn from(hash: InfoHash) -> CompactInfoHash {
+        CompactInfoHash(hash)
+    }
+}
+
+impl From<CompactInfoHash> for InfoHash {
+    fn from(compact: CompactInfoHash) -> InfoHash {
+        compact.0
+    }
+}
+
+impl From<CompactInfoHash> for String {
+    fn from(compact: CompactInfoHash) -> String {
+        compact.0.to_full_string()
+    }
+}
+
+impl TryFrom<String> for CompactInfoHash {
+    type Error = InfoHashError;
+
+    fn try_from(s: String) -> Result<CompactInfoHash, InfoHashError> {
+        Ok(CompactInfoHash(InfoHash::new(&s)?))
+    }
+}
+
+/// A CompactInfoHash is described by its full string.
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for CompactInfoHash {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "CompactInfoHash".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "pattern": "^([0-9a-f]{40}|[0-9a-f]{64}|[0-9a-f]{40}:[0-9a-f]{64})$",
+        })
+    }
+}
+
 /// Try to turn a stringy value into an [InfoHash](crate::hash::InfoHash). For user-submitted data that may or may not be
 /// actual infohash, use [ToSingleTarget](crate::target::ToSingleTarget) instead.
 pub trait TryInfoHash {
@@ -159,7 +685,7 @@ impl TryInfoHash for InfoHash {
 
 impl TryInfoHash for &InfoHash {
     fn try_infohash(&self) -> Result<InfoHash, InfoHashError> {
-        InfoHash::new(self.as_str())
+        Ok((*self).clone())
     }
 }
 
@@ -174,7 +700,7 @@ mod tests {
         let hash = res.unwrap();
         assert_eq!(
             hash,
-            InfoHash::V1("c811b41641a09d192b8ed81b14064fff55d85ce3".to_string())
+            InfoHash::V1(HexDigest::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap())
         );
     }
 
@@ -186,7 +712,8 @@ mod tests {
         assert_eq!(
             hash,
             InfoHash::V2(
-                "caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e".to_string()
+                HexDigest::new("caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e")
+                    .unwrap()
             )
         );
     }
@@ -198,7 +725,53 @@ mod tests {
         let hash = res.unwrap();
         assert_eq!(
             hash,
-            InfoHash::V1("c811b41641a09d192b8ed81b14064fff55d85ce3".to_string())
+            InfoHash::V1(HexDigest::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap())
+        );
+    }
+
+    #[test]
+    fn can_load_base32_infohash_v1() {
+        let hash = InfoHash::from_base32("ZAI3IFSBUCORSK4O3ANRIBSP75K5QXHD").unwrap();
+        assert_eq!(
+            hash,
+            InfoHash::V1(HexDigest::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap())
+        );
+        assert_eq!(
+            InfoHash::from_base32("zai3ifsbucorsk4o3anribsp75k5qxhd").unwrap(),
+            hash
+        );
+    }
+
+    #[test]
+    fn does_not_load_base32_in_new() {
+        let res = InfoHash::new("ZAI3IFSBUCORSK4O3ANRIBSP75K5QXHD");
+        assert_eq!(
+            res.unwrap_err(),
+            InfoHashError::InvalidChars {
+                hash: "ZAI3IFSBUCORSK4O3ANRIBSP75K5QXHD".to_string()
+            }
+        );
+
+        // Truncated hex is not mistaken for base32
+        let res = InfoHash::new("c811b41641a09d192b8ed81b14064fff");
+        assert_eq!(
+            res.unwrap_err(),
+            InfoHashError::InvalidLength {
+                hash: "c811b41641a09d192b8ed81b14064fff".to_string(),
+                len: 32
+            }
+        );
+        assert!(InfoHash::new("abcdefabcdefabcdefabcdefabcdefab").is_err());
+    }
+
+    #[test]
+    fn fails_invalid_base32_chars() {
+        let res = InfoHash::from_base32("ZAI3IFSBUCORSK4O3ANRIBSP75K5QXH1");
+        assert_eq!(
+            res.unwrap_err(),
+            InfoHashError::InvalidChars {
+                hash: "ZAI3IFSBUCORSK4O3ANRIBSP75K5QXH1".to_string()
+            }
         );
     }
 
@@ -214,12 +787,202 @@ mod tests {
         assert_eq!(
             hash,
             InfoHash::Hybrid((
-                "c811b41641a09d192b8ed81b14064fff55d85ce3".to_string(),
-                "caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e".to_string()
+                HexDigest::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap(),
+                HexDigest::new("caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e")
+                    .unwrap()
             ))
         );
     }
 
+    #[test]
+    fn can_roundtrip_full_string() {
+        let hybrid = InfoHash::Hybrid((
+            HexDigest::new("631a31dd0a46257d5078c0dee4e66e26f73e42ac").unwrap(),
+            HexDigest::new("d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb")
+                .unwrap(),
+        ));
+        let full = hybrid.to_full_string();
+        assert_eq!(full, "631a31dd0a46257d5078c0dee4e66e26f73e42ac:d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb");
+        assert_eq!(InfoHash::new(&full).unwrap(), hybrid);
+        assert_eq!(
+            InfoHash::new("631A31DD0A46257D5078C0DEE4E66E26F73E42AC:D8DD32AC93357C368556AF3AC1D95C9D76BD0DFF6FA9833ECDAC3D53134EFABB").unwrap(),
+            hybrid
+        );
+
+        let v1 = InfoHash::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap();
+        assert_eq!(v1.to_full_string(), v1.as_str());
+
+        // The v1 digest comes first
+        let reversed = format!("{}:{}", hybrid.v2().unwrap(), hybrid.v1().unwrap());
+        assert!(InfoHash::new(&reversed).is_err());
+        assert!(InfoHash::new(
+            "c811b41641a09d192b8ed81b14064fff55d85ce3:c811b41641a09d192b8ed81b14064fff55d85ce3"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn deserialize_validates_digests() {
+        let hybrid: InfoHash = serde_json::from_str(r#"{"Hybrid":["631A31DD0A46257D5078C0DEE4E66E26F73E42AC","d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb"]}"#).unwrap();
+        assert_eq!(
+            hybrid.to_full_string(),
+            "631a31dd0a46257d5078c0dee4e66e26f73e42ac:d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb"
+        );
+        let json = serde_json::to_string(&hybrid).unwrap();
+        assert_eq!(serde_json::from_str::<InfoHash>(&json).unwrap(), hybrid);
+
+        for invalid in [
+            r#"{"V1":"not a hash"}"#,
+            r#"{"V1":"d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb"}"#,
+            r#"{"V1":"ZAI3ILCAUGOA3GFYQKOBOBU55AMXMDG6"}"#,
+            r#"{"V2":"g8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb"}"#,
+            r#"{"Hybrid":["d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb","631a31dd0a46257d5078c0dee4e66e26f73e42ac"]}"#,
+        ] {
+            assert!(
+                serde_json::from_str::<InfoHash>(invalid).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn compact_serializes_as_string() {
+        let hybrid = InfoHash::new("631a31dd0a46257d5078c0dee4e66e26f73e42ac:d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb").unwrap();
+        let json = serde_json::to_string(&CompactInfoHash(hybrid.clone())).unwrap();
+        assert_eq!(json, format!("\"{}\"", hybrid.to_full_string()));
+        assert_eq!(
+            serde_json::from_str::<CompactInfoHash>(&json).unwrap(),
+            CompactInfoHash(hybrid)
+        );
+        assert!(serde_json::from_str::<CompactInfoHash>("\"nothex\"").is_err());
+    }
+
+    #[test]
+    fn can_access_digests() {
+        let hashv1 = InfoHash::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap();
+        let hashv2 =
+            InfoHash::new("caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e")
+                .unwrap();
+        let hybrid = hashv1.hybrid(&hashv2).unwrap();
+
+        assert!(hashv1.is_v1());
+        assert_eq!(
+            hashv1.v1().as_deref(),
+            Some("c811b41641a09d192b8ed81b14064fff55d85ce3")
+        );
+        assert_eq!(hashv1.v2(), None);
+
+        assert!(hashv2.is_v2());
+        assert_eq!(hashv2.v1(), None);
+        assert_eq!(hashv2.v2(), Some(hashv2.as_str()));
+
+        assert!(hybrid.is_hybrid());
+        assert!(!hybrid.is_v1() && !hybrid.is_v2());
+        assert_eq!(hybrid.v1(), hashv1.v1());
+        assert_eq!(hybrid.v2(), hashv2.v2());
+    }
+
+    #[test]
+    fn can_be_used_as_map_key() {
+        use std::collections::{BTreeMap, HashMap};
+
+        let hashv1 = InfoHash::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap();
+        let hashv2 =
+            InfoHash::new("caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e")
+                .unwrap();
+        let hybrid = hashv1.hybrid(&hashv2).unwrap();
+
+        let mut map = HashMap::new();
+        map.insert(hashv1.clone(), "v1");
+        map.insert(hybrid.clone(), "hybrid");
+        map.insert(hashv2.clone(), "v2");
+        assert_eq!(map.get(&hashv1), Some(&"v1"));
+        assert_eq!(map.get(&hybrid), Some(&"hybrid"));
+
+        let tree: BTreeMap<InfoHash, &str> = map.into_iter().collect();
+        let keys: Vec<&InfoHash> = tree.keys().collect();
+        assert_eq!(keys, vec![&hashv1, &hashv2, &hybrid]);
+    }
+
+    #[test]
+    fn matches_hybrid_components() {
+        let hashv1 = InfoHash::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap();
+        let hashv2 =
+            InfoHash::new("caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e")
+                .unwrap();
+        let hybrid = hashv1.hybrid(&hashv2).unwrap();
+
+        assert!(hybrid.matches(&hashv1));
+        assert!(hashv1.matches(&hybrid));
+        assert!(hybrid.matches(&hashv2));
+        assert!(hybrid.matches(&hybrid));
+        assert!(hashv1.matches(&hashv1));
+        assert!(!hashv1.matches(&hashv2));
+    }
+
+    #[test]
+    fn does_not_match_conflicting_hybrid() {
+        let hashv1 = InfoHash::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap();
+        let hybrid1 = hashv1
+            .hybrid(
+                &InfoHash::new("caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e")
+                    .unwrap(),
+            )
+            .unwrap();
+        let hybrid2 = hashv1
+            .hybrid(
+                &InfoHash::new("d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb")
+                    .unwrap(),
+            )
+            .unwrap();
+
+        assert!(!hybrid1.matches(&hybrid2));
+        assert!(hybrid1.matches(&hashv1));
+        assert!(hybrid2.matches(&hashv1));
+    }
+
+    #[test]
+    fn stores_raw_digests_inline() {
+        assert_eq!(std::mem::size_of::<HexDigest<20>>(), 20);
+        assert_eq!(std::mem::size_of::<HexDigest<32>>(), 32);
+        assert!(std::mem::size_of::<InfoHash>() <= 56);
+
+        let digest = HexDigest::<20>::new("C811B41641A09D192B8ED81B14064FFF55D85CE3").unwrap();
+        assert_eq!(digest, "c811b41641a09d192b8ed81b14064fff55d85ce3");
+        assert_eq!(&digest.as_bytes()[..3], &[0xc8, 0x11, 0xb4]);
+        assert_eq!(HexDigest::from_bytes(*digest.as_bytes()), digest);
+        assert_eq!(
+            format!("{digest:?}"),
+            "\"c811b41641a09d192b8ed81b14064fff55d85ce3\""
+        );
+        assert_eq!(
+            HexDigest::<20>::new("c811b41641a09d192b8ed81b14064fff55d85ce3aa").unwrap_err(),
+            InfoHashError::InvalidLength {
+                hash: "c811b41641a09d192b8ed81b14064fff55d85ce3aa".to_string(),
+                len: 42
+            }
+        );
+        assert!(HexDigest::<20>::new("z811b41641a09d192b8ed81b14064fff55d85ce3").is_err());
+        assert_eq!(
+            serde_json::to_string(&InfoHash::V1(digest)).unwrap(),
+            r#"{"V1":"c811b41641a09d192b8ed81b14064fff55d85ce3"}"#
+        );
+
+        let hex = InfoHash::V1(digest).as_str();
+        assert_eq!(hex.len(), 40);
+        assert_eq!(
+            String::from(hex),
+            "c811b41641a09d192b8ed81b14064fff55d85ce3"
+        );
+    }
+
+    #[test]
+    fn orders_by_digest() {
+        let first = InfoHash::new("0000000000000000000000000000000000000001").unwrap();
+        let second = InfoHash::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap();
+        assert!(first < second);
+    }
+
     #[test]
     fn fails_invalid_chars() {
         let res = InfoHash::new("D811B41641A09D192B8eD81B14064FFF55D85WWW");
@@ -291,4 +1054,38 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn from_info_dict() {
+        for path in [
+            "tests/bittorrent-v1-emma-goldman.torrent",
+            "tests/bittorrent-v2-test.torrent",
+            "tests/bittorrent-v2-hybrid-test.torrent",
+        ] {
+            let slice = std::fs::read(path).unwrap();
+            let torrent = crate::TorrentFile::from_slice(&slice).unwrap();
+            let hash = InfoHash::from_info_dict(torrent.info_bytes(), None).unwrap();
+            assert_eq!(hash, InfoHash::from(&torrent));
+        }
+
+        let slice = std::fs::read("tests/bittorrent-v2-hybrid-test.torrent").unwrap();
+        let torrent = crate::TorrentFile::from_slice(&slice).unwrap();
+        let v1 = InfoHash::from_info_dict(torrent.info_bytes(), Some(1)).unwrap();
+        assert_eq!(v1.as_str(), "631a31dd0a46257d5078c0dee4e66e26f73e42ac");
+        assert_eq!(
+            InfoHash::from_info_dict(torrent.info_bytes(), Some(3)).unwrap_err(),
+            TorrentFileError::WrongVersion { version: 3 }
+        );
+
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let torrent = crate::TorrentFile::from_slice(&slice).unwrap();
+        assert!(matches!(
+            InfoHash::from_info_dict(torrent.info_bytes(), Some(2)),
+            Err(TorrentFileError::NotATorrent { .. })
+        ));
+        assert!(matches!(
+            InfoHash::from_info_dict(b"d4:name1:ae-", None),
+            Err(TorrentFileError::NotATorrent { .. })
+        ));
+    }
 }
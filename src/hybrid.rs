@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use crate::{InfoHash, SingleTarget, TorrentID};
+
+/// A lookup table of known hybrid torrents, resolving either of their hashes to the full
+/// hybrid [`InfoHash`](crate::hash::InfoHash).
+///
+/// Peers and trackers announce a hybrid torrent with its infohash v1 or v2, depending on the
+/// protocol version they support. A HybridMap finds the hybrid torrent from any of its v1
+/// digest, v2 digest or [`TorrentID`](crate::id::TorrentID), so that both announcements are
+/// attributed to the same torrent.
+///
+/// ```
+/// use hightorrent::{HybridMap, InfoHash, SingleTarget};
+///
+/// let v1 = InfoHash::new("631a31dd0a46257d5078c0dee4e66e26f73e42ac").unwrap();
+/// let v2 = InfoHash::new("d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb").unwrap();
+/// let hybrid = v1.hybrid(&v2).unwrap();
+///
+/// let mut map = HybridMap::new();
+/// assert!(map.insert(&hybrid));
+///
+/// let target = SingleTarget::new(&v1.as_str()).unwrap();
+/// assert_eq!(map.resolve(&target).unwrap().v2(), v2.v2());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct HybridMap {
+    hashes: HashMap<TorrentID, InfoHash>,
+    by_v1: HashMap<String, TorrentID>,
+}
+
+impl HybridMap {
+    pub fn new() -> HybridMap {
+        HybridMap::default()
+    }
+
+    /// Adds a hybrid infohash to the map, replacing any hybrid infohash with the same v1 or v2
+    /// digest. Returns false, leaving the map untouched, when the infohash is not hybrid.
+    pub fn insert(&mut self, hash: &InfoHash) -> bool {
+        let v1 = match hash {
+            InfoHash::Hybrid((v1, _)) => v1,
+            _ => return false,
+        };
+
+        let id = hash.id();
+        if let Some(previous) = self.by_v1.insert(v1.to_string(), id) {
+            self.hashes.remove(&previous);
+        }
+        if let Some(InfoHash::Hybrid((previous, _))) = self.hashes.insert(id, hash.clone()) {
+            if previous != *v1 {
+                self.by_v1.remove(previous.to_hex().as_str());
+            }
+        }
+        true
+    }
+
+    /// Finds the hybrid infohash matching a [`SingleTarget`](crate::target::SingleTarget), with
+    /// the same rules as [`SingleTarget::matches_hash`](crate::target::SingleTarget::matches_hash):
+    /// the target can be the full v1 or v2 digest, or the TorrentID.
+    pub fn resolve(&self, target: &SingleTarget) -> Option<&InfoHash> {
+        // Priority is given to matching v2, like SingleTarget does
+        let id = TorrentID::new(target.truncated()).ok()?;
+        if let Some(hash) = self.hashes.get(&id) {
+            if target.matches_hash(hash) {
+                return Some(hash);
+            }
+        }
+        self.by_v1
+            .get(target.as_str())
+            .and_then(|id| self.hashes.get(id))
+    }
+
+    /// Returns the number of hybrid infohashes in the map.
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    /// Returns true when the map contains no hybrid infohash.
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+}
+
+impl<'a> Extend<&'a InfoHash> for HybridMap {
+    /// Inserts the hybrid infohashes, ignoring the others.
+    fn extend<I: IntoIterator<Item = &'a InfoHash>>(&mut self, iter: I) {
+        for hash in iter {
+            self.insert(hash);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V1: &str = "631a31dd0a46257d5078c0dee4e66e26f73e42ac";
+    const V2: &str = "d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb";
+
+    fn hybrid(v1: &str, v2: &str) -> InfoHash {
+        InfoHash::new(v1)
+            .unwrap()
+            .hybrid(&InfoHash::new(v2).unwrap())
+            .unwrap()
+    }
+
+    fn target(s: &str) -> SingleTarget {
+        SingleTarget::new(s).unwrap()
+    }
+
+    #[test]
+    fn resolves_both_hashes() {
+        let hash = hybrid(V1, V2);
+        let mut map = HybridMap::new();
+        assert!(!map.insert(&InfoHash::new(V1).unwrap()));
+        assert!(map.insert(&hash));
+
+        assert_eq!(map.resolve(&target(V1)), Some(&hash));
+        assert_eq!(map.resolve(&target(V2)), Some(&hash));
+        assert_eq!(map.resolve(&target(&V2[..40])), Some(&hash));
+        assert_eq!(
+            map.resolve(&target("c811b41641a09d192b8ed81b14064fff55d85ce3")),
+            None
+        );
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn replaces_conflicting_hashes() {
+        let other_v2 = "caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e";
+        let mut map = HybridMap::new();
+        map.extend([&hybrid(V1, V2), &hybrid(V1, other_v2)]);
+
+        assert_eq!(map.len(), 1);
+        assert_eq!(
+            map.resolve(&target(V1)).unwrap().v2().as_deref(),
+            Some(other_v2)
+        );
+        assert_eq!(map.resolve(&target(V2)), None);
+    }
+}
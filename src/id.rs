@@ -16,39 +16,140 @@ use crate::{InfoHash, InfoHashError};
 /// [`InfoHash`](crate::hash::InfoHash) with the
 /// [`TorrentID::from_infohash`](crate::id::TorrentID::from_infohash) and
 /// [`InfoHash::id`](crate::hash::InfoHash::id) methods.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct TorrentID(String);
+///
+/// The TorrentID is stored inline as exactly [`TorrentID::LEN`](crate::id::TorrentID::LEN) bytes,
+/// so it is [`Copy`] and cannot hold a string of another length. When deserialized, the string
+/// must be exactly 40 hex characters, and is normalized to lowercase.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow),
+    diesel(sql_type = diesel::sql_types::Text)
+)]
+pub struct TorrentID([u8; TorrentID::LEN]);
 
 impl TorrentID {
+    /// The length of a TorrentID, in characters.
+    pub const LEN: usize = 40;
+
     pub fn new<T: AsRef<str>>(s: T) -> Result<TorrentID, InfoHashError> {
         Self::from_str(s.as_ref())
     }
 
-    pub fn from_infohash(hash: &InfoHash) -> TorrentID {
-        match hash {
-            InfoHash::V1(v2hash) => TorrentID(v2hash.to_string()),
-            InfoHash::V2(v2hash) | InfoHash::Hybrid((_, v2hash)) => {
-                let mut truncated = v2hash.to_string();
-                truncated.truncate(40);
-                TorrentID(truncated)
-            }
+    /// Builds a TorrentID from a string without checking that it is a lowercase hex digest,
+    /// for constants and tests. Panics if the string is not [`TorrentID::LEN`](crate::id::TorrentID::LEN)
+    /// characters long, which fails the build when used in a constant.
+    ///
+    /// ```
+    /// use hightorrent::TorrentID;
+    ///
+    /// const ID: TorrentID = TorrentID::from_str_unchecked("c811b41641a09d192b8ed81b14064fff55d85ce3");
+    /// assert_eq!(ID, TorrentID::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap());
+    /// ```
+    pub const fn from_str_unchecked(s: &str) -> TorrentID {
+        let bytes = s.as_bytes();
+        assert!(
+            bytes.len() == TorrentID::LEN,
+            "TorrentID must be 40 characters long"
+        );
+
+        let mut id = [0; TorrentID::LEN];
+        let mut i = 0;
+        while i < TorrentID::LEN {
+            id[i] = bytes[i];
+            i += 1;
         }
+        TorrentID(id)
+    }
+
+    pub fn from_infohash(hash: &InfoHash) -> TorrentID {
+        // Infohash digests are lowercase hex, at least 40 characters long, and the v2 digest
+        // is used for hybrid infohashes
+        TorrentID::from_str_unchecked(&hash.as_str()[..TorrentID::LEN])
     }
 
     pub fn as_str(&self) -> &str {
-        &self.0
+        // The bytes were copied from a whole string, so they are valid UTF-8
+        std::str::from_utf8(&self.0).unwrap()
+    }
+}
+
+impl std::fmt::Debug for TorrentID {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("TorrentID").field(&self.as_str()).finish()
     }
 }
 
 impl std::fmt::Display for TorrentID {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}", self.as_str())
     }
 }
 
 impl AsRef<str> for TorrentID {
     fn as_ref(&self) -> &str {
-        &self.0
+        self.as_str()
+    }
+}
+
+/// A TorrentID is described by its lowercase hex string.
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for TorrentID {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "TorrentID".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "pattern": "^[0-9a-f]{40}$",
+        })
+    }
+}
+
+impl From<TorrentID> for String {
+    fn from(id: TorrentID) -> String {
+        id.as_str().to_string()
+    }
+}
+
+impl TryFrom<String> for TorrentID {
+    type Error = InfoHashError;
+
+    /// Parses an exact TorrentID, unlike [`TorrentID::new`](crate::id::TorrentID::new) which also
+    /// accepts any infohash and truncates it.
+    fn try_from(s: String) -> Result<TorrentID, Self::Error> {
+        if !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(InfoHashError::InvalidChars { hash: s });
+        }
+        if s.len() != TorrentID::LEN {
+            return Err(InfoHashError::InvalidLength {
+                len: s.len(),
+                hash: s,
+            });
+        }
+        Ok(TorrentID::from_str_unchecked(&s.to_ascii_lowercase()))
+    }
+}
+
+impl From<InfoHash> for TorrentID {
+    fn from(hash: InfoHash) -> TorrentID {
+        TorrentID::from_infohash(&hash)
+    }
+}
+
+impl From<&InfoHash> for TorrentID {
+    fn from(hash: &InfoHash) -> TorrentID {
+        TorrentID::from_infohash(hash)
+    }
+}
+
+impl TryFrom<&str> for TorrentID {
+    type Error = InfoHashError;
+
+    fn try_from(s: &str) -> Result<TorrentID, Self::Error> {
+        TorrentID::from_str(s)
     }
 }
 
@@ -60,3 +161,43 @@ impl FromStr for TorrentID {
         Ok(Self::from_infohash(&hash))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serde_roundtrips_as_string() {
+        let id = TorrentID::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap();
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, "\"c811b41641a09d192b8ed81b14064fff55d85ce3\"");
+        assert_eq!(serde_json::from_str::<TorrentID>(&json).unwrap(), id);
+
+        assert!(serde_json::from_str::<TorrentID>("\"c811b41641a09d192b8e\"").is_err());
+    }
+
+    #[test]
+    fn deserialize_validates_id() {
+        let id: TorrentID =
+            serde_json::from_str("\"C811B41641A09D192B8ED81B14064FFF55D85CE3\"").unwrap();
+        assert_eq!(id.as_str(), "c811b41641a09d192b8ed81b14064fff55d85ce3");
+
+        assert_eq!(
+            TorrentID::try_from("g811b41641a09d192b8ed81b14064fff55d85ce3".to_string()),
+            Err(InfoHashError::InvalidChars {
+                hash: "g811b41641a09d192b8ed81b14064fff55d85ce3".to_string()
+            })
+        );
+        // Full infohashes are not TorrentIDs
+        assert!(serde_json::from_str::<TorrentID>(
+            "\"caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e\""
+        )
+        .is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_str_unchecked_checks_length() {
+        TorrentID::from_str_unchecked("c811b41641a09d192b8e");
+    }
+}
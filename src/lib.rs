@@ -9,6 +9,21 @@
 //! HighTorrent provides utilities to extract name and hash from torrents/magnets, using the
 //! [`MagnetLink`](crate::magnet::MagnetLink) and [`TorrentFile`](crate::torrent_file::TorrentFile) structures, but could provide more advanced utilities in the future (PRs welcome). Additionally, it provides the [`Torrent`](crate::torrent::Torrent) struct and the
 //! [`ToTorrent`](crate::torrent::ToTorrent) trait representing fully-loaded torrents ; those helpers are intended to be used by more diverse torrenting libraries to provide interoperability out-of-the-box.
+//! Implementations for the qBittorrent, Transmission and Deluge APIs are provided behind the
+//! `qbittorrent`, `transmission` and `deluge` crate features, and database column types for
+//! [sea-orm](https://www.sea-ql.org/SeaORM/), [sqlx](https://github.com/launchbadge/sqlx) and
+//! [diesel](https://diesel.rs/) are provided behind the `sea_orm`, `sqlx` and `diesel` crate features.
+//! Torrent files can also be persisted without a SQL stack with the `TorrentStore` trait behind the
+//! `storage` crate feature, and the `redb` feature provides a [redb](https://www.redb.org/) backend.
+//! JSON schemas for the public types are provided by [schemars](https://graham.cool/schemars/)
+//! behind the `schemars` crate feature, and JavaScript bindings for web UIs are provided by
+//! [wasm-bindgen](https://rustwasm.github.io/wasm-bindgen/) behind the `wasm` crate feature.
+//! Python classes are provided by [PyO3](https://pyo3.rs/) behind the `python` crate feature.
+//! Torrent files can be loaded asynchronously with [tokio](https://tokio.rs/) behind the `tokio` crate feature.
+//! Valid infohashes, magnets and torrents can be generated for fuzzing and property-based testing
+//! behind the `arbitrary` and `proptest` crate features.
+//! The roundtrip checks run by the cargo-fuzz targets of the `fuzz` directory are provided
+//! behind the `fuzzing` crate feature.
 //!
 //! Finally, the [`SingleTarget`](crate::target::SingleTarget) and
 //! [`MultiTarget`](crate::target::MultiTarget) structures represent one or more torrents you wish to
@@ -21,26 +36,145 @@
 #[macro_use]
 extern crate serde;
 
+mod category;
+pub use category::{Category, CategoryError};
+
+mod content;
+pub use content::{DirNode, FileAttributes, SafePath, SafePathError, TorrentContent};
+
 mod hash;
-pub use hash::{InfoHash, InfoHashError, TryInfoHash};
+pub use hash::{CompactInfoHash, HexDigest, HexString, InfoHash, InfoHashError, TryInfoHash};
 
 mod id;
 pub use id::TorrentID;
 
+mod hybrid;
+pub use hybrid::HybridMap;
+
+mod limits;
+pub use limits::TorrentLimits;
+
 mod list;
-pub use list::TorrentList;
+pub use list::{SortKey, SortOrder, TorrentEvent, TorrentList};
+
+mod editor;
+pub use editor::{TorrentEditError, TorrentEditor};
+
+mod export;
+pub use export::ExportError;
+
+pub mod merkle;
 
 mod magnet;
-pub use magnet::{MagnetLink, MagnetLinkError};
+pub use magnet::{MagnetLink, MagnetLinkError, MagnetParseOptions};
+
+mod pending;
+pub use pending::{MetadataState, PendingMetadata, PendingMetadataError};
+
+mod resume;
+pub use resume::{Bitfield, ResumeData, ResumeDataError};
+
+mod priority;
+pub use priority::{FilePriorities, FilePriority, FilePriorityError};
+
+mod progress;
+pub use progress::Progress;
+
+mod timestamp;
+pub use timestamp::Timestamp;
+
+mod peer;
+pub use peer::{CompactPeersV4, CompactPeersV6, PeerAddr, PeerAddrError};
 
 mod torrent;
-pub use torrent::{ToTorrent, Torrent};
+pub use torrent::{ToTorrent, Torrent, TorrentBuilder, TorrentColumn, TorrentError};
 
 mod torrent_file;
-pub use torrent_file::{TorrentFile, TorrentFileError};
+pub use torrent_file::{
+    DecodedInfo, DecodedTorrent, ParseOptions, TorrentFile, TorrentFileError, TorrentSignature,
+};
+
+mod torrent_file_ref;
+pub use torrent_file_ref::TorrentFileRef;
+
+mod bencode;
 
 mod target;
 pub use target::{MultiTarget, SingleTarget, ToSingleTarget};
 
+mod detect;
+pub use detect::{detect, Detected};
+
+#[cfg(any(feature = "sea_orm", feature = "sqlx", feature = "diesel"))]
+mod db;
+
+#[cfg(feature = "storage")]
+mod storage;
+#[cfg(feature = "redb")]
+pub use storage::RedbStore;
+#[cfg(feature = "storage")]
+pub use storage::{MemoryStore, StorageError, TorrentStore};
+
+#[cfg(feature = "qbittorrent")]
+mod qbittorrent;
+#[cfg(feature = "qbittorrent")]
+pub use qbittorrent::{QBittorrentTorrent, QBittorrentTracker};
+
+#[cfg(feature = "transmission")]
+mod transmission;
+#[cfg(feature = "transmission")]
+pub use transmission::{TransmissionStatus, TransmissionTorrent, TransmissionTracker};
+
+#[cfg(feature = "deluge")]
+mod deluge;
+#[cfg(feature = "deluge")]
+pub use deluge::{DelugeTorrent, DelugeTracker};
+
+#[cfg(feature = "session")]
+mod session;
+#[cfg(feature = "session")]
+pub use session::{LibtorrentResume, RqbitSession, RqbitTorrent, SessionError};
+
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+mod generators;
+
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
+
+#[cfg(feature = "notify")]
+mod watch;
+#[cfg(feature = "notify")]
+pub use watch::{FolderWatcher, WatchError, WatchEvent};
+
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "python")]
+pub use python::{python_module, PyInfoHash, PyMagnetLink, PyTorrentFile, PyTorrentID};
+
+#[cfg(feature = "wasm")]
+mod wasm;
+#[cfg(feature = "wasm")]
+pub use wasm::{WasmInfoHash, WasmMagnetLink, WasmTorrentFile};
+
+mod tiers;
+pub use tiers::TrackerTiers;
+
+mod validation;
+pub use validation::{HybridMismatch, ValidationIssue, ValidationReport, ValidationSeverity};
+
+mod search;
+pub use search::TorrentIndex;
+
+mod state;
+pub use state::{SavedState, StateError};
+
+mod selection;
+#[cfg(feature = "regex")]
+pub use selection::PatternError;
+pub use selection::{FileSelection, FileSelectionError, FileSelectionItem, Pattern};
+
 mod tracker;
-pub use tracker::{PeerSource, Tracker, TrackerError, TrackerScheme, TryIntoTracker};
+pub use tracker::{
+    AnnounceResponse, PeerSource, ScrapeResponse, ScrapeStats, Tracker, TrackerError,
+    TrackerParseOptions, TrackerResponseError, TrackerScheme, TryIntoTracker,
+};
@@ -0,0 +1,145 @@
+/// The bandwidth and seeding limits of a torrent, set in a torrent client.
+///
+/// Every limit is optional, and None means that the torrent has no limit of its own: depending
+/// on the client, a global limit may still apply. Rates are in bytes per second, whatever the
+/// unit used by the client, so that limits can be compared and translated between clients.
+///
+/// The adapters for torrent client APIs, such as
+/// [`QBittorrentTorrent`](crate::qbittorrent::QBittorrentTorrent), convert the limits reported
+/// by the client, and [`ToTorrent`](crate::torrent::ToTorrent) attaches them to the
+/// [`Torrent`](crate::torrent::Torrent).
+///
+/// ```
+/// use hightorrent::TorrentLimits;
+///
+/// let limits = TorrentLimits::from_qbittorrent(1_048_576, -1, 2.0, -2);
+/// assert_eq!(limits.download_limit, Some(1_048_576));
+/// assert_eq!(limits.upload_limit, None);
+/// assert_eq!(limits.ratio_limit, Some(2.0));
+/// assert!(!limits.is_unlimited());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TorrentLimits {
+    /// The maximum download rate, in bytes per second
+    #[serde(default)]
+    pub download_limit: Option<u64>,
+    /// The maximum upload rate, in bytes per second
+    #[serde(default)]
+    pub upload_limit: Option<u64>,
+    /// The share ratio after which the torrent stops seeding
+    #[serde(default)]
+    pub ratio_limit: Option<f64>,
+    /// The seeding time after which the torrent stops seeding, in seconds
+    #[serde(default)]
+    pub seeding_time_limit: Option<u64>,
+}
+
+impl TorrentLimits {
+    /// Returns true when no limit is set.
+    pub fn is_unlimited(&self) -> bool {
+        *self == TorrentLimits::default()
+    }
+
+    /// Converts the limits reported by the qBittorrent Web API (`/api/v2/torrents/info`).
+    ///
+    /// Rates are in bytes per second, and zero or negative when unlimited. The ratio and
+    /// seeding time (in minutes) are -1 when unlimited and -2 when the global limit applies.
+    pub fn from_qbittorrent(
+        dl_limit: i64,
+        up_limit: i64,
+        ratio_limit: f64,
+        seeding_time_limit: i64,
+    ) -> TorrentLimits {
+        TorrentLimits {
+            download_limit: positive(dl_limit),
+            upload_limit: positive(up_limit),
+            ratio_limit: Some(ratio_limit).filter(|ratio| *ratio >= 0.0),
+            seeding_time_limit: u64::try_from(seeding_time_limit)
+                .ok()
+                .map(|minutes| minutes * 60),
+        }
+    }
+
+    /// Converts the limits reported by the Transmission RPC `torrent-get` method.
+    ///
+    /// Rates are in kB/s (1000 bytes per second), and only apply when their `limited` flag
+    /// is set. The seed ratio only applies when `seed_ratio_mode` is 1 (per-torrent ratio).
+    /// Transmission has no seeding time limit.
+    pub fn from_transmission(
+        download_limited: bool,
+        download_limit: i64,
+        upload_limited: bool,
+        upload_limit: i64,
+        seed_ratio_mode: i64,
+        seed_ratio_limit: f64,
+    ) -> TorrentLimits {
+        let rate = |limited: bool, limit: i64| {
+            if limited {
+                u64::try_from(limit).ok().map(|kb| kb * 1000)
+            } else {
+                None
+            }
+        };
+        TorrentLimits {
+            download_limit: rate(download_limited, download_limit),
+            upload_limit: rate(upload_limited, upload_limit),
+            ratio_limit: Some(seed_ratio_limit).filter(|_| seed_ratio_mode == 1),
+            seeding_time_limit: None,
+        }
+    }
+
+    /// Converts the limits reported by the Deluge JSON-RPC API.
+    ///
+    /// Rates are in KiB/s, and -1 when unlimited. The stop ratio only applies when
+    /// `stop_at_ratio` is set. Deluge has no seeding time limit.
+    pub fn from_deluge(
+        max_download_speed: f64,
+        max_upload_speed: f64,
+        stop_at_ratio: bool,
+        stop_ratio: f64,
+    ) -> TorrentLimits {
+        let rate = |kib: f64| {
+            Some(kib)
+                .filter(|kib| *kib > 0.0)
+                .map(|kib| (kib * 1024.0) as u64)
+        };
+        TorrentLimits {
+            download_limit: rate(max_download_speed),
+            upload_limit: rate(max_upload_speed),
+            ratio_limit: Some(stop_ratio).filter(|_| stop_at_ratio),
+            seeding_time_limit: None,
+        }
+    }
+}
+
+/// Returns a strictly positive limit, or None.
+fn positive(limit: i64) -> Option<u64> {
+    u64::try_from(limit).ok().filter(|limit| *limit > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_client_limits() {
+        let limits = TorrentLimits::from_qbittorrent(0, 51200, -1.0, 90);
+        assert_eq!(limits.download_limit, None);
+        assert_eq!(limits.upload_limit, Some(51200));
+        assert_eq!(limits.ratio_limit, None);
+        assert_eq!(limits.seeding_time_limit, Some(5400));
+
+        let limits = TorrentLimits::from_transmission(true, 100, false, 50, 1, 1.5);
+        assert_eq!(limits.download_limit, Some(100_000));
+        assert_eq!(limits.upload_limit, None);
+        assert_eq!(limits.ratio_limit, Some(1.5));
+        assert!(TorrentLimits::from_transmission(false, 100, false, 50, 0, 1.5).is_unlimited());
+
+        let limits = TorrentLimits::from_deluge(-1.0, 64.0, true, 2.0);
+        assert_eq!(limits.download_limit, None);
+        assert_eq!(limits.upload_limit, Some(65536));
+        assert_eq!(limits.ratio_limit, Some(2.0));
+        assert!(TorrentLimits::from_deluge(-1.0, -1.0, false, 2.0).is_unlimited());
+    }
+}
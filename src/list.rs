@@ -1,9 +1,77 @@
-use crate::{SingleTarget, Torrent};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use crate::{MultiTarget, Progress, SingleTarget, Torrent, TorrentColumn, TorrentID};
+
+/// A field to sort a [`TorrentList`](crate::list::TorrentList) by, with
+/// [`TorrentList::sort_by`](crate::list::TorrentList::sort_by).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortKey {
+    /// The name, ignoring case
+    Name,
+    Size,
+    /// The completed percentage
+    Progress,
+    /// When the torrent was added. Unknown dates come first in ascending order.
+    DateStart,
+    /// When the torrent was completed. Unfinished torrents come first in ascending order.
+    DateEnd,
+    State,
+}
+
+impl SortKey {
+    fn compare(&self, a: &Torrent, b: &Torrent) -> Ordering {
+        match self {
+            SortKey::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            SortKey::Size => a.size.cmp(&b.size),
+            SortKey::Progress => a.progress.percent().total_cmp(&b.progress.percent()),
+            SortKey::DateStart => a.date_start.cmp(&b.date_start),
+            SortKey::DateEnd => a.date_end.cmp(&b.date_end),
+            SortKey::State => a.state.cmp(&b.state),
+        }
+    }
+}
+
+/// The direction of a sort, with [`TorrentList::sort_by`](crate::list::TorrentList::sort_by).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+/// A change between two snapshots of a [`TorrentList`](crate::list::TorrentList), as returned by
+/// [`TorrentList::diff`](crate::list::TorrentList::diff).
+#[derive(Clone, Debug)]
+pub enum TorrentEvent {
+    /// The torrent is only in the newer snapshot
+    Added { torrent: Torrent },
+    /// The torrent is only in the older snapshot
+    Removed { torrent: Torrent },
+    ProgressChanged {
+        id: TorrentID,
+        old: Progress,
+        new: Progress,
+    },
+    StateChanged {
+        id: TorrentID,
+        old: String,
+        new: String,
+    },
+}
+
+impl TorrentEvent {
+    /// Returns the [`TorrentID`](crate::id::TorrentID) of the torrent concerned by the event.
+    pub fn id(&self) -> TorrentID {
+        match self {
+            TorrentEvent::Added { torrent } | TorrentEvent::Removed { torrent } => torrent.id,
+            TorrentEvent::ProgressChanged { id, .. } | TorrentEvent::StateChanged { id, .. } => *id,
+        }
+    }
+}
 
 /// A list of [`Torrent`](crate::torrent::Torrent), with querying/filtering capabilities.
-///
-/// TODO: Implement filter method for finding MultipleTarget
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TorrentList(Vec<Torrent>);
 
 impl TorrentList {
@@ -23,6 +91,64 @@ impl TorrentList {
         self.0
     }
 
+    /// Returns the number of torrents in the TorrentList.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true when the TorrentList contains no torrent.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an iterator over the torrents in the TorrentList.
+    pub fn iter(&self) -> std::slice::Iter<'_, Torrent> {
+        self.0.iter()
+    }
+
+    /// Returns the number of torrents in the TorrentList, like
+    /// [`len`](crate::list::TorrentList::len). This is meant to be sent along a
+    /// [`page`](crate::list::TorrentList::page), so that clients know how many pages there are.
+    pub fn total(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns up to `limit` torrents, starting at `offset`, without cloning them. The page is
+    /// empty when `offset` is past the end of the TorrentList.
+    ///
+    /// ```
+    /// use hightorrent::{InfoHash, Torrent, TorrentList};
+    ///
+    /// let torrent = |hash| {
+    ///     let hash = InfoHash::new(hash).unwrap();
+    ///     Torrent::builder().hash(hash).name("test").build().unwrap()
+    /// };
+    /// let list = TorrentList::from_vec(vec![
+    ///     torrent("c811b41641a09d192b8ed81b14064fff55d85ce3"),
+    ///     torrent("631a31dd0a46257d5078c0dee4e66e26f73e42ac"),
+    /// ]);
+    ///
+    /// assert_eq!(list.page(1, 10).len(), 1);
+    /// assert!(list.page(2, 10).is_empty());
+    /// assert_eq!(list.total(), 2);
+    /// ```
+    pub fn page(&self, offset: usize, limit: usize) -> &[Torrent] {
+        let start = offset.min(self.0.len());
+        let end = start.saturating_add(limit).min(self.0.len());
+        &self.0[start..end]
+    }
+
+    /// Returns an iterator over the TorrentList in slices of `size` torrents, the last slice
+    /// being shorter when needed. Panics when `size` is zero.
+    pub fn chunks(&self, size: usize) -> std::slice::Chunks<'_, Torrent> {
+        self.0.chunks(size)
+    }
+
+    /// Returns an iterator allowing to modify the torrents in the TorrentList.
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, Torrent> {
+        self.0.iter_mut()
+    }
+
     /// Find a single torrent in the TorrentList, matching a specific
     /// [`SingleTarget`](crate::target::SingleTarget).
     pub fn get(&self, target: &SingleTarget) -> Option<Torrent> {
@@ -31,6 +157,209 @@ impl TorrentList {
             .find(|t| target.matches_hash(&t.hash))
             .cloned()
     }
+
+    /// Finds all the torrents in the TorrentList matching a specific
+    /// [`MultiTarget`](crate::target::MultiTarget).
+    pub fn filter(&self, target: &MultiTarget) -> TorrentList {
+        self.0
+            .iter()
+            .filter(|t| target.matches(t))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns whether a torrent in the TorrentList matches a specific
+    /// [`SingleTarget`](crate::target::SingleTarget).
+    pub fn contains(&self, target: &SingleTarget) -> bool {
+        self.0.iter().any(|t| target.matches_hash(&t.hash))
+    }
+
+    /// Removes the first torrent matching a specific
+    /// [`SingleTarget`](crate::target::SingleTarget) from the TorrentList, and returns it.
+    pub fn remove(&mut self, target: &SingleTarget) -> Option<Torrent> {
+        let pos = self.0.iter().position(|t| target.matches_hash(&t.hash))?;
+        Some(self.0.remove(pos))
+    }
+
+    /// Replaces the torrent with the same [`TorrentID`](crate::id::TorrentID) as `entry`,
+    /// and returns the previous entry. If no such torrent is found, `entry` is appended
+    /// to the TorrentList and None is returned.
+    pub fn replace(&mut self, entry: Torrent) -> Option<Torrent> {
+        match self.0.iter_mut().find(|t| t.id == entry.id) {
+            Some(existing) => Some(std::mem::replace(existing, entry)),
+            None => {
+                self.0.push(entry);
+                None
+            }
+        }
+    }
+
+    /// Removes torrents with a duplicate [`TorrentID`](crate::id::TorrentID), keeping
+    /// only the first occurrence of each.
+    pub fn dedup_by_id(&mut self) {
+        let mut seen: HashSet<TorrentID> = HashSet::new();
+        self.0.retain(|t| seen.insert(t.id));
+    }
+
+    /// Merges the torrents of another TorrentList into this one. Entries designating the same
+    /// torrent are combined with [`Torrent::merge`](crate::torrent::Torrent::merge), including
+    /// a v1 entry and a v2 entry which are both part of the same hybrid torrent. New torrents
+    /// are appended.
+    pub fn merge(&mut self, other: TorrentList) {
+        for entry in other.0 {
+            self.insert_merged(entry);
+        }
+    }
+
+    /// Inserts an entry at the position of the first entry it matches, after merging all the
+    /// entries it matches. Merging can turn the entry into a hybrid torrent, which then matches
+    /// more entries, so the list is scanned again after each merge.
+    fn insert_merged(&mut self, mut entry: Torrent) {
+        let mut position = None;
+        while let Some(pos) = self.0.iter().position(|t| t.hash.matches(&entry.hash)) {
+            let mut existing = self.0.remove(pos);
+            existing.merge(&entry);
+            entry = existing;
+            position = Some(position.map_or(pos, |p: usize| p.min(pos)));
+        }
+        match position {
+            Some(pos) => self.0.insert(pos, entry),
+            None => self.0.push(entry),
+        }
+    }
+
+    /// Sorts the TorrentList by a single [`SortKey`](crate::list::SortKey). The sort is stable, so
+    /// torrents which compare equal keep their order.
+    pub fn sort_by(&mut self, key: SortKey, order: SortOrder) {
+        self.sort_by_keys(&[(key, order)]);
+    }
+
+    /// Sorts the TorrentList by several [`SortKey`](crate::list::SortKey)s, each key breaking the
+    /// ties of the previous ones, such as state then name. The sort is stable, so torrents which
+    /// compare equal on all keys keep their order.
+    pub fn sort_by_keys(&mut self, keys: &[(SortKey, SortOrder)]) {
+        self.0.sort_by(|a, b| {
+            keys.iter()
+                .map(|(key, order)| match order {
+                    SortOrder::Ascending => key.compare(a, b),
+                    SortOrder::Descending => key.compare(b, a),
+                })
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+    }
+
+    /// Compares the TorrentList with an older snapshot of the same list, for example when polling
+    /// a torrent client, and returns the changes as events. Torrents are matched by their
+    /// [`TorrentID`](crate::id::TorrentID).
+    ///
+    /// Removed torrents come first, in their order in the older snapshot, followed by the other
+    /// events in the order of this TorrentList. A torrent whose progress and state both changed
+    /// produces a ProgressChanged event followed by a StateChanged event.
+    pub fn diff(&self, older: &TorrentList) -> Vec<TorrentEvent> {
+        // Only the first of duplicate entries is compared
+        let mut previous: HashMap<TorrentID, &Torrent> = HashMap::new();
+        for torrent in older.iter() {
+            previous.entry(torrent.id).or_insert(torrent);
+        }
+        let mut current: HashMap<TorrentID, &Torrent> = HashMap::new();
+        for torrent in self.iter() {
+            current.entry(torrent.id).or_insert(torrent);
+        }
+
+        let mut events = Vec::new();
+        for torrent in older.iter() {
+            if !current.contains_key(&torrent.id) && previous.remove(&torrent.id).is_some() {
+                events.push(TorrentEvent::Removed {
+                    torrent: torrent.clone(),
+                });
+            }
+        }
+
+        for torrent in self.iter() {
+            if current.remove(&torrent.id).is_none() {
+                continue;
+            }
+            let old = match previous.get(&torrent.id) {
+                Some(old) => old,
+                None => {
+                    events.push(TorrentEvent::Added {
+                        torrent: torrent.clone(),
+                    });
+                    continue;
+                }
+            };
+            if old.progress != torrent.progress {
+                events.push(TorrentEvent::ProgressChanged {
+                    id: torrent.id,
+                    old: old.progress,
+                    new: torrent.progress,
+                });
+            }
+            if old.state != torrent.state {
+                events.push(TorrentEvent::StateChanged {
+                    id: torrent.id,
+                    old: old.state.clone(),
+                    new: torrent.state.clone(),
+                });
+            }
+        }
+        events
+    }
+
+    /// Formats the TorrentList as a table with the requested columns, for command-line output.
+    ///
+    /// The first line contains the column headers, followed by one line per torrent. Columns
+    /// are separated by two spaces, and aligned to the left except for numeric columns.
+    pub fn fmt_table(&self, columns: &[TorrentColumn]) -> String {
+        let rows: Vec<Vec<String>> = self
+            .0
+            .iter()
+            .map(|t| columns.iter().map(|c| t.cell(*c)).collect())
+            .collect();
+
+        let widths: Vec<usize> = columns
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                rows.iter()
+                    .map(|row| row[i].chars().count())
+                    .chain(std::iter::once(c.header().len()))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let headers = columns.iter().map(|c| c.header().to_string()).collect();
+        let mut out = String::new();
+        for row in std::iter::once(headers).chain(rows) {
+            let mut line = String::new();
+            for (i, (column, cell)) in columns.iter().zip(row).enumerate() {
+                if i > 0 {
+                    line.push_str("  ");
+                }
+                let padding = " ".repeat(widths[i] - cell.chars().count());
+                if column.is_numeric() {
+                    line.push_str(&padding);
+                    line.push_str(&cell);
+                } else {
+                    line.push_str(&cell);
+                    line.push_str(&padding);
+                }
+            }
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Formats the TorrentList as a table with the [`TorrentColumn::DEFAULT`](crate::torrent::TorrentColumn::DEFAULT)
+/// columns. See [`TorrentList::fmt_table`](crate::list::TorrentList::fmt_table).
+impl std::fmt::Display for TorrentList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.fmt_table(TorrentColumn::DEFAULT))
+    }
 }
 
 impl Default for TorrentList {
@@ -48,6 +377,15 @@ impl IntoIterator for TorrentList {
     }
 }
 
+impl<'a> IntoIterator for &'a TorrentList {
+    type Item = &'a Torrent;
+    type IntoIter = std::slice::Iter<'a, Torrent>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
 impl FromIterator<Torrent> for TorrentList {
     fn from_iter<I: IntoIterator<Item = Torrent>>(iter: I) -> Self {
         let mut c = TorrentList::new();
@@ -62,9 +400,12 @@ impl FromIterator<Torrent> for TorrentList {
 
 #[cfg(test)]
 mod tests {
-    use crate::{InfoHash, SingleTarget, Torrent};
+    use crate::{
+        Category, InfoHash, MultiTarget, PeerSource, Progress, SingleTarget, Torrent,
+        TorrentColumn, Tracker,
+    };
 
-    use super::TorrentList;
+    use super::{SortKey, SortOrder, TorrentEvent, TorrentList};
 
     fn dummy_list() -> TorrentList {
         TorrentList::from_vec(vec![
@@ -89,6 +430,109 @@ mod tests {
         ])
     }
 
+    #[test]
+    fn filters_by_tracker() {
+        let mut list = dummy_list();
+        let tracker = Tracker::new("udp://tracker.opentrackr.org:1337/announce").unwrap();
+        let other = Tracker::new("https://tracker.example.org/announce").unwrap();
+        for (torrent, source) in list.iter_mut().zip([&tracker, &other, &tracker]) {
+            torrent.sources.push(PeerSource::Tracker(source.clone()));
+        }
+
+        assert_eq!(list.filter(&MultiTarget::from(tracker)).len(), 2);
+        assert_eq!(list.filter(&MultiTarget::Tracker(other)).len(), 1);
+        let unused = Tracker::new("http://tracker.example.com/announce").unwrap();
+        assert!(list.filter(&MultiTarget::from(unused)).is_empty());
+    }
+
+    #[test]
+    fn paginates() {
+        let list = dummy_list();
+        assert_eq!(list.page(0, 2).len(), 2);
+        assert_eq!(list.page(2, 2)[0].id, list.iter().nth(2).unwrap().id);
+        assert!(list.page(3, 2).is_empty());
+        assert!(list.page(usize::MAX, usize::MAX).is_empty());
+        assert_eq!(list.page(1, usize::MAX).len(), 2);
+        assert_eq!(list.chunks(2).map(|c| c.len()).collect::<Vec<_>>(), [2, 1]);
+        assert_eq!(list.total(), 3);
+    }
+
+    #[test]
+    fn sorts_by_keys() {
+        let mut list = dummy_list();
+        for (torrent, (name, state)) in list.iter_mut().zip([
+            ("beta", "seeding"),
+            ("Alpha", "seeding"),
+            ("gamma", "downloading"),
+        ]) {
+            torrent.name = name.to_string();
+            torrent.state = state.to_string();
+        }
+        let names = |list: &TorrentList| list.iter().map(|t| t.name.clone()).collect::<Vec<_>>();
+
+        list.sort_by(SortKey::Name, SortOrder::Ascending);
+        assert_eq!(names(&list), ["Alpha", "beta", "gamma"]);
+        list.sort_by(SortKey::Name, SortOrder::Descending);
+        assert_eq!(names(&list), ["gamma", "beta", "Alpha"]);
+
+        list.sort_by_keys(&[
+            (SortKey::State, SortOrder::Descending),
+            (SortKey::Name, SortOrder::Ascending),
+        ]);
+        assert_eq!(names(&list), ["Alpha", "beta", "gamma"]);
+
+        // Stable when all keys are equal
+        list.sort_by(SortKey::Size, SortOrder::Descending);
+        assert_eq!(names(&list), ["Alpha", "beta", "gamma"]);
+    }
+
+    #[test]
+    fn diffs_snapshots() {
+        let older = dummy_list();
+        let mut newer = dummy_list();
+        let removed = newer.0.remove(0);
+        newer.0[0].progress = Progress::from_percent(50);
+        newer.0[0].state = "downloading".to_string();
+        let added = Torrent::dummy_from_hash(
+            &InfoHash::new("5d5b7c4c2d10a2a1b1a8da8c4e3b0c3c45e8f5a1").unwrap(),
+        );
+        newer.push(added.clone());
+
+        let events = newer.diff(&older);
+        assert_eq!(events.len(), 4);
+        assert!(
+            matches!(&events[0], TorrentEvent::Removed { torrent } if torrent.id == removed.id)
+        );
+        assert!(matches!(
+            &events[1],
+            TorrentEvent::ProgressChanged { old, new, .. }
+                if *old == Progress::default() && *new == Progress::from_percent(50)
+        ));
+        assert!(matches!(
+            &events[2],
+            TorrentEvent::StateChanged { old, new, .. } if old.is_empty() && new == "downloading"
+        ));
+        assert_eq!(events[1].id(), newer.0[0].id);
+        assert!(matches!(&events[3], TorrentEvent::Added { torrent } if torrent.id == added.id));
+
+        assert!(older.diff(&older).is_empty());
+    }
+
+    #[test]
+    fn filters_by_category() {
+        let mut list = dummy_list();
+        let categories = ["linux/distros/debian", "linux", "linuxbooks"];
+        for (torrent, category) in list.iter_mut().zip(categories) {
+            torrent.category = Some(Category::new(category).unwrap());
+        }
+
+        let linux = MultiTarget::from(Category::new("linux").unwrap());
+        assert_eq!(list.filter(&linux).len(), 2);
+        let debian = MultiTarget::from(Category::new("linux/distros/debian").unwrap());
+        assert_eq!(list.filter(&debian).len(), 1);
+        assert_eq!(list.filter(&MultiTarget::All).len(), 3);
+    }
+
     #[test]
     fn matches_v1() {
         let list = dummy_list();
@@ -183,6 +627,93 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_remove() {
+        let mut list = dummy_list();
+        let target = SingleTarget::new("631a31dd0a46257d5078c0dee4e66e26f73e42ac").unwrap();
+
+        assert!(list.contains(&target));
+        let removed = list.remove(&target).unwrap();
+        assert_eq!(
+            removed.id.as_str(),
+            "d8dd32ac93357c368556af3ac1d95c9d76bd0dff"
+        );
+        assert!(!list.contains(&target));
+        assert_eq!(list.len(), 2);
+        assert!(list.remove(&target).is_none());
+    }
+
+    #[test]
+    fn merges_v1_and_v2_into_hybrid() {
+        let v1 = InfoHash::new("631a31dd0a46257d5078c0dee4e66e26f73e42ac").unwrap();
+        let v2 = InfoHash::new("d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb")
+            .unwrap();
+        let hybrid = v1.hybrid(&v2).unwrap();
+        let other = InfoHash::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap();
+
+        let mut list = TorrentList::from_vec(vec![
+            Torrent::dummy_from_hash(&v1),
+            Torrent::dummy_from_hash(&other),
+            Torrent::dummy_from_hash(&v2),
+        ]);
+        let mut entry = Torrent::dummy_from_hash(&hybrid);
+        entry.name = "bittorrent-v1-v2-hybrid-test".to_string();
+        let new = Torrent::dummy_from_hash(
+            &InfoHash::new("caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e")
+                .unwrap(),
+        );
+        list.merge(TorrentList::from_vec(vec![entry, new.clone()]));
+
+        let hashes: Vec<InfoHash> = list.iter().map(|t| t.hash.clone()).collect();
+        assert_eq!(hashes, [hybrid, other, new.hash]);
+        assert_eq!(
+            list.iter().next().unwrap().name,
+            "bittorrent-v1-v2-hybrid-test"
+        );
+    }
+
+    #[test]
+    fn can_replace() {
+        let mut list = dummy_list();
+        let hash = InfoHash::new("C811B41641A09D192B8ED81B14064FFF55D85CE3").unwrap();
+        let mut torrent = Torrent::dummy_from_hash(&hash);
+        torrent.name = "Emma Goldman".to_string();
+
+        let previous = list.replace(torrent).unwrap();
+        assert_eq!(previous.name, "");
+        assert_eq!(list.len(), 3);
+        assert_eq!(
+            list.get(&SingleTarget::from(&hash)).unwrap().name,
+            "Emma Goldman"
+        );
+
+        let other = InfoHash::new("0000000000000000000000000000000000000000").unwrap();
+        assert!(list.replace(Torrent::dummy_from_hash(&other)).is_none());
+        assert_eq!(list.len(), 4);
+    }
+
+    #[test]
+    fn can_dedup_by_id() {
+        let mut list = dummy_list();
+        for torrent in dummy_list() {
+            list.push(torrent);
+        }
+        assert_eq!(list.len(), 6);
+
+        list.dedup_by_id();
+        assert_eq!(list.len(), 3);
+        assert!(!list.is_empty());
+    }
+
+    #[test]
+    fn can_iter_mut() {
+        let mut list = dummy_list();
+        for torrent in list.iter_mut() {
+            torrent.progress = Progress::from_percent(100);
+        }
+        assert!(list.iter().all(|t| t.progress.is_complete()));
+    }
+
     #[test]
     fn matches_v2_truncated() {
         let list = dummy_list();
@@ -196,4 +727,51 @@ mod tests {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn formats_table() {
+        let mut list = dummy_list();
+        for (i, t) in list.iter_mut().enumerate() {
+            t.name = format!("torrent {i}");
+            t.size = 1536 * 1024_i64.pow(i as u32);
+            t.progress = Progress::from_percent(50 * i as u8);
+            t.state = "seeding".to_string();
+        }
+
+        assert_eq!(
+            list.fmt_table(&[TorrentColumn::Id, TorrentColumn::Name, TorrentColumn::Size]),
+            "ID        NAME          SIZE\n\
+             c811b416  torrent 0  1.5 KiB\n\
+             d8dd32ac  torrent 1  1.5 MiB\n\
+             caf1e1c3  torrent 2  1.5 GiB\n"
+        );
+
+        assert_eq!(
+            list.to_string().lines().next().unwrap(),
+            "ID        NAME          SIZE  PROGRESS  STATE"
+        );
+        assert_eq!(
+            list.to_string().lines().nth(3).unwrap(),
+            "caf1e1c3  torrent 2  1.5 GiB      100%  seeding"
+        );
+        assert_eq!(
+            TorrentList::new().to_string(),
+            "ID  NAME  SIZE  PROGRESS  STATE\n"
+        );
+    }
+
+    #[test]
+    fn formats_torrent() {
+        let mut torrent = dummy_list().to_vec().remove(0);
+        torrent.name = "Emma Goldman".to_string();
+        torrent.size = 1000;
+        torrent.progress = Progress::from_percent(42);
+        assert_eq!(torrent.to_string(), "c811b416 Emma Goldman (1000 B, 42%)");
+
+        torrent.state = "downloading".to_string();
+        assert_eq!(
+            torrent.to_string(),
+            "c811b416 Emma Goldman (1000 B, 42%, downloading)"
+        );
+    }
 }
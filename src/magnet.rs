@@ -1,6 +1,12 @@
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use url::Url;
 
-use crate::{InfoHash, InfoHashError, TorrentID};
+use std::borrow::Cow;
+
+use crate::{
+    FileSelection, FileSelectionError, InfoHash, InfoHashError, PeerAddr, PeerAddrError,
+    SingleTarget, TorrentID, Tracker, TrackerError, TrackerParseOptions,
+};
 
 /// Error occurred during parsing a [`MagnetLink`](crate::magnet::MagnetLink).
 #[derive(Clone, Debug, PartialEq)]
@@ -17,10 +23,45 @@ pub enum MagnetLinkError {
     InvalidHash { source: InfoHashError },
     /// Too many hashes were found in the magnet URI, expected two at most.
     TooManyHashes { number: usize },
-    /// No name was contained in the magnet URI. This is technically allowed by
-    /// some implementations, but should not be encouraged/supported.
-    #[cfg(feature = "magnet_force_name")]
+    /// A `urn:btmh:` hash was not a valid hex-encoded multihash.
+    InvalidMultihash { multihash: String },
+    /// A `urn:btmh:` multihash uses a hash function other than sha2-256 (function code `0x12`,
+    /// 32 bytes long), which is the only one defined by Bittorrent v2.
+    UnsupportedMultihash { code: u64, length: u64 },
+    /// The exact length (`xl` URL param) was not a valid unsigned integer.
+    InvalidExactLength { length: String },
+    /// The exact source (`xs` URL param) was not a valid URI.
+    InvalidExactSource { source: url::ParseError },
+    /// A peer address (`x.pe` URL param) was not a valid
+    /// [`PeerAddr`](crate::peer::PeerAddr).
+    InvalidPeer { source: PeerAddrError },
+    /// The file selection (`so` URL param) was not a valid
+    /// [`FileSelection`](crate::selection::FileSelection).
+    InvalidFileSelection { source: FileSelectionError },
+    /// A tracker (`tr` URL param) was not a valid [`Tracker`](crate::tracker::Tracker), and
+    /// [`MagnetParseOptions::strict_trackers`](crate::magnet::MagnetParseOptions::strict_trackers)
+    /// is enabled.
+    InvalidTracker { source: TrackerError },
+    /// An exact topic (`xt` URL param) used a URN namespace which is not a known content hash, and
+    /// [`MagnetParseOptions::allow_unknown_urns`](crate::magnet::MagnetParseOptions::allow_unknown_urns)
+    /// is disabled.
+    UnknownUrn { urn: String },
+    /// An exact topic (`xt` URL param) used a Bittorrent URN namespace which is not supported,
+    /// such as a `urn:btpk:` public key for mutable torrents, or a malformed `urn:btih`/`urn:btmh`
+    /// prefix.
+    UnsupportedURN { urn: String },
+    /// A URL param had an empty value, and
+    /// [`MagnetParseOptions::allow_empty_values`](crate::magnet::MagnetParseOptions::allow_empty_values)
+    /// is disabled.
+    EmptyValue { key: String },
+    /// No name was contained in the magnet URI, and
+    /// [`MagnetParseOptions::require_name`](crate::magnet::MagnetParseOptions::require_name)
+    /// is enabled. This is technically allowed by some implementations, but should not be
+    /// encouraged/supported.
     NoNameFound,
+    /// The magnet URI is longer than allowed by
+    /// [`MagnetParseOptions::max_length`](crate::magnet::MagnetParseOptions::max_length).
+    TooLong { length: usize, max: usize },
 }
 
 impl std::fmt::Display for MagnetLinkError {
@@ -41,10 +82,48 @@ impl std::fmt::Display for MagnetLinkError {
             MagnetLinkError::TooManyHashes { number } => {
                 write!(f, "Too many hashes ({number})")
             }
-            #[cfg(feature = "magnet_force_name")]
+            MagnetLinkError::InvalidMultihash { multihash } => {
+                write!(f, "Invalid multihash: {multihash}")
+            }
+            MagnetLinkError::UnsupportedMultihash { code, length } => {
+                write!(
+                    f,
+                    "Unsupported multihash function {code:#x} with length {length} (only sha2-256 is supported)"
+                )
+            }
+            MagnetLinkError::InvalidExactLength { length } => {
+                write!(f, "Invalid exact length: {length}")
+            }
+            MagnetLinkError::InvalidExactSource { source } => {
+                write!(f, "Invalid exact source: {source}")
+            }
+            MagnetLinkError::InvalidPeer { source } => {
+                write!(f, "Invalid peer: {source}")
+            }
+            MagnetLinkError::InvalidFileSelection { source } => {
+                write!(f, "Invalid file selection: {source}")
+            }
+            MagnetLinkError::InvalidTracker { source } => {
+                write!(f, "Invalid tracker: {source}")
+            }
+            MagnetLinkError::UnknownUrn { urn } => {
+                write!(f, "Unknown URN: {urn}")
+            }
+            MagnetLinkError::UnsupportedURN { urn } => {
+                write!(
+                    f,
+                    "Unsupported URN: {urn} (only urn:btih: and urn:btmh: are supported)"
+                )
+            }
+            MagnetLinkError::EmptyValue { key } => {
+                write!(f, "Empty value for URL param: {key}")
+            }
             MagnetLinkError::NoNameFound => {
                 write!(f, "No name found")
             }
+            MagnetLinkError::TooLong { length, max } => {
+                write!(f, "Magnet URI too long: {length} bytes (max {max})")
+            }
         }
     }
 }
@@ -55,6 +134,24 @@ impl From<InfoHashError> for MagnetLinkError {
     }
 }
 
+impl From<PeerAddrError> for MagnetLinkError {
+    fn from(e: PeerAddrError) -> MagnetLinkError {
+        MagnetLinkError::InvalidPeer { source: e }
+    }
+}
+
+impl From<FileSelectionError> for MagnetLinkError {
+    fn from(e: FileSelectionError) -> MagnetLinkError {
+        MagnetLinkError::InvalidFileSelection { source: e }
+    }
+}
+
+impl From<TrackerError> for MagnetLinkError {
+    fn from(e: TrackerError) -> MagnetLinkError {
+        MagnetLinkError::InvalidTracker { source: e }
+    }
+}
+
 impl From<url::ParseError> for MagnetLinkError {
     fn from(e: url::ParseError) -> MagnetLinkError {
         MagnetLinkError::InvalidURI { source: e }
@@ -66,41 +163,187 @@ impl std::error::Error for MagnetLinkError {
         match self {
             MagnetLinkError::InvalidURI { source } => Some(source),
             MagnetLinkError::InvalidHash { source } => Some(source),
+            MagnetLinkError::InvalidExactSource { source } => Some(source),
+            MagnetLinkError::InvalidPeer { source } => Some(source),
+            MagnetLinkError::InvalidTracker { source } => Some(source),
+            MagnetLinkError::InvalidFileSelection { source } => Some(source),
             _ => None,
         }
     }
 }
 
+/// Options controlling how a [`MagnetLink`](crate::magnet::MagnetLink) is parsed with
+/// [`MagnetLink::new_with`](crate::magnet::MagnetLink::new_with).
+///
+/// The [`Default`](std::default::Default) options are the ones used by
+/// [`MagnetLink::new`](crate::magnet::MagnetLink::new). They can be tweaked at runtime, so that
+/// libraries depending on HighTorrent do not need to pick crate features for their users.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MagnetParseOptions {
+    /// Fail with [`MagnetLinkError::NoNameFound`](crate::magnet::MagnetLinkError::NoNameFound)
+    /// when there is no name (`dn` URL param). Disabled by default, unless the deprecated
+    /// `magnet_force_name` crate feature is enabled.
+    pub require_name: bool,
+    /// Accept exact topics (`xt` URL params) which are neither Bittorrent infohashes nor other
+    /// known content hashes (such as `urn:sha1:` or `urn:ed2k:`). They are exposed by
+    /// [`MagnetLink::other_exact_topics`](crate::magnet::MagnetLink::other_exact_topics).
+    /// When disabled, fail with [`MagnetLinkError::UnknownUrn`](crate::magnet::MagnetLinkError::UnknownUrn).
+    /// Enabled by default.
+    pub allow_unknown_urns: bool,
+    /// Maximum number of trackers (`tr` URL params) to keep, extra trackers being ignored.
+    /// Unlimited by default.
+    pub max_trackers: Option<usize>,
+    /// Ignore URL params with an empty value (eg. `dn=`). When disabled, fail with
+    /// [`MagnetLinkError::EmptyValue`](crate::magnet::MagnetLinkError::EmptyValue).
+    /// Enabled by default.
+    pub allow_empty_values: bool,
+    /// Decode `+` as a space in URL params, as done for HTML forms
+    /// (`application/x-www-form-urlencoded`). When disabled, `+` is kept as is and only
+    /// percent-escapes (such as `%20`) are decoded, as specified by RFC 3986. Enabled by default,
+    /// because most clients encode spaces in the magnet name (`dn` URL param) as `+`.
+    pub plus_as_space: bool,
+    /// Accept trackers (`tr` URL params) with an unknown scheme, as described in
+    /// [`TrackerParseOptions::allow_unknown_schemes`](crate::tracker::TrackerParseOptions::allow_unknown_schemes).
+    /// When disabled, they are ignored like other invalid trackers. Disabled by default.
+    pub allow_unknown_tracker_schemes: bool,
+    /// Fail with [`MagnetLinkError::InvalidTracker`](crate::magnet::MagnetLinkError::InvalidTracker)
+    /// when a tracker (`tr` URL param) is invalid. When disabled, invalid trackers are ignored,
+    /// as done when parsing a [`TorrentFile`](crate::torrent_file::TorrentFile). Disabled by
+    /// default.
+    pub strict_trackers: bool,
+    /// Fail with [`MagnetLinkError::TooLong`](crate::magnet::MagnetLinkError::TooLong) when the
+    /// magnet URI is longer than this size, in bytes, before parsing it. Defaults to
+    /// [`DEFAULT_MAX_LENGTH`](crate::magnet::MagnetParseOptions::DEFAULT_MAX_LENGTH).
+    pub max_length: Option<usize>,
+}
+
+impl MagnetParseOptions {
+    /// Default for [`max_length`](crate::magnet::MagnetParseOptions::max_length) (64 KiB).
+    pub const DEFAULT_MAX_LENGTH: usize = 64 * 1024;
+}
+
+impl Default for MagnetParseOptions {
+    fn default() -> MagnetParseOptions {
+        MagnetParseOptions {
+            require_name: cfg!(feature = "magnet_force_name"),
+            allow_unknown_urns: true,
+            max_trackers: None,
+            allow_empty_values: true,
+            plus_as_space: true,
+            allow_unknown_tracker_schemes: false,
+            strict_trackers: false,
+            max_length: Some(MagnetParseOptions::DEFAULT_MAX_LENGTH),
+        }
+    }
+}
+
 /// A Magnet URI, which contains the infohash(es) but not the entire meta info.
 ///
 /// The MagnetLink can provide information about the torrent
-/// [`name`](crate::magnet::MagnetLink::name) and [`hash`](crate::magnet::MagnetLink::hash).
+/// [`name`](crate::magnet::MagnetLink::name) and [`hash`](crate::magnet::MagnetLink::hash),
+/// as well as the optional [`exact_length`](crate::magnet::MagnetLink::exact_length) and
+/// [`exact_source`](crate::magnet::MagnetLink::exact_source), the
+/// [`trackers`](crate::magnet::MagnetLink::trackers), the
+/// [`web_seeds`](crate::magnet::MagnetLink::web_seeds), the
+/// [`acceptable_sources`](crate::magnet::MagnetLink::acceptable_sources), the
+/// [`peers`](crate::magnet::MagnetLink::peers) and the
+/// [`file_selection`](crate::magnet::MagnetLink::file_selection). The name and trackers can be modified, and the
+/// MagnetLink can be formatted back to a magnet URI with [`Display`](std::fmt::Display).
 /// Other fields can be contained in the magnet URI, as explained [on Wikipedia](https://en.wikipedia.org/wiki/Magnet_URI_scheme). However,
 /// they are currently not exposed by this library.
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow),
+    diesel(sql_type = diesel::sql_types::Text)
+)]
 pub struct MagnetLink {
     hash: InfoHash,
     name: String,
+    name_raw: String,
+    exact_length: Option<u64>,
+    exact_source: Option<Url>,
+    other_exact_topics: Vec<String>,
+    trackers: Vec<Tracker>,
+    web_seeds: Vec<Url>,
+    acceptable_sources: Vec<Url>,
+    peers: Vec<PeerAddr>,
+    file_selection: Option<FileSelection>,
+    extra_params: Vec<(String, String)>,
 }
 
 impl MagnetLink {
     /// Generates a new MagnetLink from a string. Will fail if the string is not a valid URL, and
     /// in the conditions defined in [`MagnetLink::from_url`](crate::magnet::MagnetLink::from_url).
     pub fn new(s: &str) -> Result<MagnetLink, MagnetLinkError> {
+        MagnetLink::new_with(s, MagnetParseOptions::default())
+    }
+
+    /// Generates a new MagnetLink from a string like [`MagnetLink::new`](crate::magnet::MagnetLink::new),
+    /// with custom [`MagnetParseOptions`](crate::magnet::MagnetParseOptions).
+    pub fn new_with(s: &str, options: MagnetParseOptions) -> Result<MagnetLink, MagnetLinkError> {
+        check_length(s, options)?;
         let u = Url::parse(s)?;
-        MagnetLink::from_url(&u)
+        MagnetLink::from_url_with(&u, options)
+    }
+
+    /// Finds the magnet URIs in free-form text, such as an HTML page or an RSS feed, and parses
+    /// them with [`MagnetLink::new`](crate::magnet::MagnetLink::new), in the order they appear.
+    ///
+    /// A magnet URI starts with `magnet:?`, ignoring case, and ends before whitespace, quotes
+    /// or angle brackets. Trailing punctuation is not part of the URI, nor is a closing
+    /// parenthesis or bracket without a matching opening one, so that URIs written in prose or
+    /// Markdown are found. HTML-escaped ampersands (`&amp;`) are unescaped before parsing.
+    ///
+    /// ```
+    /// use hightorrent::MagnetLink;
+    ///
+    /// let html = r#"<a href="magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&amp;dn=Emma">
+    /// Download</a> or try (magnet:?xt=urn:btih:nothex)."#;
+    /// let magnets = MagnetLink::find_in_text(html);
+    /// assert_eq!(magnets.len(), 2);
+    /// assert_eq!(magnets[0].as_ref().unwrap().name(), "Emma");
+    /// assert!(magnets[1].is_err());
+    /// ```
+    pub fn find_in_text(text: &str) -> Vec<Result<MagnetLink, MagnetLinkError>> {
+        find_magnet_uris(text)
+            .iter()
+            .map(|uri| MagnetLink::new(uri))
+            .collect()
     }
 
     /// Generates a new MagnetLink from a parsed URL.
     /// Will generate a weird name if multiple "dn" params are contained in the URL.
     /// Will fail if:
+    ///   - the URI is longer than
+    ///     [`MagnetParseOptions::max_length`](crate::magnet::MagnetParseOptions::max_length)
     ///   - the scheme is not `magnet`
-    ///   - there is no name (`dn` URL param)
+    ///   - there is no name (`dn` URL param), when
+    ///     [`MagnetParseOptions::require_name`](crate::magnet::MagnetParseOptions::require_name) is enabled
     ///   - no hash was found (`xt` URL param, with `urn:btih:` prefix for v1 infohash,
-    ///     `urn:btmh:1220` for v2 infohash)
+    ///     `urn:btmh:` for v2 infohash, ignoring case)
+    ///   - another Bittorrent URN was found, such as `urn:btpk:`
+    ///   - a v2 infohash is not a valid sha2-256 [multihash](https://multiformats.io/multihash/)
     ///   - more than one hash of the same type was found
     ///   - the hashes were not valid according to [`InfoHash::new`](crate::hash::InfoHash::new)
+    ///   - the exact length (`xl` URL param) is not a valid unsigned integer
+    ///   - the exact source (`xs` URL param) is not a valid URI
+    ///   - a tracker (`tr` URL param) is not a valid [`Tracker`](crate::tracker::Tracker)
+    ///   - a peer (`x.pe` URL param) is not a valid [`PeerAddr`](crate::peer::PeerAddr)
+    ///   - the file selection (`so` URL param) is not a valid
+    ///     [`FileSelection`](crate::selection::FileSelection)
     pub fn from_url(u: &Url) -> Result<MagnetLink, MagnetLinkError> {
+        MagnetLink::from_url_with(u, MagnetParseOptions::default())
+    }
+
+    /// Generates a new MagnetLink from a parsed URL like
+    /// [`MagnetLink::from_url`](crate::magnet::MagnetLink::from_url), with custom
+    /// [`MagnetParseOptions`](crate::magnet::MagnetParseOptions).
+    pub fn from_url_with(
+        u: &Url,
+        options: MagnetParseOptions,
+    ) -> Result<MagnetLink, MagnetLinkError> {
+        check_length(u.as_str(), options)?;
         if u.scheme() != "magnet" {
             return Err(MagnetLinkError::InvalidScheme {
                 scheme: u.scheme().to_string(),
@@ -108,29 +351,113 @@ impl MagnetLink {
         }
 
         let mut name = String::new();
+        let mut name_raw = String::new();
         let mut hashes: Vec<String> = Vec::new();
+        let mut exact_length: Option<u64> = None;
+        let mut exact_source: Option<Url> = None;
+        let mut other_exact_topics: Vec<String> = Vec::new();
+        let mut trackers: Vec<Tracker> = Vec::new();
+        let mut web_seeds: Vec<Url> = Vec::new();
+        let mut acceptable_sources: Vec<Url> = Vec::new();
+        let mut peers: Vec<PeerAddr> = Vec::new();
+        let mut file_selection: Option<FileSelection> = None;
+        let mut extra_params: Vec<(String, String)> = Vec::new();
+
+        for (raw_key, raw_val) in raw_query_pairs(u.query().unwrap_or("")) {
+            let key = decode_param(raw_key, options.plus_as_space);
+            let val = decode_param(raw_val, options.plus_as_space);
+            if val.is_empty() {
+                if options.allow_empty_values {
+                    continue;
+                }
+                return Err(MagnetLinkError::EmptyValue {
+                    key: key.to_string(),
+                });
+            }
 
-        for (key, val) in u.query_pairs() {
             // Deref cow into str then reference it
             match &*key {
                 "xt" => {
-                    if val.starts_with("urn:btih:") {
-                        // Infohash v1
-                        hashes.push(val.strip_prefix("urn:btih:").unwrap().to_string());
-                    } else if val.starts_with("urn:btmh:1220") {
+                    // URN namespaces are case-insensitive (RFC 8141)
+                    let val = val.trim();
+                    if let Some(hash) = strip_prefix_ignore_case(val, "urn:btih:") {
+                        // Infohash v1, hex or base32-encoded in older magnets
+                        if hash.len() == 32 {
+                            hashes.push(InfoHash::from_base32(hash)?.to_string());
+                        } else {
+                            hashes.push(hash.to_string());
+                        }
+                    } else if let Some(multihash) = strip_prefix_ignore_case(val, "urn:btmh:") {
                         // Infohash v2
-                        hashes.push(val.strip_prefix("urn:btmh:1220").unwrap().to_string());
+                        hashes.push(parse_multihash(multihash)?);
+                    } else if strip_prefix_ignore_case(val, "urn:bt").is_some() {
+                        return Err(MagnetLinkError::UnsupportedURN {
+                            urn: val.to_string(),
+                        });
+                    } else if options.allow_unknown_urns || is_known_urn(val) {
+                        other_exact_topics.push(val.to_string());
+                    } else {
+                        return Err(MagnetLinkError::UnknownUrn {
+                            urn: val.to_string(),
+                        });
                     }
                 }
                 "dn" => {
                     name.push_str(&val);
+                    name_raw.push_str(raw_val);
+                }
+                "xl" => {
+                    let length =
+                        val.parse::<u64>()
+                            .map_err(|_| MagnetLinkError::InvalidExactLength {
+                                length: val.to_string(),
+                            })?;
+                    exact_length = Some(length);
+                }
+                "xs" => {
+                    let source = Url::parse(&val)
+                        .map_err(|e| MagnetLinkError::InvalidExactSource { source: e })?;
+                    exact_source = Some(source);
+                }
+                "as" => {
+                    if let Some(source) = parse_web_seed(&val) {
+                        acceptable_sources.push(source);
+                    }
+                }
+                "tr" => {
+                    let tracker = match Tracker::new_with(
+                        &val,
+                        TrackerParseOptions {
+                            allow_unknown_schemes: options.allow_unknown_tracker_schemes,
+                        },
+                    ) {
+                        Ok(tracker) => tracker,
+                        Err(e) if options.strict_trackers => return Err(e.into()),
+                        Err(_) => continue,
+                    };
+                    let full = options
+                        .max_trackers
+                        .map_or(false, |max| trackers.len() >= max);
+                    if !full && !trackers.contains(&tracker) {
+                        trackers.push(tracker);
+                    }
+                }
+                "ws" => {
+                    if let Some(seed) = parse_web_seed(&val) {
+                        web_seeds.push(seed);
+                    }
+                }
+                "x.pe" => {
+                    peers.push(PeerAddr::new(&val)?);
+                }
+                "so" => {
+                    file_selection = Some(FileSelection::new(&val)?);
                 }
-                _ => continue,
+                _ => extra_params.push((key.to_string(), val.to_string())),
             }
         }
 
-        #[cfg(feature = "magnet_force_name")]
-        if name.is_empty() {
+        if options.require_name && name.is_empty() {
             return Err(MagnetLinkError::NoNameFound);
         }
 
@@ -162,7 +489,17 @@ impl MagnetLink {
 
         Ok(MagnetLink {
             name,
+            name_raw,
             hash: final_hash,
+            exact_length,
+            exact_source,
+            other_exact_topics,
+            trackers,
+            web_seeds,
+            acceptable_sources,
+            peers,
+            file_selection,
+            extra_params,
         })
     }
 
@@ -173,21 +510,549 @@ impl MagnetLink {
 
     /// Returns the torrent name contained in the MagnetLink. If multiple names are contained in the URL,
     /// they will all be appended. If no name is contained in the magnet link, the result of this function will be empty.
-    /// However, when the `magnet_force_name` feature is enabled, the `MagnetLink` creation will have errored when the name
-    /// is not provided and so this function is guaranteed to return an actual name.
+    /// However, when [`MagnetParseOptions::require_name`](crate::magnet::MagnetParseOptions::require_name) is enabled,
+    /// the `MagnetLink` creation will have errored when the name is not provided and so this function is guaranteed to
+    /// return an actual name.
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    /// Returns a name to show in user interfaces, which is never empty. This is the
+    /// [`name`](crate::magnet::MagnetLink::name) when there is one, including a name resolved
+    /// later and set with [`set_name`](crate::magnet::MagnetLink::set_name). Otherwise, it is the
+    /// [`TorrentID`](crate::id::TorrentID) truncated to 8 characters, as in
+    /// [`TorrentColumn::Id`](crate::torrent::TorrentColumn::Id).
+    pub fn display_name(&self) -> Cow<'_, str> {
+        if self.name.trim().is_empty() {
+            Cow::Owned(self.id().as_str().chars().take(8).collect())
+        } else {
+            Cow::Borrowed(&self.name)
+        }
+    }
+
+    /// Returns the torrent name exactly as contained in the MagnetLink, without decoding
+    /// percent-escapes or `+` signs. This can be useful when clients disagree on the
+    /// decoded [`name`](crate::magnet::MagnetLink::name).
+    pub fn name_raw(&self) -> &str {
+        &self.name_raw
+    }
+
     /// Returns the [`TorrentID`](crate::id::TorrentID) for the MagnetLink
     pub fn id(&self) -> TorrentID {
         self.hash.id()
     }
+
+    /// Returns the exact length of the torrent contents in bytes (`xl` URL param), if any.
+    pub fn exact_length(&self) -> Option<u64> {
+        self.exact_length
+    }
+
+    /// Returns the exact source (`xs` URL param) where the torrent file can be downloaded
+    /// from, if any. This can be an HTTP(S) URL or another URI such as a `urn:btpk:` key.
+    pub fn exact_source(&self) -> Option<&Url> {
+        self.exact_source.as_ref()
+    }
+
+    /// Returns the trackers (`tr` URL params) contained in the MagnetLink, in order and without
+    /// duplicates.
+    pub fn trackers(&self) -> &[Tracker] {
+        &self.trackers
+    }
+
+    /// Returns the [BEP-0053](https://www.bittorrent.org/beps/bep_0053.html) file selection
+    /// (`so` URL param), if any, listing the only files which should be downloaded.
+    pub fn file_selection(&self) -> Option<&FileSelection> {
+        self.file_selection.as_ref()
+    }
+
+    /// Returns the exact topics (`xt` URL params) which are not Bittorrent infohashes, such as
+    /// `urn:sha1:` or `urn:ed2k:` hashes, as found in the MagnetLink. They are kept so that
+    /// formatting the MagnetLink does not lose data.
+    pub fn other_exact_topics(&self) -> &[String] {
+        &self.other_exact_topics
+    }
+
+    /// Replaces the torrent name (`dn` URL param). An empty name removes it from the magnet URI.
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+        self.name_raw = encode_param(name).to_string();
+    }
+
+    /// Replaces the [BEP-0053](https://www.bittorrent.org/beps/bep_0053.html) file selection
+    /// (`so` URL param), such as one returned by
+    /// [`TorrentFile::select_files`](crate::torrent_file::TorrentFile::select_files). None
+    /// removes it from the magnet URI.
+    pub fn set_file_selection(&mut self, selection: Option<FileSelection>) {
+        self.file_selection = selection;
+    }
+
+    /// Appends a tracker (`tr` URL param), unless the same tracker is already present.
+    /// Returns whether the tracker was added.
+    pub fn add_tracker(&mut self, tracker: Tracker) -> bool {
+        if self.trackers.contains(&tracker) {
+            return false;
+        }
+        self.trackers.push(tracker);
+        true
+    }
+
+    /// Merges another MagnetLink for the same torrent into this one. Returns false, leaving the
+    /// MagnetLink unchanged, when the infohashes do not [`match`](crate::hash::InfoHash::matches).
+    ///
+    /// The richer infohash is kept, so that merging a v1 MagnetLink with the hybrid MagnetLink it
+    /// is part of produces a hybrid MagnetLink. The name, exact length and exact source of this
+    /// MagnetLink are kept when they are set, and filled from `other` otherwise. Trackers, web
+    /// seeds, peers and other exact topics are combined without duplicates.
+    pub fn merge(&mut self, other: &MagnetLink) -> bool {
+        if !self.hash.matches(&other.hash) {
+            return false;
+        }
+
+        if other.hash.is_hybrid() {
+            self.hash = other.hash.clone();
+        }
+        if self.name.is_empty() {
+            self.name = other.name.clone();
+            self.name_raw = other.name_raw.clone();
+        }
+        self.exact_length = self.exact_length.or(other.exact_length);
+        if self.exact_source.is_none() {
+            self.exact_source = other.exact_source.clone();
+        }
+        for tracker in &other.trackers {
+            self.add_tracker(tracker.clone());
+        }
+        for web_seed in &other.web_seeds {
+            if !self.web_seeds.contains(web_seed) {
+                self.web_seeds.push(web_seed.clone());
+            }
+        }
+        for source in &other.acceptable_sources {
+            if !self.acceptable_sources.contains(source) {
+                self.acceptable_sources.push(source.clone());
+            }
+        }
+        for peer in &other.peers {
+            if !self.peers.contains(peer) {
+                self.peers.push(peer.clone());
+            }
+        }
+        for topic in &other.other_exact_topics {
+            if !self.other_exact_topics.contains(topic) {
+                self.other_exact_topics.push(topic.clone());
+            }
+        }
+        true
+    }
+
+    /// Deduplicates MagnetLinks designating the same torrent, merging them with
+    /// [`MagnetLink::merge`](crate::magnet::MagnetLink::merge) instead of comparing their URIs.
+    /// This includes a v1 MagnetLink and a v2 MagnetLink when the hybrid MagnetLink they are part
+    /// of is also present. MagnetLinks are returned in the order they first appeared.
+    pub fn dedup<I: IntoIterator<Item = MagnetLink>>(magnets: I) -> Vec<MagnetLink> {
+        let mut deduped: Vec<MagnetLink> = Vec::new();
+        for mut magnet in magnets {
+            // Merging can turn the magnet into a hybrid, which then matches more entries, so the
+            // list is scanned again after each merge. The earliest MagnetLink is merged into.
+            let mut position: Option<usize> = None;
+            while let Some(pos) = deduped.iter().position(|m| m.hash.matches(&magnet.hash)) {
+                let mut existing = deduped.remove(pos);
+                if position.map_or(true, |p| pos < p) {
+                    existing.merge(&magnet);
+                    magnet = existing;
+                    position = Some(pos);
+                } else {
+                    magnet.merge(&existing);
+                }
+            }
+            match position {
+                Some(pos) => deduped.insert(pos, magnet),
+                None => deduped.push(magnet),
+            }
+        }
+        deduped
+    }
+
+    /// Removes a tracker (`tr` URL param). Returns whether the tracker was present.
+    pub fn remove_tracker(&mut self, tracker: &Tracker) -> bool {
+        let len = self.trackers.len();
+        self.trackers.retain(|t| t != tracker);
+        self.trackers.len() != len
+    }
+
+    /// Removes all trackers (`tr` URL params), for example before adding your own.
+    pub fn strip_trackers(&mut self) {
+        self.trackers.clear();
+    }
+
+    /// Returns all the decoded URL params of the MagnetLink, including extension params
+    /// which are not otherwise supported by this library (such as `kt` keywords).
+    ///
+    /// The params reflect the current state of the MagnetLink, in the order they are formatted by
+    /// [`Display`](std::fmt::Display): exact topics, name, exact length and source, trackers,
+    /// web seeds, peers, file selection, and finally other params in their original order.
+    pub fn params(&self) -> impl Iterator<Item = (&str, Cow<'_, str>)> {
+        self.to_pairs().into_iter()
+    }
+
+    /// Builds the URL params of the MagnetLink, shared by
+    /// [`params`](crate::magnet::MagnetLink::params) and [`Display`](std::fmt::Display).
+    fn to_pairs(&self) -> Vec<(&str, Cow<'_, str>)> {
+        let mut pairs = Vec::new();
+        if let Some(v1) = self.hash.v1() {
+            pairs.push(("xt", Cow::Owned(format!("urn:btih:{v1}"))));
+        }
+        if let Some(v2) = self.hash.v2() {
+            let topic = format!("urn:btmh:{MULTIHASH_SHA256:02x}20{v2}");
+            pairs.push(("xt", Cow::Owned(topic)));
+        }
+        for topic in &self.other_exact_topics {
+            pairs.push(("xt", Cow::Borrowed(topic.as_str())));
+        }
+        if !self.name.is_empty() {
+            pairs.push(("dn", Cow::Borrowed(self.name.as_str())));
+        }
+        if let Some(length) = self.exact_length {
+            pairs.push(("xl", Cow::Owned(length.to_string())));
+        }
+        if let Some(source) = &self.exact_source {
+            pairs.push(("xs", Cow::Borrowed(source.as_str())));
+        }
+        for source in &self.acceptable_sources {
+            pairs.push(("as", Cow::Borrowed(source.as_str())));
+        }
+        for tracker in &self.trackers {
+            pairs.push(("tr", Cow::Borrowed(tracker.url())));
+        }
+        for seed in &self.web_seeds {
+            pairs.push(("ws", Cow::Borrowed(seed.as_str())));
+        }
+        for peer in &self.peers {
+            pairs.push(("x.pe", Cow::Owned(peer.to_string())));
+        }
+        if let Some(selection) = &self.file_selection {
+            pairs.push(("so", Cow::Owned(selection.to_string())));
+        }
+        for (key, val) in &self.extra_params {
+            pairs.push((key.as_str(), Cow::Borrowed(val.as_str())));
+        }
+        pairs
+    }
+
+    /// Returns the [BEP-0019](https://www.bittorrent.org/beps/bep_0019.html) web seeds
+    /// (`ws` URL params) contained in the MagnetLink. Web seeds which are not HTTP(S) URLs are
+    /// ignored, like in [`TorrentFile::web_seeds`](crate::torrent_file::TorrentFile::web_seeds).
+    pub fn web_seeds(&self) -> &[Url] {
+        &self.web_seeds
+    }
+
+    /// Returns the acceptable sources (`as` URL params) contained in the MagnetLink, which are
+    /// HTTP(S) URLs to download the file directly, outside of Bittorrent. Other URLs are ignored.
+    pub fn acceptable_sources(&self) -> &[Url] {
+        &self.acceptable_sources
+    }
+
+    /// Returns the peer addresses (`x.pe` URL params) contained in the MagnetLink, which
+    /// can be used to bootstrap a download without trackers or DHT.
+    pub fn peers(&self) -> &[PeerAddr] {
+        &self.peers
+    }
+
+    /// Returns true when both MagnetLinks produce the same magnet URI, including their name,
+    /// trackers and other URL params. Unlike `==`, which only compares infohashes, this tells
+    /// apart two links to the same torrent.
+    pub fn exact_eq(&self, other: &MagnetLink) -> bool {
+        self.to_string() == other.to_string()
+    }
+}
+
+/// Finds the magnet URIs in free-form text, unescaping HTML ampersands.
+fn find_magnet_uris(text: &str) -> Vec<String> {
+    // ASCII lowercase keeps the byte offsets of the original text
+    let lowercase = text.to_ascii_lowercase();
+    let mut uris = Vec::new();
+    let mut offset = 0;
+
+    while let Some(start) = lowercase[offset..].find("magnet:?").map(|pos| offset + pos) {
+        let len = text[start..]
+            .find(|c: char| c.is_whitespace() || "\"'<>`{}|\\^".contains(c))
+            .unwrap_or(text.len() - start);
+        offset = start + len;
+
+        let mut uri = &text[start..offset];
+        // A quote may also be HTML-escaped
+        for entity in ["&quot;", "&#34;", "&lt;", "&gt;"] {
+            if let Some(end) = uri.find(entity) {
+                uri = &uri[..end];
+            }
+        }
+        loop {
+            let trimmed = uri.trim_end_matches(['.', ',', ';', ':', '!', '?']);
+            let trimmed = match trimmed.chars().last() {
+                Some(')') if trimmed.matches('(').count() < trimmed.matches(')').count() => {
+                    &trimmed[..trimmed.len() - 1]
+                }
+                Some(']') if trimmed.matches('[').count() < trimmed.matches(']').count() => {
+                    &trimmed[..trimmed.len() - 1]
+                }
+                _ => trimmed,
+            };
+            if trimmed == uri {
+                break;
+            }
+            uri = trimmed;
+        }
+
+        uris.push(
+            uri.replace("&amp;", "&")
+                .replace("&#38;", "&")
+                .replace("&#x26;", "&"),
+        );
+    }
+    uris
+}
+
+impl std::fmt::Display for MagnetLink {
+    /// Formats the MagnetLink as a magnet URI, which can be parsed again with
+    /// [`MagnetLink::new`](crate::magnet::MagnetLink::new).
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "magnet:")?;
+        for (i, (key, val)) in self.to_pairs().iter().enumerate() {
+            let sep = if i == 0 { '?' } else { '&' };
+            write!(f, "{sep}{}={}", encode_param(key), encode_param(val))?;
+        }
+        Ok(())
+    }
+}
+
+/// MagnetLinks are equal when they have the same [`InfoHash`](crate::hash::InfoHash), regardless
+/// of their name, trackers or other URL params, so that sets and maps of MagnetLinks contain
+/// one entry per torrent. Infohashes are compared strictly, so a v1 MagnetLink is not equal to a
+/// hybrid MagnetLink for the same torrent; use [`InfoHash::matches`](crate::hash::InfoHash::matches)
+/// for that. To compare full magnet URIs, use [`MagnetLink::exact_eq`](crate::magnet::MagnetLink::exact_eq).
+impl PartialEq for MagnetLink {
+    fn eq(&self, other: &MagnetLink) -> bool {
+        self.hash == other.hash
+    }
+}
+
+impl Eq for MagnetLink {}
+
+impl std::hash::Hash for MagnetLink {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.hash.hash(state);
+    }
+}
+
+/// A MagnetLink is described by its magnet URI string, as produced by its
+/// [`Display`](std::fmt::Display) implementation.
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for MagnetLink {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "MagnetLink".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "format": "uri",
+            "pattern": "^magnet:\\?",
+        })
+    }
+}
+
+/// Characters which are percent-encoded in URL params. Spaces are encoded as `%20` (never `+`)
+/// so that the name is decoded the same regardless of the
+/// [`plus_as_space`](crate::magnet::MagnetParseOptions::plus_as_space) policy.
+const PARAM_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~')
+    .remove(b':')
+    .remove(b'/')
+    .remove(b',');
+
+pub(crate) fn encode_param(val: &str) -> impl std::fmt::Display + '_ {
+    utf8_percent_encode(val, PARAM_ENCODE_SET)
+}
+
+/// URN namespaces of content hashes commonly found in magnets alongside Bittorrent infohashes.
+const KNOWN_URNS: &[&str] = &[
+    "urn:sha1:",
+    "urn:ed2k:",
+    "urn:ed2khash:",
+    "urn:aich:",
+    "urn:tree:tiger:",
+    "urn:tree:tiger/:",
+    "urn:bitprint:",
+    "urn:kzhash:",
+    "urn:md5:",
+    "urn:crc32:",
+];
+
+fn check_length(s: &str, options: MagnetParseOptions) -> Result<(), MagnetLinkError> {
+    match options.max_length {
+        Some(max) if s.len() > max => Err(MagnetLinkError::TooLong {
+            length: s.len(),
+            max,
+        }),
+        _ => Ok(()),
+    }
+}
+
+fn is_known_urn(urn: &str) -> bool {
+    KNOWN_URNS
+        .iter()
+        .any(|known| strip_prefix_ignore_case(urn, known).is_some())
+}
+
+/// Strips an ASCII prefix from a string, ignoring ASCII case.
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    match s.get(..prefix.len()) {
+        Some(start) if start.eq_ignore_ascii_case(prefix) => Some(&s[prefix.len()..]),
+        _ => None,
+    }
+}
+
+/// Splits a raw URL query into its undecoded key/value pairs. Empty pairs are skipped.
+fn raw_query_pairs(query: &str) -> impl Iterator<Item = (&str, &str)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+}
+
+/// Decodes a raw URL query component, according to the
+/// [`plus_as_space`](crate::magnet::MagnetParseOptions::plus_as_space) policy.
+/// Invalid UTF-8 sequences are replaced.
+fn decode_param(raw: &str, plus_as_space: bool) -> Cow<'_, str> {
+    if plus_as_space && raw.contains('+') {
+        let replaced = raw.replace('+', " ");
+        Cow::Owned(
+            percent_decode_str(&replaced)
+                .decode_utf8_lossy()
+                .into_owned(),
+        )
+    } else {
+        percent_decode_str(raw).decode_utf8_lossy()
+    }
+}
+
+/// Multihash function code for sha2-256, the hash function used by Bittorrent v2
+const MULTIHASH_SHA256: u64 = 0x12;
+
+/// Decodes a hex-encoded [multihash](https://multiformats.io/multihash/) into the hex-encoded
+/// digest it contains. The multihash is made of the hash function code and the digest length
+/// (both unsigned varints), followed by the digest itself.
+fn parse_multihash(multihash: &str) -> Result<String, MagnetLinkError> {
+    let invalid = || MagnetLinkError::InvalidMultihash {
+        multihash: multihash.to_string(),
+    };
+
+    if !multihash.is_ascii() {
+        return Err(invalid());
+    }
+
+    let mut rest = multihash;
+    let code = read_hex_varint(&mut rest).ok_or_else(invalid)?;
+    let length = read_hex_varint(&mut rest).ok_or_else(invalid)?;
+
+    if code != MULTIHASH_SHA256 || length != 32 {
+        return Err(MagnetLinkError::UnsupportedMultihash { code, length });
+    }
+
+    if rest.len() as u64 != length * 2 {
+        return Err(invalid());
+    }
+
+    Ok(rest.to_string())
+}
+
+/// Reads an unsigned varint from the start of a hex string, advancing the string
+/// past the varint. Returns None if the string is not valid hex or the varint is truncated.
+fn read_hex_varint(s: &mut &str) -> Option<u64> {
+    let mut value: u64 = 0;
+    // A u64 varint is at most 9 bytes long in the multiformats spec
+    for i in 0..9 {
+        let byte = u8::from_str_radix(s.get(0..2)?, 16).ok()?;
+        *s = &s[2..];
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Parses a BEP-0019 web seed URL. Returns None if the URL is invalid, or if its scheme
+/// is not `http` or `https`.
+pub(crate) fn parse_web_seed(s: &str) -> Option<Url> {
+    let url = Url::parse(s).ok()?;
+    match url.scheme() {
+        "http" | "https" => Some(url),
+        _ => None,
+    }
+}
+
+impl std::str::FromStr for MagnetLink {
+    type Err = MagnetLinkError;
+
+    fn from_str(s: &str) -> Result<MagnetLink, Self::Err> {
+        MagnetLink::new(s)
+    }
+}
+
+impl TryFrom<&str> for MagnetLink {
+    type Error = MagnetLinkError;
+
+    fn try_from(s: &str) -> Result<MagnetLink, Self::Error> {
+        MagnetLink::new(s)
+    }
+}
+
+impl TryFrom<&Url> for MagnetLink {
+    type Error = MagnetLinkError;
+
+    fn try_from(u: &Url) -> Result<MagnetLink, Self::Error> {
+        MagnetLink::from_url(u)
+    }
+}
+
+impl From<MagnetLink> for InfoHash {
+    fn from(magnet: MagnetLink) -> InfoHash {
+        magnet.hash
+    }
+}
+
+impl From<&MagnetLink> for InfoHash {
+    fn from(magnet: &MagnetLink) -> InfoHash {
+        magnet.hash.clone()
+    }
+}
+
+impl From<&MagnetLink> for TorrentID {
+    fn from(magnet: &MagnetLink) -> TorrentID {
+        magnet.id()
+    }
+}
+
+impl From<&MagnetLink> for SingleTarget {
+    fn from(magnet: &MagnetLink) -> SingleTarget {
+        SingleTarget::from(&magnet.hash)
+    }
+}
+
+impl From<MagnetLink> for SingleTarget {
+    fn from(magnet: MagnetLink) -> SingleTarget {
+        SingleTarget::from(&magnet.hash)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{HexDigest, TrackerScheme};
 
     #[test]
     fn can_load_v1() {
@@ -200,7 +1065,29 @@ mod tests {
         );
         assert_eq!(
             magnet.hash,
-            InfoHash::V1("c811b41641a09d192b8ed81b14064fff55d85ce3".to_string())
+            InfoHash::V1(HexDigest::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap())
+        );
+    }
+
+    #[test]
+    fn can_load_base32_v1() {
+        let magnet = MagnetLink::new(
+            "magnet:?xt=urn:btih:MMNDDXIKIYSX2UDYYDPOJZTOE33T4QVM&dn=bittorrent-v1-v2-hybrid-test",
+        )
+        .unwrap();
+        assert_eq!(
+            magnet.hash,
+            InfoHash::V1(HexDigest::new("631a31dd0a46257d5078c0dee4e66e26f73e42ac").unwrap())
+        );
+
+        let res = MagnetLink::new("magnet:?xt=urn:btih:MMNDDXIKIYSX2UDYYDPOJZTOE33T4QV1&dn=test");
+        assert_eq!(
+            res.unwrap_err(),
+            MagnetLinkError::InvalidHash {
+                source: InfoHashError::InvalidChars {
+                    hash: "MMNDDXIKIYSX2UDYYDPOJZTOE33T4QV1".to_string()
+                }
+            }
         );
     }
 
@@ -213,8 +1100,9 @@ mod tests {
         assert_eq!(
             magnet.hash,
             InfoHash::Hybrid((
-                "631a31dd0a46257d5078c0dee4e66e26f73e42ac".to_string(),
-                "d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb".to_string()
+                HexDigest::new("631a31dd0a46257d5078c0dee4e66e26f73e42ac").unwrap(),
+                HexDigest::new("d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb")
+                    .unwrap()
             ))
         );
     }
@@ -227,11 +1115,392 @@ mod tests {
         assert_eq!(
             magnet.hash,
             InfoHash::V2(
-                "caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e".to_string()
+                HexDigest::new("caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e")
+                    .unwrap()
             )
         );
     }
 
+    #[test]
+    fn can_load_name_with_plus_policy() {
+        let uri = "magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=C%2B%2B+for+dummies%20(2nd+ed.)";
+        let magnet = MagnetLink::new(uri).unwrap();
+        assert_eq!(magnet.name(), "C++ for dummies (2nd ed.)");
+        assert_eq!(magnet.name_raw(), "C%2B%2B+for+dummies%20(2nd+ed.)");
+
+        let options = MagnetParseOptions {
+            plus_as_space: false,
+            ..Default::default()
+        };
+        let magnet = MagnetLink::new_with(uri, options).unwrap();
+        assert_eq!(magnet.name(), "C+++for+dummies (2nd+ed.)");
+        assert_eq!(magnet.name_raw(), "C%2B%2B+for+dummies%20(2nd+ed.)");
+    }
+
+    #[test]
+    fn can_load_trackers() {
+        let magnet_source =
+            std::fs::read_to_string("tests/bittorrent-v1-emma-goldman.magnet").unwrap();
+        let magnet = MagnetLink::new(&magnet_source).unwrap();
+        // One of the 10 trackers is duplicated
+        assert_eq!(magnet.trackers().len(), 9);
+        assert_eq!(
+            magnet.trackers()[0].url(),
+            "udp://tracker.coppersurfer.tk:6969/announce"
+        );
+
+        let options = MagnetParseOptions {
+            max_trackers: Some(2),
+            ..Default::default()
+        };
+        let magnet = MagnetLink::new_with(&magnet_source, options).unwrap();
+        assert_eq!(magnet.trackers().len(), 2);
+    }
+
+    #[test]
+    fn can_roundtrip() {
+        for path in [
+            "tests/bittorrent-v1-emma-goldman.magnet",
+            "tests/bittorrent-v2-test.magnet",
+            "tests/bittorrent-v2-hybrid-test.magnet",
+        ] {
+            let magnet = MagnetLink::new(&std::fs::read_to_string(path).unwrap()).unwrap();
+            let reparsed = MagnetLink::new(&magnet.to_string()).unwrap();
+            assert_eq!(reparsed.hash(), magnet.hash());
+            assert_eq!(reparsed.name(), magnet.name());
+            assert_eq!(reparsed.trackers(), magnet.trackers());
+        }
+
+        let uri = "magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=a%20b%2Bc%26d&xl=12&tr=udp://example.com:6969/announce&ws=http://example.com/a%3Fb&x.pe=1.2.3.4:5678";
+        let magnet = MagnetLink::new(uri).unwrap();
+        assert_eq!(magnet.to_string(), uri);
+    }
+
+    #[test]
+    fn can_read_params() {
+        let uri = "magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=Emma+Goldman&kt=anarchism+essays&so=0,2,4-6&tr=udp%3A%2F%2Fexample.com%3A6969";
+        let magnet = MagnetLink::new(uri).unwrap();
+        let params: Vec<(&str, Cow<str>)> = magnet.params().collect();
+        assert_eq!(
+            params,
+            vec![
+                (
+                    "xt",
+                    "urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3".into()
+                ),
+                ("dn", "Emma Goldman".into()),
+                ("tr", "udp://example.com:6969".into()),
+                ("so", "0,2,4-6".into()),
+                ("kt", "anarchism essays".into()),
+            ]
+        );
+        assert_eq!(
+            magnet.to_string(),
+            "magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=Emma%20Goldman&tr=udp://example.com:6969&so=0,2,4-6&kt=anarchism%20essays"
+        );
+    }
+
+    #[test]
+    fn can_load_file_selection() {
+        let magnet = MagnetLink::new(
+            "magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=Goldman&so=0,2,4-6",
+        )
+        .unwrap();
+        let selection = magnet.file_selection().unwrap();
+        assert_eq!(selection.indices().collect::<Vec<_>>(), vec![0, 2, 4, 5, 6]);
+
+        let res = MagnetLink::new(
+            "magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=Goldman&so=4-2",
+        );
+        assert_eq!(
+            res.unwrap_err(),
+            MagnetLinkError::InvalidFileSelection {
+                source: FileSelectionError::InvalidRange { start: 4, end: 2 }
+            }
+        );
+    }
+
+    #[test]
+    fn can_convert() {
+        let magnet_source =
+            std::fs::read_to_string("tests/bittorrent-v2-hybrid-test.magnet").unwrap();
+        let magnet = MagnetLink::try_from(magnet_source.as_str()).unwrap();
+        assert_eq!(
+            magnet_source.parse::<MagnetLink>().unwrap().hash(),
+            magnet.hash()
+        );
+
+        let target = SingleTarget::from(&magnet);
+        assert!(target.matches_hash(magnet.hash()));
+        assert_eq!(TorrentID::from(&magnet), magnet.id());
+        assert_eq!(InfoHash::from(&magnet), *magnet.hash());
+
+        fn target_of(t: impl Into<SingleTarget>) -> SingleTarget {
+            t.into()
+        }
+        assert_eq!(target_of(magnet.clone()), target);
+        assert!(InfoHash::from(magnet).is_hybrid());
+    }
+
+    #[test]
+    fn can_mutate() {
+        let mut magnet = MagnetLink::new(
+            &std::fs::read_to_string("tests/bittorrent-v2-hybrid-test.magnet").unwrap(),
+        )
+        .unwrap();
+        magnet.set_name("Hybrid test & co");
+        assert_eq!(magnet.name(), "Hybrid test & co");
+        assert_eq!(magnet.name_raw(), "Hybrid%20test%20%26%20co");
+
+        let tracker = Tracker::new("udp://tracker.example.com:1337/announce").unwrap();
+        assert!(magnet.add_tracker(tracker.clone()));
+        assert!(!magnet.add_tracker(tracker.clone()));
+        assert_eq!(
+            magnet.to_string(),
+            "magnet:?xt=urn:btih:631a31dd0a46257d5078c0dee4e66e26f73e42ac&xt=urn:btmh:1220d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb&dn=Hybrid%20test%20%26%20co&tr=udp://tracker.example.com:1337/announce"
+        );
+
+        assert!(magnet.remove_tracker(&tracker));
+        assert!(!magnet.remove_tracker(&tracker));
+        magnet.add_tracker(tracker);
+        magnet.strip_trackers();
+        assert!(magnet.trackers().is_empty());
+    }
+
+    #[test]
+    fn skips_invalid_trackers() {
+        let magnet = MagnetLink::new("magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=test&tr=udp%3A%2F%2Ftracker.example.com&tr=gopher%3A%2F%2Fx&tr=not+a+url&tr=udp%3A%2F%2Ftracker.example.com%3A1337").unwrap();
+        assert_eq!(magnet.trackers().len(), 1);
+        assert_eq!(magnet.trackers()[0].url(), "udp://tracker.example.com:1337");
+    }
+
+    #[test]
+    fn fails_load_invalid_tracker_when_strict() {
+        let options = MagnetParseOptions {
+            strict_trackers: true,
+            ..Default::default()
+        };
+        let res = MagnetLink::new_with("magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&tr=ftp%3A%2F%2Fexample.com", options);
+        assert_eq!(
+            res.unwrap_err(),
+            MagnetLinkError::InvalidTracker {
+                source: TrackerError::InvalidScheme {
+                    scheme: "ftp".to_string()
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn options_unknown_tracker_schemes() {
+        let uri = "magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=test&tr=ftp%3A%2F%2Fexample.com";
+        let options = MagnetParseOptions {
+            allow_unknown_tracker_schemes: true,
+            ..Default::default()
+        };
+        let magnet = MagnetLink::new_with(uri, options).unwrap();
+        assert_eq!(
+            magnet.trackers()[0].scheme(),
+            &TrackerScheme::Other("ftp".to_string())
+        );
+    }
+
+    #[test]
+    fn options_require_name() {
+        let options = MagnetParseOptions {
+            require_name: true,
+            ..Default::default()
+        };
+        let res = MagnetLink::new_with(
+            "magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=",
+            options,
+        );
+        assert_eq!(res.unwrap_err(), MagnetLinkError::NoNameFound);
+    }
+
+    #[test]
+    fn display_name_falls_back_to_id() {
+        let options = MagnetParseOptions {
+            require_name: false,
+            ..Default::default()
+        };
+        let mut magnet = MagnetLink::new_with(
+            "magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3",
+            options,
+        )
+        .unwrap();
+        assert_eq!(magnet.name(), "");
+        assert_eq!(magnet.display_name(), "c811b416");
+
+        magnet.set_name("Emma Goldman");
+        assert_eq!(magnet.display_name(), "Emma Goldman");
+    }
+
+    #[test]
+    fn finds_magnets_in_text() {
+        let v1 = "magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=Emma";
+        let hybrid = std::fs::read_to_string("tests/bittorrent-v2-hybrid-test.magnet").unwrap();
+        let hybrid = hybrid.trim();
+        let text = format!(
+            "Get it [here]({v1}+(1911)). Mirror: {}, or\n\
+             <item><link>{}</link></item> MAGNET:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&#38;dn=Emma&#38;tr=udp%3A%2F%2Ftracker.example%3A6969.",
+            hybrid.replace('&', "&amp;"),
+            v1,
+        );
+
+        let magnets: Vec<MagnetLink> = MagnetLink::find_in_text(&text)
+            .into_iter()
+            .map(|m| m.unwrap())
+            .collect();
+        assert_eq!(magnets.len(), 4);
+        assert_eq!(magnets[0].name(), "Emma (1911)");
+        assert_eq!(
+            magnets[1].to_string(),
+            MagnetLink::new(hybrid).unwrap().to_string()
+        );
+        assert_eq!(magnets[2].to_string(), v1);
+        assert_eq!(magnets[3].trackers().len(), 1);
+
+        assert!(MagnetLink::find_in_text("no magnet: here").is_empty());
+        assert_eq!(
+            find_magnet_uris("'magnet:?xt=urn:btih:abc&quot;>"),
+            vec!["magnet:?xt=urn:btih:abc"]
+        );
+    }
+
+    #[test]
+    fn dedups_across_hash_types() {
+        let v1 = MagnetLink::new(
+            "magnet:?xt=urn:btih:631a31dd0a46257d5078c0dee4e66e26f73e42ac&dn=v1&tr=udp%3A%2F%2Fone.example%3A6969",
+        )
+        .unwrap();
+        let v2 = MagnetLink::new(
+            "magnet:?xt=urn:btmh:1220d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb&dn=v2&tr=udp%3A%2F%2Ftwo.example%3A6969",
+        )
+        .unwrap();
+        let hybrid = MagnetLink::new(
+            &std::fs::read_to_string("tests/bittorrent-v2-hybrid-test.magnet").unwrap(),
+        )
+        .unwrap();
+        let other = MagnetLink::new(
+            "magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=other",
+        )
+        .unwrap();
+
+        let deduped = MagnetLink::dedup([v1.clone(), v2.clone(), other, hybrid.clone(), v1]);
+        assert_eq!(deduped.len(), 2);
+        assert_eq!(deduped[0].hash(), hybrid.hash());
+        assert_eq!(deduped[0].name(), "v1");
+        for tracker in v2.trackers().iter().chain(hybrid.trackers()) {
+            assert!(deduped[0].trackers().contains(tracker));
+        }
+        assert_eq!(deduped[1].name(), "other");
+    }
+
+    #[test]
+    fn compares_by_infohash() {
+        let first = MagnetLink::new(
+            "magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=first",
+        )
+        .unwrap();
+        let second = MagnetLink::new(
+            "magnet:?xt=urn:btih:C811B41641A09D192B8ED81B14064FFF55D85CE3&dn=second&tr=udp%3A%2F%2Ftracker.example%3A6969",
+        )
+        .unwrap();
+        assert_eq!(first, second);
+        assert!(!first.exact_eq(&second));
+        assert!(first.exact_eq(&first.clone()));
+
+        let set: std::collections::HashSet<MagnetLink> = [first, second].into_iter().collect();
+        assert_eq!(set.len(), 1);
+
+        let other = MagnetLink::new(
+            "magnet:?xt=urn:btih:631a31dd0a46257d5078c0dee4e66e26f73e42ac&dn=first",
+        )
+        .unwrap();
+        assert!(!set.contains(&other));
+    }
+
+    #[test]
+    fn options_max_length() {
+        let uri = format!(
+            "magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=test&x.foo={}",
+            "a".repeat(MagnetParseOptions::DEFAULT_MAX_LENGTH)
+        );
+        assert_eq!(
+            MagnetLink::new(&uri).unwrap_err(),
+            MagnetLinkError::TooLong {
+                length: uri.len(),
+                max: MagnetParseOptions::DEFAULT_MAX_LENGTH
+            }
+        );
+
+        let options = MagnetParseOptions {
+            max_length: None,
+            ..Default::default()
+        };
+        assert!(MagnetLink::new_with(&uri, options).is_ok());
+    }
+
+    #[test]
+    fn options_unknown_urns() {
+        let uri =
+            "magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&xt=urn:foo:bar&dn=test";
+        let magnet = MagnetLink::new(uri).unwrap();
+        assert_eq!(magnet.other_exact_topics(), &["urn:foo:bar".to_string()]);
+        let options = MagnetParseOptions {
+            allow_unknown_urns: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            MagnetLink::new_with(uri, options).unwrap_err(),
+            MagnetLinkError::UnknownUrn {
+                urn: "urn:foo:bar".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn can_load_other_exact_topics() {
+        let uri = "magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&xt=urn:sha1:YNCKHTQCWBTRNJIV4WNAE52SJUQCZO5C&xt=urn:ed2k:354B15E68FB8F36D7CD88FF94116CDC1&dn=test";
+        let options = MagnetParseOptions {
+            allow_unknown_urns: false,
+            ..Default::default()
+        };
+        let magnet = MagnetLink::new_with(uri, options).unwrap();
+        assert_eq!(
+            magnet.hash(),
+            &InfoHash::V1(HexDigest::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap())
+        );
+        assert_eq!(
+            magnet.other_exact_topics(),
+            &[
+                "urn:sha1:YNCKHTQCWBTRNJIV4WNAE52SJUQCZO5C".to_string(),
+                "urn:ed2k:354B15E68FB8F36D7CD88FF94116CDC1".to_string()
+            ]
+        );
+        assert_eq!(magnet.to_string(), uri);
+    }
+
+    #[test]
+    fn options_empty_values() {
+        let uri = "magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=test&xl=&tr=";
+        let magnet = MagnetLink::new(uri).unwrap();
+        assert_eq!(magnet.exact_length(), None);
+        assert!(magnet.trackers().is_empty());
+
+        let options = MagnetParseOptions {
+            allow_empty_values: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            MagnetLink::new_with(uri, options).unwrap_err(),
+            MagnetLinkError::EmptyValue {
+                key: "xl".to_string()
+            }
+        );
+    }
+
     #[test]
     #[cfg(not(feature = "magnet_force_name"))]
     fn can_load_without_name() {
@@ -241,7 +1510,165 @@ mod tests {
         assert_eq!(magnet.name, "".to_string());
         assert_eq!(
             magnet.hash,
-            InfoHash::V1("c811b41641a09d192b8ed81b14064fff55d85ce3".to_string())
+            InfoHash::V1(HexDigest::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap())
+        );
+    }
+
+    #[test]
+    fn can_load_exact_length_and_source() {
+        let magnet = MagnetLink::new("magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=Goldman&xl=1337&xs=http%3A%2F%2Fexample.com%2Fgoldman.torrent").unwrap();
+        assert_eq!(magnet.exact_length(), Some(1337));
+        assert_eq!(
+            magnet.exact_source().unwrap().as_str(),
+            "http://example.com/goldman.torrent"
+        );
+    }
+
+    #[test]
+    fn fails_load_invalid_exact_length() {
+        let res = MagnetLink::new(
+            "magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=Goldman&xl=-12",
+        );
+        assert!(res.is_err());
+        let err = res.unwrap_err();
+        assert_eq!(
+            err,
+            MagnetLinkError::InvalidExactLength {
+                length: "-12".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn can_load_web_seeds() {
+        let magnet = MagnetLink::new("magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=Goldman&ws=https%3A%2F%2Fexample.com%2Fgoldman&ws=http%3A%2F%2Fmirror.example.org%2F").unwrap();
+        let seeds: Vec<&str> = magnet.web_seeds().iter().map(|u| u.as_str()).collect();
+        assert_eq!(
+            seeds,
+            vec!["https://example.com/goldman", "http://mirror.example.org/"]
+        );
+    }
+
+    #[test]
+    fn can_load_acceptable_sources() {
+        let uri = "magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=Goldman&as=https%3A%2F%2Fexample.com%2Fgoldman.zip";
+        let magnet = MagnetLink::new(uri).unwrap();
+        assert_eq!(
+            magnet.acceptable_sources()[0].as_str(),
+            "https://example.com/goldman.zip"
+        );
+        assert!(magnet.web_seeds().is_empty());
+        let reparsed = MagnetLink::new(&magnet.to_string()).unwrap();
+        assert_eq!(reparsed.acceptable_sources(), magnet.acceptable_sources());
+
+        let magnet = MagnetLink::new("magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=Goldman&as=goldman.zip").unwrap();
+        assert!(magnet.acceptable_sources().is_empty());
+    }
+
+    #[test]
+    fn skips_invalid_web_seeds() {
+        let magnet = MagnetLink::new("magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=Goldman&ws=ftp%3A%2F%2Fexample.com%2Fgoldman&ws=https%3A%2F%2Fexample.com%2Fgoldman").unwrap();
+        assert_eq!(magnet.web_seeds().len(), 1);
+        assert_eq!(
+            magnet.web_seeds()[0].as_str(),
+            "https://example.com/goldman"
+        );
+    }
+
+    #[test]
+    fn can_load_peers() {
+        let magnet = MagnetLink::new("magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=Goldman&x.pe=10.0.0.2:6881&x.pe=peer.example.com:51413").unwrap();
+        let peers: Vec<String> = magnet.peers().iter().map(|p| p.to_string()).collect();
+        assert_eq!(peers, vec!["10.0.0.2:6881", "peer.example.com:51413"]);
+    }
+
+    #[test]
+    fn fails_load_invalid_peer() {
+        let res = MagnetLink::new(
+            "magnet:?xt=urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3&dn=Goldman&x.pe=10.0.0.2",
+        );
+        assert!(res.is_err());
+        let err = res.unwrap_err();
+        assert_eq!(
+            err,
+            MagnetLinkError::InvalidPeer {
+                source: PeerAddrError::MissingPort {
+                    addr: "10.0.0.2".to_string()
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn can_load_uppercase_multihash() {
+        let magnet = MagnetLink::new("magnet:?xt=urn:btmh:1220CAF1E1C30E81CB361B9EE167C4AA64228A7FA4FA9F6105232B28AD099F3A302E&dn=bittorrent-v2-test").unwrap();
+        assert_eq!(
+            magnet.hash,
+            InfoHash::V2(
+                HexDigest::new("caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e")
+                    .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn can_load_case_insensitive_urns() {
+        let magnet = MagnetLink::new("magnet:?xt=URN:BTIH:c811b41641a09d192b8ed81b14064fff55d85ce3&xt=Urn:BtMh:1220caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e&dn=test").unwrap();
+        assert!(magnet.hash().is_hybrid());
+
+        let magnet = MagnetLink::new(
+            "magnet:?xt=%20urn:btih:c811b41641a09d192b8ed81b14064fff55d85ce3%20&dn=test",
+        )
+        .unwrap();
+        assert_eq!(
+            magnet.hash(),
+            &InfoHash::V1(HexDigest::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap())
+        );
+    }
+
+    #[test]
+    fn fails_load_unsupported_urn() {
+        for urn in [
+            "urn:btpk:8543d3e6115f0f98c944077a4493dcd543e49c739fd998550a1f614ab36ed63e",
+            "URN:BTIH-c811b41641a09d192b8ed81b14064fff55d85ce3",
+        ] {
+            let res = MagnetLink::new(&format!("magnet:?xt={urn}&dn=test"));
+            assert_eq!(
+                res.unwrap_err(),
+                MagnetLinkError::UnsupportedURN {
+                    urn: urn.to_string()
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn fails_load_unsupported_multihash() {
+        // sha3-256 multihash
+        let res = MagnetLink::new("magnet:?xt=urn:btmh:1620caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e&dn=bittorrent-v2-test");
+        assert!(res.is_err());
+        let err = res.unwrap_err();
+        assert_eq!(
+            err,
+            MagnetLinkError::UnsupportedMultihash {
+                code: 0x16,
+                length: 32
+            }
+        );
+    }
+
+    #[test]
+    fn fails_load_invalid_multihash() {
+        let res = MagnetLink::new(
+            "magnet:?xt=urn:btmh:1220caf1e1c30e81cb361b9ee167c4aa&dn=bittorrent-v2-test",
+        );
+        assert!(res.is_err());
+        let err = res.unwrap_err();
+        assert_eq!(
+            err,
+            MagnetLinkError::InvalidMultihash {
+                multihash: "1220caf1e1c30e81cb361b9ee167c4aa".to_string()
+            }
         );
     }
 
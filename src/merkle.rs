@@ -0,0 +1,187 @@
+//! Merkle trees for Bittorrent v2, as described in [BEP-0052](https://www.bittorrent.org/beps/bep_0052.html).
+//!
+//! Each file of a v2 torrent is split in [`BLOCK_SIZE`](crate::merkle::BLOCK_SIZE) blocks, whose
+//! SHA-256 hashes are the leaves of a binary merkle tree. The root of that tree is the file's
+//! `pieces root`. When the number of leaves is not a power of two, the tree is padded with zeroed
+//! leaves.
+//!
+//! ```
+//! use hightorrent::merkle;
+//!
+//! let data = vec![7u8; 3 * merkle::BLOCK_SIZE];
+//! let leaves: Vec<_> = data.chunks(merkle::BLOCK_SIZE).map(merkle::block_hash).collect();
+//! let root = merkle::root(&leaves);
+//!
+//! let proof = merkle::proof(&leaves, 2).unwrap();
+//! assert!(merkle::verify(&leaves[2], 2, &proof, &root));
+//! assert!(!merkle::verify(&leaves[2], 1, &proof, &root));
+//! ```
+
+use sha2::{Digest, Sha256};
+
+/// A SHA-256 hash, as stored in merkle trees.
+pub type Hash = [u8; 32];
+
+/// The size of the blocks hashed as leaves of the merkle tree (16 KiB).
+pub const BLOCK_SIZE: usize = 16 * 1024;
+
+/// Hashes a block of data to produce a leaf. The last block of a file may be shorter than
+/// [`BLOCK_SIZE`](crate::merkle::BLOCK_SIZE).
+pub fn block_hash(block: &[u8]) -> Hash {
+    Sha256::digest(block).into()
+}
+
+/// Hashes two sibling nodes to produce their parent.
+pub fn parent_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Returns the root of a tree of `2^height` zeroed leaves, used to pad incomplete trees.
+pub fn pad_hash(height: u32) -> Hash {
+    let mut hash = [0; 32];
+    for _ in 0..height {
+        hash = parent_hash(&hash, &hash);
+    }
+    hash
+}
+
+/// Computes the merkle root of a list of leaf hashes, padded with zeroed leaves.
+///
+/// An empty list of leaves has a zeroed root, although empty files have no `pieces root` at all.
+pub fn root(leaves: &[Hash]) -> Hash {
+    root_with_padding(leaves, [0; 32])
+}
+
+/// Computes the `pieces root` of a file from its BEP-0052 piece layer, which contains the roots
+/// of the subtrees covering each piece. `piece_length` must be a power of two, and at least
+/// [`BLOCK_SIZE`](crate::merkle::BLOCK_SIZE).
+pub fn root_from_layer(layer: &[Hash], piece_length: u64) -> Hash {
+    let blocks = piece_length / BLOCK_SIZE as u64;
+    root_with_padding(layer, pad_hash(blocks.trailing_zeros()))
+}
+
+fn root_with_padding(leaves: &[Hash], padding: Hash) -> Hash {
+    if leaves.is_empty() {
+        return [0; 32];
+    }
+
+    let mut level = leaves.to_vec();
+    let mut padding = padding;
+    while level.len() > 1 {
+        level = next_level(&level, &padding);
+        padding = parent_hash(&padding, &padding);
+    }
+    level[0]
+}
+
+fn next_level(level: &[Hash], padding: &Hash) -> Vec<Hash> {
+    level
+        .chunks(2)
+        .map(|pair| parent_hash(&pair[0], pair.get(1).unwrap_or(padding)))
+        .collect()
+}
+
+/// Builds the proof for the leaf at `index`: the uncle hashes needed to recompute the root, from
+/// the bottom of the tree to the top. Returns `None` when the index is out of bounds.
+pub fn proof(leaves: &[Hash], index: usize) -> Option<Vec<Hash>> {
+    if index >= leaves.len() {
+        return None;
+    }
+
+    let mut proof = Vec::new();
+    let mut level = leaves.to_vec();
+    let mut padding = [0; 32];
+    let mut index = index;
+    while level.len() > 1 {
+        proof.push(*level.get(index ^ 1).unwrap_or(&padding));
+        level = next_level(&level, &padding);
+        padding = parent_hash(&padding, &padding);
+        index /= 2;
+    }
+    Some(proof)
+}
+
+/// Verifies that `hash` is the leaf at `index` of the tree with the given `root`, using the
+/// uncle hashes returned by [`proof`](crate::merkle::proof).
+pub fn verify(hash: &Hash, index: usize, proof: &[Hash], root: &Hash) -> bool {
+    // The index must fit in a tree of that height
+    if proof.len() < usize::BITS as usize && index >> proof.len() != 0 {
+        return false;
+    }
+
+    let mut hash = *hash;
+    let mut index = index;
+    for uncle in proof {
+        hash = if index % 2 == 0 {
+            parent_hash(&hash, uncle)
+        } else {
+            parent_hash(uncle, &hash)
+        };
+        index /= 2;
+    }
+    hash == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(count: u8) -> Vec<Hash> {
+        (0..count).map(|i| block_hash(&[i])).collect()
+    }
+
+    #[test]
+    fn root_of_single_leaf_is_the_leaf() {
+        let leaves = leaves(1);
+        assert_eq!(root(&leaves), leaves[0]);
+        assert_eq!(root(&[]), [0; 32]);
+    }
+
+    #[test]
+    fn root_pads_with_zeroed_leaves() {
+        let mut padded = leaves(3);
+        padded.push([0; 32]);
+        assert_eq!(root(&leaves(3)), root(&padded));
+        assert_eq!(
+            root(&padded),
+            parent_hash(
+                &parent_hash(&padded[0], &padded[1]),
+                &parent_hash(&padded[2], &padded[3])
+            )
+        );
+    }
+
+    #[test]
+    fn root_from_layer_matches_root() {
+        // Pieces of 4 blocks, with 3 pieces and a half
+        let leaves = leaves(14);
+        let layer: Vec<Hash> = leaves
+            .chunks(4)
+            .map(|piece| {
+                let mut piece = piece.to_vec();
+                piece.resize(4, [0; 32]);
+                root(&piece)
+            })
+            .collect();
+        let piece_length = 4 * BLOCK_SIZE as u64;
+        assert_eq!(root_from_layer(&layer, piece_length), root(&leaves));
+    }
+
+    #[test]
+    fn proofs_verify() {
+        for count in 1..10 {
+            let leaves = leaves(count);
+            let root = root(&leaves);
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = proof(&leaves, index).unwrap();
+                assert!(verify(leaf, index, &proof, &root));
+                assert!(!verify(&[1; 32], index, &proof, &root));
+                assert!(!verify(leaf, index + 16, &proof, &root));
+            }
+            assert!(proof(&leaves, leaves.len()).is_none());
+        }
+    }
+}
@@ -0,0 +1,338 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::str::FromStr;
+
+/// Error occurred during parsing a [`PeerAddr`](crate::peer::PeerAddr).
+#[derive(Clone, Debug, PartialEq)]
+pub enum PeerAddrError {
+    /// The address did not contain a `:port` suffix.
+    MissingPort { addr: String },
+    /// The port was not a valid non-zero 16-bit integer.
+    InvalidPort { addr: String },
+    /// The host was neither an IP address nor a valid hostname.
+    InvalidHost { addr: String },
+    /// The length of compact peers was not a multiple of the length of a single peer.
+    InvalidCompactLength { len: usize, peer_len: usize },
+}
+
+impl std::fmt::Display for PeerAddrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PeerAddrError::MissingPort { addr } => write!(f, "Missing peer port: {addr}"),
+            PeerAddrError::InvalidPort { addr } => write!(f, "Invalid peer port: {addr}"),
+            PeerAddrError::InvalidHost { addr } => write!(f, "Invalid peer host: {addr}"),
+            PeerAddrError::InvalidCompactLength { len, peer_len } => write!(
+                f,
+                "Invalid compact peers length: {len} bytes is not a multiple of {peer_len}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PeerAddrError {}
+
+/// The address of a single peer, as found in a magnet `x.pe` parameter
+/// ([BEP-0009](https://www.bittorrent.org/beps/bep_0009.html)).
+///
+/// A peer can be designated either by an IP address, or by a hostname which needs to be
+/// resolved before connecting. IPv6 addresses are enclosed in brackets (eg. `[::1]:6881`).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PeerAddr {
+    Ip(SocketAddr),
+    Host { host: String, port: u16 },
+}
+
+impl PeerAddr {
+    /// Parses a `host:port` peer address.
+    pub fn new(s: &str) -> Result<PeerAddr, PeerAddrError> {
+        if let Ok(addr) = SocketAddr::from_str(s) {
+            if addr.port() == 0 {
+                return Err(PeerAddrError::InvalidPort {
+                    addr: s.to_string(),
+                });
+            }
+            return Ok(PeerAddr::Ip(addr));
+        }
+
+        let (host, port) = s
+            .rsplit_once(':')
+            .ok_or_else(|| PeerAddrError::MissingPort {
+                addr: s.to_string(),
+            })?;
+
+        let port = match port.parse::<u16>() {
+            Ok(port) if port != 0 => port,
+            _ => {
+                return Err(PeerAddrError::InvalidPort {
+                    addr: s.to_string(),
+                })
+            }
+        };
+
+        // An IP address which failed to parse as SocketAddr above must be
+        // an unbracketed IPv6 address
+        if host.parse::<IpAddr>().is_ok() || !is_valid_hostname(host) {
+            return Err(PeerAddrError::InvalidHost {
+                addr: s.to_string(),
+            });
+        }
+
+        Ok(PeerAddr::Host {
+            host: host.to_lowercase(),
+            port,
+        })
+    }
+
+    /// Returns the port of the peer.
+    pub fn port(&self) -> u16 {
+        match self {
+            PeerAddr::Ip(addr) => addr.port(),
+            PeerAddr::Host { port, .. } => *port,
+        }
+    }
+}
+
+/// Checks a hostname is made of non-empty labels of ASCII letters, digits and hyphens.
+fn is_valid_hostname(host: &str) -> bool {
+    !host.is_empty()
+        && host.len() <= 253
+        && host.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+        })
+}
+
+impl std::fmt::Display for PeerAddr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PeerAddr::Ip(addr) => write!(f, "{addr}"),
+            PeerAddr::Host { host, port } => write!(f, "{host}:{port}"),
+        }
+    }
+}
+
+impl FromStr for PeerAddr {
+    type Err = PeerAddrError;
+
+    fn from_str(s: &str) -> Result<PeerAddr, Self::Err> {
+        PeerAddr::new(s)
+    }
+}
+
+impl From<SocketAddr> for PeerAddr {
+    fn from(addr: SocketAddr) -> PeerAddr {
+        PeerAddr::Ip(addr)
+    }
+}
+
+// Compact peer encodings, used in tracker responses and PEX messages
+impl PeerAddr {
+    /// The length of a compact IPv4 peer.
+    pub const COMPACT_V4_LEN: usize = 6;
+    /// The length of a compact IPv6 peer.
+    pub const COMPACT_V6_LEN: usize = 18;
+
+    /// Decodes compact IPv4 peers ([BEP-0023](https://www.bittorrent.org/beps/bep_0023.html)),
+    /// made of the IP address followed by the port, in network byte order. Fails when the
+    /// length is not a multiple of [`COMPACT_V4_LEN`](crate::peer::PeerAddr::COMPACT_V4_LEN).
+    ///
+    /// ```
+    /// use hightorrent::PeerAddr;
+    ///
+    /// let bytes = [10, 0, 0, 1, 0x1a, 0xe1, 192, 168, 1, 2, 0xc8, 0xd5];
+    /// let peers: Vec<_> = PeerAddr::compact_v4(&bytes).unwrap().collect();
+    /// assert_eq!(peers[1].to_string(), "192.168.1.2:51413");
+    /// assert_eq!(PeerAddr::encode_compact_v4(&peers), bytes);
+    ///
+    /// assert!(PeerAddr::compact_v6(&bytes).is_err());
+    /// ```
+    pub fn compact_v4(bytes: &[u8]) -> Result<CompactPeersV4<'_>, PeerAddrError> {
+        check_compact_len(bytes, PeerAddr::COMPACT_V4_LEN)?;
+        Ok(CompactPeersV4(bytes.chunks_exact(PeerAddr::COMPACT_V4_LEN)))
+    }
+
+    /// Decodes compact IPv6 peers ([BEP-0007](https://www.bittorrent.org/beps/bep_0007.html)),
+    /// in the same order as IPv4 peers. Fails when the length is not a multiple of
+    /// [`COMPACT_V6_LEN`](crate::peer::PeerAddr::COMPACT_V6_LEN).
+    pub fn compact_v6(bytes: &[u8]) -> Result<CompactPeersV6<'_>, PeerAddrError> {
+        check_compact_len(bytes, PeerAddr::COMPACT_V6_LEN)?;
+        Ok(CompactPeersV6(bytes.chunks_exact(PeerAddr::COMPACT_V6_LEN)))
+    }
+
+    /// Encodes IPv4 peers in the compact form decoded by
+    /// [`compact_v4`](crate::peer::PeerAddr::compact_v4).
+    pub fn encode_compact_v4<'a>(peers: impl IntoIterator<Item = &'a SocketAddrV4>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for peer in peers {
+            bytes.extend(peer.ip().octets());
+            bytes.extend(peer.port().to_be_bytes());
+        }
+        bytes
+    }
+
+    /// Encodes IPv6 peers in the compact form decoded by
+    /// [`compact_v6`](crate::peer::PeerAddr::compact_v6). Flow info and scope IDs are not
+    /// encoded.
+    pub fn encode_compact_v6<'a>(peers: impl IntoIterator<Item = &'a SocketAddrV6>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for peer in peers {
+            bytes.extend(peer.ip().octets());
+            bytes.extend(peer.port().to_be_bytes());
+        }
+        bytes
+    }
+}
+
+fn check_compact_len(bytes: &[u8], peer_len: usize) -> Result<(), PeerAddrError> {
+    if bytes.len() % peer_len != 0 {
+        return Err(PeerAddrError::InvalidCompactLength {
+            len: bytes.len(),
+            peer_len,
+        });
+    }
+    Ok(())
+}
+
+/// An iterator over compact IPv4 peers, created by
+/// [`PeerAddr::compact_v4`](crate::peer::PeerAddr::compact_v4).
+#[derive(Clone, Debug)]
+pub struct CompactPeersV4<'a>(std::slice::ChunksExact<'a, u8>);
+
+impl Iterator for CompactPeersV4<'_> {
+    type Item = SocketAddrV4;
+
+    fn next(&mut self) -> Option<SocketAddrV4> {
+        let peer = self.0.next()?;
+        let ip = Ipv4Addr::new(peer[0], peer[1], peer[2], peer[3]);
+        Some(SocketAddrV4::new(
+            ip,
+            u16::from_be_bytes([peer[4], peer[5]]),
+        ))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl ExactSizeIterator for CompactPeersV4<'_> {}
+
+/// An iterator over compact IPv6 peers, created by
+/// [`PeerAddr::compact_v6`](crate::peer::PeerAddr::compact_v6).
+#[derive(Clone, Debug)]
+pub struct CompactPeersV6<'a>(std::slice::ChunksExact<'a, u8>);
+
+impl Iterator for CompactPeersV6<'_> {
+    type Item = SocketAddrV6;
+
+    fn next(&mut self) -> Option<SocketAddrV6> {
+        let peer = self.0.next()?;
+        // Chunks are always COMPACT_V6_LEN bytes long
+        let ip: [u8; 16] = peer[..16].try_into().unwrap();
+        let port = u16::from_be_bytes([peer[16], peer[17]]);
+        Some(SocketAddrV6::new(Ipv6Addr::from(ip), port, 0, 0))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl ExactSizeIterator for CompactPeersV6<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_ipv4() {
+        let peer = PeerAddr::new("192.168.1.12:6881").unwrap();
+        assert_eq!(
+            peer,
+            PeerAddr::Ip(SocketAddr::from_str("192.168.1.12:6881").unwrap())
+        );
+        assert_eq!(peer.port(), 6881);
+    }
+
+    #[test]
+    fn can_parse_ipv6() {
+        let peer = PeerAddr::new("[2001:db8::1]:51413").unwrap();
+        assert_eq!(peer.to_string(), "[2001:db8::1]:51413");
+    }
+
+    #[test]
+    fn can_parse_hostname() {
+        let peer = PeerAddr::new("Peer.Example.com:6881").unwrap();
+        assert_eq!(
+            peer,
+            PeerAddr::Host {
+                host: "peer.example.com".to_string(),
+                port: 6881
+            }
+        );
+    }
+
+    #[test]
+    fn fails_missing_port() {
+        assert_eq!(
+            PeerAddr::new("example.com").unwrap_err(),
+            PeerAddrError::MissingPort {
+                addr: "example.com".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn fails_invalid_port() {
+        assert_eq!(
+            PeerAddr::new("example.com:99999").unwrap_err(),
+            PeerAddrError::InvalidPort {
+                addr: "example.com:99999".to_string()
+            }
+        );
+        assert!(PeerAddr::new("10.0.0.1:0").is_err());
+    }
+
+    #[test]
+    fn fails_invalid_host() {
+        assert_eq!(
+            PeerAddr::new("exa mple.com:6881").unwrap_err(),
+            PeerAddrError::InvalidHost {
+                addr: "exa mple.com:6881".to_string()
+            }
+        );
+        assert!(PeerAddr::new("2001:db8::1:6881").is_err());
+    }
+
+    #[test]
+    fn decodes_compact_peers() {
+        let mut bytes = vec![0; 15];
+        bytes.extend([1, 0x1a, 0xe1]);
+        bytes.extend([0x20, 0x01, 0x0d, 0xb8]);
+        bytes.extend([0; 11]);
+        bytes.extend([1, 0xc8, 0xd5]);
+
+        let peers = PeerAddr::compact_v6(&bytes).unwrap();
+        assert_eq!(peers.len(), 2);
+        let peers: Vec<SocketAddrV6> = peers.collect();
+        assert_eq!(peers[0].to_string(), "[::1]:6881");
+        assert_eq!(peers[1].to_string(), "[2001:db8::1]:51413");
+        assert_eq!(PeerAddr::encode_compact_v6(&peers), bytes);
+
+        // 36 bytes are also 6 IPv4 peers
+        assert_eq!(PeerAddr::compact_v4(&bytes).unwrap().len(), 6);
+        assert_eq!(PeerAddr::compact_v4(&[]).unwrap().count(), 0);
+        assert_eq!(
+            PeerAddr::compact_v4(&bytes[..35]).unwrap_err(),
+            PeerAddrError::InvalidCompactLength {
+                len: 35,
+                peer_len: 6
+            }
+        );
+        assert!(PeerAddr::compact_v6(&bytes[..6]).is_err());
+    }
+}
@@ -0,0 +1,246 @@
+use crate::{InfoHash, MagnetLink, TorrentFile, TorrentID};
+
+/// Error occurred during a transition of a [`PendingMetadata`](crate::pending::PendingMetadata)
+/// entry.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PendingMetadataError {
+    /// No MagnetLink in the registry matches the torrent.
+    UnknownTorrent { id: TorrentID },
+    /// The metadata of the MagnetLink was already resolved.
+    AlreadyResolved { id: TorrentID },
+}
+
+impl std::fmt::Display for PendingMetadataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PendingMetadataError::UnknownTorrent { id } => {
+                write!(f, "No pending magnet link for torrent {id}")
+            }
+            PendingMetadataError::AlreadyResolved { id } => {
+                write!(f, "Metadata for torrent {id} was already resolved")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PendingMetadataError {}
+
+/// The metadata resolution state of a MagnetLink in a
+/// [`PendingMetadata`](crate::pending::PendingMetadata) registry.
+#[derive(Clone, Debug)]
+pub enum MetadataState {
+    /// The metadata is being fetched from peers.
+    AwaitingMetadata,
+    /// The metadata was received, and matches the infohash of the MagnetLink.
+    Resolved(Box<TorrentFile>),
+}
+
+impl MetadataState {
+    pub fn is_resolved(&self) -> bool {
+        matches!(self, MetadataState::Resolved(_))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct PendingEntry {
+    magnet: MagnetLink,
+    state: MetadataState,
+}
+
+/// A registry of [`MagnetLink`](crate::magnet::MagnetLink)s whose metadata is being resolved,
+/// indexed by [`TorrentID`](crate::id::TorrentID).
+///
+/// A MagnetLink is added [`AwaitingMetadata`](crate::pending::MetadataState::AwaitingMetadata),
+/// and becomes [`Resolved`](crate::pending::MetadataState::Resolved) when the
+/// [`TorrentFile`](crate::torrent_file::TorrentFile) received from peers matches its infohash.
+/// Matching is hybrid-aware, so a v1 MagnetLink is resolved by the hybrid TorrentFile it is part
+/// of. Entries are kept in the order they were added.
+///
+/// ```
+/// use hightorrent::{MagnetLink, PendingMetadata, TorrentFile};
+///
+/// let magnet = MagnetLink::new(
+///     &std::fs::read_to_string("tests/bittorrent-v1-emma-goldman.magnet").unwrap()
+/// ).unwrap();
+/// let mut pending = PendingMetadata::new();
+/// assert!(pending.insert(magnet.clone()));
+/// assert_eq!(pending.awaiting().count(), 1);
+///
+/// let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+/// pending.resolve(TorrentFile::from_slice(&slice).unwrap()).unwrap();
+/// assert!(pending.state(&magnet.id()).unwrap().is_resolved());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PendingMetadata {
+    entries: Vec<PendingEntry>,
+}
+
+impl PendingMetadata {
+    pub fn new() -> PendingMetadata {
+        PendingMetadata::default()
+    }
+
+    /// Adds a MagnetLink awaiting its metadata. Returns false when a MagnetLink for the same
+    /// torrent is already registered, in which case the new MagnetLink is
+    /// [`merge`](crate::magnet::MagnetLink::merge)d into it, to combine their trackers.
+    pub fn insert(&mut self, magnet: MagnetLink) -> bool {
+        match self.position(magnet.hash()) {
+            Some(pos) => {
+                self.entries[pos].magnet.merge(&magnet);
+                false
+            }
+            None => {
+                self.entries.push(PendingEntry {
+                    magnet,
+                    state: MetadataState::AwaitingMetadata,
+                });
+                true
+            }
+        }
+    }
+
+    /// Marks the MagnetLink matching a TorrentFile as resolved, and returns the MagnetLink.
+    /// Fails when no MagnetLink matches, or when its metadata was already resolved.
+    pub fn resolve(&mut self, torrent: TorrentFile) -> Result<&MagnetLink, PendingMetadataError> {
+        let id = torrent.id();
+        let pos = self
+            .position(&InfoHash::from(&torrent))
+            .ok_or(PendingMetadataError::UnknownTorrent { id })?;
+
+        let entry = &mut self.entries[pos];
+        if entry.state.is_resolved() {
+            return Err(PendingMetadataError::AlreadyResolved {
+                id: entry.magnet.id(),
+            });
+        }
+        entry.state = MetadataState::Resolved(Box::new(torrent));
+        Ok(&entry.magnet)
+    }
+
+    /// Puts a resolved MagnetLink back to awaiting its metadata, for example when the received
+    /// metadata was rejected by the client. Returns the discarded TorrentFile, if any.
+    pub fn reset(&mut self, id: &TorrentID) -> Option<TorrentFile> {
+        let entry = self.entries.iter_mut().find(|e| e.magnet.id() == *id)?;
+        match std::mem::replace(&mut entry.state, MetadataState::AwaitingMetadata) {
+            MetadataState::Resolved(torrent) => Some(*torrent),
+            MetadataState::AwaitingMetadata => None,
+        }
+    }
+
+    /// Removes a MagnetLink from the registry, returning it with its state.
+    pub fn remove(&mut self, id: &TorrentID) -> Option<(MagnetLink, MetadataState)> {
+        let pos = self.entries.iter().position(|e| e.magnet.id() == *id)?;
+        let entry = self.entries.remove(pos);
+        Some((entry.magnet, entry.state))
+    }
+
+    /// Removes all the resolved MagnetLinks from the registry, returning them with their
+    /// TorrentFile.
+    pub fn take_resolved(&mut self) -> Vec<(MagnetLink, TorrentFile)> {
+        let mut resolved = Vec::new();
+        let mut awaiting = Vec::new();
+        for entry in self.entries.drain(..) {
+            match entry.state {
+                MetadataState::Resolved(torrent) => resolved.push((entry.magnet, *torrent)),
+                MetadataState::AwaitingMetadata => awaiting.push(entry),
+            }
+        }
+        self.entries = awaiting;
+        resolved
+    }
+
+    /// Returns the MagnetLink registered with a TorrentID.
+    pub fn magnet(&self, id: &TorrentID) -> Option<&MagnetLink> {
+        self.get(id).map(|e| &e.magnet)
+    }
+
+    /// Returns the state of the MagnetLink registered with a TorrentID.
+    pub fn state(&self, id: &TorrentID) -> Option<&MetadataState> {
+        self.get(id).map(|e| &e.state)
+    }
+
+    /// Returns the MagnetLinks still awaiting their metadata.
+    pub fn awaiting(&self) -> impl Iterator<Item = &MagnetLink> {
+        self.entries
+            .iter()
+            .filter(|e| !e.state.is_resolved())
+            .map(|e| &e.magnet)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn get(&self, id: &TorrentID) -> Option<&PendingEntry> {
+        self.entries.iter().find(|e| e.magnet.id() == *id)
+    }
+
+    fn position(&self, hash: &InfoHash) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|e| e.magnet.hash().matches(hash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hybrid_torrent() -> TorrentFile {
+        let slice = std::fs::read("tests/bittorrent-v2-hybrid-test.torrent").unwrap();
+        TorrentFile::from_slice(&slice).unwrap()
+    }
+
+    #[test]
+    fn resolves_metadata() {
+        let v1 = MagnetLink::new(
+            "magnet:?xt=urn:btih:631a31dd0a46257d5078c0dee4e66e26f73e42ac&dn=hybrid&tr=udp%3A%2F%2Fone.example%3A6969",
+        )
+        .unwrap();
+        let other = MagnetLink::new(
+            &std::fs::read_to_string("tests/bittorrent-v1-emma-goldman.magnet").unwrap(),
+        )
+        .unwrap();
+        let mut pending = PendingMetadata::new();
+        assert!(pending.insert(v1.clone()));
+        assert!(pending.insert(other.clone()));
+        assert!(!pending.insert(v1.clone()));
+        assert_eq!(pending.len(), 2);
+
+        // The hybrid torrent resolves the v1 magnet link
+        let torrent = hybrid_torrent();
+        assert_eq!(pending.resolve(torrent.clone()).unwrap(), &v1);
+        assert_eq!(
+            pending.resolve(torrent).unwrap_err(),
+            PendingMetadataError::AlreadyResolved { id: v1.id() }
+        );
+        assert_eq!(pending.awaiting().collect::<Vec<_>>(), vec![&other],);
+
+        assert!(pending.reset(&v1.id()).is_some());
+        assert!(!pending.state(&v1.id()).unwrap().is_resolved());
+        pending.resolve(hybrid_torrent()).unwrap();
+
+        let resolved = pending.take_resolved();
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].1.hash(), hybrid_torrent().hash());
+        assert_eq!(pending.len(), 1);
+        assert!(pending.magnet(&v1.id()).is_none());
+
+        assert!(pending.remove(&other.id()).is_some());
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn rejects_unknown_torrent() {
+        let mut pending = PendingMetadata::new();
+        let torrent = hybrid_torrent();
+        assert_eq!(
+            pending.resolve(torrent.clone()).unwrap_err(),
+            PendingMetadataError::UnknownTorrent { id: torrent.id() }
+        );
+    }
+}
@@ -0,0 +1,348 @@
+use crate::{FileSelection, TorrentFile};
+
+/// Error occurred while building or converting [`FilePriorities`](crate::priority::FilePriorities).
+#[derive(Clone, Debug, PartialEq)]
+pub enum FilePriorityError {
+    /// The number of priorities does not match the number of files in the torrent.
+    FileCountMismatch { expected: usize, found: usize },
+    /// The file index is not in the priorities.
+    IndexOutOfRange { index: usize, len: usize },
+    /// The value is not a priority in the client encoding.
+    InvalidPriority { value: i64 },
+    /// The Transmission `wanted` and `priorities` arrays do not have the same length.
+    LengthMismatch { wanted: usize, priorities: usize },
+}
+
+impl std::fmt::Display for FilePriorityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilePriorityError::FileCountMismatch { expected, found } => {
+                write!(f, "Expected priorities for {expected} files, found {found}")
+            }
+            FilePriorityError::IndexOutOfRange { index, len } => {
+                write!(f, "File index {index} out of range for {len} files")
+            }
+            FilePriorityError::InvalidPriority { value } => {
+                write!(f, "Invalid file priority: {value}")
+            }
+            FilePriorityError::LengthMismatch { wanted, priorities } => write!(
+                f,
+                "Mismatched lengths of wanted ({wanted}) and priorities ({priorities})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FilePriorityError {}
+
+/// The download priority of a file in a torrent.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum FilePriority {
+    /// The file is not downloaded
+    Skip,
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+impl FilePriority {
+    /// Returns true when the file is downloaded, whatever its priority.
+    pub fn is_wanted(&self) -> bool {
+        *self != FilePriority::Skip
+    }
+
+    /// Converts a qBittorrent file priority, as found in the `priority` field of the WebUI API
+    /// `torrents/files` method. qBittorrent has no low priority, and libtorrent levels 2 to 5
+    /// are reported as normal priority.
+    pub fn from_qbittorrent(value: i64) -> Result<FilePriority, FilePriorityError> {
+        match value {
+            0 => Ok(FilePriority::Skip),
+            1..=5 => Ok(FilePriority::Normal),
+            6 | 7 => Ok(FilePriority::High),
+            value => Err(FilePriorityError::InvalidPriority { value }),
+        }
+    }
+
+    /// Returns the qBittorrent file priority, as expected by the WebUI API
+    /// `torrents/filePrio` method. Low priority is sent as normal priority.
+    pub fn to_qbittorrent(&self) -> u8 {
+        match self {
+            FilePriority::Skip => 0,
+            FilePriority::Low | FilePriority::Normal => 1,
+            FilePriority::High => 6,
+        }
+    }
+
+    /// Converts a Transmission file priority, as found in the `wanted` and `priorities` fields
+    /// of the RPC `torrent-get` method.
+    pub fn from_transmission(
+        wanted: bool,
+        priority: i64,
+    ) -> Result<FilePriority, FilePriorityError> {
+        let priority = match priority {
+            -1 => FilePriority::Low,
+            0 => FilePriority::Normal,
+            1 => FilePriority::High,
+            value => return Err(FilePriorityError::InvalidPriority { value }),
+        };
+        Ok(if wanted { priority } else { FilePriority::Skip })
+    }
+
+    /// Returns the Transmission `wanted` flag and priority. Skipped files keep the normal
+    /// priority, as done by Transmission.
+    pub fn to_transmission(&self) -> (bool, i8) {
+        match self {
+            FilePriority::Skip => (false, 0),
+            FilePriority::Low => (true, -1),
+            FilePriority::Normal => (true, 0),
+            FilePriority::High => (true, 1),
+        }
+    }
+}
+
+impl std::fmt::Display for FilePriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilePriority::Skip => write!(f, "skip"),
+            FilePriority::Low => write!(f, "low"),
+            FilePriority::Normal => write!(f, "normal"),
+            FilePriority::High => write!(f, "high"),
+        }
+    }
+}
+
+/// The download priorities of the files in a torrent, by content index.
+///
+/// The content index of a file is its position in
+/// [`TorrentFile::files`](crate::torrent_file::TorrentFile::files), which skips padding files
+/// like torrent clients do in their user interface. FilePriorities are serialized as a list of
+/// priority names, and converted to and from the encodings of qBittorrent and Transmission, so
+/// that frontends and backends share the same representation.
+///
+/// ```
+/// use hightorrent::{FilePriorities, FilePriority, TorrentFile};
+///
+/// let torrent = TorrentFile::from_path("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+/// let mut priorities = FilePriorities::for_torrent(&torrent);
+/// priorities.set(0, FilePriority::Skip).unwrap();
+/// priorities.set(1, FilePriority::High).unwrap();
+/// assert!(priorities.validate(&torrent).is_ok());
+/// assert_eq!(&priorities.to_qbittorrent()[..3], &[0, 6, 1]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct FilePriorities(Vec<FilePriority>);
+
+impl FilePriorities {
+    /// Creates the priorities of `count` files, all with normal priority.
+    pub fn new(count: usize) -> FilePriorities {
+        FilePriorities(vec![FilePriority::Normal; count])
+    }
+
+    /// Creates the priorities of the files in a TorrentFile, all with normal priority.
+    pub fn for_torrent(torrent: &TorrentFile) -> FilePriorities {
+        FilePriorities::new(torrent.files().len())
+    }
+
+    pub fn from_vec(priorities: Vec<FilePriority>) -> FilePriorities {
+        FilePriorities(priorities)
+    }
+
+    pub fn to_vec(self) -> Vec<FilePriority> {
+        self.0
+    }
+
+    /// Checks that there is one priority for every file of a TorrentFile.
+    pub fn validate(&self, torrent: &TorrentFile) -> Result<(), FilePriorityError> {
+        let expected = torrent.files().len();
+        if self.0.len() != expected {
+            return Err(FilePriorityError::FileCountMismatch {
+                expected,
+                found: self.0.len(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the priority of a file, or None when the index is out of range.
+    pub fn get(&self, index: usize) -> Option<FilePriority> {
+        self.0.get(index).copied()
+    }
+
+    /// Sets the priority of a file. Fails when the index is out of range.
+    pub fn set(&mut self, index: usize, priority: FilePriority) -> Result<(), FilePriorityError> {
+        let len = self.0.len();
+        let entry = self
+            .0
+            .get_mut(index)
+            .ok_or(FilePriorityError::IndexOutOfRange { index, len })?;
+        *entry = priority;
+        Ok(())
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, FilePriority> {
+        self.0.iter()
+    }
+
+    /// Returns the files which are downloaded, or None when all files are skipped.
+    pub fn wanted(&self) -> Option<FileSelection> {
+        FileSelection::from_indices(
+            self.0
+                .iter()
+                .enumerate()
+                .filter(|(_, priority)| priority.is_wanted())
+                .map(|(index, _)| index),
+        )
+        .ok()
+    }
+
+    /// Converts qBittorrent file priorities, as described in
+    /// [`FilePriority::from_qbittorrent`](crate::priority::FilePriority::from_qbittorrent).
+    pub fn from_qbittorrent(values: &[i64]) -> Result<FilePriorities, FilePriorityError> {
+        values
+            .iter()
+            .map(|value| FilePriority::from_qbittorrent(*value))
+            .collect()
+    }
+
+    /// Returns the qBittorrent file priorities, as described in
+    /// [`FilePriority::to_qbittorrent`](crate::priority::FilePriority::to_qbittorrent).
+    pub fn to_qbittorrent(&self) -> Vec<u8> {
+        self.0.iter().map(|p| p.to_qbittorrent()).collect()
+    }
+
+    /// Converts the Transmission `wanted` and `priorities` arrays, as described in
+    /// [`FilePriority::from_transmission`](crate::priority::FilePriority::from_transmission).
+    pub fn from_transmission(
+        wanted: &[bool],
+        priorities: &[i64],
+    ) -> Result<FilePriorities, FilePriorityError> {
+        if wanted.len() != priorities.len() {
+            return Err(FilePriorityError::LengthMismatch {
+                wanted: wanted.len(),
+                priorities: priorities.len(),
+            });
+        }
+        wanted
+            .iter()
+            .zip(priorities)
+            .map(|(wanted, priority)| FilePriority::from_transmission(*wanted, *priority))
+            .collect()
+    }
+
+    /// Returns the Transmission `wanted` and `priorities` arrays, as described in
+    /// [`FilePriority::to_transmission`](crate::priority::FilePriority::to_transmission).
+    pub fn to_transmission(&self) -> (Vec<bool>, Vec<i8>) {
+        self.0.iter().map(|p| p.to_transmission()).unzip()
+    }
+}
+
+impl FromIterator<FilePriority> for FilePriorities {
+    fn from_iter<I: IntoIterator<Item = FilePriority>>(iter: I) -> FilePriorities {
+        FilePriorities(iter.into_iter().collect())
+    }
+}
+
+impl<'a> IntoIterator for &'a FilePriorities {
+    type Item = &'a FilePriority;
+    type IntoIter = std::slice::Iter<'a, FilePriority>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use FilePriority::*;
+
+    #[test]
+    fn converts_client_priorities() {
+        let priorities = FilePriorities::from_vec(vec![Skip, Low, Normal, High]);
+        assert_eq!(priorities.to_qbittorrent(), vec![0, 1, 1, 6]);
+        assert_eq!(
+            FilePriorities::from_qbittorrent(&[0, 1, 4, 7]).unwrap(),
+            FilePriorities::from_vec(vec![Skip, Normal, Normal, High])
+        );
+        assert_eq!(
+            FilePriorities::from_qbittorrent(&[-1]),
+            Err(FilePriorityError::InvalidPriority { value: -1 })
+        );
+
+        let (wanted, values) = priorities.to_transmission();
+        assert_eq!(wanted, vec![false, true, true, true]);
+        assert_eq!(values, vec![0, -1, 0, 1]);
+        let values: Vec<i64> = values.into_iter().map(i64::from).collect();
+        assert_eq!(
+            FilePriorities::from_transmission(&wanted, &values).unwrap(),
+            priorities
+        );
+        assert_eq!(
+            FilePriorities::from_transmission(&wanted, &values[..2]),
+            Err(FilePriorityError::LengthMismatch {
+                wanted: 4,
+                priorities: 2
+            })
+        );
+    }
+
+    #[test]
+    fn validates_against_torrent() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        let count = torrent.files().len();
+
+        let mut priorities = FilePriorities::for_torrent(&torrent);
+        assert_eq!(priorities.len(), count);
+        assert_eq!(
+            priorities.set(count, Skip),
+            Err(FilePriorityError::IndexOutOfRange {
+                index: count,
+                len: count
+            })
+        );
+        priorities.set(1, Skip).unwrap();
+        assert_eq!(
+            priorities.wanted().unwrap().to_string(),
+            format!("0,2-{}", count - 1)
+        );
+        assert!(priorities.validate(&torrent).is_ok());
+
+        assert_eq!(
+            FilePriorities::new(1).validate(&torrent),
+            Err(FilePriorityError::FileCountMismatch {
+                expected: count,
+                found: 1
+            })
+        );
+        assert_eq!(FilePriorities::new(2).wanted().unwrap().to_string(), "0-1");
+        assert!(FilePriorities::from_vec(vec![Skip]).wanted().is_none());
+    }
+
+    #[test]
+    fn serializes_priority_names() {
+        let priorities = FilePriorities::from_vec(vec![Skip, High]);
+        let json = serde_json::to_string(&priorities).unwrap();
+        assert_eq!(json, r#"["skip","high"]"#);
+        assert_eq!(
+            serde_json::from_str::<FilePriorities>(&json).unwrap(),
+            priorities
+        );
+    }
+}
@@ -0,0 +1,159 @@
+/// The download progress of a [`Torrent`](crate::torrent::Torrent), in bytes.
+///
+/// When a backend only reports a percentage and the size of the torrent is unknown, the
+/// progress is expressed over 100 units, as created by
+/// [`Progress::from_percent`](crate::progress::Progress::from_percent).
+///
+/// For compatibility with data serialized when the progress was an integer percentage, a
+/// Progress can be deserialized from a number between 0 and 100.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(from = "RawProgress")]
+pub struct Progress {
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+enum RawProgress {
+    Percent(u8),
+    Bytes { bytes_done: u64, bytes_total: u64 },
+}
+
+impl From<RawProgress> for Progress {
+    fn from(raw: RawProgress) -> Progress {
+        match raw {
+            RawProgress::Percent(percent) => Progress::from_percent(percent),
+            RawProgress::Bytes {
+                bytes_done,
+                bytes_total,
+            } => Progress::new(bytes_done, bytes_total),
+        }
+    }
+}
+
+impl Progress {
+    /// Creates a new Progress. `bytes_done` is capped to `bytes_total`.
+    pub fn new(bytes_done: u64, bytes_total: u64) -> Progress {
+        Progress {
+            bytes_done: bytes_done.min(bytes_total),
+            bytes_total,
+        }
+    }
+
+    /// Creates a Progress from a completed fraction (between 0 and 1) of `bytes_total`.
+    ///
+    /// When `bytes_total` is zero (unknown size), the progress is expressed over 100 units.
+    /// The progress is only complete when the fraction is 1, even if it is rounded up to
+    /// `bytes_total`.
+    pub fn from_fraction(fraction: f64, bytes_total: u64) -> Progress {
+        let fraction = if fraction.is_nan() {
+            0.0
+        } else {
+            fraction.clamp(0.0, 1.0)
+        };
+        let bytes_total = if bytes_total == 0 { 100 } else { bytes_total };
+
+        let mut bytes_done = (fraction * bytes_total as f64).round() as u64;
+        if fraction < 1.0 && bytes_done >= bytes_total {
+            bytes_done = bytes_total - 1;
+        }
+        Progress::new(bytes_done, bytes_total)
+    }
+
+    /// Creates a Progress from an integer percentage, expressed over 100 units.
+    /// Percentages above 100 are capped.
+    pub fn from_percent(percent: u8) -> Progress {
+        Progress::new(percent as u64, 100)
+    }
+
+    /// Returns the completed percentage (0-100). An empty torrent has no progress.
+    pub fn percent(&self) -> f64 {
+        if self.bytes_total == 0 {
+            return 0.0;
+        }
+
+        self.bytes_done as f64 * 100.0 / self.bytes_total as f64
+    }
+
+    /// Returns whether all bytes have been downloaded.
+    pub fn is_complete(&self) -> bool {
+        self.bytes_total > 0 && self.bytes_done == self.bytes_total
+    }
+}
+
+/// Formats the Progress as a percentage, with one decimal when it is not a whole number
+/// (eg. `42%` or `99.6%`). `100%` is only displayed when the Progress is
+/// [`complete`](crate::progress::Progress::is_complete), so an almost complete Progress is
+/// displayed as `99.9%`.
+impl std::fmt::Display for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.bytes_total == 0 {
+            return write!(f, "0%");
+        }
+        // Rounded with integers, so that floating point errors do not change the result
+        let (done, total) = (self.bytes_done as u128, self.bytes_total as u128);
+        let mut tenths = (done * 2000 + total) / (total * 2);
+        if tenths == 1000 && !self.is_complete() {
+            tenths = 999;
+        }
+        if tenths % 10 == 0 {
+            write!(f, "{}%", tenths / 10)
+        } else {
+            write!(f, "{}.{}%", tenths / 10, tenths % 10)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_percent() {
+        assert_eq!(Progress::new(512, 1024).percent(), 50.0);
+        assert_eq!(Progress::new(2048, 1024), Progress::new(1024, 1024));
+        assert!(Progress::new(1024, 1024).is_complete());
+        assert_eq!(Progress::default().percent(), 0.0);
+        assert!(!Progress::default().is_complete());
+
+        assert_eq!(
+            Progress::from_fraction(0.996, 1000),
+            Progress::new(996, 1000)
+        );
+        assert_eq!(Progress::from_fraction(0.5, 0), Progress::from_percent(50));
+        // Incomplete torrents are never rounded up to complete
+        assert_eq!(
+            Progress::from_fraction(0.996, 0),
+            Progress::from_percent(99)
+        );
+        assert_eq!(Progress::from_fraction(0.9999, 10), Progress::new(9, 10));
+        assert!(Progress::from_fraction(1.0, 0).is_complete());
+        assert_eq!(Progress::from_fraction(1.5, 10), Progress::new(10, 10));
+        assert_eq!(Progress::from_fraction(f64::NAN, 10), Progress::new(0, 10));
+        assert_eq!(Progress::from_percent(200), Progress::from_percent(100));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Progress::from_percent(42).to_string(), "42%");
+        assert_eq!(Progress::new(996, 1000).to_string(), "99.6%");
+        assert_eq!(Progress::new(9999, 10000).to_string(), "99.9%");
+        assert_eq!(Progress::new(10000, 10000).to_string(), "100%");
+        assert_eq!(Progress::new(2, 3).to_string(), "66.7%");
+        assert_eq!(Progress::default().to_string(), "0%");
+    }
+
+    #[test]
+    fn serde_compat() {
+        let progress: Progress = serde_json::from_str("42").unwrap();
+        assert_eq!(progress, Progress::from_percent(42));
+
+        let progress = Progress::new(1, 3);
+        let json = serde_json::to_string(&progress).unwrap();
+        assert_eq!(json, r#"{"bytes_done":1,"bytes_total":3}"#);
+        assert_eq!(serde_json::from_str::<Progress>(&json).unwrap(), progress);
+    }
+}
@@ -0,0 +1,246 @@
+//! Python bindings for indexers and scripts, built with [PyO3](https://pyo3.rs/).
+//!
+//! The [`MagnetLink`](crate::magnet::MagnetLink), [`TorrentFile`](crate::torrent_file::TorrentFile),
+//! [`InfoHash`](crate::hash::InfoHash) and [`TorrentID`](crate::id::TorrentID) types are exported
+//! as Python classes with the same names, so that Python code parses and validates torrents with
+//! the same rules as Rust code. Parsing errors are raised as exceptions deriving from
+//! `hightorrent.HighTorrentError`, itself a `ValueError`.
+//!
+//! To build a Python extension module, build the crate as a `cdylib` with the `python` feature
+//! and PyO3's `extension-module` feature, for example with [maturin](https://www.maturin.rs/).
+
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use crate::{InfoHash, MagnetLink, TorrentFile, TorrentID};
+
+mod exceptions {
+    use pyo3::create_exception;
+    use pyo3::exceptions::PyValueError;
+
+    create_exception!(hightorrent, HighTorrentError, PyValueError);
+    create_exception!(hightorrent, MagnetLinkError, HighTorrentError);
+    create_exception!(hightorrent, TorrentFileError, HighTorrentError);
+    create_exception!(hightorrent, InfoHashError, HighTorrentError);
+}
+
+use exceptions::{HighTorrentError, InfoHashError, MagnetLinkError, TorrentFileError};
+
+impl From<crate::MagnetLinkError> for PyErr {
+    fn from(e: crate::MagnetLinkError) -> PyErr {
+        MagnetLinkError::new_err(e.to_string())
+    }
+}
+
+impl From<crate::TorrentFileError> for PyErr {
+    fn from(e: crate::TorrentFileError) -> PyErr {
+        TorrentFileError::new_err(e.to_string())
+    }
+}
+
+impl From<crate::InfoHashError> for PyErr {
+    fn from(e: crate::InfoHashError) -> PyErr {
+        InfoHashError::new_err(e.to_string())
+    }
+}
+
+/// A [`MagnetLink`](crate::magnet::MagnetLink), exported to Python as `MagnetLink`.
+#[pyclass(name = "MagnetLink", module = "hightorrent", frozen)]
+pub struct PyMagnetLink(MagnetLink);
+
+#[pymethods]
+impl PyMagnetLink {
+    /// Parses a magnet link, raising `MagnetLinkError` when it is invalid.
+    #[new]
+    pub fn new(s: &str) -> PyResult<PyMagnetLink> {
+        Ok(PyMagnetLink(MagnetLink::new(s)?))
+    }
+
+    #[getter]
+    pub fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    #[getter]
+    pub fn hash(&self) -> PyInfoHash {
+        PyInfoHash(self.0.hash().clone())
+    }
+
+    #[getter]
+    pub fn id(&self) -> PyTorrentID {
+        PyTorrentID(self.0.id())
+    }
+
+    #[getter]
+    pub fn trackers(&self) -> Vec<String> {
+        self.0
+            .trackers()
+            .iter()
+            .map(|t| t.url().to_string())
+            .collect()
+    }
+
+    pub fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("MagnetLink({:?})", self.0.to_string())
+    }
+}
+
+/// A [`TorrentFile`](crate::torrent_file::TorrentFile), exported to Python as `TorrentFile`.
+#[pyclass(name = "TorrentFile", module = "hightorrent", frozen)]
+pub struct PyTorrentFile(TorrentFile);
+
+#[pymethods]
+impl PyTorrentFile {
+    /// Parses a torrent from bytes, raising `TorrentFileError` when it is invalid.
+    #[new]
+    pub fn new(bytes: &[u8]) -> PyResult<PyTorrentFile> {
+        Ok(PyTorrentFile(TorrentFile::from_slice(bytes)?))
+    }
+
+    #[getter]
+    pub fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    #[getter]
+    pub fn hash(&self) -> PyInfoHash {
+        PyInfoHash(InfoHash::from(&self.0))
+    }
+
+    #[getter]
+    pub fn id(&self) -> PyTorrentID {
+        PyTorrentID(self.0.id())
+    }
+
+    #[getter]
+    pub fn comment(&self) -> Option<&str> {
+        self.0.comment()
+    }
+
+    #[getter]
+    pub fn trackers(&self) -> Vec<String> {
+        self.0
+            .tracker_tiers()
+            .iter()
+            .map(|t| t.url().to_string())
+            .collect()
+    }
+
+    /// The files of the torrent, as `(path, size)` tuples.
+    #[getter]
+    pub fn files(&self) -> Vec<(String, u64)> {
+        self.0
+            .files()
+            .into_iter()
+            .map(|f| (f.path.to_string(), f.size))
+            .collect()
+    }
+
+    /// The problems found by [`TorrentFile::validate`](crate::torrent_file::TorrentFile::validate),
+    /// as messages.
+    pub fn validate(&self) -> Vec<String> {
+        self.0
+            .validate()
+            .issues()
+            .iter()
+            .map(|i| i.to_string())
+            .collect()
+    }
+
+    /// The original bencoded bytes of the torrent.
+    pub fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, self.0.as_bytes())
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!(
+            "TorrentFile(name={:?}, hash={:?})",
+            self.0.name(),
+            self.0.hash()
+        )
+    }
+}
+
+/// An [`InfoHash`](crate::hash::InfoHash), exported to Python as `InfoHash`.
+#[pyclass(name = "InfoHash", module = "hightorrent", frozen, eq, hash)]
+#[derive(PartialEq, Eq, Hash)]
+pub struct PyInfoHash(InfoHash);
+
+#[pymethods]
+impl PyInfoHash {
+    /// Parses an infohash, raising `InfoHashError` when it is invalid.
+    #[new]
+    pub fn new(s: &str) -> PyResult<PyInfoHash> {
+        Ok(PyInfoHash(InfoHash::new(s)?))
+    }
+
+    #[getter]
+    pub fn v1(&self) -> Option<String> {
+        self.0.v1().map(String::from)
+    }
+
+    #[getter]
+    pub fn v2(&self) -> Option<String> {
+        self.0.v2().map(String::from)
+    }
+
+    #[getter]
+    pub fn is_hybrid(&self) -> bool {
+        self.0.is_hybrid()
+    }
+
+    #[getter]
+    pub fn id(&self) -> PyTorrentID {
+        PyTorrentID(self.0.id())
+    }
+
+    pub fn __str__(&self) -> String {
+        self.0.as_str().into()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("InfoHash({:?})", self.0.as_str())
+    }
+}
+
+/// A [`TorrentID`](crate::id::TorrentID), exported to Python as `TorrentID`.
+#[pyclass(name = "TorrentID", module = "hightorrent", frozen, eq)]
+#[derive(PartialEq)]
+pub struct PyTorrentID(TorrentID);
+
+#[pymethods]
+impl PyTorrentID {
+    /// Parses a torrent ID, raising `InfoHashError` when it is invalid.
+    #[new]
+    pub fn new(s: &str) -> PyResult<PyTorrentID> {
+        Ok(PyTorrentID(TorrentID::new(s)?))
+    }
+
+    pub fn __str__(&self) -> &str {
+        self.0.as_str()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("TorrentID({:?})", self.0.as_str())
+    }
+}
+
+/// The `hightorrent` Python module.
+#[pymodule]
+#[pyo3(name = "hightorrent")]
+pub fn python_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add_class::<PyMagnetLink>()?;
+    m.add_class::<PyTorrentFile>()?;
+    m.add_class::<PyInfoHash>()?;
+    m.add_class::<PyTorrentID>()?;
+    m.add("HighTorrentError", py.get_type::<HighTorrentError>())?;
+    m.add("MagnetLinkError", py.get_type::<MagnetLinkError>())?;
+    m.add("TorrentFileError", py.get_type::<TorrentFileError>())?;
+    m.add("InfoHashError", py.get_type::<InfoHashError>())?;
+    Ok(())
+}
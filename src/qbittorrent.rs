@@ -0,0 +1,264 @@
+use crate::{
+    Category, InfoHash, InfoHashError, Progress, Timestamp, ToTorrent, Torrent, TorrentLimits,
+    Tracker, TrackerError, TryIntoTracker,
+};
+
+/// A torrent, as returned by the qBittorrent Web API (`/api/v2/torrents/info`).
+///
+/// Only the fields required to build a [`Torrent`](crate::torrent::Torrent) are deserialized.
+/// The infohash is validated during deserialization, from the `infohash_v1`/`infohash_v2`
+/// fields (qBittorrent >= 4.4) or from the `hash` field for older versions.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "RawQBittorrentTorrent")]
+pub struct QBittorrentTorrent {
+    pub hash: InfoHash,
+    pub name: String,
+    pub save_path: String,
+    /// Unix timestamp (in seconds) when the torrent was added
+    pub added_on: i64,
+    /// Unix timestamp (in seconds) when the torrent was completed, or a negative/zero
+    /// value when the torrent is not complete
+    pub completion_on: i64,
+    /// Progress ratio (0-1)
+    pub progress: f64,
+    pub size: i64,
+    pub uploaded: Option<u64>,
+    pub downloaded: Option<u64>,
+    pub state: String,
+    pub tags: Vec<String>,
+    /// The category, which is None when empty or invalid
+    pub category: Option<Category>,
+    /// The limits from the `dl_limit`, `up_limit`, `ratio_limit` and `seeding_time_limit`
+    /// fields
+    pub limits: TorrentLimits,
+}
+
+#[derive(Deserialize)]
+struct RawQBittorrentTorrent {
+    #[serde(default)]
+    hash: String,
+    #[serde(default)]
+    infohash_v1: String,
+    #[serde(default)]
+    infohash_v2: String,
+    name: String,
+    #[serde(default)]
+    save_path: String,
+    #[serde(default)]
+    added_on: i64,
+    #[serde(default)]
+    completion_on: i64,
+    #[serde(default)]
+    progress: f64,
+    #[serde(default)]
+    size: i64,
+    #[serde(default)]
+    uploaded: Option<u64>,
+    #[serde(default)]
+    downloaded: Option<u64>,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    tags: String,
+    #[serde(default)]
+    category: String,
+    #[serde(default)]
+    dl_limit: i64,
+    #[serde(default)]
+    up_limit: i64,
+    #[serde(default)]
+    ratio_limit: Option<f64>,
+    #[serde(default)]
+    seeding_time_limit: Option<i64>,
+}
+
+impl TryFrom<RawQBittorrentTorrent> for QBittorrentTorrent {
+    type Error = InfoHashError;
+
+    fn try_from(raw: RawQBittorrentTorrent) -> Result<QBittorrentTorrent, InfoHashError> {
+        let hash = match (raw.infohash_v1.as_str(), raw.infohash_v2.as_str()) {
+            ("", "") => InfoHash::new(&raw.hash)?,
+            (v1, "") => InfoHash::new(v1)?,
+            ("", v2) => InfoHash::new(v2)?,
+            (v1, v2) => InfoHash::new(v1)?.hybrid(&InfoHash::new(v2)?)?,
+        };
+
+        let tags = raw
+            .tags
+            .split(',')
+            .map(|tag| tag.trim())
+            .filter(|tag| !tag.is_empty())
+            .map(|tag| tag.to_string())
+            .collect();
+
+        Ok(QBittorrentTorrent {
+            hash,
+            name: raw.name,
+            save_path: raw.save_path,
+            added_on: raw.added_on,
+            completion_on: raw.completion_on,
+            progress: raw.progress,
+            size: raw.size,
+            uploaded: raw.uploaded,
+            downloaded: raw.downloaded,
+            state: raw.state,
+            tags,
+            category: Category::new(&raw.category).ok(),
+            limits: TorrentLimits::from_qbittorrent(
+                raw.dl_limit,
+                raw.up_limit,
+                raw.ratio_limit.unwrap_or(-1.0),
+                raw.seeding_time_limit.unwrap_or(-1),
+            ),
+        })
+    }
+}
+
+impl ToTorrent for QBittorrentTorrent {
+    fn to_torrent(&self) -> Torrent {
+        Torrent {
+            name: self.name.clone(),
+            path: self.save_path.clone(),
+            date_start: Timestamp::from_secs_opt(self.added_on),
+            date_end: Timestamp::from_secs_opt(self.completion_on),
+            progress: Progress::from_fraction(self.progress, u64::try_from(self.size).unwrap_or(0)),
+            size: self.size,
+            uploaded: self.uploaded,
+            downloaded: self.downloaded,
+            state: self.state.clone(),
+            tags: self.tags.clone(),
+            category: self.category.clone(),
+            // Trackers are not listed by /api/v2/torrents/info
+            sources: Vec::new(),
+            limits: self.limits,
+            id: self.hash.id(),
+            hash: self.hash.clone(),
+        }
+    }
+}
+
+/// A tracker, as returned by the qBittorrent Web API (`/api/v2/torrents/trackers`).
+///
+/// qBittorrent also lists the DHT, PeX and LSD as pseudo-trackers (eg. `** [DHT] **`), which
+/// will fail to convert into a [`Tracker`](crate::tracker::Tracker).
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct QBittorrentTracker {
+    pub url: String,
+    #[serde(default)]
+    pub tier: i64,
+}
+
+impl TryIntoTracker for QBittorrentTracker {
+    fn try_into_tracker(&self) -> Result<Tracker, TrackerError> {
+        Tracker::new(&self.url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_convert_torrent() {
+        let json = r#"{
+            "added_on": 1591173906,
+            "completion_on": 1591174000,
+            "hash": "631a31dd0a46257d5078c0dee4e66e26f73e42ac",
+            "infohash_v1": "631a31dd0a46257d5078c0dee4e66e26f73e42ac",
+            "infohash_v2": "d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb",
+            "name": "bittorrent-v1-v2-hybrid-test",
+            "progress": 0.996,
+            "save_path": "/data/torrents",
+            "size": 369266999,
+            "uploaded": 738533998,
+            "downloaded": 369266999,
+            "state": "stalledUP",
+            "tags": "demoscene, amiga",
+            "category": "demoscene/amiga/",
+            "dl_limit": -1,
+            "up_limit": 102400,
+            "ratio_limit": -2,
+            "seeding_time_limit": 1440
+        }"#;
+        let qbit: QBittorrentTorrent = serde_json::from_str(json).unwrap();
+        let torrent = qbit.to_torrent();
+
+        assert!(torrent.hash.is_hybrid());
+        assert_eq!(
+            torrent.id.as_str(),
+            "d8dd32ac93357c368556af3ac1d95c9d76bd0dff"
+        );
+        assert_eq!(torrent.name, "bittorrent-v1-v2-hybrid-test");
+        assert_eq!(torrent.path, "/data/torrents");
+        assert_eq!(torrent.progress, Progress::new(367789931, 369266999));
+        assert_eq!(torrent.progress.to_string(), "99.6%");
+        assert_eq!(torrent.date_end, Timestamp::from_secs_opt(1591174000));
+        assert_eq!(torrent.tags, vec!["demoscene", "amiga"]);
+        assert_eq!(
+            torrent.category.as_ref().unwrap().as_str(),
+            "demoscene/amiga"
+        );
+        assert_eq!(torrent.ratio(), Some(2.0));
+        assert_eq!(
+            torrent.limits,
+            TorrentLimits {
+                upload_limit: Some(102400),
+                seeding_time_limit: Some(86400),
+                ..TorrentLimits::default()
+            }
+        );
+    }
+
+    #[test]
+    fn can_convert_legacy_torrent() {
+        let json = r#"{
+            "hash": "c811b41641a09d192b8ed81b14064fff55d85ce3",
+            "name": "Goldman, Emma - Essential Works of Anarchism",
+            "progress": 0.5,
+            "completion_on": -1,
+            "tags": ""
+        }"#;
+        let torrent = serde_json::from_str::<QBittorrentTorrent>(json)
+            .unwrap()
+            .to_torrent();
+
+        assert!(torrent.hash.is_v1());
+        // Size is unknown, so progress is expressed over 100 units
+        assert_eq!(torrent.progress, Progress::from_percent(50));
+        assert_eq!(torrent.date_end, None);
+        assert_eq!(torrent.ratio(), None);
+        assert!(torrent.tags.is_empty());
+        assert_eq!(torrent.category, None);
+    }
+
+    #[test]
+    fn unknown_size_is_not_complete() {
+        for size in ["0", "-1"] {
+            let json = format!(
+                r#"{{"hash": "c811b41641a09d192b8ed81b14064fff55d85ce3", "name": "test", "progress": 0.996, "size": {size}}}"#
+            );
+            let torrent = serde_json::from_str::<QBittorrentTorrent>(&json)
+                .unwrap()
+                .to_torrent();
+            assert_eq!(torrent.progress, Progress::from_percent(99));
+            assert!(!torrent.progress.is_complete());
+        }
+    }
+
+    #[test]
+    fn fails_invalid_hash() {
+        let json = r#"{"hash": "notahash", "name": "test"}"#;
+        assert!(serde_json::from_str::<QBittorrentTorrent>(json).is_err());
+    }
+
+    #[test]
+    fn can_convert_tracker() {
+        let json = r#"[
+            {"url": "udp://tracker.opentrackr.org:1337/announce", "tier": 0},
+            {"url": "** [DHT] **", "tier": -1}
+        ]"#;
+        let trackers: Vec<QBittorrentTracker> = serde_json::from_str(json).unwrap();
+        assert!(trackers[0].try_into_tracker().is_ok());
+        assert!(trackers[1].try_into_tracker().is_err());
+    }
+}
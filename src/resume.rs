@@ -0,0 +1,348 @@
+use bt_bencode::{ByteString, Value as BencodeValue};
+
+use std::collections::BTreeMap;
+
+use crate::bencode::nesting_depth;
+use crate::torrent_file::bencode_i64;
+use crate::{FileSelection, ParseOptions, TorrentID};
+
+/// Error occurred during parsing a [`ResumeData`](crate::resume::ResumeData) or a
+/// [`Bitfield`](crate::resume::Bitfield).
+#[derive(Clone, Debug, PartialEq)]
+pub enum ResumeDataError {
+    // TODO: bt_bencode::Error is not PartialEq so we store error as String
+    InvalidBencode {
+        reason: String,
+    },
+    MissingField {
+        field: String,
+    },
+    InvalidField {
+        field: String,
+    },
+    /// The bytes do not hold a bitfield of `len` pieces, or its spare bits are set.
+    InvalidBitfield {
+        len: usize,
+        bytes: usize,
+    },
+}
+
+impl std::fmt::Display for ResumeDataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResumeDataError::InvalidBencode { reason } => write!(f, "Invalid bencode: {reason}"),
+            ResumeDataError::MissingField { field } => write!(f, "Missing field: {field}"),
+            ResumeDataError::InvalidField { field } => write!(f, "Invalid field: {field}"),
+            ResumeDataError::InvalidBitfield { len, bytes } => {
+                write!(f, "Invalid bitfield of {bytes} bytes for {len} pieces")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ResumeDataError {}
+
+/// The set of pieces of a torrent which were downloaded and verified.
+///
+/// The bitfield is stored as in the Bittorrent `bitfield` message
+/// ([BEP 3](https://www.bittorrent.org/beps/bep_0003.html)): the highest bit of the first byte
+/// is piece 0, and spare bits at the end of the last byte are cleared.
+///
+/// ```
+/// use hightorrent::Bitfield;
+///
+/// let mut bitfield = Bitfield::new(10);
+/// bitfield.set(0, true);
+/// bitfield.set(9, true);
+/// assert_eq!(bitfield.as_bytes(), &[0b1000_0000, 0b0100_0000]);
+/// assert_eq!(bitfield.count_ones(), 2);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Bitfield {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl Bitfield {
+    /// Creates a bitfield of `len` pieces, none of them downloaded.
+    pub fn new(len: usize) -> Bitfield {
+        Bitfield {
+            bytes: vec![0; (len + 7) / 8],
+            len,
+        }
+    }
+
+    /// Parses a bitfield of `len` pieces. Fails when the number of bytes does not match, or
+    /// when spare bits are set.
+    pub fn from_bytes(bytes: &[u8], len: usize) -> Result<Bitfield, ResumeDataError> {
+        let err = ResumeDataError::InvalidBitfield {
+            len,
+            bytes: bytes.len(),
+        };
+        if bytes.len() != (len + 7) / 8 {
+            return Err(err);
+        }
+        if len % 8 != 0 && bytes[bytes.len() - 1] & (0xff >> (len % 8)) != 0 {
+            return Err(err);
+        }
+        Ok(Bitfield {
+            bytes: bytes.to_vec(),
+            len,
+        })
+    }
+
+    /// Returns the number of pieces in the bitfield.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns whether a piece was downloaded. Pieces out of range are not.
+    pub fn get(&self, piece: usize) -> bool {
+        piece < self.len && self.bytes[piece / 8] & (0x80 >> (piece % 8)) != 0
+    }
+
+    /// Marks a piece as downloaded or not.
+    ///
+    /// Panics when the piece is out of range.
+    pub fn set(&mut self, piece: usize, have: bool) {
+        assert!(
+            piece < self.len,
+            "piece {piece} out of range for bitfield of {} pieces",
+            self.len
+        );
+        let mask = 0x80 >> (piece % 8);
+        if have {
+            self.bytes[piece / 8] |= mask;
+        } else {
+            self.bytes[piece / 8] &= !mask;
+        }
+    }
+
+    /// Returns the number of downloaded pieces.
+    pub fn count_ones(&self) -> usize {
+        self.bytes.iter().map(|b| b.count_ones() as usize).sum()
+    }
+
+    /// Returns whether all the pieces were downloaded.
+    pub fn is_complete(&self) -> bool {
+        self.count_ones() == self.len
+    }
+
+    /// Returns the indices of the downloaded pieces.
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).filter(|piece| self.get(*piece))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the number of bytes in the downloaded pieces, for a torrent of `total_size`
+    /// bytes split in pieces of `piece_length` bytes. The last piece may be shorter.
+    pub fn verified_bytes(&self, piece_length: u64, total_size: u64) -> u64 {
+        self.ones()
+            .map(|piece| {
+                let start = (piece as u64).saturating_mul(piece_length);
+                total_size.saturating_sub(start).min(piece_length)
+            })
+            .sum()
+    }
+}
+
+/// The download state of a torrent, which a client saves to resume the torrent without
+/// checking its files again.
+///
+/// ResumeData is identified by the [`TorrentID`](crate::id::TorrentID) of the torrent. It is
+/// serialized to a compact bencoded dict with [`ResumeData::to_bytes`](crate::resume::ResumeData::to_bytes),
+/// holding the raw [`Bitfield`](crate::resume::Bitfield) and one byte per file priority.
+/// With the `session` feature, it can be converted from libtorrent resume data with
+/// `ResumeData::from_fastresume`.
+///
+/// ```
+/// use hightorrent::{ResumeData, TorrentID};
+///
+/// let id = TorrentID::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap();
+/// let mut resume = ResumeData::new(id, 3);
+/// resume.pieces.set(1, true);
+/// resume.verified_bytes = 16384;
+/// resume.file_priorities = vec![4, 0];
+///
+/// let parsed = ResumeData::from_slice(&resume.to_bytes()).unwrap();
+/// assert_eq!(parsed, resume);
+/// assert_eq!(parsed.selected_files().unwrap().to_string(), "0");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResumeData {
+    pub id: TorrentID,
+    /// The pieces which were downloaded and verified
+    pub pieces: Bitfield,
+    /// The number of bytes in the verified pieces
+    pub verified_bytes: u64,
+    /// The priority of every file, by file index, using libtorrent levels: 0 means the file
+    /// is not downloaded, 1 is the lowest priority, 4 the default and 7 the highest. Files
+    /// without a priority have the default priority.
+    pub file_priorities: Vec<u8>,
+}
+
+impl ResumeData {
+    /// The priority of files which were not assigned a priority.
+    pub const DEFAULT_PRIORITY: u8 = 4;
+
+    /// Creates the ResumeData of a torrent with `piece_count` pieces, none of them downloaded.
+    pub fn new(id: TorrentID, piece_count: usize) -> ResumeData {
+        ResumeData {
+            id,
+            pieces: Bitfield::new(piece_count),
+            verified_bytes: 0,
+            file_priorities: Vec::new(),
+        }
+    }
+
+    /// Returns the priority of a file.
+    pub fn file_priority(&self, index: usize) -> u8 {
+        self.file_priorities
+            .get(index)
+            .copied()
+            .unwrap_or(ResumeData::DEFAULT_PRIORITY)
+    }
+
+    /// Returns the files with a non-zero priority, among the files with a priority, or None
+    /// when no such file is selected. Files without a priority are not included, because their
+    /// number is unknown.
+    pub fn selected_files(&self) -> Option<FileSelection> {
+        FileSelection::from_indices(
+            self.file_priorities
+                .iter()
+                .enumerate()
+                .filter(|(_, priority)| **priority > 0)
+                .map(|(index, _)| index),
+        )
+        .ok()
+    }
+
+    /// Serializes the ResumeData to a compact bencoded dict.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut dict: BTreeMap<ByteString, BencodeValue> = BTreeMap::new();
+        dict.insert(ByteString::from("id"), BencodeValue::from(self.id.as_str()));
+        dict.insert(
+            ByteString::from("pieces"),
+            BencodeValue::ByteStr(ByteString::from(self.pieces.as_bytes())),
+        );
+        dict.insert(
+            ByteString::from("piece count"),
+            BencodeValue::Int((self.pieces.len() as u64).into()),
+        );
+        dict.insert(
+            ByteString::from("verified"),
+            BencodeValue::Int(self.verified_bytes.into()),
+        );
+        dict.insert(
+            ByteString::from("priorities"),
+            BencodeValue::ByteStr(ByteString::from(self.file_priorities.clone())),
+        );
+        // Serializing a bencode Value cannot fail
+        bt_bencode::to_vec(&BencodeValue::Dict(dict)).unwrap()
+    }
+
+    /// Parses a ResumeData serialized by
+    /// [`ResumeData::to_bytes`](crate::resume::ResumeData::to_bytes).
+    pub fn from_slice(s: &[u8]) -> Result<ResumeData, ResumeDataError> {
+        // Values are decoded recursively, so deeply nested data would overflow the stack
+        let depth = nesting_depth(s);
+        if depth > ParseOptions::DEFAULT_MAX_NESTING_DEPTH {
+            return Err(ResumeDataError::InvalidBencode {
+                reason: format!("nested too deep: {depth} levels"),
+            });
+        }
+        let dict: BencodeValue =
+            bt_bencode::from_slice(s).map_err(|e| ResumeDataError::InvalidBencode {
+                reason: e.to_string(),
+            })?;
+
+        let field = |key: &str| {
+            dict.get(key).ok_or(ResumeDataError::MissingField {
+                field: key.to_string(),
+            })
+        };
+        let invalid = |key: &str| ResumeDataError::InvalidField {
+            field: key.to_string(),
+        };
+        let int = |key: &str| {
+            field(key).and_then(|v| {
+                bencode_i64(v)
+                    .and_then(|v| u64::try_from(v).ok())
+                    .ok_or_else(|| invalid(key))
+            })
+        };
+        let bytes =
+            |key: &str| field(key).and_then(|v| v.as_byte_str().ok_or_else(|| invalid(key)));
+
+        let id = field("id")?
+            .as_str()
+            .and_then(|id| TorrentID::new(id).ok())
+            .ok_or_else(|| invalid("id"))?;
+        let piece_count =
+            usize::try_from(int("piece count")?).map_err(|_| invalid("piece count"))?;
+
+        Ok(ResumeData {
+            id,
+            pieces: Bitfield::from_bytes(bytes("pieces")?, piece_count)?,
+            verified_bytes: int("verified")?,
+            file_priorities: bytes("priorities")?.to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bitfield_spare_bits() {
+        let mut bitfield = Bitfield::from_bytes(&[0xff, 0b1110_0000], 11).unwrap();
+        assert!(bitfield.is_complete());
+        assert!(!bitfield.get(11));
+        bitfield.set(3, false);
+        assert_eq!(bitfield.ones().count(), 10);
+        assert_eq!(bitfield.verified_bytes(10, 105), 95);
+        assert_eq!(bitfield.verified_bytes(i64::MAX as u64, u64::MAX), u64::MAX);
+
+        assert_eq!(
+            Bitfield::from_bytes(&[0xff, 0b1111_0000], 11),
+            Err(ResumeDataError::InvalidBitfield { len: 11, bytes: 2 })
+        );
+        assert!(Bitfield::from_bytes(&[0xff], 11).is_err());
+        assert!(Bitfield::from_bytes(&[], 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn resume_data_roundtrip() {
+        let id = TorrentID::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap();
+        let mut resume = ResumeData::new(id, 9);
+        resume.pieces.set(8, true);
+        resume.file_priorities = vec![0, 7, 1];
+        assert_eq!(resume.file_priority(1), 7);
+        assert_eq!(resume.file_priority(5), ResumeData::DEFAULT_PRIORITY);
+        assert_eq!(resume.selected_files().unwrap().to_string(), "1-2");
+
+        let bytes = resume.to_bytes();
+        assert_eq!(ResumeData::from_slice(&bytes).unwrap(), resume);
+
+        assert_eq!(
+            ResumeData::from_slice(b"d2:id4:teste").unwrap_err(),
+            ResumeDataError::InvalidField {
+                field: "id".to_string()
+            }
+        );
+        assert_eq!(
+            ResumeData::from_slice(b"de").unwrap_err(),
+            ResumeDataError::MissingField {
+                field: "id".to_string()
+            }
+        );
+    }
+}
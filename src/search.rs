@@ -0,0 +1,187 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{Torrent, TorrentFile, TorrentID, TorrentList};
+
+/// Weight of a token found in a torrent name.
+const NAME_WEIGHT: u32 = 3;
+/// Weight of a token found in a tag or category.
+const TAG_WEIGHT: u32 = 2;
+/// Weight of a token found in a file path.
+const FILE_WEIGHT: u32 = 1;
+
+/// An in-memory inverted index, to search torrents by words of their name, tags, category and
+/// optionally file paths.
+///
+/// Text is split into tokens on any character which is not alphanumeric, and tokens are compared
+/// ignoring case. Every word of the query must match a token, either exactly or as its prefix.
+/// Results are ranked by relevance: name matches rank above tag matches, which rank above file
+/// path matches, and exact matches rank above prefix matches. Torrents with the same score are
+/// returned in the order they were indexed.
+///
+/// ```
+/// use hightorrent::{InfoHash, Torrent, TorrentIndex, TorrentList};
+///
+/// let torrent = Torrent::builder()
+///     .hash(InfoHash::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap())
+///     .name("Goldman, Emma - Essential Works of Anarchism")
+///     .build()
+///     .unwrap();
+/// let index = TorrentIndex::build(&TorrentList::from_vec(vec![torrent.clone()]));
+///
+/// assert_eq!(index.search("emma anarch"), vec![torrent.id]);
+/// assert!(index.search("emma bakunin").is_empty());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TorrentIndex {
+    /// For every token, the torrents containing it with the weight of the best field
+    tokens: BTreeMap<String, HashMap<TorrentID, u32>>,
+    /// The position of every torrent in indexing order, to break ties
+    order: HashMap<TorrentID, usize>,
+}
+
+impl TorrentIndex {
+    pub fn new() -> TorrentIndex {
+        TorrentIndex::default()
+    }
+
+    /// Indexes the names, tags and categories of the torrents in a
+    /// [`TorrentList`](crate::list::TorrentList).
+    pub fn build(list: &TorrentList) -> TorrentIndex {
+        let mut index = TorrentIndex::new();
+        for torrent in list {
+            index.insert(torrent);
+        }
+        index
+    }
+
+    /// Indexes the name, tags and category of a torrent.
+    pub fn insert(&mut self, torrent: &Torrent) {
+        self.add(torrent.id, &torrent.name, NAME_WEIGHT);
+        for tag in &torrent.tags {
+            self.add(torrent.id, tag, TAG_WEIGHT);
+        }
+        if let Some(category) = &torrent.category {
+            self.add(torrent.id, category.as_str(), TAG_WEIGHT);
+        }
+    }
+
+    /// Indexes the file paths of a [`TorrentFile`](crate::torrent_file::TorrentFile), under its
+    /// [`TorrentID`](crate::id::TorrentID). Listing files requires the torrent metadata, so
+    /// they are indexed separately from the [`Torrent`](crate::torrent::Torrent).
+    pub fn insert_files(&mut self, torrent: &TorrentFile) {
+        let id = torrent.id();
+        for file in torrent.files() {
+            self.add(id, &file.path.to_string(), FILE_WEIGHT);
+        }
+    }
+
+    fn add(&mut self, id: TorrentID, text: &str, weight: u32) {
+        let position = self.order.len();
+        self.order.entry(id).or_insert(position);
+        for token in tokenize(text) {
+            let best = self.tokens.entry(token).or_default().entry(id).or_insert(0);
+            *best = (*best).max(weight);
+        }
+    }
+
+    /// Returns the torrents matching every word of the query, the most relevant first. An empty
+    /// query matches nothing.
+    pub fn search(&self, query: &str) -> Vec<TorrentID> {
+        let mut scores: Option<HashMap<TorrentID, u32>> = None;
+        for word in tokenize(query) {
+            let mut matches: HashMap<TorrentID, u32> = HashMap::new();
+            let tokens = self
+                .tokens
+                .range(word.clone()..)
+                .take_while(|(token, _)| token.starts_with(&word));
+            for (token, postings) in tokens {
+                let exact = *token == word;
+                for (id, weight) in postings {
+                    let score = if exact { weight * 2 } else { *weight };
+                    let best = matches.entry(*id).or_insert(0);
+                    *best = (*best).max(score);
+                }
+            }
+
+            scores = Some(match scores {
+                None => matches,
+                Some(scores) => scores
+                    .into_iter()
+                    .filter_map(|(id, score)| matches.get(&id).map(|m| (id, score + m)))
+                    .collect(),
+            });
+        }
+
+        let mut results: Vec<(TorrentID, u32)> = scores.unwrap_or_default().into_iter().collect();
+        results.sort_by(|(a, a_score), (b, b_score)| {
+            b_score
+                .cmp(a_score)
+                .then_with(|| self.order[a].cmp(&self.order[b]))
+        });
+        results.into_iter().map(|(id, _)| id).collect()
+    }
+}
+
+/// Splits text into lowercase alphanumeric tokens.
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Category, InfoHash};
+
+    fn torrent(hash: &str, name: &str, tags: &[&str]) -> Torrent {
+        Torrent::builder()
+            .hash(InfoHash::new(hash).unwrap())
+            .name(name)
+            .tags(tags.iter().map(|t| t.to_string()).collect())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn ranks_matches() {
+        let debian = torrent(
+            "c811b41641a09d192b8ed81b14064fff55d85ce3",
+            "debian-12.5.0-amd64-netinst.iso",
+            &["linux"],
+        );
+        let mut ubuntu = torrent(
+            "631a31dd0a46257d5078c0dee4e66e26f73e42ac",
+            "ubuntu-24.04-desktop-amd64.iso",
+            &["Debian-based"],
+        );
+        ubuntu.category = Some(Category::new("linux/distros").unwrap());
+        let index =
+            TorrentIndex::build(&TorrentList::from_vec(vec![ubuntu.clone(), debian.clone()]));
+
+        assert_eq!(index.search("DEBIAN"), vec![debian.id, ubuntu.id]);
+        assert_eq!(index.search("deb amd64"), vec![debian.id, ubuntu.id]);
+        assert_eq!(index.search("linux"), vec![ubuntu.id, debian.id]);
+        assert_eq!(index.search("distros"), vec![ubuntu.id]);
+        assert!(index.search("debian desktop netinst").is_empty());
+        assert!(index.search("").is_empty());
+        assert!(index.search(" - ").is_empty());
+    }
+
+    #[test]
+    fn searches_file_paths() {
+        let slice = std::fs::read("tests/bittorrent-v2-hybrid-test.torrent").unwrap();
+        let torrent_file = TorrentFile::from_slice(&slice).unwrap();
+        let file = torrent_file.files()[0]
+            .path
+            .components()
+            .last()
+            .unwrap()
+            .clone();
+
+        let mut index = TorrentIndex::new();
+        assert!(index.search(&file).is_empty());
+        index.insert_files(&torrent_file);
+        assert_eq!(index.search(&file), vec![torrent_file.id()]);
+    }
+}
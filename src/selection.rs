@@ -0,0 +1,534 @@
+use std::str::FromStr;
+
+/// Error occurred during parsing a [`FileSelection`](crate::selection::FileSelection).
+#[derive(Clone, Debug, PartialEq)]
+pub enum FileSelectionError {
+    /// The selection did not contain any file index.
+    Empty,
+    /// A file index was not a valid unsigned integer.
+    InvalidIndex { index: String },
+    /// A range of file indices was in decreasing order (eg. `6-4`).
+    InvalidRange { start: usize, end: usize },
+}
+
+impl std::fmt::Display for FileSelectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileSelectionError::Empty => write!(f, "Empty file selection"),
+            FileSelectionError::InvalidIndex { index } => {
+                write!(f, "Invalid file index: {index}")
+            }
+            FileSelectionError::InvalidRange { start, end } => {
+                write!(f, "Invalid file range (start is after end): {start}-{end}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FileSelectionError {}
+
+/// A single item of a [`FileSelection`](crate::selection::FileSelection).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FileSelectionItem {
+    /// A single file index
+    Index(usize),
+    /// An inclusive range of file indices
+    Range { start: usize, end: usize },
+}
+
+impl FileSelectionItem {
+    /// Returns true when the file index is selected by this item.
+    pub fn contains(&self, index: usize) -> bool {
+        match self {
+            FileSelectionItem::Index(i) => *i == index,
+            FileSelectionItem::Range { start, end } => (*start..=*end).contains(&index),
+        }
+    }
+}
+
+impl std::fmt::Display for FileSelectionItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileSelectionItem::Index(i) => write!(f, "{i}"),
+            FileSelectionItem::Range { start, end } => write!(f, "{start}-{end}"),
+        }
+    }
+}
+
+/// A [BEP-0053](https://www.bittorrent.org/beps/bep_0053.html) file selection, found in the
+/// `so` (select only) magnet parameter.
+///
+/// The selection is a comma-separated list of zero-based file indices and inclusive ranges
+/// of file indices, such as `0,2,4-6`.
+///
+/// ```
+/// use hightorrent::FileSelection;
+///
+/// let selection: FileSelection = "0,2,4-6".parse().unwrap();
+/// assert!(selection.contains(5));
+/// assert!(!selection.contains(3));
+/// assert_eq!(selection.indices().collect::<Vec<_>>(), vec![0, 2, 4, 5, 6]);
+/// assert_eq!(selection.to_string(), "0,2,4-6");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FileSelection(Vec<FileSelectionItem>);
+
+impl FileSelection {
+    /// Parses a file selection string, such as `0,2,4-6`.
+    pub fn new(s: &str) -> Result<FileSelection, FileSelectionError> {
+        let items = s
+            .split(',')
+            .map(parse_item)
+            .collect::<Result<Vec<FileSelectionItem>, FileSelectionError>>()?;
+
+        FileSelection::from_items(items)
+    }
+
+    /// Builds a file selection from its items. Fails if there is no item, or if a range
+    /// is in decreasing order.
+    pub fn from_items(items: Vec<FileSelectionItem>) -> Result<FileSelection, FileSelectionError> {
+        if items.is_empty() {
+            return Err(FileSelectionError::Empty);
+        }
+
+        for item in &items {
+            if let FileSelectionItem::Range { start, end } = *item {
+                if start > end {
+                    return Err(FileSelectionError::InvalidRange { start, end });
+                }
+            }
+        }
+
+        Ok(FileSelection(items))
+    }
+
+    /// Builds a file selection from file indices, merging consecutive indices into ranges.
+    /// Fails if there is no index.
+    ///
+    /// ```
+    /// use hightorrent::FileSelection;
+    ///
+    /// let selection = FileSelection::from_indices([6, 0, 4, 5, 2]).unwrap();
+    /// assert_eq!(selection.to_string(), "0,2,4-6");
+    /// ```
+    pub fn from_indices<I: IntoIterator<Item = usize>>(
+        indices: I,
+    ) -> Result<FileSelection, FileSelectionError> {
+        let mut indices: Vec<usize> = indices.into_iter().collect();
+        indices.sort_unstable();
+        indices.dedup();
+
+        let mut items = Vec::new();
+        let mut iter = indices.into_iter().peekable();
+        while let Some(start) = iter.next() {
+            let mut end = start;
+            while iter.peek() == Some(&(end + 1)) {
+                end += 1;
+                iter.next();
+            }
+            items.push(if start == end {
+                FileSelectionItem::Index(start)
+            } else {
+                FileSelectionItem::Range { start, end }
+            });
+        }
+
+        FileSelection::from_items(items)
+    }
+
+    /// Returns the items of the selection, in their original order.
+    pub fn items(&self) -> &[FileSelectionItem] {
+        &self.0
+    }
+
+    /// Returns true when the file index is selected.
+    pub fn contains(&self, index: usize) -> bool {
+        self.0.iter().any(|item| item.contains(index))
+    }
+
+    /// Returns all the selected file indices, in increasing order and without duplicates.
+    pub fn indices(&self) -> impl Iterator<Item = usize> {
+        let mut indices: Vec<usize> = self
+            .0
+            .iter()
+            .flat_map(|item| match *item {
+                FileSelectionItem::Index(i) => i..=i,
+                FileSelectionItem::Range { start, end } => start..=end,
+            })
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        indices.into_iter()
+    }
+}
+
+fn parse_index(s: &str) -> Result<usize, FileSelectionError> {
+    s.trim()
+        .parse()
+        .map_err(|_| FileSelectionError::InvalidIndex {
+            index: s.to_string(),
+        })
+}
+
+fn parse_item(s: &str) -> Result<FileSelectionItem, FileSelectionError> {
+    match s.split_once('-') {
+        Some((start, end)) => Ok(FileSelectionItem::Range {
+            start: parse_index(start)?,
+            end: parse_index(end)?,
+        }),
+        None => Ok(FileSelectionItem::Index(parse_index(s)?)),
+    }
+}
+
+impl std::fmt::Display for FileSelection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, item) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{item}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for FileSelection {
+    type Err = FileSelectionError;
+
+    fn from_str(s: &str) -> Result<FileSelection, Self::Err> {
+        FileSelection::new(s)
+    }
+}
+
+/// Error occurred during parsing a [`Pattern`](crate::selection::Pattern).
+#[cfg(feature = "regex")]
+#[derive(Clone, Debug, PartialEq)]
+pub enum PatternError {
+    InvalidRegex { pattern: String, reason: String },
+}
+
+#[cfg(feature = "regex")]
+impl std::fmt::Display for PatternError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatternError::InvalidRegex { pattern, reason } => {
+                write!(f, "Invalid regex {pattern:?}: {reason}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "regex")]
+impl std::error::Error for PatternError {}
+
+/// A pattern matching the paths of the files in a torrent, to select them with
+/// [`TorrentFile::select_files`](crate::torrent_file::TorrentFile::select_files).
+///
+/// Paths are the [`SafePath`](crate::content::SafePath)s of the files, with `/` separators,
+/// starting with the torrent name for multi-file torrents. Globs support:
+///
+/// - `?` for any character except `/`
+/// - `*` for any number of characters except `/`
+/// - `**` for any number of characters, and `**/` for any number of directories
+/// - `[abc]`, `[a-z]` and `[!abc]` for a character in (or not in) a set
+///
+/// Like in `.gitignore` files, a glob without `/` is matched against the file name, and a glob
+/// with `/` is matched against the whole path. Matching is case-sensitive. With the `regex`
+/// feature, regular expressions are matched against the whole path, and can match part of it.
+///
+/// ```
+/// use hightorrent::Pattern;
+///
+/// assert!(Pattern::glob("*.mkv").matches("Show/Season 1/E01.mkv"));
+/// assert!(Pattern::glob("Show/**/E0?.mkv").matches("Show/Season 1/E01.mkv"));
+/// assert!(!Pattern::glob("Show/*.mkv").matches("Show/Season 1/E01.mkv"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Pattern(PatternKind);
+
+#[derive(Clone, Debug)]
+enum PatternKind {
+    Glob {
+        tokens: Vec<GlobToken>,
+        file_name_only: bool,
+    },
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum GlobToken {
+    Literal(char),
+    /// `?`
+    AnyChar,
+    /// `*`
+    AnyChars,
+    /// `**`
+    AnyPath,
+    /// `**/`
+    AnyDirs,
+    /// `[...]`, with inclusive ranges of characters
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+impl Pattern {
+    /// Parses a glob. Any string is a valid glob: an unclosed `[` is a literal character.
+    pub fn glob(glob: &str) -> Pattern {
+        let chars: Vec<char> = glob.chars().collect();
+        let mut tokens = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                '*' if chars.get(i + 1) == Some(&'*') => {
+                    if chars.get(i + 2) == Some(&'/') {
+                        tokens.push(GlobToken::AnyDirs);
+                        i += 3;
+                    } else {
+                        tokens.push(GlobToken::AnyPath);
+                        i += 2;
+                    }
+                    continue;
+                }
+                '*' => tokens.push(GlobToken::AnyChars),
+                '?' => tokens.push(GlobToken::AnyChar),
+                '[' => {
+                    if let Some((class, len)) = parse_class(&chars[i..]) {
+                        tokens.push(class);
+                        i += len;
+                        continue;
+                    }
+                    tokens.push(GlobToken::Literal('['));
+                }
+                c => tokens.push(GlobToken::Literal(c)),
+            }
+            i += 1;
+        }
+
+        Pattern(PatternKind::Glob {
+            tokens,
+            file_name_only: !glob.contains('/'),
+        })
+    }
+
+    /// Parses a regular expression.
+    #[cfg(feature = "regex")]
+    pub fn regex(regex: &str) -> Result<Pattern, PatternError> {
+        regex::Regex::new(regex)
+            .map(|regex| Pattern(PatternKind::Regex(regex)))
+            .map_err(|e| PatternError::InvalidRegex {
+                pattern: regex.to_string(),
+                reason: e.to_string(),
+            })
+    }
+
+    /// Returns true when the pattern matches a file path.
+    pub fn matches(&self, path: &str) -> bool {
+        match &self.0 {
+            PatternKind::Glob {
+                tokens,
+                file_name_only,
+            } => {
+                let path = if *file_name_only {
+                    path.rsplit('/').next().unwrap_or(path)
+                } else {
+                    path
+                };
+                let text: Vec<char> = path.chars().collect();
+                let mut memo = vec![None; (tokens.len() + 1) * (text.len() + 1)];
+                glob_matches(tokens, &text, 0, 0, &mut memo)
+            }
+            #[cfg(feature = "regex")]
+            PatternKind::Regex(regex) => regex.is_match(path),
+        }
+    }
+}
+
+/// Parses a `[...]` character class at the start of the chars, returning the token and the
+/// number of chars it spans. Returns None when the class is not closed.
+fn parse_class(chars: &[char]) -> Option<(GlobToken, usize)> {
+    let mut i = 1;
+    let negated = chars.get(i) == Some(&'!');
+    if negated {
+        i += 1;
+    }
+
+    let mut ranges = Vec::new();
+    // A `]` right after the opening bracket is part of the class
+    let start = i;
+    loop {
+        let c = *chars.get(i)?;
+        if c == ']' && i > start {
+            return Some((GlobToken::Class { negated, ranges }, i + 1));
+        }
+        match (chars.get(i + 1), chars.get(i + 2)) {
+            (Some('-'), Some(end)) if *end != ']' => {
+                ranges.push((c, *end));
+                i += 3;
+            }
+            _ => {
+                ranges.push((c, c));
+                i += 1;
+            }
+        }
+    }
+}
+
+/// Matches the tokens from `t` against the text from `c`, memoizing results for each position
+/// so that consecutive wildcards do not take exponential time.
+fn glob_matches(
+    tokens: &[GlobToken],
+    text: &[char],
+    t: usize,
+    c: usize,
+    memo: &mut Vec<Option<bool>>,
+) -> bool {
+    let key = t * (text.len() + 1) + c;
+    if let Some(matched) = memo[key] {
+        return matched;
+    }
+
+    let next = text.get(c).copied();
+    let matched = match tokens.get(t) {
+        None => next.is_none(),
+        Some(GlobToken::Literal(l)) => {
+            next == Some(*l) && glob_matches(tokens, text, t + 1, c + 1, memo)
+        }
+        Some(GlobToken::AnyChar) => {
+            next.map_or(false, |n| n != '/') && glob_matches(tokens, text, t + 1, c + 1, memo)
+        }
+        Some(GlobToken::Class { negated, ranges }) => {
+            next.map_or(false, |n| {
+                n != '/' && ranges.iter().any(|(s, e)| (*s..=*e).contains(&n)) != *negated
+            }) && glob_matches(tokens, text, t + 1, c + 1, memo)
+        }
+        Some(GlobToken::AnyChars) => {
+            glob_matches(tokens, text, t + 1, c, memo)
+                || (next.map_or(false, |n| n != '/') && glob_matches(tokens, text, t, c + 1, memo))
+        }
+        Some(GlobToken::AnyPath) => {
+            glob_matches(tokens, text, t + 1, c, memo)
+                || (next.is_some() && glob_matches(tokens, text, t, c + 1, memo))
+        }
+        Some(GlobToken::AnyDirs) => {
+            glob_matches(tokens, text, t + 1, c, memo)
+                || (c..text.len())
+                    .any(|i| text[i] == '/' && glob_matches(tokens, text, t + 1, i + 1, memo))
+        }
+    };
+
+    memo[key] = Some(matched);
+    matched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_selection() {
+        let selection = FileSelection::new("0,2,4-6,5").unwrap();
+        assert_eq!(
+            selection.items(),
+            &[
+                FileSelectionItem::Index(0),
+                FileSelectionItem::Index(2),
+                FileSelectionItem::Range { start: 4, end: 6 },
+                FileSelectionItem::Index(5),
+            ]
+        );
+        assert_eq!(selection.indices().collect::<Vec<_>>(), vec![0, 2, 4, 5, 6]);
+        assert_eq!(selection.to_string(), "0,2,4-6,5");
+        assert!(!selection.contains(7));
+    }
+
+    #[test]
+    fn builds_selection_from_indices() {
+        assert_eq!(
+            FileSelection::from_indices([3, 1, 2, 2, 7])
+                .unwrap()
+                .items(),
+            &[
+                FileSelectionItem::Range { start: 1, end: 3 },
+                FileSelectionItem::Index(7),
+            ]
+        );
+        assert_eq!(
+            FileSelection::from_indices([]).unwrap_err(),
+            FileSelectionError::Empty
+        );
+    }
+
+    #[test]
+    fn matches_globs() {
+        let path = "Show/Season 1/E01.mkv";
+        for glob in [
+            "*.mkv",
+            "E0[0-9].mkv",
+            "E0[!2-9].*",
+            "Show/Season ?/*",
+            "Show/**",
+            "**/E01.mkv",
+            "Show/**/E01.mkv",
+            "Show/Season 1/**/E01.mkv",
+        ] {
+            assert!(Pattern::glob(glob).matches(path), "{glob}");
+        }
+        for glob in [
+            "*.MKV",
+            "E0[2-9].mkv",
+            "Show/*.mkv",
+            "Show/*",
+            "Season 1/*",
+            "[Show/**",
+        ] {
+            assert!(!Pattern::glob(glob).matches(path), "{glob}");
+        }
+        assert!(Pattern::glob("[Show").matches("[Show"));
+        assert!(Pattern::glob("a[]]b").matches("a]b"));
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn matches_regexes() {
+        let pattern = Pattern::regex(r"(?i)season \d+/.*\.MKV$").unwrap();
+        assert!(pattern.matches("Show/Season 1/E01.mkv"));
+        assert!(!pattern.matches("Show/Extras/E01.mkv"));
+        assert!(matches!(
+            Pattern::regex("(").unwrap_err(),
+            PatternError::InvalidRegex { .. }
+        ));
+    }
+
+    #[test]
+    fn fails_parse_invalid_selection() {
+        assert_eq!(
+            FileSelection::new("").unwrap_err(),
+            FileSelectionError::InvalidIndex {
+                index: "".to_string()
+            }
+        );
+        assert_eq!(
+            FileSelection::new("1,a").unwrap_err(),
+            FileSelectionError::InvalidIndex {
+                index: "a".to_string()
+            }
+        );
+        assert_eq!(
+            FileSelection::new("-1").unwrap_err(),
+            FileSelectionError::InvalidIndex {
+                index: "".to_string()
+            }
+        );
+        assert_eq!(
+            FileSelection::new("6-4").unwrap_err(),
+            FileSelectionError::InvalidRange { start: 6, end: 4 }
+        );
+        assert_eq!(
+            FileSelection::from_items(Vec::new()).unwrap_err(),
+            FileSelectionError::Empty
+        );
+    }
+}
@@ -0,0 +1,799 @@
+use bt_bencode::Value as BencodeValue;
+use rustc_hex::ToHex;
+
+use crate::bencode::nesting_depth;
+use crate::torrent::tracker_sources;
+use crate::torrent_file::bencode_i64;
+use crate::{
+    Category, InfoHash, InfoHashError, ParseOptions, Progress, ResumeData, Timestamp, ToTorrent,
+    Torrent, TorrentFile, TorrentFileError, TorrentLimits, Tracker, TrackerTiers,
+};
+
+/// Error occurred during parsing session state from another torrent client.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SessionError {
+    // TODO: bt_bencode::Error is not PartialEq so we store error as String
+    InvalidBencode { reason: String },
+    // serde_json::Error is not PartialEq either
+    InvalidJson { reason: String },
+    MissingField { field: String },
+    InvalidHash { source: InfoHashError },
+    InvalidTorrent { source: TorrentFileError },
+}
+
+impl std::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionError::InvalidBencode { reason } => write!(f, "Invalid bencode: {reason}"),
+            SessionError::InvalidJson { reason } => write!(f, "Invalid JSON: {reason}"),
+            SessionError::MissingField { field } => write!(f, "Missing field: {field}"),
+            SessionError::InvalidHash { source } => write!(f, "Invalid hash: {source}"),
+            SessionError::InvalidTorrent { source } => write!(f, "Invalid torrent: {source}"),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SessionError::InvalidHash { source } => Some(source),
+            SessionError::InvalidTorrent { source } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<InfoHashError> for SessionError {
+    fn from(e: InfoHashError) -> SessionError {
+        SessionError::InvalidHash { source: e }
+    }
+}
+
+impl From<TorrentFileError> for SessionError {
+    fn from(e: TorrentFileError) -> SessionError {
+        SessionError::InvalidTorrent { source: e }
+    }
+}
+
+/// A torrent loaded from libtorrent resume data (`.fastresume` file), as written by
+/// libtorrent-based clients such as qBittorrent or Deluge.
+///
+/// The torrent name and size can only be found when the info dict is embedded in the resume
+/// data (libtorrent >= 2.0), or when qBittorrent stored a custom name. Otherwise, they will
+/// be respectively empty and zero, and the progress is expressed in pieces instead of bytes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LibtorrentResume {
+    pub hash: InfoHash,
+    pub name: String,
+    pub save_path: String,
+    /// Unix timestamp (in seconds) when the torrent was added
+    pub added_time: i64,
+    /// Unix timestamp (in seconds) when the torrent was completed, or 0
+    pub completed_time: i64,
+    /// Verified bytes, computed from the pieces bitfield
+    pub progress: Progress,
+    pub size: i64,
+    pub total_uploaded: Option<u64>,
+    pub total_downloaded: Option<u64>,
+    pub paused: bool,
+    /// Tags set by qBittorrent, if any
+    pub tags: Vec<String>,
+    /// Category set by qBittorrent, if any
+    pub category: Option<Category>,
+    pub trackers: TrackerTiers,
+}
+
+impl LibtorrentResume {
+    /// Parses libtorrent resume data. Will fail if the data is not valid bencode, or if no
+    /// valid infohash is found.
+    pub fn from_slice(s: &[u8]) -> Result<LibtorrentResume, SessionError> {
+        let resume = decode_resume(s)?;
+        let hash = resume_hash(&resume)?;
+
+        let string = |key: &str| resume.get(key).and_then(|v| v.as_str()).map(String::from);
+        let int = |key: &str| resume.get(key).and_then(bencode_i64).unwrap_or(0);
+        let counter = |key: &str| {
+            resume
+                .get(key)
+                .and_then(bencode_i64)
+                .and_then(|v| u64::try_from(v).ok())
+        };
+
+        let info = resume.get("info");
+        let name = string("qBt-name")
+            .filter(|name| !name.is_empty())
+            .or_else(|| string("name"))
+            .or_else(|| {
+                info.and_then(|i| i.get("name"))
+                    .and_then(|n| n.as_str())
+                    .map(String::from)
+            })
+            .unwrap_or_default();
+        let save_path = string("qBt-savePath")
+            .filter(|path| !path.is_empty())
+            .or_else(|| string("save_path"))
+            .unwrap_or_default();
+
+        // An overflowing size is as unknown as a missing one
+        let size = info.and_then(info_size).unwrap_or(0);
+        let progress = resume
+            .get("pieces")
+            .and_then(|p| p.as_byte_str())
+            .map(|pieces| pieces_progress(pieces, info, size))
+            .unwrap_or_default();
+
+        let tags = resume
+            .get("qBt-tags")
+            .and_then(|t| t.as_list())
+            .map(|list| {
+                list.iter()
+                    .filter_map(|t| t.as_str())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let category = resume
+            .get("qBt-category")
+            .and_then(|c| c.as_str())
+            .and_then(|c| Category::new(c).ok());
+
+        let mut trackers = TrackerTiers::new();
+        for tier in resume
+            .get("trackers")
+            .and_then(|t| t.as_list())
+            .into_iter()
+            .flatten()
+            .filter_map(|tier| tier.as_list())
+        {
+            trackers.push_tier(
+                tier.iter()
+                    .filter_map(|url| url.as_str())
+                    .filter_map(|url| Tracker::new(url).ok())
+                    .collect(),
+            );
+        }
+
+        Ok(LibtorrentResume {
+            hash,
+            name,
+            save_path,
+            added_time: int("added_time"),
+            completed_time: int("completed_time"),
+            progress,
+            size,
+            total_uploaded: counter("total_uploaded"),
+            total_downloaded: counter("total_downloaded"),
+            paused: int("paused") != 0,
+            tags,
+            category,
+            trackers,
+        })
+    }
+}
+
+/// Decodes libtorrent resume data.
+fn decode_resume(s: &[u8]) -> Result<BencodeValue, SessionError> {
+    // Values are decoded recursively, so deeply nested data would overflow the stack
+    let depth = nesting_depth(s);
+    if depth > ParseOptions::DEFAULT_MAX_NESTING_DEPTH {
+        return Err(SessionError::InvalidBencode {
+            reason: format!("nested too deep: {depth} levels"),
+        });
+    }
+    bt_bencode::from_slice(s).map_err(|e| SessionError::InvalidBencode {
+        reason: e.to_string(),
+    })
+}
+
+/// Finds the infohash of libtorrent resume data, from its raw v1 and v2 digests.
+fn resume_hash(resume: &BencodeValue) -> Result<InfoHash, SessionError> {
+    let raw_hash = |key: &str, len: usize| {
+        resume
+            .get(key)
+            .and_then(|v| v.as_byte_str())
+            .filter(|v| v.len() == len && v.iter().any(|b| *b != 0))
+            .map(|v| InfoHash::new(&v.to_hex::<String>()))
+            .transpose()
+    };
+    match (raw_hash("info-hash", 20)?, raw_hash("info-hash2", 32)?) {
+        (Some(v1), Some(v2)) => Ok(v1.hybrid(&v2)?),
+        (Some(hash), None) | (None, Some(hash)) => Ok(hash),
+        (None, None) => Err(SessionError::MissingField {
+            field: "info-hash".to_string(),
+        }),
+    }
+}
+
+impl ResumeData {
+    /// Converts libtorrent resume data (`.fastresume` file) to a
+    /// [`ResumeData`](crate::resume::ResumeData), with its pieces bitfield and file priorities.
+    ///
+    /// The verified bytes are computed from the size of the pieces, which is only known when
+    /// the info dict is embedded in the resume data (libtorrent >= 2.0). Otherwise, they are
+    /// zero.
+    pub fn from_fastresume(s: &[u8]) -> Result<ResumeData, SessionError> {
+        let resume = decode_resume(s)?;
+        let hash = resume_hash(&resume)?;
+
+        // One byte per piece, with the lowest bit set when the piece is downloaded
+        let pieces = resume
+            .get("pieces")
+            .and_then(|p| p.as_byte_str())
+            .map(|p| p.as_slice())
+            .unwrap_or_default();
+        let mut data = ResumeData::new(hash.id(), pieces.len());
+        for (piece, state) in pieces.iter().enumerate() {
+            data.pieces.set(piece, state & 1 == 1);
+        }
+
+        if let Some(info) = resume.get("info") {
+            let piece_length = info.get("piece length").and_then(bencode_i64).unwrap_or(0);
+            data.verified_bytes = data.pieces.verified_bytes(
+                piece_length.max(0) as u64,
+                info_size(info).unwrap_or(0).max(0) as u64,
+            );
+        }
+
+        data.file_priorities = resume
+            .get("file_priority")
+            .and_then(|p| p.as_list())
+            .map(|list| {
+                list.iter()
+                    .map(|p| {
+                        bencode_i64(p)
+                            .map(|p| p.clamp(0, 7) as u8)
+                            .unwrap_or(ResumeData::DEFAULT_PRIORITY)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(data)
+    }
+}
+
+/// Computes the total size of the files described by an info dict, or `None` when it
+/// overflows.
+fn info_size(info: &BencodeValue) -> Option<i64> {
+    if let Some(length) = info.get("length").and_then(bencode_i64) {
+        return Some(length);
+    }
+
+    if let Some(files) = info.get("files").and_then(|f| f.as_list()) {
+        return files
+            .iter()
+            .filter_map(|f| f.get("length").and_then(bencode_i64))
+            .try_fold(0i64, |total, length| total.checked_add(length));
+    }
+
+    // Bittorrent v2 file tree: files are dicts with an empty key holding the file attributes
+    fn tree_size(node: &BencodeValue) -> Option<i64> {
+        node.as_dict()
+            .map(|dict| {
+                dict.iter().try_fold(0i64, |total, (key, child)| {
+                    let size = if key.is_empty() {
+                        child.get("length").and_then(bencode_i64).unwrap_or(0)
+                    } else {
+                        tree_size(child)?
+                    };
+                    total.checked_add(size)
+                })
+            })
+            .unwrap_or(Some(0))
+    }
+    info.get("file tree").map(tree_size).unwrap_or(Some(0))
+}
+
+/// Computes the verified bytes from a resume pieces bitfield, which has one byte per piece
+/// with the lowest bit set when the piece is downloaded.
+///
+/// When the piece length or size is unknown, or does not match the number of pieces, the
+/// progress is expressed in pieces. It is also the case for v2-only torrents, whose pieces
+/// are aligned to file boundaries.
+fn pieces_progress(pieces: &[u8], info: Option<&BencodeValue>, size: i64) -> Progress {
+    let have = |piece: &u8| piece & 1 == 1;
+    let by_pieces = Progress::new(
+        pieces.iter().filter(|p| have(p)).count() as u64,
+        pieces.len() as u64,
+    );
+
+    let piece_length = info
+        .filter(|i| i.get("pieces").is_some())
+        .and_then(|i| i.get("piece length"))
+        .and_then(bencode_i64)
+        .and_then(|l| u64::try_from(l).ok())
+        .filter(|l| *l > 0);
+    let (piece_length, size) = match (piece_length, u64::try_from(size)) {
+        (Some(piece_length), Ok(size)) if size > 0 => (piece_length, size),
+        _ => return by_pieces,
+    };
+    let piece_count = size / piece_length + u64::from(size % piece_length != 0);
+    if piece_count != pieces.len() as u64 {
+        return by_pieces;
+    }
+
+    // Only the last piece may be shorter than the piece length
+    let done = pieces
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| have(p))
+        .map(|(index, _)| piece_length.min(size - index as u64 * piece_length))
+        .sum();
+    Progress::new(done, size)
+}
+
+fn torrent_state(paused: bool, progress: &Progress) -> String {
+    if paused {
+        "paused".to_string()
+    } else if progress.is_complete() {
+        "seeding".to_string()
+    } else {
+        "downloading".to_string()
+    }
+}
+
+impl ToTorrent for LibtorrentResume {
+    fn to_torrent(&self) -> Torrent {
+        Torrent {
+            name: self.name.clone(),
+            path: self.save_path.clone(),
+            date_start: Timestamp::from_secs_opt(self.added_time),
+            date_end: Timestamp::from_secs_opt(self.completed_time),
+            progress: self.progress,
+            size: self.size,
+            uploaded: self.total_uploaded,
+            downloaded: self.total_downloaded,
+            state: torrent_state(self.paused, &self.progress),
+            tags: self.tags.clone(),
+            category: self.category.clone(),
+            sources: tracker_sources(self.trackers.iter().cloned()),
+            limits: TorrentLimits::default(),
+            id: self.hash.id(),
+            hash: self.hash.clone(),
+        }
+    }
+}
+
+/// A torrent loaded from an [rqbit](https://github.com/ikatson/rqbit) session file.
+///
+/// rqbit does not persist download progress in its session file. The progress of converted
+/// [`Torrent`](crate::torrent::Torrent)s is a placeholder left to
+/// [`Progress::default`](crate::progress::Progress::default) (unknown), and their state is
+/// only ever `paused` or `downloading`, depending on whether the torrent is paused, even if
+/// rqbit is actually seeding it.
+#[derive(Clone, Debug)]
+pub struct RqbitTorrent {
+    pub hash: InfoHash,
+    pub output_folder: String,
+    pub paused: bool,
+    pub trackers: Vec<Tracker>,
+    /// The torrent file, when it was persisted in the session
+    pub torrent_file: Option<TorrentFile>,
+}
+
+/// An [rqbit](https://github.com/ikatson/rqbit) session file (`session.json`).
+#[derive(Clone, Debug)]
+pub struct RqbitSession {
+    pub torrents: Vec<RqbitTorrent>,
+}
+
+#[derive(Deserialize)]
+struct RawRqbitSession {
+    #[serde(default)]
+    torrents: std::collections::BTreeMap<String, RawRqbitTorrent>,
+}
+
+#[derive(Deserialize)]
+struct RawRqbitTorrent {
+    info_hash: String,
+    #[serde(default)]
+    torrent_bytes: Option<String>,
+    #[serde(default)]
+    trackers: Vec<String>,
+    #[serde(default)]
+    output_folder: String,
+    #[serde(default)]
+    is_paused: bool,
+}
+
+impl RqbitSession {
+    /// Parses an rqbit session file. Will fail if the session is not valid JSON, or if a
+    /// torrent in the session has an invalid infohash or torrent file.
+    pub fn from_slice(s: &[u8]) -> Result<RqbitSession, SessionError> {
+        let session: RawRqbitSession =
+            serde_json::from_slice(s).map_err(|e| SessionError::InvalidJson {
+                reason: e.to_string(),
+            })?;
+
+        let mut torrents = Vec::new();
+        // Torrents are keyed by their numeric ID in the session
+        let mut entries: Vec<(String, RawRqbitTorrent)> = session.torrents.into_iter().collect();
+        entries.sort_by_key(|(id, _)| id.parse::<u64>().unwrap_or(u64::MAX));
+
+        for (_id, raw) in entries {
+            let torrent_file = match raw.torrent_bytes.as_deref() {
+                Some(encoded) if !encoded.is_empty() => {
+                    let bytes =
+                        decode_base64(encoded).ok_or_else(|| SessionError::InvalidJson {
+                            reason: "torrent_bytes is not valid base64".to_string(),
+                        })?;
+                    Some(TorrentFile::from_slice(&bytes)?)
+                }
+                _ => None,
+            };
+
+            torrents.push(RqbitTorrent {
+                hash: InfoHash::new(&raw.info_hash)?,
+                output_folder: raw.output_folder,
+                paused: raw.is_paused,
+                trackers: raw
+                    .trackers
+                    .iter()
+                    .filter_map(|url| Tracker::new(url).ok())
+                    .collect(),
+                torrent_file,
+            });
+        }
+
+        Ok(RqbitSession { torrents })
+    }
+}
+
+impl ToTorrent for RqbitTorrent {
+    fn to_torrent(&self) -> Torrent {
+        Torrent {
+            name: self
+                .torrent_file
+                .as_ref()
+                .map(|t| t.name().to_string())
+                .unwrap_or_default(),
+            path: self.output_folder.clone(),
+            date_start: None,
+            date_end: None,
+            // Placeholders, see the RqbitTorrent docs
+            progress: Progress::default(),
+            size: 0,
+            uploaded: None,
+            downloaded: None,
+            state: torrent_state(self.paused, &Progress::default()),
+            tags: Vec::new(),
+            category: None,
+            sources: tracker_sources(self.trackers.iter().cloned()),
+            limits: TorrentLimits::default(),
+            id: self.hash.id(),
+            hash: self.hash.clone(),
+        }
+    }
+}
+
+/// Decodes standard base64 (RFC 4648, with optional padding).
+fn decode_base64(s: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(s.len() * 3 / 4);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for c in s.trim_end_matches('=').bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bt_bencode::ByteString;
+    use std::collections::BTreeMap;
+
+    fn fastresume(entries: Vec<(&str, BencodeValue)>) -> Vec<u8> {
+        let dict: BTreeMap<ByteString, BencodeValue> = entries
+            .into_iter()
+            .map(|(k, v)| (ByteString::from(k), v))
+            .collect();
+        bt_bencode::to_vec(&BencodeValue::Dict(dict)).unwrap()
+    }
+
+    fn raw(hex: &str) -> BencodeValue {
+        let bytes: Vec<u8> = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect();
+        BencodeValue::ByteStr(ByteString::from(bytes))
+    }
+
+    #[test]
+    fn can_load_fastresume() {
+        let data = fastresume(vec![
+            ("info-hash", raw("631a31dd0a46257d5078c0dee4e66e26f73e42ac")),
+            (
+                "info-hash2",
+                raw("d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb"),
+            ),
+            (
+                "qBt-name",
+                BencodeValue::from("bittorrent-v1-v2-hybrid-test"),
+            ),
+            ("save_path", BencodeValue::from("/data/torrents")),
+            ("added_time", BencodeValue::from(1591173906)),
+            ("paused", BencodeValue::from(0)),
+            (
+                "pieces",
+                BencodeValue::ByteStr(ByteString::from(vec![1, 1, 1, 0])),
+            ),
+            (
+                "qBt-tags",
+                BencodeValue::List(vec![BencodeValue::from("demoscene")]),
+            ),
+            ("qBt-category", BencodeValue::from("demos/amiga")),
+            (
+                "trackers",
+                BencodeValue::List(vec![
+                    BencodeValue::List(vec![BencodeValue::from(
+                        "udp://tracker.opentrackr.org:1337/announce",
+                    )]),
+                    BencodeValue::List(vec![BencodeValue::from("not a tracker")]),
+                ]),
+            ),
+        ]);
+
+        let resume = LibtorrentResume::from_slice(&data).unwrap();
+        assert!(resume.hash.is_hybrid());
+        // Without an embedded info dict, the progress is expressed in pieces
+        assert_eq!(resume.progress, Progress::new(3, 4));
+        assert_eq!(resume.trackers.len(), 1);
+
+        let torrent = resume.to_torrent();
+        assert_eq!(torrent.name, "bittorrent-v1-v2-hybrid-test");
+        assert_eq!(torrent.path, "/data/torrents");
+        assert_eq!(torrent.date_start, Timestamp::from_secs_opt(1591173906));
+        assert_eq!(torrent.state, "downloading");
+        assert_eq!(torrent.tags, vec!["demoscene"]);
+        assert_eq!(torrent.category.as_ref().unwrap().as_str(), "demos/amiga");
+        assert_eq!(torrent.sources.len(), 1);
+        assert!(torrent.uses_tracker(&resume.trackers.tiers()[0][0]));
+        assert_eq!(
+            torrent.id.as_str(),
+            "d8dd32ac93357c368556af3ac1d95c9d76bd0dff"
+        );
+    }
+
+    #[test]
+    fn can_load_fastresume_with_info() {
+        let slice = std::fs::read("tests/bittorrent-v2-test.torrent").unwrap();
+        let torrent: BencodeValue = bt_bencode::from_slice(&slice).unwrap();
+        let data = fastresume(vec![
+            (
+                "info-hash2",
+                raw("caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e"),
+            ),
+            ("info", torrent.get("info").unwrap().clone()),
+        ]);
+
+        let resume = LibtorrentResume::from_slice(&data).unwrap();
+        assert!(resume.hash.is_v2());
+        assert_eq!(resume.name, "bittorrent-v2-test");
+        assert!(resume.size > 0);
+    }
+
+    #[test]
+    fn computes_fastresume_verified_bytes() {
+        let info = |length: i64| {
+            let dict: BTreeMap<ByteString, BencodeValue> = vec![
+                ("length", BencodeValue::from(length)),
+                ("name", BencodeValue::from("test")),
+                ("piece length", BencodeValue::from(16384)),
+                ("pieces", BencodeValue::from("a".repeat(60).as_str())),
+            ]
+            .into_iter()
+            .map(|(k, v)| (ByteString::from(k), v))
+            .collect();
+            BencodeValue::Dict(dict)
+        };
+        let resume = |length: i64, pieces: Vec<u8>| {
+            let data = fastresume(vec![
+                ("info-hash", raw("c811b41641a09d192b8ed81b14064fff55d85ce3")),
+                ("info", info(length)),
+                ("pieces", BencodeValue::ByteStr(ByteString::from(pieces))),
+            ]);
+            LibtorrentResume::from_slice(&data).unwrap()
+        };
+
+        // The last piece only holds 40000 - 2 * 16384 = 7232 bytes
+        let partial = resume(40000, vec![1, 0, 1]);
+        assert_eq!(partial.progress, Progress::new(16384 + 7232, 40000));
+        assert_eq!(partial.to_torrent().progress, partial.progress);
+        assert_eq!(partial.to_torrent().state, "downloading");
+
+        let almost = resume(40000, vec![1, 1, 0]);
+        assert_eq!(almost.progress, Progress::new(32768, 40000));
+        assert!(!almost.progress.is_complete());
+
+        let complete = resume(40000, vec![1, 1, 1]);
+        assert!(complete.progress.is_complete());
+        assert_eq!(complete.to_torrent().state, "seeding");
+
+        // Pieces not matching the size are counted as is
+        let mismatch = resume(40000, vec![1, 1, 1, 0]);
+        assert_eq!(mismatch.progress, Progress::new(3, 4));
+    }
+
+    #[test]
+    fn can_convert_fastresume_to_resume_data() {
+        let slice = std::fs::read("tests/bittorrent-v2-test.torrent").unwrap();
+        let torrent: BencodeValue = bt_bencode::from_slice(&slice).unwrap();
+        let info = torrent.get("info").unwrap();
+        let piece_length = info.get("piece length").and_then(bencode_i64).unwrap();
+        let size = info_size(info).unwrap();
+        let piece_count = ((size + piece_length - 1) / piece_length) as usize;
+        let data = fastresume(vec![
+            (
+                "info-hash2",
+                raw("caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e"),
+            ),
+            ("info", info.clone()),
+            (
+                "pieces",
+                BencodeValue::ByteStr(ByteString::from(vec![1; piece_count])),
+            ),
+            (
+                "file_priority",
+                BencodeValue::List(vec![BencodeValue::from(0), BencodeValue::from(9)]),
+            ),
+        ]);
+
+        let resume = ResumeData::from_fastresume(&data).unwrap();
+        assert_eq!(
+            resume.id.as_str(),
+            "caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa"
+        );
+        assert!(resume.pieces.is_complete());
+        assert_eq!(resume.verified_bytes, size as u64);
+        assert_eq!(resume.file_priorities, vec![0, 7]);
+
+        // Without the info dict, the size of pieces is unknown
+        let data = fastresume(vec![
+            ("info-hash", raw("631a31dd0a46257d5078c0dee4e66e26f73e42ac")),
+            (
+                "pieces",
+                BencodeValue::ByteStr(ByteString::from(vec![1, 0, 1])),
+            ),
+        ]);
+        let resume = ResumeData::from_fastresume(&data).unwrap();
+        assert_eq!(resume.pieces.ones().collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(resume.verified_bytes, 0);
+        assert!(resume.file_priorities.is_empty());
+
+        // Huge pieces must not overflow
+        let info: BTreeMap<ByteString, BencodeValue> = vec![
+            ("length", BencodeValue::from(i64::MAX)),
+            ("name", BencodeValue::from("test")),
+            ("piece length", BencodeValue::from(i64::MAX)),
+        ]
+        .into_iter()
+        .map(|(k, v)| (ByteString::from(k), v))
+        .collect();
+        let data = fastresume(vec![
+            ("info-hash", raw("631a31dd0a46257d5078c0dee4e66e26f73e42ac")),
+            ("info", BencodeValue::Dict(info)),
+            (
+                "pieces",
+                BencodeValue::ByteStr(ByteString::from(vec![1, 1, 1, 1])),
+            ),
+        ]);
+        let resume = ResumeData::from_fastresume(&data).unwrap();
+        assert_eq!(resume.verified_bytes, i64::MAX as u64);
+    }
+
+    #[test]
+    fn fails_fastresume_without_hash() {
+        let data = fastresume(vec![("name", BencodeValue::from("test"))]);
+        assert_eq!(
+            LibtorrentResume::from_slice(&data).unwrap_err(),
+            SessionError::MissingField {
+                field: "info-hash".to_string()
+            }
+        );
+
+        let mut data = b"d6:extral".to_vec();
+        data.extend(std::iter::repeat(b'l').take(100_000));
+        assert!(matches!(
+            LibtorrentResume::from_slice(&data).unwrap_err(),
+            SessionError::InvalidBencode { .. }
+        ));
+    }
+
+    #[test]
+    fn can_load_rqbit_session() {
+        let json = r#"{
+            "torrents": {
+                "1": {
+                    "info_hash": "c811b41641a09d192b8ed81b14064fff55d85ce3",
+                    "trackers": ["udp://tracker.opentrackr.org:1337/announce"],
+                    "output_folder": "/home/rqbit/Downloads",
+                    "is_paused": true
+                },
+                "0": {
+                    "info_hash": "725c81f81c50b1172755e4c72ff65f1455c5fa8b",
+                    "torrent_bytes": "ZDQ6aW5mb2Q2Omxlbmd0aGkxMmU0Om5hbWU0OnRlc3QxMjpwaWVjZSBsZW5ndGhpMTYzODRlNjpwaWVjZXMyMDphYWFhYWFhYWFhYWFhYWFhYWFhYWVl",
+                    "trackers": [],
+                    "output_folder": "/home/rqbit/Downloads"
+                }
+            }
+        }"#;
+        let session = RqbitSession::from_slice(json.as_bytes()).unwrap();
+        assert_eq!(session.torrents.len(), 2);
+
+        let first = session.torrents[0].to_torrent();
+        assert_eq!(first.name, "test");
+        assert_eq!(first.state, "downloading");
+        assert_eq!(first.progress, Progress::default());
+        assert_eq!(
+            session.torrents[0].torrent_file.as_ref().unwrap().hash(),
+            "725c81f81c50b1172755e4c72ff65f1455c5fa8b"
+        );
+
+        let second = session.torrents[1].to_torrent();
+        assert_eq!(second.state, "paused");
+        assert_eq!(session.torrents[1].trackers.len(), 1);
+        assert!(second.uses_tracker(&session.torrents[1].trackers[0]));
+    }
+
+    #[test]
+    fn counts_pieces_on_size_overflow() {
+        let file = || {
+            let dict: BTreeMap<ByteString, BencodeValue> = vec![
+                ("length", BencodeValue::from(i64::MAX)),
+                ("path", BencodeValue::List(vec![BencodeValue::from("a")])),
+            ]
+            .into_iter()
+            .map(|(k, v)| (ByteString::from(k), v))
+            .collect();
+            BencodeValue::Dict(dict)
+        };
+        let info: BTreeMap<ByteString, BencodeValue> = vec![
+            ("files", BencodeValue::List(vec![file(), file()])),
+            ("name", BencodeValue::from("test")),
+            ("piece length", BencodeValue::from(16384)),
+            ("pieces", BencodeValue::from("a".repeat(40).as_str())),
+        ]
+        .into_iter()
+        .map(|(k, v)| (ByteString::from(k), v))
+        .collect();
+        let data = fastresume(vec![
+            ("info-hash", raw("c811b41641a09d192b8ed81b14064fff55d85ce3")),
+            ("info", BencodeValue::Dict(info)),
+            (
+                "pieces",
+                BencodeValue::ByteStr(ByteString::from(vec![1, 0])),
+            ),
+        ]);
+
+        let resume = LibtorrentResume::from_slice(&data).unwrap();
+        assert_eq!(resume.size, 0);
+        assert_eq!(resume.progress, Progress::new(1, 2));
+    }
+
+    #[test]
+    fn fails_rqbit_invalid_hash() {
+        let json = r#"{"torrents": {"0": {"info_hash": "1234"}}}"#;
+        assert!(matches!(
+            RqbitSession::from_slice(json.as_bytes()).unwrap_err(),
+            SessionError::InvalidHash { .. }
+        ));
+    }
+}
@@ -0,0 +1,572 @@
+//! Persistence of a [`TorrentList`](crate::list::TorrentList) to a versioned bencode file.
+
+use bt_bencode::{ByteString, Value as BencodeValue};
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::bencode::nesting_depth;
+use crate::torrent_file::bencode_i64;
+use crate::{
+    Category, InfoHash, MagnetLink, MagnetParseOptions, ParseOptions, PeerSource, Progress,
+    Timestamp, Torrent, TorrentFile, TorrentID, TorrentLimits, TorrentList,
+};
+
+/// Error occurred while saving or loading a [`SavedState`](crate::state::SavedState).
+#[derive(Clone, Debug, PartialEq)]
+pub enum StateError {
+    // std::io::Error is not Clone/PartialEq so we store its kind and message
+    Io {
+        kind: std::io::ErrorKind,
+        reason: String,
+    },
+    // TODO: bt_bencode::Error is not PartialEq so we store error as String
+    InvalidBencode {
+        reason: String,
+    },
+    /// The state was saved by a newer, incompatible version of the format.
+    UnsupportedVersion {
+        version: i64,
+    },
+    /// The torrent at this position in the saved list is invalid.
+    InvalidEntry {
+        index: usize,
+        reason: String,
+    },
+}
+
+impl std::fmt::Display for StateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateError::Io { kind: _, reason } => write!(f, "Failed to access state: {reason}"),
+            StateError::InvalidBencode { reason } => write!(f, "Invalid bencode: {reason}"),
+            StateError::UnsupportedVersion { version } => {
+                write!(f, "Unsupported state format version: {version}")
+            }
+            StateError::InvalidEntry { index, reason } => {
+                write!(f, "Invalid torrent #{index} in state: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+impl From<std::io::Error> for StateError {
+    fn from(e: std::io::Error) -> StateError {
+        StateError::Io {
+            kind: e.kind(),
+            reason: e.to_string(),
+        }
+    }
+}
+
+type BencodeDict = BTreeMap<ByteString, BencodeValue>;
+
+/// The keys of a torrent entry understood by this version of the format.
+const ENTRY_KEYS: [&str; 20] = [
+    "hash",
+    "name",
+    "path",
+    "size",
+    "bytes_done",
+    "bytes_total",
+    "date_start",
+    "date_end",
+    "uploaded",
+    "downloaded",
+    "state",
+    "tags",
+    "category",
+    "sources",
+    "download_limit",
+    "upload_limit",
+    "ratio_limit",
+    "seeding_time_limit",
+    "torrent_file",
+    "magnet",
+];
+
+/// A [`TorrentList`](crate::list::TorrentList) with the metadata of its torrents, which can
+/// be saved to disk and loaded again, for example to restore a client session.
+///
+/// The state is stored as a bencoded dict, with a `version` of the format and the list of
+/// `torrents`. Each torrent is a dict holding the fields of the
+/// [`Torrent`](crate::torrent::Torrent), along with the original bytes of its
+/// [`TorrentFile`](crate::torrent_file::TorrentFile) and its
+/// [`MagnetLink`](crate::magnet::MagnetLink), when known. TorrentFiles and MagnetLinks are
+/// looked up by [`TorrentID`](crate::id::TorrentID), and only saved for the torrents in the
+/// list.
+///
+/// The format is forward-compatible: new fields are added without changing the `version`,
+/// which is only increased for incompatible changes. Unknown fields, at the top level or in a
+/// torrent, are kept when loading and written back when saving, so that a state saved by a
+/// newer version is not degraded by an older one.
+///
+/// Saving is atomic: the state is written to a temporary file next to the destination, which
+/// then replaces it, so that a crash never leaves a truncated state behind.
+///
+/// ```
+/// use hightorrent::{SavedState, ToTorrent, TorrentFile, TorrentList};
+///
+/// let torrent = TorrentFile::from_path("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+/// let mut state = SavedState::new(TorrentList::from_vec(vec![torrent.to_torrent()]));
+/// state.insert_torrent_file(torrent.clone());
+///
+/// let loaded = SavedState::from_slice(&state.to_bytes()).unwrap();
+/// assert_eq!(loaded.list().len(), 1);
+/// assert_eq!(loaded.torrent_file(&torrent.id()).unwrap().as_bytes(), torrent.as_bytes());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SavedState {
+    list: TorrentList,
+    torrent_files: HashMap<TorrentID, TorrentFile>,
+    magnets: HashMap<TorrentID, MagnetLink>,
+    /// Unknown fields of the torrent entries
+    entry_extra: HashMap<TorrentID, BencodeDict>,
+    /// Unknown top-level fields
+    extra: BencodeDict,
+}
+
+impl SavedState {
+    /// The current version of the format. It is only increased for incompatible changes.
+    pub const VERSION: i64 = 1;
+
+    pub fn new(list: TorrentList) -> SavedState {
+        SavedState {
+            list,
+            ..SavedState::default()
+        }
+    }
+
+    pub fn list(&self) -> &TorrentList {
+        &self.list
+    }
+
+    pub fn list_mut(&mut self) -> &mut TorrentList {
+        &mut self.list
+    }
+
+    pub fn into_list(self) -> TorrentList {
+        self.list
+    }
+
+    /// Stores the TorrentFile of a torrent, replacing any TorrentFile with the same TorrentID.
+    pub fn insert_torrent_file(&mut self, torrent: TorrentFile) -> Option<TorrentFile> {
+        self.torrent_files.insert(torrent.id(), torrent)
+    }
+
+    /// Returns the TorrentFile stored for a torrent.
+    pub fn torrent_file(&self, id: &TorrentID) -> Option<&TorrentFile> {
+        self.torrent_files.get(id)
+    }
+
+    /// Stores the MagnetLink of a torrent, replacing any MagnetLink with the same TorrentID.
+    pub fn insert_magnet(&mut self, magnet: MagnetLink) -> Option<MagnetLink> {
+        self.magnets.insert(magnet.id(), magnet)
+    }
+
+    /// Returns the MagnetLink stored for a torrent.
+    pub fn magnet(&self, id: &TorrentID) -> Option<&MagnetLink> {
+        self.magnets.get(id)
+    }
+
+    /// Serializes the state to bencode.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let torrents = self
+            .list
+            .iter()
+            .map(|torrent| BencodeValue::Dict(self.encode_entry(torrent)))
+            .collect();
+
+        let mut dict = self.extra.clone();
+        dict.insert(
+            key("version"),
+            BencodeValue::Int(SavedState::VERSION.into()),
+        );
+        dict.insert(key("torrents"), BencodeValue::List(torrents));
+        // Serializing a bencode Value cannot fail
+        bt_bencode::to_vec(&BencodeValue::Dict(dict)).unwrap()
+    }
+
+    /// Parses a state serialized by [`SavedState::to_bytes`](crate::state::SavedState::to_bytes).
+    pub fn from_slice(s: &[u8]) -> Result<SavedState, StateError> {
+        // Values are decoded recursively, so deeply nested data would overflow the stack
+        let depth = nesting_depth(s);
+        if depth > ParseOptions::DEFAULT_MAX_NESTING_DEPTH {
+            return Err(StateError::InvalidBencode {
+                reason: format!("nested too deep: {depth} levels"),
+            });
+        }
+        let mut dict = match bt_bencode::from_slice(s) {
+            Ok(BencodeValue::Dict(dict)) => dict,
+            Ok(_) => {
+                return Err(StateError::InvalidBencode {
+                    reason: "state is not a dict".to_string(),
+                })
+            }
+            Err(e) => {
+                return Err(StateError::InvalidBencode {
+                    reason: e.to_string(),
+                })
+            }
+        };
+
+        let version = dict
+            .remove(&key("version"))
+            .as_ref()
+            .and_then(bencode_i64)
+            .ok_or(StateError::InvalidBencode {
+                reason: "missing version".to_string(),
+            })?;
+        if version > SavedState::VERSION {
+            return Err(StateError::UnsupportedVersion { version });
+        }
+
+        let entries = match dict.remove(&key("torrents")) {
+            Some(BencodeValue::List(entries)) => entries,
+            None => Vec::new(),
+            Some(_) => {
+                return Err(StateError::InvalidBencode {
+                    reason: "torrents is not a list".to_string(),
+                })
+            }
+        };
+
+        let mut state = SavedState {
+            extra: dict,
+            ..SavedState::default()
+        };
+        for (index, entry) in entries.into_iter().enumerate() {
+            state
+                .decode_entry(entry)
+                .map_err(|reason| StateError::InvalidEntry { index, reason })?;
+        }
+        Ok(state)
+    }
+
+    /// Saves the state to a file, atomically replacing it if it exists.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), StateError> {
+        let path = path.as_ref();
+        let tmp = tmp_path(path);
+        let res = std::fs::File::create(&tmp).and_then(|mut file| {
+            file.write_all(&self.to_bytes())?;
+            file.sync_all()
+        });
+        if let Err(e) = res.and_then(|_| std::fs::rename(&tmp, path)) {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// Loads a state saved with [`SavedState::save`](crate::state::SavedState::save).
+    pub fn load(path: impl AsRef<Path>) -> Result<SavedState, StateError> {
+        SavedState::from_slice(&std::fs::read(path)?)
+    }
+
+    fn encode_entry(&self, t: &Torrent) -> BencodeDict {
+        let mut entry = self.entry_extra.get(&t.id).cloned().unwrap_or_default();
+        let mut set = |k: &str, v: BencodeValue| {
+            entry.insert(key(k), v);
+        };
+        let string = |s: &str| BencodeValue::ByteStr(ByteString::from(s));
+
+        set("hash", string(&t.hash.to_full_string()));
+        set("name", string(&t.name));
+        set("path", string(&t.path));
+        set("size", BencodeValue::Int(t.size.into()));
+        set(
+            "bytes_done",
+            BencodeValue::Int(t.progress.bytes_done.into()),
+        );
+        set(
+            "bytes_total",
+            BencodeValue::Int(t.progress.bytes_total.into()),
+        );
+        if let Some(date) = t.date_start {
+            set("date_start", BencodeValue::Int(date.as_secs().into()));
+        }
+        if let Some(date) = t.date_end {
+            set("date_end", BencodeValue::Int(date.as_secs().into()));
+        }
+        if let Some(uploaded) = t.uploaded {
+            set("uploaded", BencodeValue::Int(uploaded.into()));
+        }
+        if let Some(downloaded) = t.downloaded {
+            set("downloaded", BencodeValue::Int(downloaded.into()));
+        }
+        set("state", string(&t.state));
+        set(
+            "tags",
+            BencodeValue::List(t.tags.iter().map(|tag| string(tag)).collect()),
+        );
+        if let Some(category) = &t.category {
+            set("category", string(category.as_str()));
+        }
+        set(
+            "sources",
+            BencodeValue::List(t.sources.iter().map(|s| string(&s.to_string())).collect()),
+        );
+        if let Some(limit) = t.limits.download_limit {
+            set("download_limit", BencodeValue::Int(limit.into()));
+        }
+        if let Some(limit) = t.limits.upload_limit {
+            set("upload_limit", BencodeValue::Int(limit.into()));
+        }
+        if let Some(ratio) = t.limits.ratio_limit {
+            // Bencode has no floating point numbers
+            set("ratio_limit", string(&ratio.to_string()));
+        }
+        if let Some(limit) = t.limits.seeding_time_limit {
+            set("seeding_time_limit", BencodeValue::Int(limit.into()));
+        }
+        if let Some(torrent) = self.torrent_files.get(&t.id) {
+            set(
+                "torrent_file",
+                BencodeValue::ByteStr(ByteString::from(torrent.as_bytes())),
+            );
+        }
+        if let Some(magnet) = self.magnets.get(&t.id) {
+            set("magnet", string(&magnet.to_string()));
+        }
+        entry
+    }
+
+    fn decode_entry(&mut self, entry: BencodeValue) -> Result<(), String> {
+        let mut entry = match entry {
+            BencodeValue::Dict(entry) => entry,
+            _ => return Err("not a dict".to_string()),
+        };
+
+        let string = |name: &str| -> Result<Option<String>, String> {
+            match entry.get(name.as_bytes()) {
+                None => Ok(None),
+                Some(v) => v
+                    .as_str()
+                    .map(|s| Some(s.to_string()))
+                    .ok_or(format!("invalid {name}")),
+            }
+        };
+        let int = |name: &str| -> Result<Option<i64>, String> {
+            match entry.get(name.as_bytes()) {
+                None => Ok(None),
+                Some(v) => bencode_i64(v).map(Some).ok_or(format!("invalid {name}")),
+            }
+        };
+        let counter = |name: &str| -> Result<Option<u64>, String> {
+            int(name)?
+                .map(|v| u64::try_from(v).map_err(|_| format!("invalid {name}")))
+                .transpose()
+        };
+        let strings = |name: &str| -> Result<Vec<String>, String> {
+            match entry.get(name.as_bytes()) {
+                None => Ok(Vec::new()),
+                Some(v) => v
+                    .as_array()
+                    .ok_or(format!("invalid {name}"))?
+                    .iter()
+                    .map(|s| {
+                        s.as_str()
+                            .map(String::from)
+                            .ok_or(format!("invalid {name}"))
+                    })
+                    .collect(),
+            }
+        };
+
+        let hash = string("hash")?.ok_or("missing hash".to_string())?;
+        let hash = InfoHash::new(&hash).map_err(|e| e.to_string())?;
+        let id = hash.id();
+        let torrent = Torrent {
+            name: string("name")?.unwrap_or_default(),
+            path: string("path")?.unwrap_or_default(),
+            date_start: int("date_start")?.and_then(Timestamp::from_secs_opt),
+            date_end: int("date_end")?.and_then(Timestamp::from_secs_opt),
+            progress: Progress::new(
+                counter("bytes_done")?.unwrap_or(0),
+                counter("bytes_total")?.unwrap_or(0),
+            ),
+            size: int("size")?.unwrap_or(0),
+            uploaded: counter("uploaded")?,
+            downloaded: counter("downloaded")?,
+            state: string("state")?.unwrap_or_default(),
+            tags: strings("tags")?,
+            category: string("category")?
+                .map(|c| Category::new(&c))
+                .transpose()
+                .map_err(|e| e.to_string())?,
+            sources: strings("sources")?
+                .iter()
+                .map(|s| PeerSource::from_str(s))
+                .collect::<Result<Vec<PeerSource>, _>>()
+                .map_err(|e| e.to_string())?,
+            limits: TorrentLimits {
+                download_limit: counter("download_limit")?,
+                upload_limit: counter("upload_limit")?,
+                ratio_limit: string("ratio_limit")?
+                    .map(|ratio| ratio.parse().map_err(|_| "invalid ratio_limit".to_string()))
+                    .transpose()?,
+                seeding_time_limit: counter("seeding_time_limit")?,
+            },
+            id,
+            hash,
+        };
+
+        if let Some(bytes) = entry.get("torrent_file".as_bytes()) {
+            let bytes = bytes.as_byte_str().ok_or("invalid torrent_file")?;
+            let torrent_file = TorrentFile::from_slice(bytes).map_err(|e| e.to_string())?;
+            self.torrent_files.insert(id, torrent_file);
+        }
+        if let Some(magnet) = string("magnet")? {
+            let options = MagnetParseOptions {
+                require_name: false,
+                ..MagnetParseOptions::default()
+            };
+            let magnet = MagnetLink::new_with(&magnet, options).map_err(|e| e.to_string())?;
+            self.magnets.insert(id, magnet);
+        }
+
+        entry.retain(|k, _| {
+            !ENTRY_KEYS
+                .iter()
+                .any(|known| known.as_bytes() == k.as_slice())
+        });
+        if !entry.is_empty() {
+            self.entry_extra.insert(id, entry);
+        }
+        self.list.push(torrent);
+        Ok(())
+    }
+}
+
+impl From<TorrentList> for SavedState {
+    fn from(list: TorrentList) -> SavedState {
+        SavedState::new(list)
+    }
+}
+
+fn key(k: &str) -> ByteString {
+    ByteString::from(k)
+}
+
+/// Returns the temporary file written before replacing the state file, in the same folder so
+/// that it can be renamed atomically.
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+impl TorrentList {
+    /// Saves the TorrentList to a file, atomically replacing it if it exists. To also save the
+    /// TorrentFiles and MagnetLinks of the torrents, use a
+    /// [`SavedState`](crate::state::SavedState).
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), StateError> {
+        SavedState::new(self.clone()).save(path)
+    }
+
+    /// Loads a TorrentList saved with [`TorrentList::save`](crate::list::TorrentList::save) or
+    /// [`SavedState::save`](crate::state::SavedState::save).
+    pub fn load(path: impl AsRef<Path>) -> Result<TorrentList, StateError> {
+        Ok(SavedState::load(path)?.into_list())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ToTorrent;
+
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("hightorrent-{name}-{}", std::process::id()))
+    }
+
+    fn torrent_file(path: &str) -> TorrentFile {
+        TorrentFile::from_slice(&std::fs::read(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn saves_and_loads_state() {
+        let v1 = torrent_file("tests/bittorrent-v1-emma-goldman.torrent");
+        let hybrid = torrent_file("tests/bittorrent-v2-hybrid-test.torrent");
+        let magnet = MagnetLink::new(
+            &std::fs::read_to_string("tests/bittorrent-v1-emma-goldman.magnet").unwrap(),
+        )
+        .unwrap();
+
+        let mut v1_torrent = v1.to_torrent();
+        v1_torrent.date_start = Timestamp::from_secs_opt(1700000000);
+        v1_torrent.uploaded = Some(1234);
+        v1_torrent.tags = vec!["books".to_string()];
+        v1_torrent.category = Some(Category::new("books/essays").unwrap());
+        v1_torrent.limits.upload_limit = Some(51200);
+        v1_torrent.limits.ratio_limit = Some(1.5);
+        let list = TorrentList::from_vec(vec![v1_torrent, hybrid.to_torrent()]);
+
+        let mut state = SavedState::new(list.clone());
+        state.insert_torrent_file(hybrid.clone());
+        state.insert_magnet(magnet.clone());
+        let path = temp_file("state.bencode");
+        state.save(&path).unwrap();
+        assert!(!tmp_path(&path).exists());
+
+        let loaded = SavedState::load(&path).unwrap();
+        // The CSV export covers all the fields of a Torrent, except for its limits
+        assert_eq!(loaded.list().to_csv(), list.to_csv());
+        let limits: Vec<TorrentLimits> = loaded.list().iter().map(|t| t.limits).collect();
+        assert_eq!(limits, list.iter().map(|t| t.limits).collect::<Vec<_>>());
+        assert_eq!(
+            loaded.torrent_file(&hybrid.id()).unwrap().as_bytes(),
+            hybrid.as_bytes()
+        );
+        assert!(loaded.torrent_file(&v1.id()).is_none());
+        assert_eq!(
+            loaded.magnet(&v1.id()).unwrap().to_string(),
+            magnet.to_string()
+        );
+        assert_eq!(loaded.to_bytes(), state.to_bytes());
+
+        assert_eq!(TorrentList::load(&path).unwrap().len(), 2);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            TorrentList::load(&path),
+            Err(StateError::Io {
+                kind: std::io::ErrorKind::NotFound,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn preserves_unknown_fields() {
+        let hash = "c811b41641a09d192b8ed81b14064fff55d85ce3";
+        let newer = format!(
+            "d8:torrentsld4:hash40:{hash}4:name4:test8:priorityi7eee7:versioni1e7:watchedl5:/tmp/ee"
+        );
+        let state = SavedState::from_slice(newer.as_bytes()).unwrap();
+        assert_eq!(state.list().iter().next().unwrap().name, "test");
+
+        let saved = String::from_utf8(state.to_bytes()).unwrap();
+        assert!(saved.contains("8:priorityi7e"));
+        assert!(saved.contains("7:watchedl5:/tmp/e"));
+    }
+
+    #[test]
+    fn rejects_invalid_state() {
+        assert_eq!(
+            SavedState::from_slice(b"d7:versioni2ee").unwrap_err(),
+            StateError::UnsupportedVersion { version: 2 }
+        );
+        assert!(matches!(
+            SavedState::from_slice(b"d8:torrentsl4:teste7:versioni1ee"),
+            Err(StateError::InvalidEntry { index: 0, .. })
+        ));
+        assert!(matches!(
+            SavedState::from_slice(b"le"),
+            Err(StateError::InvalidBencode { .. })
+        ));
+    }
+}
@@ -0,0 +1,169 @@
+//! Persistence of torrent files in embedded key-value stores.
+//!
+//! Torrents are stored as their original bencoded bytes, keyed by their
+//! [`TorrentID`](crate::id::TorrentID).
+
+use std::collections::BTreeMap;
+
+use crate::{InfoHash, SingleTarget, ToTorrent, TorrentFile, TorrentFileError, TorrentList};
+
+#[cfg(feature = "redb")]
+mod redb;
+#[cfg(feature = "redb")]
+pub use self::redb::RedbStore;
+
+/// Error occurred while reading or writing a [`TorrentStore`](crate::storage::TorrentStore).
+#[derive(Clone, Debug, PartialEq)]
+pub enum StorageError {
+    // Backend errors are not all Clone/PartialEq so we store error as String
+    Backend { reason: String },
+    InvalidTorrent { source: TorrentFileError },
+}
+
+impl std::fmt::Display for StorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageError::Backend { reason } => write!(f, "Storage backend error: {reason}"),
+            StorageError::InvalidTorrent { source } => {
+                write!(f, "Invalid stored torrent: {source}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StorageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StorageError::InvalidTorrent { source } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<TorrentFileError> for StorageError {
+    fn from(e: TorrentFileError) -> StorageError {
+        StorageError::InvalidTorrent { source: e }
+    }
+}
+
+/// A persistent collection of [`TorrentFile`](crate::torrent_file::TorrentFile)s.
+///
+/// Torrents are looked up with the same semantics as
+/// [`TorrentList::get`](crate::list::TorrentList::get), so a hybrid torrent can be found
+/// by its infohash v2, its [`TorrentID`](crate::id::TorrentID), or its infohash v1.
+pub trait TorrentStore {
+    /// Stores a torrent, replacing any torrent with the same [`TorrentID`](crate::id::TorrentID).
+    fn put(&mut self, torrent: TorrentFile) -> Result<(), StorageError>;
+
+    /// Finds the torrent matching a [`SingleTarget`](crate::target::SingleTarget).
+    fn get(&self, target: &SingleTarget) -> Result<Option<TorrentFile>, StorageError>;
+
+    /// Removes the torrent matching a [`SingleTarget`](crate::target::SingleTarget), and returns it.
+    fn remove(&mut self, target: &SingleTarget) -> Result<Option<TorrentFile>, StorageError>;
+
+    /// Returns all stored torrents, as a [`TorrentList`](crate::list::TorrentList).
+    fn list(&self) -> Result<TorrentList, StorageError>;
+}
+
+/// An in-memory [`TorrentStore`](crate::storage::TorrentStore), which is lost when dropped.
+///
+/// This is the reference implementation of the trait, useful for tests.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStore {
+    torrents: BTreeMap<String, TorrentFile>,
+}
+
+impl MemoryStore {
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+
+    fn find(&self, target: &SingleTarget) -> Option<String> {
+        if let Some(torrent) = self.torrents.get(target.truncated()) {
+            if target.matches_hash(&InfoHash::from(torrent)) {
+                return Some(target.truncated().to_string());
+            }
+        }
+
+        // A hybrid torrent may be targeted by its infohash v1, which is not its key
+        self.torrents
+            .iter()
+            .find(|(_, torrent)| target.matches_hash(&InfoHash::from(*torrent)))
+            .map(|(key, _)| key.clone())
+    }
+}
+
+impl TorrentStore for MemoryStore {
+    fn put(&mut self, torrent: TorrentFile) -> Result<(), StorageError> {
+        self.torrents
+            .insert(torrent.id().as_str().to_string(), torrent);
+        Ok(())
+    }
+
+    fn get(&self, target: &SingleTarget) -> Result<Option<TorrentFile>, StorageError> {
+        Ok(self
+            .find(target)
+            .and_then(|key| self.torrents.get(&key).cloned()))
+    }
+
+    fn remove(&mut self, target: &SingleTarget) -> Result<Option<TorrentFile>, StorageError> {
+        Ok(self.find(target).and_then(|key| self.torrents.remove(&key)))
+    }
+
+    fn list(&self) -> Result<TorrentList, StorageError> {
+        Ok(self.torrents.values().map(|t| t.to_torrent()).collect())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Checks the behavior shared by all [`TorrentStore`] implementations.
+    pub(crate) fn check_store<S: TorrentStore>(store: &mut S) {
+        let hybrid = TorrentFile::from_slice(
+            &std::fs::read("tests/bittorrent-v2-hybrid-test.torrent").unwrap(),
+        )
+        .unwrap();
+        let v1 = TorrentFile::from_slice(
+            &std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap(),
+        )
+        .unwrap();
+
+        assert!(store.list().unwrap().is_empty());
+        store.put(hybrid.clone()).unwrap();
+        store.put(v1.clone()).unwrap();
+        // Storing again replaces the existing torrent
+        store.put(v1.clone()).unwrap();
+        assert_eq!(store.list().unwrap().len(), 2);
+
+        // Hybrid torrent can be found by infohash v2, TorrentID and infohash v1
+        let v2_target = SingleTarget::new(&InfoHash::from(&hybrid).v2().unwrap()).unwrap();
+        let id_target = SingleTarget::new(hybrid.id().as_str()).unwrap();
+        let v1_target = SingleTarget::new(&InfoHash::from(&hybrid).v1().unwrap()).unwrap();
+        for target in [&v2_target, &id_target, &v1_target] {
+            let found = store.get(target).unwrap().unwrap();
+            assert_eq!(found.as_bytes(), hybrid.as_bytes());
+        }
+
+        let v1_found = store.get(&SingleTarget::new(&v1.hash()).unwrap()).unwrap();
+        assert_eq!(v1_found.unwrap().as_bytes(), v1.as_bytes());
+
+        let unknown = SingleTarget::new("0000000000000000000000000000000000000000").unwrap();
+        assert!(store.get(&unknown).unwrap().is_none());
+        assert!(store.remove(&unknown).unwrap().is_none());
+
+        let removed = store.remove(&v1_target).unwrap().unwrap();
+        assert_eq!(removed.as_bytes(), hybrid.as_bytes());
+        assert!(store.get(&v2_target).unwrap().is_none());
+
+        let list = store.list().unwrap();
+        assert_eq!(list.len(), 1);
+        assert!(list.contains(&SingleTarget::new(&v1.hash()).unwrap()));
+    }
+
+    #[test]
+    fn memory_store() {
+        check_store(&mut MemoryStore::new());
+    }
+}
@@ -0,0 +1,118 @@
+use ::redb::{Database, ReadableTable, TableDefinition};
+
+use std::path::Path;
+
+use crate::storage::{StorageError, TorrentStore};
+use crate::{InfoHash, SingleTarget, ToTorrent, TorrentFile, TorrentList};
+
+/// Torrent files by [`TorrentID`](crate::id::TorrentID)
+const TORRENTS: TableDefinition<&str, &[u8]> = TableDefinition::new("torrents");
+
+fn backend<E: Into<::redb::Error>>(e: E) -> StorageError {
+    StorageError::Backend {
+        reason: e.into().to_string(),
+    }
+}
+
+/// A [`TorrentStore`](crate::storage::TorrentStore) backed by a [redb](https://www.redb.org/)
+/// database.
+pub struct RedbStore {
+    db: Database,
+}
+
+impl RedbStore {
+    /// Opens the database at `path`, creating it if it does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<RedbStore, StorageError> {
+        RedbStore::from_database(Database::create(path).map_err(backend)?)
+    }
+
+    /// Uses an already-opened database. The `torrents` table is created if needed.
+    pub fn from_database(db: Database) -> Result<RedbStore, StorageError> {
+        let txn = db.begin_write().map_err(backend)?;
+        txn.open_table(TORRENTS).map_err(backend)?;
+        txn.commit().map_err(backend)?;
+        Ok(RedbStore { db })
+    }
+
+    /// Returns the key of the torrent matching the target, along with the torrent.
+    fn find(&self, target: &SingleTarget) -> Result<Option<(String, TorrentFile)>, StorageError> {
+        let txn = self.db.begin_read().map_err(backend)?;
+        let table = txn.open_table(TORRENTS).map_err(backend)?;
+
+        if let Some(bytes) = table.get(target.truncated()).map_err(backend)? {
+            let torrent = TorrentFile::from_slice(bytes.value())?;
+            if target.matches_hash(&InfoHash::from(&torrent)) {
+                return Ok(Some((target.truncated().to_string(), torrent)));
+            }
+        }
+
+        // A hybrid torrent may be targeted by its infohash v1, which is not its key
+        for entry in table.iter().map_err(backend)? {
+            let (key, bytes) = entry.map_err(backend)?;
+            let torrent = TorrentFile::from_slice(bytes.value())?;
+            if target.matches_hash(&InfoHash::from(&torrent)) {
+                return Ok(Some((key.value().to_string(), torrent)));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl TorrentStore for RedbStore {
+    fn put(&mut self, torrent: TorrentFile) -> Result<(), StorageError> {
+        let txn = self.db.begin_write().map_err(backend)?;
+        {
+            let mut table = txn.open_table(TORRENTS).map_err(backend)?;
+            table
+                .insert(torrent.id().as_str(), torrent.as_bytes())
+                .map_err(backend)?;
+        }
+        txn.commit().map_err(backend)
+    }
+
+    fn get(&self, target: &SingleTarget) -> Result<Option<TorrentFile>, StorageError> {
+        Ok(self.find(target)?.map(|(_, torrent)| torrent))
+    }
+
+    fn remove(&mut self, target: &SingleTarget) -> Result<Option<TorrentFile>, StorageError> {
+        let (key, torrent) = match self.find(target)? {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+
+        let txn = self.db.begin_write().map_err(backend)?;
+        {
+            let mut table = txn.open_table(TORRENTS).map_err(backend)?;
+            table.remove(key.as_str()).map_err(backend)?;
+        }
+        txn.commit().map_err(backend)?;
+        Ok(Some(torrent))
+    }
+
+    fn list(&self) -> Result<TorrentList, StorageError> {
+        let txn = self.db.begin_read().map_err(backend)?;
+        let table = txn.open_table(TORRENTS).map_err(backend)?;
+
+        let mut list = TorrentList::new();
+        for entry in table.iter().map_err(backend)? {
+            let (_, bytes) = entry.map_err(backend)?;
+            list.push(TorrentFile::from_slice(bytes.value())?.to_torrent());
+        }
+        Ok(list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::tests::check_store;
+
+    #[test]
+    fn redb_store() {
+        let db = Database::builder()
+            .create_with_backend(::redb::backends::InMemoryBackend::new())
+            .unwrap();
+        check_store(&mut RedbStore::from_database(db).unwrap());
+    }
+}
@@ -1,6 +1,6 @@
 use std::str::FromStr;
 
-use crate::{InfoHash, InfoHashError, TorrentID};
+use crate::{Category, InfoHash, InfoHashError, Torrent, TorrentID, Tracker};
 
 /// A single Torrent to interact with.
 ///
@@ -43,10 +43,18 @@ impl SingleTarget {
         self.as_str().get(0..40).unwrap()
     }
 
-    /// Returns whether the SingleTarget matches a given [InfoHash]
+    /// Returns whether the SingleTarget matches a given [`InfoHash`](crate::hash::InfoHash).
+    ///
+    /// This is the matching logic used by [`TorrentList::get`](crate::list::TorrentList::get),
+    /// so you can use it to implement your own storage lookups with identical semantics:
+    ///   - a v1 infohash is matched by its full digest
+    ///   - a v2 infohash is matched by its full digest, or by its
+    ///     [`TorrentID`](crate::id::TorrentID) (truncated digest)
+    ///   - a hybrid infohash is matched by its full v2 digest, its TorrentID, or its full
+    ///     v1 digest
     pub fn matches_hash(&self, hash: &InfoHash) -> bool {
         match hash {
-            InfoHash::V1(h) => h.as_str() == self.as_str(),
+            InfoHash::V1(h) => *h == self.as_str(),
             InfoHash::Hybrid((v1, _v2)) => {
                 // Priority is given to matching v2, for more resilience to collision attacks
                 // but we can still match hybrid by infohash v1 SingleTarget
@@ -54,10 +62,23 @@ impl SingleTarget {
             }
             InfoHash::V2(h) => {
                 // For infohash v2 we check full form, but also truncated hash form
-                h.as_str() == self.as_str() || hash.id().as_str() == self.as_str()
+                *h == self.as_str() || hash.id().as_str() == self.as_str()
             }
         }
     }
+
+    /// Returns whether the SingleTarget matches a given [`TorrentID`](crate::id::TorrentID).
+    ///
+    /// A 40 characters SingleTarget matches the TorrentID with the same value, while a
+    /// 64 characters SingleTarget (infohash v2) matches the TorrentID it truncates to.
+    ///
+    /// Note that a TorrentID alone does not carry enough information to match a hybrid
+    /// torrent by its infohash v1: use
+    /// [`matches_hash`](crate::target::SingleTarget::matches_hash) when the full
+    /// [`InfoHash`](crate::hash::InfoHash) is known.
+    pub fn matches_id(&self, id: &TorrentID) -> bool {
+        self.truncated() == id.as_str()
+    }
 }
 
 impl std::fmt::Display for SingleTarget {
@@ -94,13 +115,13 @@ impl ToSingleTarget for SingleTarget {
 
 impl From<InfoHash> for SingleTarget {
     fn from(value: InfoHash) -> SingleTarget {
-        SingleTarget::new(value.as_str()).unwrap()
+        SingleTarget::new(&value.as_str()).unwrap()
     }
 }
 
 impl From<&InfoHash> for SingleTarget {
     fn from(value: &InfoHash) -> SingleTarget {
-        SingleTarget::new(value.as_str()).unwrap()
+        SingleTarget::new(&value.as_str()).unwrap()
     }
 }
 
@@ -116,18 +137,47 @@ impl From<&TorrentID> for SingleTarget {
     }
 }
 
+impl TryFrom<&str> for SingleTarget {
+    type Error = InfoHashError;
+
+    fn try_from(value: &str) -> Result<SingleTarget, Self::Error> {
+        SingleTarget::new(value)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 /// Criteria to filter a [`TorrentList`](crate::list::TorrentList), returning multiple entries.
 ///
 /// The following criteria are available:
 ///    - MultiTarget::All applies no filter
 ///    - MultiTarget::Hash filters a single torrent matching a given SingleTarget
+///    - MultiTarget::Category filters torrents in a given [`Category`](crate::category::Category),
+///      including its subcategories
+///    - MultiTarget::Tracker filters torrents using a given [`Tracker`](crate::tracker::Tracker)
 ///    - TODO: MultiTarget::Name
-///    - TODO: MultiTarget::Tracker
 ///    - TODO: AND/OR/XOR for multiple criteria
 pub enum MultiTarget {
     All,
     Hash(SingleTarget),
+    Category(Category),
+    Tracker(Tracker),
+}
+
+impl MultiTarget {
+    /// Returns whether a [`Torrent`](crate::torrent::Torrent) matches the MultiTarget.
+    ///
+    /// This is the matching logic used by [`TorrentList::filter`](crate::list::TorrentList::filter).
+    pub fn matches(&self, torrent: &Torrent) -> bool {
+        match self {
+            MultiTarget::All => true,
+            MultiTarget::Hash(target) => target.matches_hash(&torrent.hash),
+            MultiTarget::Category(category) => torrent
+                .category
+                .as_ref()
+                .map_or(false, |c| category.contains(c)),
+            MultiTarget::Tracker(tracker) => torrent.uses_tracker(tracker),
+        }
+    }
 }
 
 impl FromStr for MultiTarget {
@@ -154,7 +204,19 @@ impl TryFrom<&str> for MultiTarget {
 // Turn an InfoHash into a SingleTarget
 impl From<InfoHash> for MultiTarget {
     fn from(h: InfoHash) -> MultiTarget {
-        MultiTarget::Hash(SingleTarget::new(h.as_str()).unwrap())
+        MultiTarget::Hash(SingleTarget::new(&h.as_str()).unwrap())
+    }
+}
+
+impl From<&InfoHash> for MultiTarget {
+    fn from(h: &InfoHash) -> MultiTarget {
+        MultiTarget::Hash(SingleTarget::from(h))
+    }
+}
+
+impl From<TorrentID> for MultiTarget {
+    fn from(id: TorrentID) -> MultiTarget {
+        MultiTarget::Hash(SingleTarget::from(id))
     }
 }
 
@@ -164,8 +226,19 @@ impl From<SingleTarget> for MultiTarget {
     }
 }
 
-#[cfg(test)]
+impl From<Category> for MultiTarget {
+    fn from(value: Category) -> MultiTarget {
+        MultiTarget::Category(value)
+    }
+}
+
+impl From<Tracker> for MultiTarget {
+    fn from(value: Tracker) -> MultiTarget {
+        MultiTarget::Tracker(value)
+    }
+}
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
@@ -179,6 +252,49 @@ mod tests {
         assert_eq!(truncated, "abcdefabcdefabcdefabcdefabcdefabcdefabcd");
     }
 
+    #[test]
+    fn singletarget_matches_id() {
+        let hash =
+            InfoHash::new("caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e")
+                .unwrap();
+        let id = hash.id();
+
+        assert!(SingleTarget::from(&hash).matches_id(&id));
+        assert!(SingleTarget::from(&id).matches_id(&id));
+        assert!(
+            !SingleTarget::new("c811b41641a09d192b8ed81b14064fff55d85ce3")
+                .unwrap()
+                .matches_id(&id)
+        );
+    }
+
+    #[test]
+    fn singletarget_matches_hash() {
+        let hash = InfoHash::new("631a31dd0a46257d5078c0dee4e66e26f73e42ac")
+            .unwrap()
+            .hybrid(
+                &InfoHash::new("d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb")
+                    .unwrap(),
+            )
+            .unwrap();
+
+        assert!(
+            SingleTarget::new("631a31dd0a46257d5078c0dee4e66e26f73e42ac")
+                .unwrap()
+                .matches_hash(&hash)
+        );
+        assert!(
+            SingleTarget::new("d8dd32ac93357c368556af3ac1d95c9d76bd0dff")
+                .unwrap()
+                .matches_hash(&hash)
+        );
+        assert!(
+            !SingleTarget::new("c811b41641a09d192b8ed81b14064fff55d85ce3")
+                .unwrap()
+                .matches_hash(&hash)
+        );
+    }
+
     #[test]
     fn singletarget_ignores_casing() {
         assert_eq!(
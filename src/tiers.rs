@@ -0,0 +1,218 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use crate::Tracker;
+
+/// An ordered list of tracker tiers, as described in
+/// [BEP-0012](https://www.bittorrent.org/beps/bep_0012.html).
+///
+/// Trackers should be tried in order: all trackers of the first tier, then all trackers of the
+/// second tier, and so on. Trackers within a tier should be
+/// [`shuffle`](crate::tiers::TrackerTiers::shuffle)d before first use. When a tracker responds,
+/// it should be [`promote`](crate::tiers::TrackerTiers::promote)d to the front of its tier,
+/// while failing trackers can be moved to the back of their tier with
+/// [`mark_failed`](crate::tiers::TrackerTiers::mark_failed).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrackerTiers(Vec<Vec<Tracker>>);
+
+impl TrackerTiers {
+    pub fn new() -> TrackerTiers {
+        TrackerTiers(Vec::new())
+    }
+
+    /// Builds TrackerTiers from a list of tiers. Empty tiers are ignored.
+    pub fn from_tiers(tiers: Vec<Vec<Tracker>>) -> TrackerTiers {
+        let mut list = TrackerTiers::new();
+        for tier in tiers {
+            list.push_tier(tier);
+        }
+        list
+    }
+
+    /// Appends a tier with lower priority than existing tiers. Empty tiers are ignored.
+    pub fn push_tier(&mut self, tier: Vec<Tracker>) {
+        if !tier.is_empty() {
+            self.0.push(tier);
+        }
+    }
+
+    /// Returns the tiers, from highest to lowest priority.
+    pub fn tiers(&self) -> &[Vec<Tracker>] {
+        &self.0
+    }
+
+    /// Returns the number of tiers.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true when there are no trackers at all.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an iterator over all trackers, in the order they should be tried.
+    pub fn iter(&self) -> impl Iterator<Item = &Tracker> {
+        self.0.iter().flatten()
+    }
+
+    /// Randomly shuffles the trackers within each tier, as recommended by BEP-0012 before
+    /// the first announce. The order of the tiers is not changed.
+    pub fn shuffle(&mut self) {
+        let state = RandomState::new();
+        let mut counter: u64 = 0;
+        for (tier_index, tier) in self.0.iter_mut().enumerate() {
+            // Fisher-Yates shuffle
+            for i in (1..tier.len()).rev() {
+                let bound = i as u64 + 1;
+                // Values from the last incomplete range of the bound are rejected, so that
+                // every index is equally likely
+                let max = u64::MAX - (u64::MAX % bound + 1) % bound;
+                let j = loop {
+                    // Each draw hashes a different input, so that tiers of the same length
+                    // are not permuted the same way
+                    let mut hasher = state.build_hasher();
+                    hasher.write_usize(tier_index);
+                    hasher.write_u64(counter);
+                    counter += 1;
+                    let value = hasher.finish();
+                    if value <= max {
+                        break (value % bound) as usize;
+                    }
+                };
+                tier.swap(i, j);
+            }
+        }
+    }
+
+    /// Moves a working tracker to the front of its tier. Returns false if the tracker
+    /// was not found.
+    pub fn promote(&mut self, tracker: &Tracker) -> bool {
+        match self.position(tracker) {
+            Some((tier, pos)) => {
+                let tier = &mut self.0[tier];
+                let tracker = tier.remove(pos);
+                tier.insert(0, tracker);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves a failing tracker to the back of its tier, so that other trackers in the same
+    /// tier are tried first next time. Returns false if the tracker was not found.
+    pub fn mark_failed(&mut self, tracker: &Tracker) -> bool {
+        match self.position(tracker) {
+            Some((tier, pos)) => {
+                let tier = &mut self.0[tier];
+                let tracker = tier.remove(pos);
+                tier.push(tracker);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn position(&self, tracker: &Tracker) -> Option<(usize, usize)> {
+        self.0
+            .iter()
+            .enumerate()
+            .find_map(|(i, tier)| tier.iter().position(|t| t == tracker).map(|pos| (i, pos)))
+    }
+}
+
+impl From<Vec<Vec<Tracker>>> for TrackerTiers {
+    fn from(tiers: Vec<Vec<Tracker>>) -> TrackerTiers {
+        TrackerTiers::from_tiers(tiers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(url: &str) -> Tracker {
+        Tracker::new(url).unwrap()
+    }
+
+    fn dummy_tiers() -> TrackerTiers {
+        TrackerTiers::from_tiers(vec![
+            vec![
+                tracker("udp://a.example.com:6969/announce"),
+                tracker("udp://b.example.com:6969/announce"),
+                tracker("udp://c.example.com:6969/announce"),
+            ],
+            vec![],
+            vec![tracker("https://d.example.com/announce")],
+        ])
+    }
+
+    fn urls(tiers: &TrackerTiers) -> Vec<&str> {
+        tiers.iter().map(|t| t.url()).collect()
+    }
+
+    #[test]
+    fn iterates_in_tier_order() {
+        let tiers = dummy_tiers();
+        assert_eq!(tiers.len(), 2);
+        assert_eq!(
+            urls(&tiers),
+            vec![
+                "udp://a.example.com:6969/announce",
+                "udp://b.example.com:6969/announce",
+                "udp://c.example.com:6969/announce",
+                "https://d.example.com/announce",
+            ]
+        );
+    }
+
+    #[test]
+    fn can_promote_and_mark_failed() {
+        let mut tiers = dummy_tiers();
+        assert!(tiers.promote(&tracker("udp://c.example.com:6969/announce")));
+        assert!(tiers.mark_failed(&tracker("udp://b.example.com:6969/announce")));
+        assert!(!tiers.promote(&tracker("udp://e.example.com:6969/announce")));
+        assert_eq!(
+            urls(&tiers),
+            vec![
+                "udp://c.example.com:6969/announce",
+                "udp://a.example.com:6969/announce",
+                "udp://b.example.com:6969/announce",
+                "https://d.example.com/announce",
+            ]
+        );
+    }
+
+    #[test]
+    fn shuffle_keeps_tiers() {
+        let mut tiers = dummy_tiers();
+        tiers.shuffle();
+        assert_eq!(tiers.tiers()[0].len(), 3);
+        assert_eq!(
+            tiers.tiers()[1],
+            vec![tracker("https://d.example.com/announce")]
+        );
+        for t in dummy_tiers().tiers()[0].iter() {
+            assert!(tiers.tiers()[0].contains(t));
+        }
+    }
+
+    #[test]
+    fn shuffle_permutes_tiers_independently() {
+        // Two tiers of 12 trackers are only permuted the same way once in 12! shuffles
+        let tier = |host: &str| -> Vec<Tracker> {
+            (0..12)
+                .map(|i| tracker(&format!("udp://{host}{i}.example.com:6969")))
+                .collect()
+        };
+        let mut tiers = TrackerTiers::from_tiers(vec![tier("a"), tier("b")]);
+        tiers.shuffle();
+
+        let order = |tier: &[Tracker]| -> Vec<String> {
+            tier.iter()
+                .map(|t| t.host().unwrap()[1..].to_string())
+                .collect()
+        };
+        assert_ne!(order(&tiers.tiers()[0]), order(&tiers.tiers()[1]));
+    }
+}
@@ -0,0 +1,142 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A point in time, as a number of seconds since the Unix epoch (UTC).
+///
+/// The Timestamp is (de)serialized as an integer number of seconds. Conversions to and from
+/// [`OffsetDateTime`](https://docs.rs/time/latest/time/struct.OffsetDateTime.html) are provided
+/// behind the `time` crate feature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(transparent)]
+pub struct Timestamp(i64);
+
+impl Timestamp {
+    /// Creates a Timestamp from a number of seconds since the Unix epoch.
+    pub fn from_secs(secs: i64) -> Timestamp {
+        Timestamp(secs)
+    }
+
+    /// Creates a Timestamp from a number of seconds since the Unix epoch, where zero or
+    /// negative values mean "no date", as used by most torrent clients.
+    pub fn from_secs_opt(secs: i64) -> Option<Timestamp> {
+        if secs > 0 {
+            Some(Timestamp(secs))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the current time.
+    pub fn now() -> Timestamp {
+        Timestamp::from(SystemTime::now())
+    }
+
+    /// Returns the number of seconds since the Unix epoch.
+    pub fn as_secs(&self) -> i64 {
+        self.0
+    }
+}
+
+impl From<SystemTime> for Timestamp {
+    fn from(time: SystemTime) -> Timestamp {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => Timestamp(since.as_secs() as i64),
+            Err(e) => Timestamp(-(e.duration().as_secs() as i64)),
+        }
+    }
+}
+
+impl From<Timestamp> for SystemTime {
+    fn from(timestamp: Timestamp) -> SystemTime {
+        if timestamp.0 >= 0 {
+            UNIX_EPOCH + Duration::from_secs(timestamp.0 as u64)
+        } else {
+            UNIX_EPOCH - Duration::from_secs(timestamp.0.unsigned_abs())
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::OffsetDateTime> for Timestamp {
+    fn from(time: time::OffsetDateTime) -> Timestamp {
+        Timestamp(time.unix_timestamp())
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<Timestamp> for time::OffsetDateTime {
+    fn from(timestamp: Timestamp) -> time::OffsetDateTime {
+        // Timestamps out of the supported range of years are clamped
+        time::OffsetDateTime::from_unix_timestamp(timestamp.0).unwrap_or(if timestamp.0 < 0 {
+            time::PrimitiveDateTime::MIN.assume_utc()
+        } else {
+            time::PrimitiveDateTime::MAX.assume_utc()
+        })
+    }
+}
+
+/// Deserializes an optional [`Timestamp`](crate::timestamp::Timestamp), where zero or negative
+/// values mean "no date", for compatibility with torrents serialized with integer dates.
+pub(crate) fn deserialize_optional<'de, D>(deserializer: D) -> Result<Option<Timestamp>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let secs: Option<i64> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(secs.and_then(Timestamp::from_secs_opt))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_secs_opt() {
+        assert_eq!(
+            Timestamp::from_secs_opt(1603243361),
+            Some(Timestamp::from_secs(1603243361))
+        );
+        assert_eq!(Timestamp::from_secs_opt(0), None);
+        assert_eq!(Timestamp::from_secs_opt(-1), None);
+    }
+
+    #[test]
+    fn system_time_roundtrip() {
+        for secs in [0, 1603243361, -86400] {
+            let timestamp = Timestamp::from_secs(secs);
+            assert_eq!(Timestamp::from(SystemTime::from(timestamp)), timestamp);
+        }
+        assert!(Timestamp::now() > Timestamp::from_secs(1603243361));
+    }
+
+    #[test]
+    fn serde_compat() {
+        #[derive(Deserialize)]
+        struct Dates {
+            #[serde(default, deserialize_with = "deserialize_optional")]
+            date: Option<Timestamp>,
+        }
+
+        let parse = |json| serde_json::from_str::<Dates>(json).unwrap().date;
+        assert_eq!(
+            parse(r#"{"date": 1603243361}"#),
+            Timestamp::from_secs_opt(1603243361)
+        );
+        assert_eq!(parse(r#"{"date": 0}"#), None);
+        assert_eq!(parse(r#"{"date": -1}"#), None);
+        assert_eq!(parse(r#"{"date": null}"#), None);
+        assert_eq!(parse("{}"), None);
+
+        let json = serde_json::to_string(&Timestamp::from_secs(1603243361)).unwrap();
+        assert_eq!(json, "1603243361");
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_roundtrip() {
+        let timestamp = Timestamp::from_secs(1603243361);
+        let datetime = time::OffsetDateTime::from(timestamp);
+        assert_eq!(datetime.year(), 2020);
+        assert_eq!(datetime.month(), time::Month::October);
+        assert_eq!(Timestamp::from(datetime), timestamp);
+    }
+}
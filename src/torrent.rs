@@ -1,6 +1,38 @@
 use serde::Deserialize;
 
-use crate::{InfoHash, TorrentID};
+use crate::timestamp::deserialize_optional;
+use crate::{
+    Category, InfoHash, PeerSource, Progress, Timestamp, TorrentID, TorrentLimits, Tracker,
+};
+
+/// Error occurred while building a [`Torrent`](crate::torrent::Torrent) with a
+/// [`TorrentBuilder`](crate::torrent::TorrentBuilder).
+#[derive(Clone, Debug, PartialEq)]
+pub enum TorrentError {
+    MissingHash,
+    EmptyName,
+    InvalidProgress { bytes_done: u64, bytes_total: u64 },
+    NegativeSize { size: i64 },
+}
+
+impl std::fmt::Display for TorrentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TorrentError::MissingHash => write!(f, "Missing torrent infohash"),
+            TorrentError::EmptyName => write!(f, "Empty torrent name"),
+            TorrentError::InvalidProgress {
+                bytes_done,
+                bytes_total,
+            } => write!(
+                f,
+                "Invalid progress: {bytes_done} bytes done out of {bytes_total}"
+            ),
+            TorrentError::NegativeSize { size } => write!(f, "Negative torrent size: {size}"),
+        }
+    }
+}
+
+impl std::error::Error for TorrentError {}
 
 /// Turn a backend-specific torrent into an agnostic [`Torrent`](crate::torrent::Torrent).
 pub trait ToTorrent {
@@ -8,19 +40,38 @@ pub trait ToTorrent {
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 /// An abstract torrent, loaded from any backend that implements
 /// [ToTorrent](crate::torrent::ToTorrent).
 pub struct Torrent {
     //pub hash: TruncatedHash,
     pub name: String,
     pub path: String,
-    pub date_start: i64,
-    pub date_end: i64,
-    /// Progress percentage (0-100)
-    pub progress: u8,
+    /// When the torrent was added, if known
+    #[serde(default, deserialize_with = "deserialize_optional")]
+    pub date_start: Option<Timestamp>,
+    /// When the torrent was completed, or None when it is not finished yet
+    #[serde(default, deserialize_with = "deserialize_optional")]
+    pub date_end: Option<Timestamp>,
+    pub progress: Progress,
     pub size: i64,
+    /// Total bytes uploaded, when reported by the backend
+    #[serde(default)]
+    pub uploaded: Option<u64>,
+    /// Total bytes downloaded, when reported by the backend
+    #[serde(default)]
+    pub downloaded: Option<u64>,
     pub state: String,
     pub tags: Vec<String>,
+    /// The hierarchical category of the torrent, if any
+    #[serde(default)]
+    pub category: Option<Category>,
+    /// The sources of peers used by the torrent, such as its trackers
+    #[serde(default)]
+    pub sources: Vec<PeerSource>,
+    /// The bandwidth and seeding limits set in the torrent client
+    #[serde(default)]
+    pub limits: TorrentLimits,
     /// The infohash of this torrent
     pub hash: InfoHash,
     /// The libtorrent-compatible TorrentID
@@ -28,7 +79,199 @@ pub struct Torrent {
     pub id: TorrentID,
 }
 
+/// Turns trackers into a list of [`PeerSource`](crate::tracker::PeerSource)s, without duplicates.
+pub(crate) fn tracker_sources<I: IntoIterator<Item = Tracker>>(trackers: I) -> Vec<PeerSource> {
+    let mut sources: Vec<PeerSource> = Vec::new();
+    for tracker in trackers {
+        let source = tracker.to_peer_source();
+        if !sources.contains(&source) {
+            sources.push(source);
+        }
+    }
+    sources
+}
+
+/// A column of a table of torrents, as printed by
+/// [`TorrentList::fmt_table`](crate::list::TorrentList::fmt_table).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TorrentColumn {
+    /// The [`TorrentID`](crate::id::TorrentID), truncated to 8 characters
+    Id,
+    /// The full [`InfoHash`](crate::hash::InfoHash)
+    Hash,
+    Name,
+    /// The human-readable size, in binary units (KiB, MiB...)
+    Size,
+    Progress,
+    /// The share ratio, or `-` when unknown
+    Ratio,
+    State,
+    Path,
+    Tags,
+}
+
+impl TorrentColumn {
+    /// The columns printed by the [`Display`](std::fmt::Display) implementation of
+    /// [`TorrentList`](crate::list::TorrentList).
+    pub const DEFAULT: &'static [TorrentColumn] = &[
+        TorrentColumn::Id,
+        TorrentColumn::Name,
+        TorrentColumn::Size,
+        TorrentColumn::Progress,
+        TorrentColumn::State,
+    ];
+
+    /// Returns the header of the column.
+    pub fn header(&self) -> &'static str {
+        match self {
+            TorrentColumn::Id => "ID",
+            TorrentColumn::Hash => "HASH",
+            TorrentColumn::Name => "NAME",
+            TorrentColumn::Size => "SIZE",
+            TorrentColumn::Progress => "PROGRESS",
+            TorrentColumn::Ratio => "RATIO",
+            TorrentColumn::State => "STATE",
+            TorrentColumn::Path => "PATH",
+            TorrentColumn::Tags => "TAGS",
+        }
+    }
+
+    /// Returns whether values in the column are numbers, which are aligned to the right.
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            TorrentColumn::Size | TorrentColumn::Progress | TorrentColumn::Ratio
+        )
+    }
+}
+
+/// Formats a size in bytes with binary units, such as `1.5 GiB`.
+pub(crate) fn format_size(size: i64) -> String {
+    const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+
+    if size < 1024 {
+        return format!("{size} B");
+    }
+
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
 impl Torrent {
+    /// Returns a [`TorrentBuilder`](crate::torrent::TorrentBuilder) to create a validated Torrent.
+    ///
+    /// ```
+    /// use hightorrent::{InfoHash, Progress, Torrent};
+    ///
+    /// let torrent = Torrent::builder()
+    ///     .hash(InfoHash::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap())
+    ///     .name("Goldman, Emma - Essential Works of Anarchism")
+    ///     .size(4726342)
+    ///     .progress(Progress::new(4726342, 4726342))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(torrent.id.as_str(), "c811b41641a09d192b8ed81b14064fff55d85ce3");
+    /// ```
+    pub fn builder() -> TorrentBuilder {
+        TorrentBuilder::default()
+    }
+
+    /// Returns the value of the torrent for a given [`TorrentColumn`](crate::torrent::TorrentColumn),
+    /// as printed in a table.
+    pub fn cell(&self, column: TorrentColumn) -> String {
+        match column {
+            TorrentColumn::Id => self.id.as_str().chars().take(8).collect(),
+            TorrentColumn::Hash => self.hash.to_string(),
+            TorrentColumn::Name => self.name.clone(),
+            TorrentColumn::Size => format_size(self.size),
+            TorrentColumn::Progress => self.progress.to_string(),
+            TorrentColumn::Ratio => match self.ratio() {
+                Some(ratio) => format!("{ratio:.2}"),
+                None => "-".to_string(),
+            },
+            TorrentColumn::State => self.state.clone(),
+            TorrentColumn::Path => self.path.clone(),
+            TorrentColumn::Tags => self.tags.join(","),
+        }
+    }
+
+    /// Returns the share ratio, which is the uploaded bytes divided by the downloaded bytes.
+    ///
+    /// Returns None when the counters are not reported by the backend, or when nothing was
+    /// downloaded.
+    pub fn ratio(&self) -> Option<f64> {
+        match (self.uploaded, self.downloaded) {
+            (Some(uploaded), Some(downloaded)) if downloaded > 0 => {
+                Some(uploaded as f64 / downloaded as f64)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns whether the torrent uses a given [`Tracker`](crate::tracker::Tracker) as a
+    /// peer source. Trackers are compared by their normalized URL.
+    pub fn uses_tracker(&self, tracker: &Tracker) -> bool {
+        self.sources
+            .iter()
+            .any(|source| matches!(source, PeerSource::Tracker(t) if t == tracker))
+    }
+
+    /// Merges another entry for the same torrent into this one, such as the same torrent loaded
+    /// from two backends. Returns false, leaving the torrent unchanged, when the infohashes do
+    /// not [`match`](crate::hash::InfoHash::matches).
+    ///
+    /// The richer infohash is kept, so that merging a v1 entry with the hybrid entry it is part of
+    /// produces a hybrid entry. Other fields of this torrent are kept when they are set, and
+    /// filled from `other` otherwise. Tags and peer sources are combined without duplicates.
+    pub fn merge(&mut self, other: &Torrent) -> bool {
+        if !self.hash.matches(&other.hash) {
+            return false;
+        }
+
+        if other.hash.is_hybrid() {
+            self.hash = other.hash.clone();
+            self.id = self.hash.id();
+        }
+        if self.name.is_empty() {
+            self.name = other.name.clone();
+        }
+        if self.path.is_empty() {
+            self.path = other.path.clone();
+        }
+        if self.state.is_empty() {
+            self.state = other.state.clone();
+        }
+        if self.size == 0 {
+            self.size = other.size;
+        }
+        if self.progress == Progress::default() {
+            self.progress = other.progress;
+        }
+        self.date_start = self.date_start.or(other.date_start);
+        self.date_end = self.date_end.or(other.date_end);
+        self.uploaded = self.uploaded.or(other.uploaded);
+        self.downloaded = self.downloaded.or(other.downloaded);
+        if self.category.is_none() {
+            self.category = other.category.clone();
+        }
+        for tag in &other.tags {
+            if !self.tags.contains(tag) {
+                self.tags.push(tag.clone());
+            }
+        }
+        for source in &other.sources {
+            if !self.sources.contains(source) {
+                self.sources.push(source.clone());
+            }
+        }
+        true
+    }
+
     /// This method is only used for tests. It will not have any useful information
     /// except for the hash and id.
     #[allow(dead_code)]
@@ -36,14 +279,316 @@ impl Torrent {
         Torrent {
             name: String::new(),
             path: String::new(),
-            date_start: 0,
-            date_end: 0,
-            progress: 0,
+            date_start: None,
+            date_end: None,
+            progress: Progress::default(),
             size: 0,
+            uploaded: None,
+            downloaded: None,
             state: String::new(),
             tags: Vec::new(),
+            category: None,
+            sources: Vec::new(),
+            limits: TorrentLimits::default(),
             hash: hash.clone(),
             id: hash.id(),
         }
     }
 }
+
+/// A builder for [`Torrent`](crate::torrent::Torrent), created by
+/// [`Torrent::builder`](crate::torrent::Torrent::builder).
+///
+/// The [`TorrentID`](crate::id::TorrentID) is always derived from the
+/// [`InfoHash`](crate::hash::InfoHash), so that they cannot disagree. Building fails when the
+/// hash is missing, the name is empty, the size is negative, or the progress has more bytes
+/// done than its total.
+#[derive(Clone, Debug, Default)]
+pub struct TorrentBuilder {
+    hash: Option<InfoHash>,
+    name: String,
+    path: String,
+    date_start: Option<Timestamp>,
+    date_end: Option<Timestamp>,
+    progress: Progress,
+    size: i64,
+    uploaded: Option<u64>,
+    downloaded: Option<u64>,
+    state: String,
+    tags: Vec<String>,
+    category: Option<Category>,
+    sources: Vec<PeerSource>,
+    limits: TorrentLimits,
+}
+
+impl TorrentBuilder {
+    pub fn hash(mut self, hash: InfoHash) -> TorrentBuilder {
+        self.hash = Some(hash);
+        self
+    }
+
+    pub fn name(mut self, name: &str) -> TorrentBuilder {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn path(mut self, path: &str) -> TorrentBuilder {
+        self.path = path.to_string();
+        self
+    }
+
+    pub fn date_start(mut self, date: Timestamp) -> TorrentBuilder {
+        self.date_start = Some(date);
+        self
+    }
+
+    pub fn date_end(mut self, date: Timestamp) -> TorrentBuilder {
+        self.date_end = Some(date);
+        self
+    }
+
+    pub fn progress(mut self, progress: Progress) -> TorrentBuilder {
+        self.progress = progress;
+        self
+    }
+
+    pub fn size(mut self, size: i64) -> TorrentBuilder {
+        self.size = size;
+        self
+    }
+
+    pub fn uploaded(mut self, uploaded: u64) -> TorrentBuilder {
+        self.uploaded = Some(uploaded);
+        self
+    }
+
+    pub fn downloaded(mut self, downloaded: u64) -> TorrentBuilder {
+        self.downloaded = Some(downloaded);
+        self
+    }
+
+    pub fn state(mut self, state: &str) -> TorrentBuilder {
+        self.state = state.to_string();
+        self
+    }
+
+    pub fn tags(mut self, tags: Vec<String>) -> TorrentBuilder {
+        self.tags = tags;
+        self
+    }
+
+    pub fn category(mut self, category: Category) -> TorrentBuilder {
+        self.category = Some(category);
+        self
+    }
+
+    pub fn sources(mut self, sources: Vec<PeerSource>) -> TorrentBuilder {
+        self.sources = sources;
+        self
+    }
+
+    pub fn limits(mut self, limits: TorrentLimits) -> TorrentBuilder {
+        self.limits = limits;
+        self
+    }
+
+    /// Validates the fields and returns the [`Torrent`](crate::torrent::Torrent).
+    pub fn build(self) -> Result<Torrent, TorrentError> {
+        let hash = self.hash.ok_or(TorrentError::MissingHash)?;
+
+        if self.name.is_empty() {
+            return Err(TorrentError::EmptyName);
+        }
+
+        if self.size < 0 {
+            return Err(TorrentError::NegativeSize { size: self.size });
+        }
+
+        if self.progress.bytes_done > self.progress.bytes_total {
+            return Err(TorrentError::InvalidProgress {
+                bytes_done: self.progress.bytes_done,
+                bytes_total: self.progress.bytes_total,
+            });
+        }
+
+        Ok(Torrent {
+            name: self.name,
+            path: self.path,
+            date_start: self.date_start,
+            date_end: self.date_end,
+            progress: self.progress,
+            size: self.size,
+            uploaded: self.uploaded,
+            downloaded: self.downloaded,
+            state: self.state,
+            tags: self.tags,
+            category: self.category,
+            sources: self.sources,
+            limits: self.limits,
+            id: hash.id(),
+            hash,
+        })
+    }
+}
+
+/// Formats the torrent on a single line, such as `c811b416 Emma Goldman (1.5 GiB, 42%, downloading)`.
+impl std::fmt::Display for Torrent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} ({}, {}",
+            self.cell(TorrentColumn::Id),
+            self.name,
+            self.cell(TorrentColumn::Size),
+            self.cell(TorrentColumn::Progress),
+        )?;
+        if !self.state.is_empty() {
+            write!(f, ", {}", self.state)?;
+        }
+        write!(f, ")")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash() -> InfoHash {
+        InfoHash::new("d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb").unwrap()
+    }
+
+    #[test]
+    fn builder_derives_id() {
+        let torrent = Torrent::builder()
+            .hash(hash())
+            .name("bittorrent-v2-test")
+            .tags(vec!["test".to_string()])
+            .date_start(Timestamp::from_secs(1591173906))
+            .build()
+            .unwrap();
+        assert_eq!(torrent.id, hash().id());
+        assert_eq!(torrent.hash, hash());
+        assert_eq!(torrent.date_start, Some(Timestamp::from_secs(1591173906)));
+        assert_eq!(torrent.date_end, None);
+    }
+
+    #[test]
+    fn sources_default_in_serde() {
+        let torrent = Torrent::dummy_from_hash(&hash());
+        let mut json = serde_json::to_value(&torrent).unwrap();
+        assert_eq!(json["sources"], serde_json::json!([]));
+
+        json.as_object_mut().unwrap().remove("sources");
+        let torrent: Torrent = serde_json::from_value(json).unwrap();
+        assert!(torrent.sources.is_empty());
+
+        let torrent = Torrent::builder()
+            .hash(hash())
+            .name("test")
+            .sources(vec![PeerSource::DHT])
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&torrent).unwrap()["sources"],
+            serde_json::json!(["dht"])
+        );
+        assert!(!torrent
+            .uses_tracker(&Tracker::new("udp://tracker.opentrackr.org:1337/announce").unwrap()));
+    }
+
+    #[test]
+    fn computes_ratio() {
+        let builder = Torrent::builder().hash(hash()).name("test");
+        let torrent = builder
+            .clone()
+            .uploaded(300)
+            .downloaded(200)
+            .build()
+            .unwrap();
+        assert_eq!(torrent.ratio(), Some(1.5));
+        assert_eq!(torrent.cell(TorrentColumn::Ratio), "1.50");
+
+        let torrent = builder.clone().uploaded(300).downloaded(0).build().unwrap();
+        assert_eq!(torrent.ratio(), None);
+        let torrent = builder.uploaded(300).build().unwrap();
+        assert_eq!(torrent.ratio(), None);
+        assert_eq!(torrent.cell(TorrentColumn::Ratio), "-");
+    }
+
+    #[test]
+    fn merges_into_hybrid() {
+        let hybrid = InfoHash::new("631a31dd0a46257d5078c0dee4e66e26f73e42ac")
+            .unwrap()
+            .hybrid(&hash())
+            .unwrap();
+        let mut torrent = Torrent::builder()
+            .hash(hash())
+            .name("bittorrent-v1-v2-hybrid-test")
+            .tags(vec!["a".to_string()])
+            .build()
+            .unwrap();
+        let other = Torrent::builder()
+            .hash(hybrid.clone())
+            .name("other name")
+            .path("/downloads")
+            .tags(vec!["a".to_string(), "b".to_string()])
+            .category(Category::new("linux/distros").unwrap())
+            .uploaded(42)
+            .build()
+            .unwrap();
+
+        assert!(torrent.merge(&other));
+        assert_eq!(torrent.hash, hybrid);
+        assert_eq!(torrent.id, hybrid.id());
+        assert_eq!(torrent.name, "bittorrent-v1-v2-hybrid-test");
+        assert_eq!(torrent.path, "/downloads");
+        assert_eq!(torrent.tags, ["a", "b"]);
+        assert_eq!(
+            torrent.category,
+            Some(Category::new("linux/distros").unwrap())
+        );
+        assert_eq!(torrent.uploaded, Some(42));
+
+        let unrelated = Torrent::dummy_from_hash(
+            &InfoHash::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap(),
+        );
+        assert!(!torrent.merge(&unrelated));
+        assert_eq!(torrent.hash, hybrid);
+    }
+
+    #[test]
+    fn builder_validates() {
+        assert_eq!(
+            Torrent::builder().name("test").build().unwrap_err(),
+            TorrentError::MissingHash
+        );
+        assert_eq!(
+            Torrent::builder().hash(hash()).build().unwrap_err(),
+            TorrentError::EmptyName
+        );
+        assert_eq!(
+            Torrent::builder()
+                .hash(hash())
+                .name("test")
+                .size(-1)
+                .build()
+                .unwrap_err(),
+            TorrentError::NegativeSize { size: -1 }
+        );
+        assert_eq!(
+            Torrent::builder()
+                .hash(hash())
+                .name("test")
+                .progress(Progress {
+                    bytes_done: 101,
+                    bytes_total: 100
+                })
+                .build()
+                .unwrap_err(),
+            TorrentError::InvalidProgress {
+                bytes_done: 101,
+                bytes_total: 100
+            }
+        );
+    }
+}
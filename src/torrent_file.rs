@@ -1,21 +1,91 @@
-use bt_bencode::Value as BencodeValue;
+use bt_bencode::value::Number;
+use bt_bencode::{ByteString, Value as BencodeValue};
+use once_cell::sync::OnceCell;
 use rustc_hex::ToHex;
 use serde::{Deserialize, Serialize};
 use sha1::{Digest, Sha1};
+use url::Url;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
 
-use crate::{InfoHash, InfoHashError, TorrentID};
+use crate::bencode::{nesting_depth, DictRef};
+use crate::content::{v1_files, v2_files, DirNode, SafePath, TorrentContent};
+use crate::magnet::parse_web_seed;
+use crate::torrent::tracker_sources;
+use crate::validation::{
+    check_piece_layers, hybrid_mismatches, validate_torrent, HybridMismatch, ValidationIssue,
+    ValidationReport,
+};
+use crate::{
+    FileSelection, FileSelectionError, HexString, InfoHash, InfoHashError, Pattern, Progress,
+    SingleTarget, ToTorrent, Torrent, TorrentID, TorrentLimits, Tracker, TrackerParseOptions,
+    TrackerTiers,
+};
 
 /// Error occurred during parsing a [`TorrentFile`](crate::torrent_file::TorrentFile).
 #[derive(Clone, Debug, PartialEq)]
 pub enum TorrentFileError {
     NoNameFound,
     // TODO: bt_bencode::Error is not PartialEq so we store error as String
-    InvalidBencode { reason: String },
-    NotATorrent { reason: String },
-    WrongVersion { version: u64 },
-    InvalidHash { source: InfoHashError },
+    InvalidBencode {
+        reason: String,
+    },
+    NotATorrent {
+        reason: String,
+    },
+    WrongVersion {
+        version: u64,
+    },
+    InvalidHash {
+        source: InfoHashError,
+    },
+    MissingAnnounce,
+    UnsafePath {
+        path: String,
+    },
+    InvalidPieceLength {
+        length: i64,
+    },
+    InvalidPieceLayers {
+        reason: String,
+    },
+    /// The torrent file could not be read.
+    // std::io::Error is not Clone/PartialEq so we store its kind and message
+    Io {
+        kind: std::io::ErrorKind,
+        reason: String,
+    },
+    /// The torrent file is larger than the maximum size allowed.
+    TooLarge {
+        size: u64,
+        max: u64,
+    },
+    /// The torrent contains more files than allowed by
+    /// [`ParseOptions::max_files`](crate::torrent_file::ParseOptions::max_files).
+    TooManyFiles {
+        count: usize,
+        max: usize,
+    },
+    /// A file path has more components than allowed by
+    /// [`ParseOptions::max_path_depth`](crate::torrent_file::ParseOptions::max_path_depth).
+    PathTooDeep {
+        path: String,
+        depth: usize,
+        max: usize,
+    },
+    /// The torrent contains more trackers than allowed by
+    /// [`ParseOptions::max_trackers`](crate::torrent_file::ParseOptions::max_trackers).
+    TooManyTrackers {
+        count: usize,
+        max: usize,
+    },
+    /// Bencoded lists and dicts are nested deeper than allowed by
+    /// [`ParseOptions::max_nesting_depth`](crate::torrent_file::ParseOptions::max_nesting_depth).
+    TooDeep {
+        depth: usize,
+        max: usize,
+    },
 }
 
 impl std::fmt::Display for TorrentFileError {
@@ -32,6 +102,36 @@ impl std::fmt::Display for TorrentFileError {
                 "Wrong torrent version: {version}, only v1 and v2 are supported)"
             ),
             TorrentFileError::InvalidHash { source } => write!(f, "Invalid hash: {source}"),
+            TorrentFileError::MissingAnnounce => write!(f, "No tracker announce URL found"),
+            TorrentFileError::UnsafePath { path } => write!(f, "Unsafe file path: {path}"),
+            TorrentFileError::InvalidPieceLength { length } => write!(
+                f,
+                "Invalid piece length (expected a positive power of two): {length}"
+            ),
+            TorrentFileError::InvalidPieceLayers { reason } => {
+                write!(f, "Invalid piece layers: {reason}")
+            }
+            TorrentFileError::Io { kind: _, reason } => {
+                write!(f, "Failed to read torrent file: {reason}")
+            }
+            TorrentFileError::TooLarge { size, max } => {
+                write!(f, "Torrent file too large: {size} bytes (max {max})")
+            }
+            TorrentFileError::TooManyFiles { count, max } => {
+                write!(f, "Too many files in torrent: {count} (max {max})")
+            }
+            TorrentFileError::PathTooDeep { path, depth, max } => {
+                write!(
+                    f,
+                    "File path too deep: {path} has {depth} components (max {max})"
+                )
+            }
+            TorrentFileError::TooManyTrackers { count, max } => {
+                write!(f, "Too many trackers in torrent: {count} (max {max})")
+            }
+            TorrentFileError::TooDeep { depth, max } => {
+                write!(f, "Bencode nested too deep: {depth} levels (max {max})")
+            }
         }
     }
 }
@@ -50,6 +150,15 @@ impl From<bt_bencode::Error> for TorrentFileError {
     }
 }
 
+impl From<std::io::Error> for TorrentFileError {
+    fn from(e: std::io::Error) -> TorrentFileError {
+        TorrentFileError::Io {
+            kind: e.kind(),
+            reason: e.to_string(),
+        }
+    }
+}
+
 impl std::error::Error for TorrentFileError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
@@ -59,71 +168,433 @@ impl std::error::Error for TorrentFileError {
     }
 }
 
+/// Options controlling how strictly a [`TorrentFile`](crate::torrent_file::TorrentFile) is
+/// parsed with [`TorrentFile::from_slice_with`](crate::torrent_file::TorrentFile::from_slice_with).
+///
+/// The [`Default`](std::default::Default) options are the ones used by
+/// [`TorrentFile::from_slice`](crate::torrent_file::TorrentFile::from_slice). For finer checks
+/// which do not fail parsing, see [`TorrentFile::validate`](crate::torrent_file::TorrentFile::validate).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Fail with [`TorrentFileError::WrongVersion`](crate::torrent_file::TorrentFileError::WrongVersion)
+    /// when the meta version is neither 1 nor 2. When disabled, the torrent version is guessed from
+    /// the fields present in the info dict. Enabled by default.
+    pub reject_unknown_version: bool,
+    /// Fail with [`TorrentFileError::MissingAnnounce`](crate::torrent_file::TorrentFileError::MissingAnnounce)
+    /// when the torrent has no tracker. Disabled by default, because trackerless torrents are valid.
+    pub require_announce: bool,
+    /// Fail with [`TorrentFileError::UnsafePath`](crate::torrent_file::TorrentFileError::UnsafePath)
+    /// when a file path contains empty, `.` or `..` components, or components with `/` or NUL
+    /// characters. Disabled by default.
+    pub reject_unsafe_paths: bool,
+    /// Fail with [`TorrentFileError::InvalidPieceLength`](crate::torrent_file::TorrentFileError::InvalidPieceLength)
+    /// when the piece length is missing or not a power of two. Disabled by default.
+    pub require_power_of_two_piece_length: bool,
+    /// Fail with [`TorrentFileError::InvalidPieceLayers`](crate::torrent_file::TorrentFileError::InvalidPieceLayers)
+    /// when the `piece layers` of a v2 or hybrid torrent do not match its file tree, as required by
+    /// BEP-0052. Disabled by default, because torrents built from magnet metadata have no piece
+    /// layers.
+    pub validate_piece_layers: bool,
+    /// Defer computing the infohash until it is first needed, for example when listing many
+    /// torrents by name only. Structural checks still happen during parsing. Disabled by default.
+    pub lazy_hash: bool,
+    /// Keep trackers with an unknown scheme, as described in
+    /// [`TrackerParseOptions::allow_unknown_schemes`](crate::tracker::TrackerParseOptions::allow_unknown_schemes).
+    /// When disabled, they are ignored like other invalid trackers. Disabled by default.
+    pub allow_unknown_tracker_schemes: bool,
+    /// Fail with [`TorrentFileError::TooLarge`](crate::torrent_file::TorrentFileError::TooLarge)
+    /// when the torrent is larger than this size, in bytes. Defaults to
+    /// [`TorrentFile::MAX_FILE_SIZE`](crate::torrent_file::TorrentFile::MAX_FILE_SIZE).
+    pub max_size: Option<u64>,
+    /// Fail with [`TorrentFileError::TooManyFiles`](crate::torrent_file::TorrentFileError::TooManyFiles)
+    /// when the torrent lists more files, including padding files. Defaults to
+    /// [`DEFAULT_MAX_FILES`](crate::torrent_file::ParseOptions::DEFAULT_MAX_FILES).
+    pub max_files: Option<usize>,
+    /// Fail with [`TorrentFileError::PathTooDeep`](crate::torrent_file::TorrentFileError::PathTooDeep)
+    /// when a file path, including the torrent name, has more components. Defaults to
+    /// [`DEFAULT_MAX_PATH_DEPTH`](crate::torrent_file::ParseOptions::DEFAULT_MAX_PATH_DEPTH).
+    pub max_path_depth: Option<usize>,
+    /// Fail with [`TorrentFileError::TooManyTrackers`](crate::torrent_file::TorrentFileError::TooManyTrackers)
+    /// when the `announce-list` has more trackers. Defaults to
+    /// [`DEFAULT_MAX_TRACKERS`](crate::torrent_file::ParseOptions::DEFAULT_MAX_TRACKERS).
+    pub max_trackers: Option<usize>,
+    /// Fail with [`TorrentFileError::TooDeep`](crate::torrent_file::TorrentFileError::TooDeep)
+    /// when bencoded lists and dicts are nested deeper, before decoding them. Defaults to
+    /// [`DEFAULT_MAX_NESTING_DEPTH`](crate::torrent_file::ParseOptions::DEFAULT_MAX_NESTING_DEPTH).
+    /// Unlike other limits, it cannot be lifted, because nested values are decoded recursively
+    /// and would overflow the stack.
+    pub max_nesting_depth: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions {
+            reject_unknown_version: true,
+            require_announce: false,
+            reject_unsafe_paths: false,
+            require_power_of_two_piece_length: false,
+            validate_piece_layers: false,
+            lazy_hash: false,
+            allow_unknown_tracker_schemes: false,
+            max_size: Some(TorrentFile::MAX_FILE_SIZE),
+            max_files: Some(ParseOptions::DEFAULT_MAX_FILES),
+            max_path_depth: Some(ParseOptions::DEFAULT_MAX_PATH_DEPTH),
+            max_trackers: Some(ParseOptions::DEFAULT_MAX_TRACKERS),
+            max_nesting_depth: ParseOptions::DEFAULT_MAX_NESTING_DEPTH,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Default for [`max_files`](crate::torrent_file::ParseOptions::max_files).
+    pub const DEFAULT_MAX_FILES: usize = 1_000_000;
+    /// Default for [`max_path_depth`](crate::torrent_file::ParseOptions::max_path_depth).
+    pub const DEFAULT_MAX_PATH_DEPTH: usize = 256;
+    /// Default for [`max_trackers`](crate::torrent_file::ParseOptions::max_trackers).
+    pub const DEFAULT_MAX_TRACKERS: usize = 1_000;
+    /// Default for [`max_nesting_depth`](crate::torrent_file::ParseOptions::max_nesting_depth),
+    /// which leaves room for v2 file trees with the maximum path depth.
+    pub const DEFAULT_MAX_NESTING_DEPTH: usize = 512;
+
+    /// Options enabling all checks, for validators.
+    pub fn strict() -> ParseOptions {
+        ParseOptions {
+            reject_unknown_version: true,
+            require_announce: true,
+            reject_unsafe_paths: true,
+            require_power_of_two_piece_length: true,
+            validate_piece_layers: true,
+            lazy_hash: false,
+            allow_unknown_tracker_schemes: false,
+            max_size: Some(TorrentFile::MAX_FILE_SIZE),
+            max_files: Some(ParseOptions::DEFAULT_MAX_FILES),
+            max_path_depth: Some(ParseOptions::DEFAULT_MAX_PATH_DEPTH),
+            max_trackers: Some(ParseOptions::DEFAULT_MAX_TRACKERS),
+            max_nesting_depth: ParseOptions::DEFAULT_MAX_NESTING_DEPTH,
+        }
+    }
+
+    /// Options disabling all checks, for archives. Only the name and infohash are required.
+    pub fn lenient() -> ParseOptions {
+        ParseOptions {
+            reject_unknown_version: false,
+            require_announce: false,
+            reject_unsafe_paths: false,
+            require_power_of_two_piece_length: false,
+            validate_piece_layers: false,
+            lazy_hash: false,
+            allow_unknown_tracker_schemes: true,
+            max_size: Some(TorrentFile::MAX_FILE_SIZE),
+            max_files: Some(ParseOptions::DEFAULT_MAX_FILES),
+            max_path_depth: Some(ParseOptions::DEFAULT_MAX_PATH_DEPTH),
+            max_trackers: Some(ParseOptions::DEFAULT_MAX_TRACKERS),
+            max_nesting_depth: ParseOptions::DEFAULT_MAX_NESTING_DEPTH,
+        }
+    }
+}
+
 /// A torrent file.
 ///
 /// The torrent file specification and related extensions are described on [Wikipedia](https://en.wikipedia.org/wiki/Torrent_file).
 /// The TorrentFile can provide information about the torrent
 /// [`name`](crate::torrent_file::TorrentFile::name) and
-/// [`hash`](crate::torrent_file::TorrentFile::hash). Other fields could be supported, but are not
-/// currently implemented by this library.
+/// [`hash`](crate::torrent_file::TorrentFile::hash), as well as the
+/// [`tracker_tiers`](crate::torrent_file::TorrentFile::tracker_tiers), the
+/// [`web_seeds`](crate::torrent_file::TorrentFile::web_seeds) and
+/// [`http_seeds`](crate::torrent_file::TorrentFile::http_seeds) and some optional metadata
+/// such as the [`comment`](crate::torrent_file::TorrentFile::comment) or
+/// [`creation_date`](crate::torrent_file::TorrentFile::creation_date). Other fields could be
+/// supported, but are not currently implemented by this library.
 ///
-/// TODO: Implement files() method to return list of files
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// The [`files`](crate::torrent_file::TorrentFile::files) contained in the torrent can be listed,
+/// and the TorrentFile can also be [`validate`](crate::torrent_file::TorrentFile::validate)d
+/// further than what is required for parsing.
+///
+/// A TorrentFile is (de)serialized as its original bytes, which are parsed again with
+/// [`TorrentFile::from_slice`](crate::torrent_file::TorrentFile::from_slice) when deserializing.
+#[derive(Clone, Debug)]
 pub struct TorrentFile {
-    hash: InfoHash,
+    hash: OnceCell<InfoHash>,
+    // Position of the info dict in the original bytes, which are hashed as-is
+    info_span: Range<usize>,
+    version: Option<u64>,
     name: String,
+    tracker_tiers: TrackerTiers,
+    web_seeds: Vec<Url>,
+    http_seeds: Vec<Url>,
+    dht_nodes: Vec<(String, u16)>,
+    comment: Option<String>,
+    creation_date: Option<i64>,
+    created_by: Option<String>,
+    encoding: Option<String>,
+    decoded: DecodedTorrent,
+    bytes: Vec<u8>,
 }
 
 /// A parsed bencode-decoded value, to ensure torrent-like structure.
 ///
-/// In its present form, DecodedTorrent only cares about the info dict, but preserves other fields
-/// as [`BencodeValue`](bt_bencode::BencodeValue) in an `extra` mapping so you can implement
-/// your own extra parsing.
+/// In its present form, DecodedTorrent only cares about the info dict and a few optional metadata
+/// fields, but preserves other fields as [`BencodeValue`](bt_bencode::Value) in an `extra`
+/// mapping so you can implement your own extra parsing, with getters such as
+/// [`extra_str`](crate::torrent_file::DecodedTorrent::extra_str).
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DecodedTorrent {
-    info: DecodedInfo,
+    pub(crate) info: DecodedInfo,
+
+    // Main tracker, only used when announce-list is absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) announce: Option<ByteString>,
+
+    // BEP-0012 tracker tiers, as a list of lists of URLs
+    #[serde(rename = "announce-list")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) announce_list: Option<BencodeValue>,
+
+    // BEP-0019 web seeds, either a single URL or a list of URLs
+    #[serde(rename = "url-list")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) url_list: Option<BencodeValue>,
+
+    // BEP-0017 HTTP seeds, as a list of URLs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) httpseeds: Option<BencodeValue>,
+
+    // BEP-0005 DHT bootstrap nodes, as a list of [host, port] lists
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) nodes: Option<BencodeValue>,
+
+    // Free-form metadata, kept as raw values so that torrents with unexpected types still
+    // parse and roundtrip. Strings may not be UTF-8.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) comment: Option<BencodeValue>,
+
+    // Unix timestamp (in seconds)
+    #[serde(rename = "creation date")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) creation_date: Option<BencodeValue>,
+
+    #[serde(rename = "created by")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) created_by: Option<BencodeValue>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) encoding: Option<BencodeValue>,
 
     // Rest of torrent dict
     #[serde(flatten)]
     #[serde(skip_serializing_if = "HashMap::is_empty")]
-    extra: HashMap<String, BencodeValue>,
+    pub(crate) extra: HashMap<String, BencodeValue>,
 }
 
 /// An info dict contained in a [`DecodedTorrent`](crate::torrent_file::DecodedTorrent).
 ///
 /// Only cares about torrent version, name, and files, but other fields are preseved in an `extra`
-/// mapping so you can implement your own extra parsing.
+/// mapping so you can implement your own extra parsing, with getters such as
+/// [`extra_int`](crate::torrent_file::DecodedInfo::extra_int).
 // bt_bencode does not support serializing None options and empty HashMaps, so we skip
 // serialization in those cases.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DecodedInfo {
     #[serde(rename = "meta version")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    version: Option<u64>,
+    pub(crate) version: Option<u64>,
 
-    name: String,
+    pub(crate) name: String,
 
     // Torrent v1/hybrid (only for single-file torrents)
     #[serde(skip_serializing_if = "Option::is_none")]
-    length: Option<u64>,
+    pub(crate) length: Option<u64>,
 
     // Torrent v1 (only for multi-files torrents)
     #[serde(skip_serializing_if = "Option::is_none")]
-    files: Option<Vec<BencodeValue>>,
+    pub(crate) files: Option<Vec<BencodeValue>>,
 
     // Torrent v2 (for both single and multi-files torrents)
     #[serde(rename = "file tree")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    file_tree: Option<BencodeValue>,
+    pub(crate) file_tree: Option<BencodeValue>,
 
     // Rest of info dict that we keep for hashing
     #[serde(flatten)]
     #[serde(skip_serializing_if = "HashMap::is_empty")]
-    extra: HashMap<String, BencodeValue>,
+    pub(crate) extra: HashMap<String, BencodeValue>,
+}
+
+/// Implements typed getters for the `extra` fields of a decoded dict.
+macro_rules! impl_extra_getters {
+    ($type:ty) => {
+        impl $type {
+            /// Returns the raw value of a field which is not otherwise parsed by this library.
+            pub fn extra(&self, key: &str) -> Option<&BencodeValue> {
+                self.extra.get(key)
+            }
+
+            /// Returns an extra field as a string, if it is a valid UTF-8 byte string.
+            pub fn extra_str(&self, key: &str) -> Option<&str> {
+                self.extra(key)?.as_str()
+            }
+
+            /// Returns an extra field as an integer.
+            pub fn extra_int(&self, key: &str) -> Option<i64> {
+                self.extra(key).and_then(bencode_i64)
+            }
+
+            /// Returns an extra field as a list.
+            pub fn extra_list(&self, key: &str) -> Option<&[BencodeValue]> {
+                self.extra(key)?.as_list().map(|l| l.as_slice())
+            }
+
+            /// Returns an extra field as a dict.
+            pub fn extra_dict(&self, key: &str) -> Option<&BTreeMap<ByteString, BencodeValue>> {
+                self.extra(key)?.as_dict()
+            }
+        }
+    };
+}
+
+impl_extra_getters!(DecodedTorrent);
+impl_extra_getters!(DecodedInfo);
+
+impl DecodedTorrent {
+    /// Returns the info dict of the torrent.
+    pub fn info(&self) -> &DecodedInfo {
+        &self.info
+    }
+}
+
+/// A [BEP-0035](https://www.bittorrent.org/beps/bep_0035.html) signature of a
+/// [`TorrentFile`](crate::torrent_file::TorrentFile), from its `signatures` dict.
+///
+/// This library does not verify signatures, which would require a crypto suite. The raw fields
+/// are exposed so that they can be checked with
+/// [`TorrentFile::verify_with`](crate::torrent_file::TorrentFile::verify_with).
+#[derive(Clone, Debug, PartialEq)]
+pub struct TorrentSignature {
+    signer: String,
+    signature: Vec<u8>,
+    certificate: Option<Vec<u8>>,
+    info: Option<BencodeValue>,
+}
+
+impl TorrentSignature {
+    /// Returns the identity of the signer, which is the key in the `signatures` dict.
+    pub fn signer(&self) -> &str {
+        &self.signer
+    }
+
+    /// Returns the raw signature.
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    /// Returns the raw certificate of the signer, if it is embedded in the torrent.
+    pub fn certificate(&self) -> Option<&[u8]> {
+        self.certificate.as_deref()
+    }
+
+    /// Returns the additional info dict covered by the signature, if any.
+    pub fn info(&self) -> Option<&BencodeValue> {
+        self.info.as_ref()
+    }
 }
 
 impl TorrentFile {
+    /// The maximum size of a torrent file read by
+    /// [`from_path`](crate::torrent_file::TorrentFile::from_path) (64 MiB). Torrents are usually
+    /// much smaller, but the piece layers of large v2 torrents take several megabytes.
+    pub const MAX_FILE_SIZE: u64 = 64 * 1024 * 1024;
+
     pub fn from_slice(s: &[u8]) -> Result<TorrentFile, TorrentFileError> {
+        TorrentFile::from_slice_with(s, ParseOptions::default())
+    }
+
+    /// Reads and parses a torrent file. Files larger than
+    /// [`MAX_FILE_SIZE`](crate::torrent_file::TorrentFile::MAX_FILE_SIZE) are refused with
+    /// [`TorrentFileError::TooLarge`](crate::torrent_file::TorrentFileError::TooLarge) before
+    /// being read.
+    ///
+    /// ```
+    /// use hightorrent::TorrentFile;
+    ///
+    /// let torrent = TorrentFile::from_path("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+    /// assert_eq!(torrent.hash(), "c811b41641a09d192b8ed81b14064fff55d85ce3");
+    /// ```
+    pub fn from_path(path: impl AsRef<std::path::Path>) -> Result<TorrentFile, TorrentFileError> {
+        use std::io::Read;
+
+        let file = std::fs::File::open(path)?;
+        check_file_size(file.metadata()?.len())?;
+        let mut bytes = Vec::new();
+        // The file may grow after its size was checked
+        file.take(TorrentFile::MAX_FILE_SIZE + 1)
+            .read_to_end(&mut bytes)?;
+        check_file_size(bytes.len() as u64)?;
+        TorrentFile::from_slice(&bytes)
+    }
+
+    /// Reads and parses a torrent file asynchronously, like
+    /// [`from_path`](crate::torrent_file::TorrentFile::from_path).
+    #[cfg(feature = "tokio")]
+    pub async fn from_path_async(
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<TorrentFile, TorrentFileError> {
+        use tokio::io::AsyncReadExt;
+
+        let file = tokio::fs::File::open(path).await?;
+        check_file_size(file.metadata().await?.len())?;
+        let mut bytes = Vec::new();
+        file.take(TorrentFile::MAX_FILE_SIZE + 1)
+            .read_to_end(&mut bytes)
+            .await?;
+        check_file_size(bytes.len() as u64)?;
+        TorrentFile::from_slice(&bytes)
+    }
+
+    /// Reads and parses many torrent files, like
+    /// [`from_path`](crate::torrent_file::TorrentFile::from_path), for example when importing a
+    /// watch folder. Results are returned in the order of the paths, and each error comes with
+    /// the path of the file which failed, so that one invalid file does not stop the import.
+    ///
+    /// With the `rayon` feature, the files are read and parsed in parallel.
+    ///
+    /// ```
+    /// use hightorrent::TorrentFile;
+    ///
+    /// let results = TorrentFile::parse_many(&[
+    ///     "tests/bittorrent-v1-emma-goldman.torrent",
+    ///     "tests/missing.torrent",
+    /// ]);
+    /// assert!(results[0].is_ok());
+    /// let (path, _error) = results[1].as_ref().unwrap_err();
+    /// assert!(path.ends_with("missing.torrent"));
+    /// ```
+    pub fn parse_many<P: AsRef<std::path::Path> + Sync>(
+        paths: &[P],
+    ) -> Vec<Result<TorrentFile, (std::path::PathBuf, TorrentFileError)>> {
+        map_many(paths, |path| {
+            TorrentFile::from_path(path).map_err(|e| (path.as_ref().to_path_buf(), e))
+        })
+    }
+
+    /// Parses many torrents from bytes, like
+    /// [`parse_many`](crate::torrent_file::TorrentFile::parse_many) does for paths. Each error
+    /// comes with the index of the slice which failed.
+    pub fn parse_many_slices<S: AsRef<[u8]> + Sync>(
+        slices: &[S],
+    ) -> Vec<Result<TorrentFile, (usize, TorrentFileError)>> {
+        let indexed: Vec<(usize, &S)> = slices.iter().enumerate().collect();
+        map_many(&indexed, |(index, slice)| {
+            TorrentFile::from_slice(slice.as_ref()).map_err(|e| (*index, e))
+        })
+    }
+
+    /// Parses a TorrentFile like [`from_slice`](crate::torrent_file::TorrentFile::from_slice),
+    /// with custom [`ParseOptions`](crate::torrent_file::ParseOptions) strictness.
+    pub fn from_slice_with(
+        s: &[u8],
+        options: ParseOptions,
+    ) -> Result<TorrentFile, TorrentFileError> {
+        check_input_limits(s, &options)?;
         let torrent: DecodedTorrent = bt_bencode::from_slice(s).map_err(|e| {
             // We store a stringy representation of the error because bt_encode::Error
             // is not PartialEq
@@ -131,56 +602,155 @@ impl TorrentFile {
                 reason: e.to_string(),
             }
         })?;
+        check_torrent_limits(&torrent, &options)?;
 
-        // We just deserialized successfully so this is a safe unwrap
-        // Unless we added an Option/HashMap and forgot to skip serialization when empty
-        let info_bytes = bt_bencode::to_vec(&torrent.info).unwrap();
-
-        let infohash = match torrent.info.version {
-            // Most v1 torrents don't declare a torrent version at all
-            Some(1) | None => {
-                // Bittorrent v1 does not necessarily have a files dict... single-file torrents
-                // just use the torrent name field for that
-                let digest = Sha1::digest(&info_bytes).to_vec().to_hex::<String>();
-                InfoHash::new(&digest)?
-            }
-            Some(2) => {
-                // Bittorrent v2 has mandatory file_tree dict
-                // see http://bittorrent.org/beps/bep_0052.html
-                if torrent.info.file_tree.is_some() {
-                    let digest = sha256::digest(info_bytes.as_slice());
-                    let hash = InfoHash::new(&digest)?;
-                    // Check if we have hybrid torrent...
-                    // If it's single-file it will have length field
-                    // If it's multi-file it will have files field
-                    if torrent.info.length.is_some() || torrent.info.files.is_some() {
-                        let digest = Sha1::digest(&info_bytes).to_vec().to_hex::<String>();
-                        hash.hybrid(&InfoHash::new(&digest)?)?
-                    } else {
-                        hash
+        // Re-encoding the info dict would change its hash when the original is not in canonical
+        // form (unsorted keys, etc), so we locate its exact bytes instead
+        let info_span = info_span(s).map_err(|reason| TorrentFileError::NotATorrent { reason })?;
+
+        let version = resolve_version(
+            torrent.info.version,
+            torrent.info.file_tree.is_some(),
+            options.reject_unknown_version,
+        )?;
+        check_hashable(version, torrent.info.file_tree.is_some())?;
+        let hash = OnceCell::new();
+        if !options.lazy_hash {
+            let infohash = hash_info(
+                &s[info_span.clone()],
+                version,
+                torrent.info.file_tree.is_some(),
+                torrent.info.length.is_some() || torrent.info.files.is_some(),
+            )?;
+            let _ = hash.set(infohash);
+        }
+
+        let web_seeds = match &torrent.url_list {
+            Some(url_list) => parse_url_list(url_list).0,
+            None => Vec::new(),
+        };
+        let http_seeds = match &torrent.httpseeds {
+            Some(httpseeds) => parse_url_list(httpseeds).0,
+            None => Vec::new(),
+        };
+
+        let tracker_tiers = parse_tracker_tiers(
+            &torrent,
+            TrackerParseOptions {
+                allow_unknown_schemes: options.allow_unknown_tracker_schemes,
+            },
+        );
+        if options.require_announce && tracker_tiers.is_empty() {
+            return Err(TorrentFileError::MissingAnnounce);
+        }
+
+        if options.reject_unsafe_paths || options.require_power_of_two_piece_length {
+            for issue in validate_torrent(&torrent).issues() {
+                match issue {
+                    ValidationIssue::InvalidPath { path } if options.reject_unsafe_paths => {
+                        return Err(TorrentFileError::UnsafePath { path: path.clone() });
+                    }
+                    ValidationIssue::MissingPieceLength
+                        if options.require_power_of_two_piece_length =>
+                    {
+                        return Err(TorrentFileError::InvalidPieceLength { length: 0 });
                     }
-                } else {
-                    return Err(TorrentFileError::NotATorrent {
-                        reason: "Torrentv2 without 'file_tree' field".to_string(),
-                    });
+                    ValidationIssue::InvalidPieceLength { length }
+                    | ValidationIssue::PieceLengthNotPowerOfTwo { length }
+                        if options.require_power_of_two_piece_length =>
+                    {
+                        return Err(TorrentFileError::InvalidPieceLength { length: *length });
+                    }
+                    _ => continue,
                 }
             }
-            _ => {
-                // Version is not null and is not 1-2
-                return Err(TorrentFileError::WrongVersion {
-                    version: torrent.info.version.unwrap(),
-                });
-            }
-        };
+        }
+
+        if options.validate_piece_layers {
+            check_piece_layers(&torrent)
+                .map_err(|reason| TorrentFileError::InvalidPieceLayers { reason })?;
+        }
 
         Ok(TorrentFile {
-            name: torrent.info.name,
-            hash: infohash,
+            name: torrent.info.name.clone(),
+            hash,
+            info_span,
+            version,
+            tracker_tiers,
+            web_seeds,
+            http_seeds,
+            dht_nodes: parse_dht_nodes(&torrent),
+            // Fields with an unexpected type are ignored
+            comment: torrent.comment.as_ref().and_then(text_field),
+            creation_date: torrent.creation_date.as_ref().and_then(bencode_i64),
+            created_by: torrent.created_by.as_ref().and_then(text_field),
+            encoding: torrent.encoding.as_ref().and_then(text_field),
+            decoded: torrent,
+            bytes: s.to_vec(),
         })
     }
 
-    pub fn hash(&self) -> &str {
-        self.hash.as_str()
+    /// Builds a TorrentFile from a bare info dict, as received from peers over
+    /// [BEP-0009](https://www.bittorrent.org/beps/bep_0009.html) (`ut_metadata`) when downloading
+    /// from a magnet link. The resulting torrent has no trackers, which can be added later with a
+    /// [`TorrentEditor`](crate::editor::TorrentEditor).
+    ///
+    /// When the info dict has no valid UTF-8 name, `name_fallback` is used instead, usually the
+    /// name of the magnet link. The info dict itself is kept untouched, so that its infohash does
+    /// not change.
+    pub fn from_info_bytes(
+        name_fallback: &str,
+        info: &[u8],
+    ) -> Result<TorrentFile, TorrentFileError> {
+        let not_a_torrent = |reason: String| TorrentFileError::NotATorrent { reason };
+        let (dict, end) = DictRef::parse(info).map_err(not_a_torrent)?;
+        if end != info.len() {
+            return Err(not_a_torrent("trailing data".to_string()));
+        }
+
+        let wrap = |info: &[u8]| [b"d4:info".as_slice(), info, b"e"].concat();
+        let has_name = dict
+            .get_bytes(b"name")
+            .map_or(false, |name| std::str::from_utf8(name).is_ok());
+        if has_name {
+            return TorrentFile::from_slice(&wrap(info));
+        }
+
+        // Decode a copy of the info dict with the fallback name, then restore the original bytes
+        check_nesting_depth(info, ParseOptions::DEFAULT_MAX_NESTING_DEPTH)?;
+        let mut patched = match bt_bencode::from_slice(info) {
+            Ok(BencodeValue::Dict(dict)) => dict,
+            Ok(_) => return Err(not_a_torrent("info is not a dict".to_string())),
+            Err(e) => return Err(not_a_torrent(e.to_string())),
+        };
+        patched.insert(ByteString::from("name"), BencodeValue::from(name_fallback));
+        let patched = bt_bencode::to_vec(&BencodeValue::Dict(patched)).unwrap();
+
+        let mut torrent = TorrentFile::from_slice(&wrap(&patched))?;
+        torrent.bytes = wrap(info);
+        torrent.info_span = 7..7 + info.len();
+        torrent.hash = OnceCell::new();
+        Ok(torrent)
+    }
+
+    /// Returns the infohash of the torrent. With
+    /// [`ParseOptions::lazy_hash`](crate::torrent_file::ParseOptions::lazy_hash), it is computed
+    /// on the first call.
+    pub fn hash(&self) -> HexString {
+        self.infohash().as_str()
+    }
+
+    fn infohash(&self) -> &InfoHash {
+        self.hash.get_or_init(|| {
+            let info = &self.decoded.info;
+            hash_info(
+                &self.bytes[self.info_span.clone()],
+                self.version,
+                info.file_tree.is_some(),
+                info.length.is_some() || info.files.is_some(),
+            )
+            .expect("info dict was checked during parsing")
+        })
     }
 
     pub fn name(&self) -> &str {
@@ -188,59 +758,1698 @@ impl TorrentFile {
     }
 
     pub fn id(&self) -> TorrentID {
-        TorrentID::from_infohash(&self.hash)
+        TorrentID::from_infohash(self.infohash())
+    }
+
+    /// Returns the trackers contained in the TorrentFile, as
+    /// [BEP-0012](https://www.bittorrent.org/beps/bep_0012.html) tiers. When the `announce-list`
+    /// field is absent, the `announce` tracker forms the only tier.
+    ///
+    /// Trackers which are not valid [`Tracker`](crate::tracker::Tracker)s are ignored. The
+    /// returned tiers are a copy, so you can keep track of working/failing trackers
+    /// without modifying the TorrentFile.
+    pub fn tracker_tiers(&self) -> TrackerTiers {
+        self.tracker_tiers.clone()
+    }
+
+    /// Returns the [BEP-0019](https://www.bittorrent.org/beps/bep_0019.html) web seeds
+    /// (`url-list` field) contained in the TorrentFile.
+    pub fn web_seeds(&self) -> &[Url] {
+        &self.web_seeds
+    }
+
+    /// Returns the [BEP-0017](https://www.bittorrent.org/beps/bep_0017.html) HTTP seeds
+    /// (`httpseeds` field) contained in the TorrentFile. Unlike web seeds, HTTP seeds are
+    /// scripts which serve pieces of the torrent by infohash.
+    pub fn http_seeds(&self) -> &[Url] {
+        &self.http_seeds
+    }
+
+    /// Returns the [BEP-0005](https://www.bittorrent.org/beps/bep_0005.html) DHT nodes
+    /// (`nodes` field) contained in the TorrentFile, as `(host, port)` pairs. Trackerless
+    /// torrents use them to join the DHT. Malformed nodes are ignored.
+    pub fn dht_nodes(&self) -> Vec<(String, u16)> {
+        self.dht_nodes.clone()
+    }
+
+    /// Returns the infohashes of the [BEP-0038](https://www.bittorrent.org/beps/bep_0038.html)
+    /// `similar` torrents, which share files with this TorrentFile. Both the info dict and the
+    /// torrent root are read, info dict first. Entries which are not a raw 20-byte or 32-byte
+    /// digest are ignored, and duplicates are removed.
+    pub fn similar(&self) -> Vec<InfoHash> {
+        let mut similar: Vec<InfoHash> = Vec::new();
+        for entry in self.bep38_entries("similar") {
+            let hash = entry
+                .as_byte_str()
+                .filter(|digest| digest.len() == 20 || digest.len() == 32)
+                .and_then(|digest| InfoHash::new(&digest.to_hex::<String>()).ok());
+            if let Some(hash) = hash {
+                if !similar.contains(&hash) {
+                    similar.push(hash);
+                }
+            }
+        }
+        similar
+    }
+
+    /// Returns the names of the [BEP-0038](https://www.bittorrent.org/beps/bep_0038.html)
+    /// `collections` this TorrentFile belongs to, read like
+    /// [`similar`](crate::torrent_file::TorrentFile::similar). Entries which are not UTF-8 strings
+    /// are ignored, and duplicates are removed.
+    pub fn collections(&self) -> Vec<String> {
+        let mut collections: Vec<String> = Vec::new();
+        for collection in self.bep38_entries("collections").filter_map(|c| c.as_str()) {
+            if !collections.iter().any(|c| c == collection) {
+                collections.push(collection.to_string());
+            }
+        }
+        collections
+    }
+
+    /// Returns the [BEP-0035](https://www.bittorrent.org/beps/bep_0035.html) signatures of the
+    /// TorrentFile, sorted by signer. Signatures without a `signature` byte string are ignored.
+    /// The `signatures` field stays in the [`DecodedTorrent`](crate::torrent_file::DecodedTorrent)
+    /// `extra` fields, so it is preserved when the torrent is re-encoded.
+    pub fn signatures(&self) -> Vec<TorrentSignature> {
+        let signatures = match self.decoded.extra_dict("signatures") {
+            Some(signatures) => signatures,
+            None => return Vec::new(),
+        };
+
+        signatures
+            .iter()
+            .filter_map(|(signer, entry)| {
+                let entry = entry.as_dict()?;
+                let field = |key: &str| entry.get(key.as_bytes());
+                Some(TorrentSignature {
+                    signer: lossy_string(signer),
+                    signature: field("signature")?.as_byte_str()?.to_vec(),
+                    certificate: field("certificate")
+                        .and_then(|c| c.as_byte_str())
+                        .map(|c| c.to_vec()),
+                    info: field("info").filter(|i| i.as_dict().is_some()).cloned(),
+                })
+            })
+            .collect()
+    }
+
+    /// Verifies the signature of a signer with a caller-provided function, returning None when
+    /// the signer did not sign the TorrentFile.
+    ///
+    /// The function receives the [`TorrentSignature`](crate::torrent_file::TorrentSignature) and
+    /// the signed message, which is the bencoded info dict of the TorrentFile, followed by the
+    /// bencoded `info` dict of the signature if it has one. It returns whether the signature is
+    /// valid for this message, using the crypto suite of the caller.
+    ///
+    /// ```
+    /// use hightorrent::TorrentFile;
+    ///
+    /// let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+    /// let torrent = TorrentFile::from_slice(&slice).unwrap();
+    /// assert_eq!(torrent.verify_with("publisher", |_signature, _message| true), None);
+    /// ```
+    pub fn verify_with<F>(&self, signer: &str, verify: F) -> Option<bool>
+    where
+        F: FnOnce(&TorrentSignature, &[u8]) -> bool,
+    {
+        let signature = self
+            .signatures()
+            .into_iter()
+            .find(|signature| signature.signer == signer)?;
+
+        let mut message = self.info_bytes().to_vec();
+        if let Some(info) = &signature.info {
+            // The value was decoded from bencode, so it can be encoded
+            message.extend(bt_bencode::to_vec(info).unwrap());
+        }
+        Some(verify(&signature, &message))
+    }
+
+    /// Returns the entries of a BEP-0038 list, from the info dict then from the torrent root.
+    fn bep38_entries<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a BencodeValue> {
+        let info = self.decoded.info.extra_list(key).unwrap_or_default();
+        let root = self.decoded.extra_list(key).unwrap_or_default();
+        info.iter().chain(root)
+    }
+
+    /// Returns the free-form comment of the TorrentFile, if any. Invalid UTF-8 sequences
+    /// are replaced with `U+FFFD REPLACEMENT CHARACTER`.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    /// Returns the creation date of the TorrentFile as a Unix timestamp in seconds, if any.
+    pub fn creation_date(&self) -> Option<i64> {
+        self.creation_date
+    }
+
+    /// Returns the name and version of the program which created the TorrentFile, if any.
+    pub fn created_by(&self) -> Option<&str> {
+        self.created_by.as_deref()
+    }
+
+    /// Returns the string encoding advertised by the TorrentFile, if any. This is a legacy
+    /// field which is rarely found in recent torrents.
+    pub fn encoding(&self) -> Option<&str> {
+        self.encoding.as_deref()
+    }
+
+    /// Returns the decoded torrent, to read the fields which are not parsed by this library with
+    /// the [`DecodedTorrent`](crate::torrent_file::DecodedTorrent) and
+    /// [`DecodedInfo`](crate::torrent_file::DecodedInfo) `extra` getters.
+    pub fn decoded(&self) -> &DecodedTorrent {
+        &self.decoded
+    }
+
+    /// Returns the original bencoded bytes the TorrentFile was parsed from. They can be stored
+    /// and parsed again with [`from_slice`](crate::torrent_file::TorrentFile::from_slice).
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the original bencoded bytes of the info dict, from which the infohash is computed.
+    pub fn info_bytes(&self) -> &[u8] {
+        &self.bytes[self.info_span.clone()]
+    }
+
+    /// Returns the files contained in the torrent, in the order of the info dict.
+    ///
+    /// File paths are [`SafePath::sanitize`](crate::content::SafePath::sanitize)d, so they can be
+    /// safely joined to a download directory. Padding files and malformed entries are skipped,
+    /// use [`files_with_padding`](crate::torrent_file::TorrentFile::files_with_padding) to
+    /// include padding files. The Bittorrent v1 files list is used when present, and the
+    /// Bittorrent v2 file tree otherwise.
+    pub fn files(&self) -> Vec<TorrentContent> {
+        self.list_files(false)
+    }
+
+    /// Returns the files contained in the torrent like
+    /// [`files`](crate::torrent_file::TorrentFile::files), including BEP-0047 padding files.
+    ///
+    /// Padding files are reported with [`TorrentContent::is_padding`](crate::content::TorrentContent::is_padding),
+    /// and are needed to map Bittorrent v1 piece offsets to the actual files.
+    pub fn files_with_padding(&self) -> Vec<TorrentContent> {
+        self.list_files(true)
+    }
+
+    /// Returns the total size of the padding files in the torrent, in bytes. It saturates at
+    /// `u64::MAX` for crafted torrents.
+    pub fn padding_size(&self) -> u64 {
+        self.files_with_padding()
+            .iter()
+            .filter(|file| file.is_padding())
+            .fold(0, |total, file| total.saturating_add(file.size))
+    }
+
+    /// Returns a fingerprint of the payload of the torrent, as a lowercase hex SHA-256 digest of
+    /// its sorted file paths and sizes.
+    ///
+    /// Unlike the infohash, the fingerprint does not depend on the piece length or on the extra
+    /// fields of the info dict, such as the `source` set by private trackers. Two torrents with
+    /// the same fingerprint describe the same files, which is how cross-seeding tools find the
+    /// same payload on different trackers. Padding files are ignored.
+    pub fn content_fingerprint(&self) -> String {
+        let mut hasher = sha2::Sha256::new();
+        for (path, size) in self.content_pairs() {
+            // Paths cannot contain NUL bytes, so entries cannot be confused
+            hasher.update(path.as_bytes());
+            hasher.update(b"\0");
+            hasher.update(size.to_string().as_bytes());
+            hasher.update(b"\n");
+        }
+        hasher.finalize().to_hex()
+    }
+
+    /// Returns whether another torrent describes the same files, with the same paths and sizes,
+    /// like compared by [`content_fingerprint`](crate::torrent_file::TorrentFile::content_fingerprint).
+    pub fn matches_content(&self, other: &TorrentFile) -> bool {
+        self.content_pairs() == other.content_pairs()
+    }
+
+    fn content_pairs(&self) -> Vec<(String, u64)> {
+        let mut pairs: Vec<(String, u64)> = self
+            .files()
+            .into_iter()
+            .map(|file| (file.path.to_string(), file.size))
+            .collect();
+        pairs.sort();
+        pairs
+    }
+
+    /// Returns the [`files`](crate::torrent_file::TorrentFile::files) of the torrent as a tree
+    /// of directories, for example to display them with the
+    /// [`DirNode`](crate::content::DirNode) Display implementation. The root is the torrent
+    /// directory for multi-file torrents, and the file itself for single-file torrents.
+    pub fn file_tree_view(&self) -> DirNode {
+        DirNode::from_files(self.name(), &self.files())
+    }
+
+    /// Selects the files whose path matches any of the patterns, for example to only download
+    /// them with the `so` parameter of a MagnetLink, set with
+    /// [`MagnetLink::set_file_selection`](crate::magnet::MagnetLink::set_file_selection). Fails
+    /// with [`FileSelectionError::Empty`](crate::selection::FileSelectionError::Empty) when no
+    /// file matches.
+    ///
+    /// File indices are positions in
+    /// [`files_with_padding`](crate::torrent_file::TorrentFile::files_with_padding), because
+    /// BEP-0053 indices count padding files, but padding files are never selected.
+    pub fn select_files(&self, patterns: &[Pattern]) -> Result<FileSelection, FileSelectionError> {
+        let indices = self
+            .files_with_padding()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, file)| !file.is_padding())
+            .filter(|(_, file)| {
+                let path = file.path.to_string();
+                patterns.iter().any(|pattern| pattern.matches(&path))
+            })
+            .map(|(index, _)| index);
+        FileSelection::from_indices(indices)
+    }
+
+    fn list_files(&self, with_padding: bool) -> Vec<TorrentContent> {
+        let info = &self.decoded.info;
+        let mut malformed = Vec::new();
+        let files = match &info.file_tree {
+            Some(tree) if info.length.is_none() && info.files.is_none() => {
+                v2_files(&info.name, tree, &mut malformed)
+            }
+            _ => v1_files(&info.name, info.length, info.files.as_ref(), &mut malformed),
+        };
+
+        files
+            .iter()
+            .filter(|file| with_padding || !file.attributes.is_padding())
+            .filter_map(|file| {
+                Some(TorrentContent {
+                    path: SafePath::sanitize(file.lossy_components()),
+                    size: u64::try_from(file.length?).ok()?,
+                    attributes: file.attributes,
+                    symlink_target: file.symlink_target(),
+                })
+            })
+            .collect()
+    }
+
+    /// Checks the TorrentFile for problems which do not prevent parsing, such as invalid piece
+    /// length, wrong number of pieces, negative file sizes, duplicate files or invalid paths.
+    /// All the problems found are returned in a
+    /// [`ValidationReport`](crate::validation::ValidationReport).
+    pub fn validate(&self) -> ValidationReport {
+        validate_torrent(&self.decoded)
+    }
+
+    /// Checks that the v1 `files` and the v2 `file tree` of a hybrid torrent describe the same
+    /// files with the same sizes, as required by BEP-0052. Padding files are ignored. Returns an
+    /// empty list for consistent hybrid torrents, and for torrents which are not hybrid.
+    pub fn check_hybrid_consistency(&self) -> Vec<HybridMismatch> {
+        hybrid_mismatches(&self.decoded)
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Returns the value of a bencode integer as i64, regardless of whether it was decoded
+/// as a signed or unsigned number.
+pub(crate) fn bencode_i64(value: &BencodeValue) -> Option<i64> {
+    match value.as_number()? {
+        Number::Signed(n) => Some(*n),
+        Number::Unsigned(n) => i64::try_from(*n).ok(),
+    }
+}
 
-    #[test]
-    fn can_read_torrent_v1() {
-        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
-        let res = TorrentFile::from_slice(&slice);
-        println!("{:?}", res);
-        assert!(res.is_ok());
-        let torrent = res.unwrap();
-        assert_eq!(
-            &torrent.name,
-            "Goldman, Emma - Essential Works of Anarchism"
-        );
-        assert_eq!(
-            torrent.hash,
-            InfoHash::V1("c811b41641a09d192b8ed81b14064fff55d85ce3".to_string())
-        );
+fn lossy_string(bytes: &ByteString) -> String {
+    String::from_utf8_lossy(bytes).to_string()
+}
+
+/// Returns a bencode string with invalid UTF-8 sequences replaced, or `None` for other types.
+fn text_field(value: &BencodeValue) -> Option<String> {
+    value.as_byte_str().map(lossy_string)
+}
+
+/// Checks the size and nesting depth of untrusted bytes, before decoding them.
+fn check_input_limits(s: &[u8], options: &ParseOptions) -> Result<(), TorrentFileError> {
+    if let Some(max) = options.max_size {
+        let size = s.len() as u64;
+        if size > max {
+            return Err(TorrentFileError::TooLarge { size, max });
+        }
     }
+    check_nesting_depth(s, options.max_nesting_depth)
+}
 
-    #[test]
-    fn can_read_torrent_v2() {
-        let slice = std::fs::read("tests/bittorrent-v2-test.torrent").unwrap();
-        let res = TorrentFile::from_slice(&slice);
-        assert!(res.is_ok());
-        let torrent = res.unwrap();
-        assert_eq!(&torrent.name, "bittorrent-v2-test");
-        assert_eq!(
-            torrent.hash,
-            InfoHash::V2(
-                "caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e".to_string()
-            )
-        );
+/// Checks the nesting depth of bencoded bytes before they are decoded recursively, including
+/// truncated bytes whose other errors are reported by the decoder.
+fn check_nesting_depth(s: &[u8], max: usize) -> Result<(), TorrentFileError> {
+    let depth = nesting_depth(s);
+    if depth > max {
+        return Err(TorrentFileError::TooDeep { depth, max });
     }
+    Ok(())
+}
 
-    #[test]
-    fn can_read_torrent_hybrid() {
-        let slice = std::fs::read("tests/bittorrent-v2-hybrid-test.torrent").unwrap();
-        let res = TorrentFile::from_slice(&slice);
-        assert!(res.is_ok());
-        let torrent = res.unwrap();
-        assert_eq!(&torrent.name, "bittorrent-v1-v2-hybrid-test");
-        assert_eq!(
-            torrent.hash,
-            InfoHash::Hybrid((
-                "631a31dd0a46257d5078c0dee4e66e26f73e42ac".to_string(),
-                "d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb".to_string()
-            ))
+/// Checks the number of trackers and files, and the depth of file paths, of a decoded torrent.
+fn check_torrent_limits(
+    torrent: &DecodedTorrent,
+    options: &ParseOptions,
+) -> Result<(), TorrentFileError> {
+    if let Some(max) = options.max_trackers {
+        let count = match &torrent.announce_list {
+            Some(BencodeValue::List(tiers)) => tiers
+                .iter()
+                .filter_map(|tier| tier.as_list())
+                .map(|tier| tier.len())
+                .sum(),
+            _ => 0,
+        };
+        if count > max {
+            return Err(TorrentFileError::TooManyTrackers { count, max });
+        }
+    }
+
+    if options.max_files.is_none() && options.max_path_depth.is_none() {
+        return Ok(());
+    }
+    // Hybrid torrents list their files twice, so both lists are checked
+    let info = &torrent.info;
+    let mut malformed = Vec::new();
+    let mut lists = vec![v1_files(
+        &info.name,
+        info.length,
+        info.files.as_ref(),
+        &mut malformed,
+    )];
+    if let Some(tree) = &info.file_tree {
+        lists.push(v2_files(&info.name, tree, &mut malformed));
+    }
+    for files in lists {
+        if let Some(max) = options.max_files {
+            if files.len() > max {
+                return Err(TorrentFileError::TooManyFiles {
+                    count: files.len(),
+                    max,
+                });
+            }
+        }
+        if let Some(max) = options.max_path_depth {
+            if let Some(file) = files.iter().find(|file| file.path.len() > max) {
+                return Err(TorrentFileError::PathTooDeep {
+                    path: file.display_path(),
+                    depth: file.path.len(),
+                    max,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_file_size(size: u64) -> Result<(), TorrentFileError> {
+    if size > TorrentFile::MAX_FILE_SIZE {
+        return Err(TorrentFileError::TooLarge {
+            size,
+            max: TorrentFile::MAX_FILE_SIZE,
+        });
+    }
+    Ok(())
+}
+
+/// Parses BEP-0012 tracker tiers from `announce-list`, falling back to `announce`.
+/// Invalid trackers and malformed tiers are ignored.
+fn parse_tracker_tiers(torrent: &DecodedTorrent, options: TrackerParseOptions) -> TrackerTiers {
+    let parse_tracker = |value: &[u8]| {
+        std::str::from_utf8(value)
+            .ok()
+            .and_then(|url| Tracker::new_with(url, options).ok())
+    };
+
+    let mut tiers = TrackerTiers::new();
+    if let Some(BencodeValue::List(list)) = &torrent.announce_list {
+        for tier in list.iter().filter_map(|tier| tier.as_list()) {
+            let tier = tier
+                .iter()
+                .filter_map(|url| url.as_byte_str())
+                .filter_map(|url| parse_tracker(url))
+                .collect();
+            tiers.push_tier(tier);
+        }
+    }
+
+    if tiers.is_empty() {
+        if let Some(tracker) = torrent.announce.as_ref().and_then(|url| parse_tracker(url)) {
+            tiers.push_tier(vec![tracker]);
+        }
+    }
+
+    tiers
+}
+
+/// Maps the items in order, in parallel with the `rayon` feature.
+#[cfg(feature = "rayon")]
+fn map_many<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync + Send) -> Vec<R> {
+    use rayon::prelude::*;
+
+    items.par_iter().map(f).collect()
+}
+
+#[cfg(not(feature = "rayon"))]
+fn map_many<T, R>(items: &[T], f: impl Fn(&T) -> R) -> Vec<R> {
+    items.iter().map(f).collect()
+}
+
+/// Parses the BEP-0005 `nodes`, skipping entries which are not a `[host, port]` list with a
+/// non-empty host and a valid port, like invalid trackers are skipped.
+fn parse_dht_nodes(torrent: &DecodedTorrent) -> Vec<(String, u16)> {
+    let list = match &torrent.nodes {
+        Some(BencodeValue::List(list)) => list,
+        _ => return Vec::new(),
+    };
+
+    list.iter()
+        .filter_map(|node| match node.as_list()?.as_slice() {
+            [host, port] => {
+                let host = host.as_str().filter(|host| !host.is_empty())?;
+                let port = port.as_u64().and_then(|port| u16::try_from(port).ok())?;
+                Some((host.to_string(), port))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Parses a BEP-0019 `url-list` or a BEP-0017 `httpseeds`, which may be a single URL or a list
+/// of URLs. Empty strings are ignored because many torrent creators produce them.
+///
+/// Like invalid trackers, entries which are not HTTP(S) URLs do not prevent using the torrent:
+/// they are returned separately, as lossy strings (empty for non-string entries), to be
+/// reported by [`validate`](crate::torrent_file::TorrentFile::validate).
+pub(crate) fn parse_url_list(url_list: &BencodeValue) -> (Vec<Url>, Vec<String>) {
+    let entries = match url_list {
+        BencodeValue::List(list) => list.iter().collect(),
+        other => vec![other],
+    };
+
+    let mut web_seeds = Vec::new();
+    let mut invalid = Vec::new();
+    for entry in entries {
+        let url = match entry.as_byte_str() {
+            Some(url) => String::from_utf8_lossy(url),
+            None => {
+                invalid.push(String::new());
+                continue;
+            }
+        };
+        if url.is_empty() {
+            continue;
+        }
+        match parse_web_seed(&url) {
+            Some(seed) => web_seeds.push(seed),
+            None => invalid.push(url.to_string()),
+        }
+    }
+
+    (web_seeds, invalid)
+}
+
+/// Returns the torrent version declared in the info dict, guessing it from the presence of a
+/// file tree when it is unknown and not rejected.
+pub(crate) fn resolve_version(
+    version: Option<u64>,
+    has_file_tree: bool,
+    reject_unknown: bool,
+) -> Result<Option<u64>, TorrentFileError> {
+    match version {
+        Some(1) | Some(2) | None => Ok(version),
+        Some(version) if reject_unknown => Err(TorrentFileError::WrongVersion { version }),
+        // Guess the version from the info dict: v2 torrents have a file tree
+        Some(_) if has_file_tree => Ok(Some(2)),
+        Some(_) => Ok(Some(1)),
+    }
+}
+
+/// Finds the position of the info dict in a bencoded torrent.
+fn info_span(s: &[u8]) -> Result<Range<usize>, String> {
+    let (torrent, _) = DictRef::parse(s)?;
+    let info = torrent
+        .get_dict(b"info")
+        .ok_or_else(|| "missing field `info`".to_string())?;
+    // The info dict is borrowed from s, so its offset is the distance between both pointers
+    let start = info.as_bytes().as_ptr() as usize - s.as_ptr() as usize;
+    Ok(start..start + info.as_bytes().len())
+}
+
+/// Checks that an info dict of the given version can be hashed.
+pub(crate) fn check_hashable(
+    version: Option<u64>,
+    has_file_tree: bool,
+) -> Result<(), TorrentFileError> {
+    // Bittorrent v2 has mandatory file_tree dict
+    // see http://bittorrent.org/beps/bep_0052.html
+    if version == Some(2) && !has_file_tree {
+        return Err(TorrentFileError::NotATorrent {
+            reason: "Torrentv2 without 'file_tree' field".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Computes the infohash of a borrowed info dict, reading its version unless a `version_hint`
+/// is given. Unknown versions are rejected.
+pub(crate) fn hash_info_dict(
+    info: DictRef,
+    version_hint: Option<u64>,
+) -> Result<InfoHash, TorrentFileError> {
+    let version = match version_hint {
+        Some(version) => Some(version),
+        None => match info.get_int(b"meta version") {
+            Some(version) => {
+                Some(
+                    u64::try_from(version).map_err(|_| TorrentFileError::NotATorrent {
+                        reason: format!("invalid meta version: {version}"),
+                    })?,
+                )
+            }
+            None => None,
+        },
+    };
+    let has_file_tree = info.get_dict(b"file tree").is_some();
+    let version = resolve_version(version, has_file_tree, true)?;
+    hash_info(
+        info.as_bytes(),
+        version,
+        has_file_tree,
+        info.get(b"length").is_some() || info.get(b"files").is_some(),
+    )
+}
+
+/// Computes the infohash of a torrent from the bencoded bytes of its info dict.
+pub(crate) fn hash_info(
+    info_bytes: &[u8],
+    version: Option<u64>,
+    has_file_tree: bool,
+    has_v1_files: bool,
+) -> Result<InfoHash, TorrentFileError> {
+    match version {
+        // Most v1 torrents don't declare a torrent version at all
+        Some(1) | None => {
+            // Bittorrent v1 does not necessarily have a files dict... single-file torrents
+            // just use the torrent name field for that
+            let digest = Sha1::digest(info_bytes).to_vec().to_hex::<String>();
+            Ok(InfoHash::new(&digest)?)
+        }
+        Some(2) => {
+            check_hashable(version, has_file_tree)?;
+
+            let digest = sha256::digest(info_bytes);
+            let hash = InfoHash::new(&digest)?;
+            // Check if we have hybrid torrent...
+            // If it's single-file it will have length field
+            // If it's multi-file it will have files field
+            if has_v1_files {
+                let digest = Sha1::digest(info_bytes).to_vec().to_hex::<String>();
+                Ok(hash.hybrid(&InfoHash::new(&digest)?)?)
+            } else {
+                Ok(hash)
+            }
+        }
+        _ => unreachable!("unknown versions are rejected or guessed first"),
+    }
+}
+
+/// A TorrentFile is not being downloaded, so the resulting [`Torrent`](crate::torrent::Torrent)
+/// has no path, dates, progress or state. Its size excludes padding files, and is capped to
+/// `i64::MAX` for crafted torrents. Its sources are its trackers.
+impl ToTorrent for TorrentFile {
+    fn to_torrent(&self) -> Torrent {
+        let size = self
+            .files()
+            .iter()
+            .fold(0u64, |total, f| total.saturating_add(f.size))
+            .min(i64::MAX as u64);
+        Torrent {
+            name: self.name.clone(),
+            path: String::new(),
+            date_start: None,
+            date_end: None,
+            progress: Progress::new(0, size),
+            size: size as i64,
+            uploaded: None,
+            downloaded: None,
+            state: String::new(),
+            tags: Vec::new(),
+            category: None,
+            sources: tracker_sources(self.tracker_tiers.iter().cloned()),
+            limits: TorrentLimits::default(),
+            id: self.id(),
+            hash: self.infohash().clone(),
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for TorrentFile {
+    type Error = TorrentFileError;
+
+    fn try_from(s: &[u8]) -> Result<TorrentFile, Self::Error> {
+        TorrentFile::from_slice(s)
+    }
+}
+
+impl From<TorrentFile> for InfoHash {
+    fn from(torrent: TorrentFile) -> InfoHash {
+        torrent.infohash().clone()
+    }
+}
+
+impl From<&TorrentFile> for InfoHash {
+    fn from(torrent: &TorrentFile) -> InfoHash {
+        torrent.infohash().clone()
+    }
+}
+
+impl From<&TorrentFile> for TorrentID {
+    fn from(torrent: &TorrentFile) -> TorrentID {
+        torrent.id()
+    }
+}
+
+impl From<&TorrentFile> for SingleTarget {
+    fn from(torrent: &TorrentFile) -> SingleTarget {
+        SingleTarget::from(torrent.infohash())
+    }
+}
+
+impl From<TorrentFile> for SingleTarget {
+    fn from(torrent: TorrentFile) -> SingleTarget {
+        SingleTarget::from(torrent.infohash())
+    }
+}
+
+impl Serialize for TorrentFile {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.bytes)
+    }
+}
+
+impl<'de> Deserialize<'de> for TorrentFile {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<TorrentFile, D::Error> {
+        // The other fields are derived from the bytes, so they are never trusted
+        let bytes = deserializer.deserialize_byte_buf(BytesVisitor)?;
+        TorrentFile::from_slice(&bytes).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Accepts byte strings, and sequences of bytes from self-describing formats such as JSON.
+struct BytesVisitor;
+
+impl<'de> serde::de::Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("the bytes of a torrent file")
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: serde::de::Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HexDigest;
+
+    #[test]
+    fn can_read_torrent_v1() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let res = TorrentFile::from_slice(&slice);
+        println!("{:?}", res);
+        assert!(res.is_ok());
+        let torrent = res.unwrap();
+        assert_eq!(
+            &torrent.name,
+            "Goldman, Emma - Essential Works of Anarchism"
+        );
+        assert_eq!(
+            *torrent.infohash(),
+            InfoHash::V1(HexDigest::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap())
+        );
+        assert_eq!(torrent.comment(), Some("dynamic metainfo from client"));
+        assert_eq!(torrent.created_by(), Some("go.torrent"));
+        assert_eq!(torrent.creation_date(), Some(1603243361));
+        assert_eq!(torrent.encoding(), None);
+    }
+
+    #[test]
+    fn keeps_original_bytes() {
+        let slice = std::fs::read("tests/bittorrent-v2-hybrid-test.torrent").unwrap();
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        assert_eq!(torrent.as_bytes(), slice.as_slice());
+    }
+
+    #[test]
+    fn can_convert() {
+        let slice = std::fs::read("tests/bittorrent-v2-test.torrent").unwrap();
+        let torrent = TorrentFile::try_from(slice.as_slice()).unwrap();
+        assert_eq!(TorrentID::from(&torrent), torrent.id());
+        assert_eq!(InfoHash::from(&torrent).as_str(), torrent.hash());
+        assert!(SingleTarget::from(&torrent).matches_id(&torrent.id()));
+        assert_eq!(
+            SingleTarget::from(torrent.clone()),
+            SingleTarget::new(&torrent.hash()).unwrap()
+        );
+    }
+
+    #[test]
+    fn can_read_tracker_tiers() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        let tiers = torrent.tracker_tiers();
+        assert!(tiers.len() > 1);
+        assert_eq!(
+            tiers.iter().next().unwrap().url(),
+            "udp://tracker.leechers-paradise.org:6969/announce"
+        );
+        assert!(tiers.tiers().iter().all(|tier| tier.len() == 1));
+    }
+
+    #[test]
+    fn can_read_announce_without_tiers() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let mut torrent: DecodedTorrent = bt_bencode::from_slice(&slice).unwrap();
+        torrent.announce_list = None;
+        let slice = bt_bencode::to_vec(&torrent).unwrap();
+
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        let tiers = torrent.tracker_tiers();
+        assert_eq!(tiers.len(), 1);
+        assert_eq!(
+            tiers.iter().next().unwrap().url(),
+            "udp://tracker.leechers-paradise.org:6969/announce"
+        );
+    }
+
+    #[test]
+    fn from_path() {
+        let torrent = TorrentFile::from_path("tests/bittorrent-v2-test.torrent").unwrap();
+        assert_eq!(torrent.name(), "bittorrent-v2-test");
+
+        let err = TorrentFile::from_path("tests/missing.torrent").unwrap_err();
+        assert!(matches!(
+            err,
+            TorrentFileError::Io {
+                kind: std::io::ErrorKind::NotFound,
+                ..
+            }
+        ));
+        assert_eq!(
+            check_file_size(TorrentFile::MAX_FILE_SIZE + 1),
+            Err(TorrentFileError::TooLarge {
+                size: TorrentFile::MAX_FILE_SIZE + 1,
+                max: TorrentFile::MAX_FILE_SIZE
+            })
+        );
+    }
+
+    #[test]
+    fn parse_options_limits() {
+        let slice = std::fs::read("tests/bittorrent-v2-hybrid-test.torrent").unwrap();
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        let files = torrent.files_with_padding().len();
+        let parse = |options| TorrentFile::from_slice_with(&slice, options).unwrap_err();
+
+        assert_eq!(
+            parse(ParseOptions {
+                max_size: Some(100),
+                ..Default::default()
+            }),
+            TorrentFileError::TooLarge {
+                size: slice.len() as u64,
+                max: 100
+            }
+        );
+        assert!(matches!(
+            parse(ParseOptions {
+                max_nesting_depth: 2,
+                ..Default::default()
+            }),
+            TorrentFileError::TooDeep { max: 2, .. }
+        ));
+        assert_eq!(
+            parse(ParseOptions {
+                max_files: Some(files - 1),
+                ..Default::default()
+            }),
+            TorrentFileError::TooManyFiles {
+                count: files,
+                max: files - 1
+            }
+        );
+        assert!(matches!(
+            parse(ParseOptions {
+                max_path_depth: Some(1),
+                ..Default::default()
+            }),
+            TorrentFileError::PathTooDeep {
+                depth: 2,
+                max: 1,
+                ..
+            }
+        ));
+
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        let trackers = torrent.tracker_tiers().iter().count();
+        assert_eq!(
+            TorrentFile::from_slice_with(
+                &slice,
+                ParseOptions {
+                    max_trackers: Some(1),
+                    ..Default::default()
+                }
+            )
+            .unwrap_err(),
+            TorrentFileError::TooManyTrackers {
+                count: trackers,
+                max: 1
+            }
+        );
+
+        // Limits can be lifted
+        let options = ParseOptions {
+            max_size: None,
+            max_files: None,
+            max_path_depth: None,
+            max_trackers: None,
+            ..Default::default()
+        };
+        assert!(TorrentFile::from_slice_with(&slice, options).is_ok());
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        let depth = ParseOptions::DEFAULT_MAX_NESTING_DEPTH + 1;
+        let mut slice = b"d13:announce-list".to_vec();
+        slice.extend(std::iter::repeat(b'l').take(depth));
+        assert_eq!(
+            TorrentFile::from_slice(&slice).unwrap_err(),
+            TorrentFileError::TooDeep {
+                depth: depth + 1,
+                max: ParseOptions::DEFAULT_MAX_NESTING_DEPTH
+            }
+        );
+
+        let mut info = b"d5:filesl".to_vec();
+        info.extend(std::iter::repeat(b'l').take(depth));
+        info.extend(std::iter::repeat(b'e').take(depth + 2));
+        assert!(matches!(
+            TorrentFile::from_info_bytes("fallback", &info).unwrap_err(),
+            TorrentFileError::TooDeep { .. }
+        ));
+    }
+
+    #[test]
+    fn parse_options_unknown_tracker_schemes() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let mut torrent: DecodedTorrent = bt_bencode::from_slice(&slice).unwrap();
+        torrent.announce_list = None;
+        torrent.announce = Some(ByteString::from("ftp://tracker.example/announce"));
+        let slice = bt_bencode::to_vec(&torrent).unwrap();
+
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        assert!(torrent.tracker_tiers().is_empty());
+
+        let torrent = TorrentFile::from_slice_with(&slice, ParseOptions::lenient()).unwrap();
+        assert_eq!(
+            torrent.tracker_tiers().iter().next().unwrap().url(),
+            "ftp://tracker.example/announce"
+        );
+    }
+
+    #[test]
+    fn preserves_metadata_fields() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let torrent: DecodedTorrent = bt_bencode::from_slice(&slice).unwrap();
+        assert!(!torrent.extra.contains_key("comment"));
+
+        let reencoded: BencodeValue =
+            bt_bencode::from_slice(&bt_bencode::to_vec(&torrent).unwrap()).unwrap();
+        let original: BencodeValue = bt_bencode::from_slice(&slice).unwrap();
+        assert_eq!(reencoded, original);
+    }
+
+    #[test]
+    fn ignores_mistyped_metadata_fields() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let mut torrent: DecodedTorrent = bt_bencode::from_slice(&slice).unwrap();
+        torrent.creation_date = Some(BencodeValue::from("2020-10-21"));
+        torrent.comment = Some(BencodeValue::from(42));
+        torrent.created_by = Some(BencodeValue::List(vec![]));
+        let slice = bt_bencode::to_vec(&torrent).unwrap();
+
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        assert_eq!(torrent.creation_date(), None);
+        assert_eq!(torrent.comment(), None);
+        assert_eq!(torrent.created_by(), None);
+
+        let reencoded = bt_bencode::to_vec(&torrent.decoded).unwrap();
+        assert_eq!(reencoded, slice);
+    }
+
+    #[test]
+    fn can_read_web_seeds() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let mut torrent: DecodedTorrent = bt_bencode::from_slice(&slice).unwrap();
+        torrent.url_list = Some(BencodeValue::List(vec![
+            BencodeValue::from("https://example.com/goldman/"),
+            BencodeValue::from(""),
+        ]));
+        let slice = bt_bencode::to_vec(&torrent).unwrap();
+
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        assert_eq!(
+            torrent.web_seeds(),
+            &[Url::parse("https://example.com/goldman/").unwrap()]
+        );
+        // Web seeds are not part of the info dict
+        assert_eq!(torrent.hash(), "c811b41641a09d192b8ed81b14064fff55d85ce3");
+    }
+
+    #[test]
+    fn can_read_http_seeds() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let mut torrent: DecodedTorrent = bt_bencode::from_slice(&slice).unwrap();
+        torrent.httpseeds = Some(BencodeValue::List(vec![BencodeValue::from(
+            "http://seed.example/seed.php",
+        )]));
+        let slice = bt_bencode::to_vec(&torrent).unwrap();
+
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        assert_eq!(
+            torrent.http_seeds(),
+            &[Url::parse("http://seed.example/seed.php").unwrap()]
+        );
+        assert!(torrent.web_seeds().is_empty());
+        assert!(!torrent.decoded().extra.contains_key("httpseeds"));
+        assert_eq!(torrent.hash(), "c811b41641a09d192b8ed81b14064fff55d85ce3");
+
+        // The field is preserved when the decoded torrent is serialized again
+        let reencoded = bt_bencode::to_vec(torrent.decoded()).unwrap();
+        assert_eq!(reencoded, slice);
+
+        let mut torrent: DecodedTorrent = bt_bencode::from_slice(&slice).unwrap();
+        torrent.httpseeds = Some(BencodeValue::List(vec![BencodeValue::Int(1.into())]));
+        let torrent = TorrentFile::from_slice(&bt_bencode::to_vec(&torrent).unwrap()).unwrap();
+        assert!(torrent.http_seeds().is_empty());
+    }
+
+    #[test]
+    fn can_read_dht_nodes() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let mut torrent: DecodedTorrent = bt_bencode::from_slice(&slice).unwrap();
+        let node = |host: &str, port: i64| {
+            BencodeValue::List(vec![
+                BencodeValue::from(host),
+                BencodeValue::Int(port.into()),
+            ])
+        };
+        torrent.nodes = Some(BencodeValue::List(vec![
+            node("router.bittorrent.com", 6881),
+            node("127.0.0.1", 70000),
+            node("", 6881),
+            BencodeValue::from("dht.example:6881"),
+            node("2001:db8::1", 6882),
+        ]));
+        let slice = bt_bencode::to_vec(&torrent).unwrap();
+
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        assert_eq!(
+            torrent.dht_nodes(),
+            vec![
+                ("router.bittorrent.com".to_string(), 6881),
+                ("2001:db8::1".to_string(), 6882)
+            ]
+        );
+        assert!(!torrent.decoded().extra.contains_key("nodes"));
+    }
+
+    #[test]
+    fn can_read_bep38_fields() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let mut torrent: DecodedTorrent = bt_bencode::from_slice(&slice).unwrap();
+        let v1 = vec![0xc8; 20];
+        let v2 = vec![0xd8; 32];
+        torrent.info.extra.insert(
+            "similar".to_string(),
+            BencodeValue::List(vec![
+                BencodeValue::ByteStr(ByteString::from(v1.clone())),
+                BencodeValue::ByteStr(ByteString::from(vec![0; 8])),
+            ]),
+        );
+        torrent.info.extra.insert(
+            "collections".to_string(),
+            BencodeValue::List(vec![BencodeValue::from("anarchism")]),
+        );
+        torrent.extra.insert(
+            "similar".to_string(),
+            BencodeValue::List(vec![
+                BencodeValue::ByteStr(ByteString::from(v2.clone())),
+                BencodeValue::ByteStr(ByteString::from(v1.clone())),
+            ]),
+        );
+        torrent.extra.insert(
+            "collections".to_string(),
+            BencodeValue::List(vec![
+                BencodeValue::from("goldman"),
+                BencodeValue::from("anarchism"),
+                BencodeValue::Int(1.into()),
+            ]),
+        );
+        let slice = bt_bencode::to_vec(&torrent).unwrap();
+
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        assert_eq!(
+            torrent.similar(),
+            vec![
+                InfoHash::new(&v1.to_hex::<String>()).unwrap(),
+                InfoHash::new(&v2.to_hex::<String>()).unwrap()
+            ]
+        );
+        assert_eq!(torrent.collections(), vec!["anarchism", "goldman"]);
+
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        assert!(torrent.similar().is_empty());
+        assert!(torrent.collections().is_empty());
+    }
+
+    #[test]
+    fn can_read_signatures() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let mut torrent: DecodedTorrent = bt_bencode::from_slice(&slice).unwrap();
+        let entry = |fields: Vec<(&str, BencodeValue)>| {
+            BencodeValue::Dict(
+                fields
+                    .into_iter()
+                    .map(|(k, v)| (ByteString::from(k), v))
+                    .collect(),
+            )
+        };
+        let extra_info = entry(vec![("expires", BencodeValue::from("2030-01-01"))]);
+        torrent.extra.insert(
+            "signatures".to_string(),
+            entry(vec![
+                (
+                    "publisher",
+                    entry(vec![
+                        ("signature", BencodeValue::from("sig")),
+                        ("info", extra_info.clone()),
+                    ]),
+                ),
+                (
+                    "broken",
+                    entry(vec![("certificate", BencodeValue::from("cert"))]),
+                ),
+            ]),
+        );
+        let slice = bt_bencode::to_vec(&torrent).unwrap();
+
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        let signatures = torrent.signatures();
+        assert_eq!(signatures.len(), 1);
+        assert_eq!(signatures[0].signer(), "publisher");
+        assert_eq!(signatures[0].signature(), b"sig");
+        assert_eq!(signatures[0].certificate(), None);
+        assert_eq!(signatures[0].info(), Some(&extra_info));
+
+        let mut expected = torrent.info_bytes().to_vec();
+        expected.extend(bt_bencode::to_vec(&extra_info).unwrap());
+        let verified = torrent.verify_with("publisher", |signature, message| {
+            signature.signature() == b"sig" && message == expected
+        });
+        assert_eq!(verified, Some(true));
+        assert_eq!(torrent.verify_with("publisher", |_, _| false), Some(false));
+        assert_eq!(torrent.verify_with("broken", |_, _| true), None);
+
+        // Signatures are preserved when re-encoding
+        assert_eq!(bt_bencode::to_vec(torrent.decoded()).unwrap(), slice);
+    }
+
+    #[test]
+    fn matches_content_across_trackers() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let original = TorrentFile::from_slice(&slice).unwrap();
+        let mut decoded: DecodedTorrent = bt_bencode::from_slice(&slice).unwrap();
+        decoded
+            .info
+            .extra
+            .insert("source".to_string(), BencodeValue::from("TRACKER"));
+        let cross_seed = TorrentFile::from_slice(&bt_bencode::to_vec(&decoded).unwrap()).unwrap();
+        assert_ne!(original.hash(), cross_seed.hash());
+        assert!(original.matches_content(&cross_seed));
+        assert_eq!(
+            original.content_fingerprint(),
+            cross_seed.content_fingerprint()
+        );
+        assert_eq!(original.content_fingerprint().len(), 64);
+
+        let slice = std::fs::read("tests/bittorrent-v2-hybrid-test.torrent").unwrap();
+        let other = TorrentFile::from_slice(&slice).unwrap();
+        assert!(!original.matches_content(&other));
+        assert_ne!(original.content_fingerprint(), other.content_fingerprint());
+    }
+
+    #[test]
+    fn can_view_file_tree() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        let tree = torrent.file_tree_view();
+        let total: u64 = torrent.files().iter().map(|file| file.size).sum();
+        assert_eq!(tree.name, torrent.name());
+        assert_eq!(tree.size, total);
+
+        let printed = tree.to_string();
+        assert!(printed.starts_with(torrent.name()));
+        for file in torrent.files() {
+            let name = file.path.components().last().unwrap();
+            assert!(printed.contains(&format!("── {name} (")));
+        }
+
+        assert_eq!(huge_files_torrent().file_tree_view().size, u64::MAX);
+    }
+
+    #[test]
+    fn can_select_files() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+
+        let selection = torrent.select_files(&[Pattern::glob("*.txt")]).unwrap();
+        assert_eq!(selection.to_string(), "0-1");
+        let selection = torrent
+            .select_files(&[
+                Pattern::glob("**/Anarchism and Other Essays/*.[jJ][pP][gG]"),
+                Pattern::glob("_GOLDMAN.txt"),
+            ])
+            .unwrap();
+        assert_eq!(selection.to_string(), "0,2,5");
+        assert_eq!(
+            torrent.select_files(&[Pattern::glob("*.mkv")]).unwrap_err(),
+            FileSelectionError::Empty
+        );
+        assert_eq!(
+            torrent.select_files(&[]).unwrap_err(),
+            FileSelectionError::Empty
+        );
+    }
+
+    #[test]
+    fn parses_many_torrents() {
+        let slices = [
+            std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap(),
+            b"d4:infoi1ee".to_vec(),
+            std::fs::read("tests/bittorrent-v2-hybrid-test.torrent").unwrap(),
+        ];
+        let results = TorrentFile::parse_many_slices(&slices);
+        assert_eq!(results.len(), 3);
+        assert_eq!(
+            results[0].as_ref().unwrap().hash(),
+            "c811b41641a09d192b8ed81b14064fff55d85ce3"
+        );
+        assert_eq!(results[1].as_ref().unwrap_err().0, 1);
+        assert!(results[2].is_ok());
+
+        let results =
+            TorrentFile::parse_many(&["tests/missing.torrent", "tests/bittorrent-v2-test.torrent"]);
+        let (path, error) = results[0].as_ref().unwrap_err();
+        assert_eq!(path, std::path::Path::new("tests/missing.torrent"));
+        assert!(matches!(error, TorrentFileError::Io { .. }));
+        assert!(results[1].is_ok());
+    }
+
+    #[test]
+    fn skips_invalid_web_seeds() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let mut torrent: DecodedTorrent = bt_bencode::from_slice(&slice).unwrap();
+        torrent.url_list = Some(BencodeValue::List(vec![
+            BencodeValue::from("udp://example.com/goldman/"),
+            BencodeValue::Int(1.into()),
+            BencodeValue::from("https://example.com/goldman/"),
+        ]));
+        let slice = bt_bencode::to_vec(&torrent).unwrap();
+
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        let seeds: Vec<&str> = torrent.web_seeds().iter().map(|u| u.as_str()).collect();
+        assert_eq!(seeds, vec!["https://example.com/goldman/"]);
+
+        let issues: Vec<ValidationIssue> = torrent
+            .validate()
+            .warnings()
+            .filter(|i| matches!(i, ValidationIssue::InvalidWebSeed { .. }))
+            .cloned()
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                ValidationIssue::InvalidWebSeed {
+                    url: "udp://example.com/goldman/".to_string()
+                },
+                ValidationIssue::InvalidWebSeed { url: String::new() },
+            ]
+        );
+    }
+
+    /// A torrent whose files are all `i64::MAX` bytes long, so their total size overflows.
+    fn huge_files_torrent() -> TorrentFile {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let mut torrent: DecodedTorrent = bt_bencode::from_slice(&slice).unwrap();
+        for file in torrent.info.files.as_mut().unwrap() {
+            if let BencodeValue::Dict(dict) = file {
+                dict.insert("length".into(), BencodeValue::from(i64::MAX));
+            }
+        }
+        TorrentFile::from_slice(&bt_bencode::to_vec(&torrent).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn can_list_files() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let files = TorrentFile::from_slice(&slice).unwrap().files();
+        assert_eq!(files.len(), 94);
+        assert!(files
+            .iter()
+            .all(|f| f.path.components()[0] == "Goldman, Emma - Essential Works of Anarchism"));
+
+        let slice = std::fs::read("tests/bittorrent-v2-test.torrent").unwrap();
+        let files = TorrentFile::from_slice(&slice).unwrap().files();
+        assert_eq!(files.len(), 11);
+
+        let slice = std::fs::read("tests/bittorrent-v2-hybrid-test.torrent").unwrap();
+        let files = TorrentFile::from_slice(&slice).unwrap().files();
+        assert_eq!(files.len(), 9);
+        assert_eq!(
+            files
+                .iter()
+                .filter(|f| f.attributes.is_executable())
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn can_convert_to_torrent() {
+        let slice = std::fs::read("tests/bittorrent-v2-hybrid-test.torrent").unwrap();
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        let converted = torrent.to_torrent();
+        assert_eq!(converted.name, torrent.name());
+        assert_eq!(converted.id, torrent.id());
+        assert_eq!(converted.hash, InfoHash::from(&torrent));
+        assert_eq!(
+            converted.size,
+            torrent.files().iter().map(|f| f.size as i64).sum::<i64>()
+        );
+        assert!(converted.size > 0);
+        for tracker in torrent.tracker_tiers().iter() {
+            assert!(converted.uses_tracker(tracker));
+        }
+
+        let converted = huge_files_torrent().to_torrent();
+        assert_eq!(converted.size, i64::MAX);
+        assert_eq!(converted.progress, Progress::new(0, i64::MAX as u64));
+    }
+
+    #[test]
+    fn can_list_padding_files() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        assert_eq!(torrent.files_with_padding(), torrent.files());
+        assert_eq!(torrent.padding_size(), 0);
+
+        let slice = std::fs::read("tests/bittorrent-v2-hybrid-test.torrent").unwrap();
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        let files = torrent.files_with_padding();
+        let padding: Vec<_> = files.iter().filter(|f| f.is_padding()).collect();
+        assert_eq!(files.len(), 17);
+        assert_eq!(padding.len(), 8);
+        assert_eq!(
+            torrent.padding_size(),
+            padding.iter().map(|f| f.size).sum::<u64>()
+        );
+        assert!(torrent.padding_size() > 0);
+
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let mut torrent: DecodedTorrent = bt_bencode::from_slice(&slice).unwrap();
+        for file in torrent.info.files.as_mut().unwrap() {
+            if let BencodeValue::Dict(dict) = file {
+                dict.insert("length".into(), BencodeValue::from(i64::MAX));
+                dict.insert("attr".into(), BencodeValue::from("p"));
+            }
+        }
+        let torrent = TorrentFile::from_slice(&bt_bencode::to_vec(&torrent).unwrap()).unwrap();
+        assert_eq!(torrent.padding_size(), u64::MAX);
+    }
+
+    #[test]
+    fn files_are_sanitized() {
+        let slice = std::fs::read("tests/bittorrent-v2-test.torrent").unwrap();
+        let mut torrent: DecodedTorrent = bt_bencode::from_slice(&slice).unwrap();
+        let mut tree = std::collections::BTreeMap::new();
+        tree.insert(
+            ByteString::from(".."),
+            torrent.info.file_tree.clone().unwrap(),
+        );
+        torrent.info.file_tree = Some(BencodeValue::Dict(tree));
+        let slice = bt_bencode::to_vec(&torrent).unwrap();
+
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        for file in torrent.files() {
+            assert!(!file.path.components().contains(&"..".to_string()));
+        }
+        assert!(!torrent.validate().is_valid());
+    }
+
+    #[test]
+    fn parse_options_version() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let mut torrent: DecodedTorrent = bt_bencode::from_slice(&slice).unwrap();
+        torrent.info.version = Some(3);
+        let slice = bt_bencode::to_vec(&torrent).unwrap();
+
+        assert_eq!(
+            TorrentFile::from_slice(&slice).unwrap_err(),
+            TorrentFileError::WrongVersion { version: 3 }
+        );
+        let torrent = TorrentFile::from_slice_with(&slice, ParseOptions::lenient()).unwrap();
+        assert!(torrent.infohash().is_v1());
+    }
+
+    #[test]
+    fn parse_options_announce() {
+        let slice = std::fs::read("tests/bittorrent-v2-test.torrent").unwrap();
+        assert!(TorrentFile::from_slice(&slice).is_ok());
+        let options = ParseOptions {
+            require_announce: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            TorrentFile::from_slice_with(&slice, options).unwrap_err(),
+            TorrentFileError::MissingAnnounce
+        );
+
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        assert!(TorrentFile::from_slice_with(&slice, ParseOptions::strict()).is_ok());
+    }
+
+    #[test]
+    fn parse_options_unsafe_path() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let mut torrent: DecodedTorrent = bt_bencode::from_slice(&slice).unwrap();
+        if let Some(BencodeValue::Dict(file)) = torrent.info.files.as_mut().unwrap().get_mut(0) {
+            file.insert(
+                "path".into(),
+                BencodeValue::List(vec!["..".into(), "evil".into()]),
+            );
+        }
+        let slice = bt_bencode::to_vec(&torrent).unwrap();
+
+        assert!(TorrentFile::from_slice(&slice).is_ok());
+        assert_eq!(
+            TorrentFile::from_slice_with(&slice, ParseOptions::strict()).unwrap_err(),
+            TorrentFileError::UnsafePath {
+                path: "Goldman, Emma - Essential Works of Anarchism/../evil".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn parse_options_piece_length() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let mut torrent: DecodedTorrent = bt_bencode::from_slice(&slice).unwrap();
+        torrent
+            .info
+            .extra
+            .insert("piece length".to_string(), BencodeValue::from(100000));
+        let slice = bt_bencode::to_vec(&torrent).unwrap();
+
+        assert!(TorrentFile::from_slice(&slice).is_ok());
+        let options = ParseOptions {
+            require_power_of_two_piece_length: true,
+            ..ParseOptions::lenient()
+        };
+        assert_eq!(
+            TorrentFile::from_slice_with(&slice, options).unwrap_err(),
+            TorrentFileError::InvalidPieceLength { length: 100000 }
+        );
+    }
+
+    #[test]
+    fn extra_getters() {
+        let slice = std::fs::read("tests/bittorrent-v1-emma-goldman.torrent").unwrap();
+        let mut torrent: DecodedTorrent = bt_bencode::from_slice(&slice).unwrap();
+        torrent
+            .extra
+            .insert("x_cross_seed".to_string(), BencodeValue::from("abc"));
+        torrent.extra.insert(
+            "collections".to_string(),
+            BencodeValue::List(vec![BencodeValue::from("books")]),
+        );
+        let slice = bt_bencode::to_vec(&torrent).unwrap();
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        let decoded = torrent.decoded();
+
+        assert_eq!(decoded.extra_str("x_cross_seed"), Some("abc"));
+        assert_eq!(decoded.extra_int("x_cross_seed"), None);
+        assert_eq!(
+            decoded.extra_list("collections"),
+            Some([BencodeValue::from("books")].as_slice())
+        );
+        assert!(decoded.extra_dict("collections").is_none());
+        assert!(decoded.extra("missing").is_none());
+        assert_eq!(decoded.info().extra_int("piece length"), Some(131072));
+        assert!(decoded.info().extra_str("pieces").is_none());
+    }
+
+    #[test]
+    fn from_info_bytes() {
+        for path in [
+            "tests/bittorrent-v1-emma-goldman.torrent",
+            "tests/bittorrent-v2-test.torrent",
+            "tests/bittorrent-v2-hybrid-test.torrent",
+        ] {
+            let slice = std::fs::read(path).unwrap();
+            let torrent = TorrentFile::from_slice(&slice).unwrap();
+            let info = TorrentFile::from_info_bytes("fallback", torrent.info_bytes()).unwrap();
+            assert_eq!(info.hash(), torrent.hash());
+            assert_eq!(info.name(), torrent.name());
+            assert_eq!(info.info_bytes(), torrent.info_bytes());
+            assert!(info.tracker_tiers().is_empty());
+        }
+
+        let info: &[u8] = b"d6:lengthi5e12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaae";
+        let torrent = TorrentFile::from_info_bytes("fallback", info).unwrap();
+        assert_eq!(torrent.name(), "fallback");
+        assert_eq!(torrent.info_bytes(), info);
+        assert_eq!(
+            torrent.hash(),
+            Sha1::digest(info).to_vec().to_hex::<String>()
+        );
+
+        assert!(matches!(
+            TorrentFile::from_info_bytes("fallback", b"d4:name1:aee"),
+            Err(TorrentFileError::NotATorrent { .. })
+        ));
+        assert!(matches!(
+            TorrentFile::from_info_bytes("fallback", b"le"),
+            Err(TorrentFileError::NotATorrent { .. })
+        ));
+    }
+
+    #[test]
+    fn hashes_non_canonical_info_dict() {
+        // Keys are not sorted, and the info dict is not the last key of the torrent
+        let info: &[u8] =
+            b"d4:name4:test6:lengthi5e6:pieces20:aaaaaaaaaaaaaaaaaaaa12:piece lengthi16384ee";
+        let mut slice = b"d4:info".to_vec();
+        slice.extend_from_slice(info);
+        slice.extend_from_slice(b"8:announce22:http://tracker.example7:comment4:teste");
+
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        let expected = Sha1::digest(info).to_vec().to_hex::<String>();
+        assert_eq!(torrent.info_bytes(), info);
+        assert_eq!(torrent.hash(), expected);
+        // Re-encoding the info dict sorts its keys, which changes the hash
+        let reencoded = bt_bencode::to_vec(&torrent.decoded.info).unwrap();
+        assert_ne!(
+            Sha1::digest(&reencoded).to_vec().to_hex::<String>(),
+            expected
+        );
+
+        let borrowed = crate::TorrentFileRef::from_slice(&slice).unwrap();
+        assert_eq!(borrowed.hash(), torrent.hash());
+    }
+
+    #[test]
+    fn hashes_non_canonical_v2_info_dict() {
+        let info: &[u8] = b"d12:meta versioni2e4:name4:test9:file treed4:testd0:d6:lengthi5e11:pieces root32:bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbeee12:piece lengthi16384ee";
+        let mut slice = b"d4:info".to_vec();
+        slice.extend_from_slice(info);
+        slice.push(b'e');
+
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        assert_eq!(torrent.hash(), sha256::digest(info));
+        assert_eq!(
+            InfoHash::from(&torrent),
+            InfoHash::V2(HexDigest::new(&sha256::digest(info)).unwrap())
+        );
+    }
+
+    #[test]
+    fn parse_options_piece_layers() {
+        let options = ParseOptions {
+            validate_piece_layers: true,
+            ..ParseOptions::lenient()
+        };
+        for path in [
+            "tests/bittorrent-v2-test.torrent",
+            "tests/bittorrent-v2-hybrid-test.torrent",
+        ] {
+            let slice = std::fs::read(path).unwrap();
+            assert!(TorrentFile::from_slice_with(&slice, options).is_ok());
+        }
+
+        let slice = std::fs::read("tests/bittorrent-v2-test.torrent").unwrap();
+        let torrent: DecodedTorrent = bt_bencode::from_slice(&slice).unwrap();
+        let layers = match torrent.extra.get("piece layers") {
+            Some(BencodeValue::Dict(layers)) => layers.clone(),
+            _ => panic!("missing piece layers"),
+        };
+        let with_layers = |layers: std::collections::BTreeMap<ByteString, BencodeValue>| {
+            let mut torrent = torrent.clone();
+            torrent
+                .extra
+                .insert("piece layers".to_string(), BencodeValue::Dict(layers));
+            let slice = bt_bencode::to_vec(&torrent).unwrap();
+            // Piece layers are not checked by default
+            assert!(TorrentFile::from_slice(&slice).is_ok());
+            TorrentFile::from_slice_with(&slice, options).unwrap_err()
+        };
+
+        let mut missing = layers.clone();
+        let root = missing.keys().next().unwrap().clone();
+        let layer = missing.remove(&root).unwrap();
+        assert!(matches!(
+            with_layers(missing),
+            TorrentFileError::InvalidPieceLayers { reason } if reason.starts_with("missing piece layer for")
+        ));
+
+        let mut truncated = layers.clone();
+        let short = layer.as_byte_str().unwrap()[32..].to_vec();
+        truncated.insert(root.clone(), BencodeValue::ByteStr(ByteString::from(short)));
+        assert!(matches!(
+            with_layers(truncated),
+            TorrentFileError::InvalidPieceLayers { reason } if reason.contains("expected")
+        ));
+
+        let mut tampered = layers.clone();
+        let mut bytes = layer.as_byte_str().unwrap().to_vec();
+        bytes[0] ^= 1;
+        tampered.insert(root, BencodeValue::ByteStr(ByteString::from(bytes)));
+        assert!(matches!(
+            with_layers(tampered),
+            TorrentFileError::InvalidPieceLayers { reason } if reason.ends_with("does not match its pieces root")
+        ));
+
+        let mut unknown = layers;
+        unknown.insert(ByteString::from(vec![0; 32]), layer);
+        assert_eq!(
+            with_layers(unknown),
+            TorrentFileError::InvalidPieceLayers {
+                reason: "piece layer does not match any file".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn parse_options_lazy_hash() {
+        let options = ParseOptions {
+            lazy_hash: true,
+            ..ParseOptions::default()
+        };
+        for path in [
+            "tests/bittorrent-v1-emma-goldman.torrent",
+            "tests/bittorrent-v2-test.torrent",
+            "tests/bittorrent-v2-hybrid-test.torrent",
+        ] {
+            let slice = std::fs::read(path).unwrap();
+            let eager = TorrentFile::from_slice(&slice).unwrap();
+            let lazy = TorrentFile::from_slice_with(&slice, options).unwrap();
+            assert!(lazy.hash.get().is_none());
+            assert_eq!(lazy.name(), eager.name());
+            assert_eq!(lazy.clone().hash(), eager.hash());
+            assert_eq!(lazy.id(), eager.id());
+        }
+    }
+
+    #[test]
+    fn can_read_torrent_v2() {
+        let slice = std::fs::read("tests/bittorrent-v2-test.torrent").unwrap();
+        let res = TorrentFile::from_slice(&slice);
+        assert!(res.is_ok());
+        let torrent = res.unwrap();
+        assert_eq!(&torrent.name, "bittorrent-v2-test");
+        assert_eq!(
+            *torrent.infohash(),
+            InfoHash::V2(
+                HexDigest::new("caf1e1c30e81cb361b9ee167c4aa64228a7fa4fa9f6105232b28ad099f3a302e")
+                    .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn can_read_torrent_hybrid() {
+        let slice = std::fs::read("tests/bittorrent-v2-hybrid-test.torrent").unwrap();
+        let res = TorrentFile::from_slice(&slice);
+        assert!(res.is_ok());
+        let torrent = res.unwrap();
+        assert_eq!(&torrent.name, "bittorrent-v1-v2-hybrid-test");
+        assert_eq!(
+            *torrent.infohash(),
+            InfoHash::Hybrid((
+                HexDigest::new("631a31dd0a46257d5078c0dee4e66e26f73e42ac").unwrap(),
+                HexDigest::new("d8dd32ac93357c368556af3ac1d95c9d76bd0dff6fa9833ecdac3d53134efabb")
+                    .unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn serde_roundtrip() {
+        let slice = std::fs::read("tests/bittorrent-v2-hybrid-test.torrent").unwrap();
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+
+        let json = serde_json::to_string(&torrent).unwrap();
+        let from_json: TorrentFile = serde_json::from_str(&json).unwrap();
+        assert_eq!(from_json.as_bytes(), slice.as_slice());
+        assert_eq!(from_json.infohash(), torrent.infohash());
+
+        let bencode = bt_bencode::to_vec(&torrent).unwrap();
+        let from_bencode: TorrentFile = bt_bencode::from_slice(&bencode).unwrap();
+        assert_eq!(from_bencode.as_bytes(), slice.as_slice());
+
+        // Tampered bytes are parsed again, instead of being trusted
+        let truncated = serde_json::to_string(&slice[..100]).unwrap();
+        assert!(serde_json::from_str::<TorrentFile>(&truncated).is_err());
+        assert!(
+            serde_json::from_str::<TorrentFile>(r#"{"info_span":{"start":0,"end":9}}"#).is_err()
         );
     }
 }
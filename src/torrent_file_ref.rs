@@ -0,0 +1,147 @@
+use crate::bencode::DictRef;
+use crate::torrent_file::hash_info_dict;
+use crate::{HexString, InfoHash, TorrentFile, TorrentFileError, TorrentID};
+
+/// A torrent file borrowed from its bencoded bytes, which is cheaper to parse than a
+/// [`TorrentFile`](crate::torrent_file::TorrentFile).
+///
+/// The torrent is scanned without being decoded, so that no field is copied except for the
+/// computed [`InfoHash`](crate::hash::InfoHash). This is useful to index many torrents by hash
+/// and name, and only convert the interesting ones to a TorrentFile with
+/// [`to_torrent_file`](crate::torrent_file_ref::TorrentFileRef::to_torrent_file).
+///
+/// The infohash is computed from the original bytes of the info dict.
+#[derive(Clone, Debug)]
+pub struct TorrentFileRef<'a> {
+    bytes: &'a [u8],
+    info: &'a [u8],
+    name: &'a str,
+    hash: InfoHash,
+}
+
+impl<'a> TorrentFileRef<'a> {
+    /// Parses a borrowed torrent file. Torrents with an unknown version are rejected, like
+    /// with [`TorrentFile::from_slice`](crate::torrent_file::TorrentFile::from_slice).
+    pub fn from_slice(s: &'a [u8]) -> Result<TorrentFileRef<'a>, TorrentFileError> {
+        let not_a_torrent = |reason: String| TorrentFileError::NotATorrent { reason };
+
+        let (torrent, end) = DictRef::parse(s).map_err(not_a_torrent)?;
+        if end != s.len() {
+            return Err(not_a_torrent("trailing data".to_string()));
+        }
+
+        let info = torrent
+            .get_dict(b"info")
+            .ok_or_else(|| not_a_torrent("missing field `info`".to_string()))?;
+        let name = info
+            .get_bytes(b"name")
+            .ok_or_else(|| not_a_torrent("missing field `name`".to_string()))?;
+        let name = std::str::from_utf8(name).map_err(|_| TorrentFileError::NoNameFound)?;
+
+        let hash = hash_info_dict(info, None)?;
+
+        Ok(TorrentFileRef {
+            bytes: s,
+            info: info.as_bytes(),
+            name,
+            hash,
+        })
+    }
+
+    pub fn hash(&self) -> HexString {
+        self.hash.as_str()
+    }
+
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    pub fn id(&self) -> TorrentID {
+        TorrentID::from_infohash(&self.hash)
+    }
+
+    /// Returns the bencoded bytes of the info dict.
+    pub fn info_bytes(&self) -> &'a [u8] {
+        self.info
+    }
+
+    /// Returns the bencoded bytes of the whole torrent file.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Fully parses the torrent as an owned [`TorrentFile`](crate::torrent_file::TorrentFile).
+    pub fn to_torrent_file(&self) -> Result<TorrentFile, TorrentFileError> {
+        TorrentFile::from_slice(self.bytes)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for TorrentFileRef<'a> {
+    type Error = TorrentFileError;
+
+    fn try_from(s: &'a [u8]) -> Result<TorrentFileRef<'a>, TorrentFileError> {
+        TorrentFileRef::from_slice(s)
+    }
+}
+
+impl From<&TorrentFileRef<'_>> for InfoHash {
+    fn from(torrent: &TorrentFileRef<'_>) -> InfoHash {
+        torrent.hash.clone()
+    }
+}
+
+impl From<TorrentFileRef<'_>> for InfoHash {
+    fn from(torrent: TorrentFileRef<'_>) -> InfoHash {
+        torrent.hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_owned_torrent() {
+        for fixture in [
+            "tests/bittorrent-v1-emma-goldman.torrent",
+            "tests/bittorrent-v2-test.torrent",
+            "tests/bittorrent-v2-hybrid-test.torrent",
+        ] {
+            let slice = std::fs::read(fixture).unwrap();
+            let borrowed = TorrentFileRef::from_slice(&slice).unwrap();
+            let owned = TorrentFile::from_slice(&slice).unwrap();
+
+            assert_eq!(borrowed.hash(), owned.hash(), "{fixture}");
+            assert_eq!(InfoHash::from(&borrowed), InfoHash::from(&owned));
+            assert_eq!(borrowed.name(), owned.name());
+            assert_eq!(borrowed.id(), owned.id());
+            assert_eq!(borrowed.as_bytes(), slice.as_slice());
+            assert!(borrowed.info_bytes().starts_with(b"d"));
+            assert_eq!(borrowed.to_torrent_file().unwrap().hash(), owned.hash());
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_torrents() {
+        let not_a_torrent = |s: &[u8]| {
+            matches!(
+                TorrentFileRef::from_slice(s),
+                Err(TorrentFileError::NotATorrent { .. })
+            )
+        };
+        assert!(not_a_torrent(b"garbage"));
+        assert!(not_a_torrent(b"d4:infod4:name4:testee trailing"));
+        assert!(not_a_torrent(b"d8:announce3:urle"));
+        assert!(not_a_torrent(b"d4:infod6:lengthi1eee"));
+        assert!(not_a_torrent(b"d4:infod4:name4:test"));
+
+        assert_eq!(
+            TorrentFileRef::from_slice(b"d4:infod12:meta versioni3e4:name4:testee").unwrap_err(),
+            TorrentFileError::WrongVersion { version: 3 }
+        );
+        assert_eq!(
+            TorrentFileRef::from_slice(b"d4:infod4:name2:\xff\xfeee").unwrap_err(),
+            TorrentFileError::NoNameFound
+        );
+    }
+}
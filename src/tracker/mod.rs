@@ -0,0 +1,867 @@
+use url::{Host, Url};
+
+use std::str::FromStr;
+
+use crate::magnet::encode_param;
+
+mod response;
+pub use self::response::{AnnounceResponse, ScrapeResponse, ScrapeStats, TrackerResponseError};
+
+/// A source of peers. Can be a [`Tracker`](crate::tracker::Tracker) or a decentralized source.
+///
+/// The PeerSource has a canonical string representation, which is either `dht`, `pex`, `lsd`
+/// or the tracker URL. It is used by the [`Display`](std::fmt::Display) and
+/// [`FromStr`](std::str::FromStr) implementations, and for (de)serialization, so that lists of
+/// peer sources can be stored in config files and databases as plain strings.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(into = "String", try_from = "String")]
+pub enum PeerSource {
+    DHT,
+    PEX,
+    LSD,
+    Tracker(Tracker),
+}
+
+/// A centralized variant of a [`Peersource`](crate::tracker::PeerSource).
+///
+/// Trackers are compared and hashed by their [`normalized`](crate::tracker::Tracker::normalized)
+/// URL, so that the same tracker found in different places (magnets, torrents, user input) is
+/// only counted once when deduplicating.
+///
+/// The [`Debug`](std::fmt::Debug) output of a Tracker is [`redacted`](crate::tracker::Tracker::redacted),
+/// so that private tracker passkeys do not leak in logs.
+#[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(from = "RawTracker")]
+pub struct Tracker {
+    scheme: TrackerScheme,
+    url: String,
+    // Computed once, because trackers are compared and hashed when deduplicating
+    #[serde(skip_serializing)]
+    normalized: String,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+struct RawTracker {
+    scheme: TrackerScheme,
+    url: String,
+}
+
+impl From<RawTracker> for Tracker {
+    fn from(raw: RawTracker) -> Tracker {
+        Tracker::from_parts(raw.scheme, raw.url)
+    }
+}
+
+impl Tracker {
+    pub fn scheme(&self) -> &TrackerScheme {
+        &self.scheme
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns true when the tracker is reached over an encrypted connection
+    /// (`https` or `wss` schemes).
+    pub fn is_secure(&self) -> bool {
+        self.url.starts_with("https:") || self.url.starts_with("wss:")
+    }
+
+    /// Returns the lowercased host of the tracker, if any. IPv6 addresses are returned without brackets,
+    /// so that they can be parsed as an [`IpAddr`](std::net::IpAddr).
+    pub fn host(&self) -> Option<String> {
+        match self.parsed_url()?.host()? {
+            Host::Domain(domain) => Some(domain.to_lowercase()),
+            Host::Ipv4(addr) => Some(addr.to_string()),
+            Host::Ipv6(addr) => Some(addr.to_string()),
+        }
+    }
+
+    /// Returns the port of the tracker. When the URL has no port, the default port for the
+    /// scheme is returned: 80 for `http` and `ws`, 443 for `https` and `wss`. UDP trackers always
+    /// have a port, although 6969 is the conventional one.
+    pub fn port(&self) -> u16 {
+        let url = self.parsed_url();
+        match url.as_ref().and_then(|url| url.port()) {
+            Some(port) => port,
+            None if self.is_secure() => 443,
+            None if self.scheme == TrackerScheme::UDP => 6969,
+            None => 80,
+        }
+    }
+
+    /// Returns the path of the tracker URL, such as `/announce`. It may be empty for
+    /// UDP trackers.
+    pub fn path(&self) -> String {
+        self.parsed_url()
+            .map(|url| url.path().to_string())
+            .unwrap_or_default()
+    }
+
+    fn parsed_url(&self) -> Option<Url> {
+        // Trackers are always built from a valid URL
+        Url::parse(&self.url).ok()
+    }
+
+    /// Returns the passkey of a private tracker, if any. The passkey is either a `passkey`,
+    /// `authkey` or `torrent_pass` URL param, or a path segment of at least 16 alphanumeric
+    /// characters, including digits (as in `/0123456789abcdef0123456789abcdef/announce`).
+    pub fn passkey(&self) -> Option<String> {
+        let url = self.parsed_url()?;
+        let segment = url
+            .path_segments()
+            .and_then(|mut segments| segments.find(|s| is_passkey_segment(s)))
+            .map(str::to_string);
+        segment.or_else(|| {
+            url.query_pairs()
+                .find(|(key, _)| PASSKEY_PARAMS.contains(&key.as_ref()))
+                .map(|(_, value)| value.into_owned())
+        })
+    }
+
+    /// Returns a copy of the Tracker with another passkey, replacing the
+    /// [`passkey`](crate::tracker::Tracker::passkey) found in the URL. When there is none, the
+    /// passkey is added as a `passkey` URL param, which UDP trackers receive as BEP-0041 URL
+    /// data.
+    ///
+    /// ```
+    /// use hightorrent::Tracker;
+    ///
+    /// let tracker = Tracker::new("https://tracker.example/announce.php?passkey=0123456789abcdef").unwrap();
+    /// let tracker = tracker.with_passkey("fedcba9876543210").unwrap();
+    /// assert_eq!(tracker.url(), "https://tracker.example/announce.php?passkey=fedcba9876543210");
+    /// assert_eq!(tracker.redacted().url(), "https://tracker.example/announce.php?passkey=REDACTED");
+    /// ```
+    pub fn with_passkey(&self, passkey: &str) -> Result<Tracker, TrackerError> {
+        let url = match self.replace_passkey(passkey) {
+            Some(url) => url,
+            None => {
+                let mut url = Url::parse(&self.url)?;
+                url.query_pairs_mut().append_pair("passkey", passkey);
+                url
+            }
+        };
+        // The scheme was already accepted, but the new URL is validated again
+        let options = TrackerParseOptions {
+            allow_unknown_schemes: true,
+        };
+        Tracker::from_url_with(&url, options)
+    }
+
+    /// Returns a copy of the Tracker where the [`passkey`](crate::tracker::Tracker::passkey) is
+    /// replaced with `REDACTED`, for logging.
+    pub fn redacted(&self) -> Tracker {
+        match self.replace_passkey("REDACTED") {
+            Some(url) => Tracker::from_parts(self.scheme.clone(), url.to_string()),
+            None => self.clone(),
+        }
+    }
+
+    /// Replaces the passkey segment or URL params with another passkey. Returns None when the
+    /// URL has no passkey.
+    fn replace_passkey(&self, passkey: &str) -> Option<Url> {
+        let mut url = self.parsed_url()?;
+
+        let segments: Vec<&str> = url.path_segments()?.collect();
+        if segments.iter().any(|s| is_passkey_segment(s)) {
+            let passkey = encode_param(passkey).to_string();
+            let path: Vec<&str> = segments
+                .iter()
+                .map(|s| {
+                    if is_passkey_segment(s) {
+                        passkey.as_str()
+                    } else {
+                        *s
+                    }
+                })
+                .collect();
+            let path = format!("/{}", path.join("/"));
+            url.set_path(&path);
+            return Some(url);
+        }
+
+        let pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        if pairs
+            .iter()
+            .any(|(key, _)| PASSKEY_PARAMS.contains(&key.as_str()))
+        {
+            url.query_pairs_mut()
+                .clear()
+                .extend_pairs(pairs.iter().map(|(key, value)| {
+                    if PASSKEY_PARAMS.contains(&key.as_str()) {
+                        (key.as_str(), passkey)
+                    } else {
+                        (key.as_str(), value.as_str())
+                    }
+                }));
+            return Some(url);
+        }
+
+        None
+    }
+
+    /// Returns true when the tracker is only reachable through an anonymous overlay network:
+    /// Tor (`.onion` host) or I2P (`.i2p` host). Such trackers must be routed through the
+    /// overlay network, instead of being resolved with DNS.
+    pub fn is_anonymous_overlay(&self) -> bool {
+        self.scheme == TrackerScheme::I2p
+            || self.host().map_or(false, |host| {
+                host.ends_with(".onion") || host.ends_with(".i2p")
+            })
+    }
+
+    /// Returns a normalized copy of the Tracker: the host is lowercased, the default port for
+    /// the scheme is removed, and trailing slashes are stripped from the path.
+    pub fn normalized(&self) -> Tracker {
+        Tracker {
+            scheme: self.scheme.clone(),
+            url: self.normalized.clone(),
+            normalized: self.normalized.clone(),
+        }
+    }
+
+    fn from_parts(scheme: TrackerScheme, url: String) -> Tracker {
+        Tracker {
+            normalized: normalize_url(&url),
+            scheme,
+            url,
+        }
+    }
+}
+
+fn normalize_url(s: &str) -> String {
+    let mut url = match Url::parse(s) {
+        Ok(url) => url,
+        // Trackers are always built from a valid URL
+        Err(_) => return s.to_string(),
+    };
+
+    if let Some(host) = url.host_str() {
+        let host = host.to_lowercase();
+        // Setting a lowercased valid host cannot fail
+        let _ = url.set_host(Some(&host));
+    }
+
+    let default_port = match url.scheme() {
+        "http" | "ws" => Some(80),
+        "https" | "wss" => Some(443),
+        _ => None,
+    };
+    if url.port().is_some() && url.port() == default_port {
+        let _ = url.set_port(None);
+    }
+
+    let path = url.path().trim_end_matches('/').to_string();
+    url.set_path(&path);
+
+    let url = url.to_string();
+    // Special schemes (http, ws) always have a trailing slash with an empty path
+    match url.strip_suffix('/') {
+        Some(stripped) if path.is_empty() => stripped.to_string(),
+        _ => url,
+    }
+}
+
+impl PartialEq for Tracker {
+    fn eq(&self, other: &Tracker) -> bool {
+        self.scheme == other.scheme && self.normalized == other.normalized
+    }
+}
+
+impl Eq for Tracker {}
+
+impl std::hash::Hash for Tracker {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.normalized.hash(state);
+    }
+}
+
+/// A protocol used by a [`Tracker`](crate::tracker::Tracker).
+///
+/// Secure and insecure variants of a protocol (eg. `wss`/`ws`) share the same TrackerScheme. Use
+/// [`Tracker::is_secure`](crate::tracker::Tracker::is_secure) to tell them apart.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum TrackerScheme {
+    Websocket,
+    Http,
+    UDP,
+    /// An HTTP tracker on the I2P network (`.i2p` host). I2P torrents form a separate swarm,
+    /// whose peers are I2P destinations, so these trackers cannot be used by regular clients.
+    I2p,
+    /// Any other scheme, only accepted when
+    /// [`TrackerParseOptions::allow_unknown_schemes`](crate::tracker::TrackerParseOptions::allow_unknown_schemes)
+    /// is enabled.
+    Other(String),
+}
+
+/// Options controlling how a [`Tracker`](crate::tracker::Tracker) is parsed with
+/// [`Tracker::new_with`](crate::tracker::Tracker::new_with).
+///
+/// The [`Default`](std::default::Default) options are the ones used by
+/// [`Tracker::new`](crate::tracker::Tracker::new).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TrackerParseOptions {
+    /// Accept trackers with a scheme other than `http`, `https`, `ws`, `wss` and `udp`, such as
+    /// trackers of overlay networks, as [`TrackerScheme::Other`](crate::tracker::TrackerScheme::Other).
+    /// When disabled, fail with [`TrackerError::InvalidScheme`](crate::tracker::TrackerError::InvalidScheme).
+    /// Disabled by default.
+    pub allow_unknown_schemes: bool,
+}
+
+impl FromStr for TrackerScheme {
+    type Err = TrackerError;
+
+    /// Parses a URL scheme. Will fail if scheme is not "http", "https", "ws", "wss" or "udp".
+    fn from_str(s: &str) -> Result<TrackerScheme, Self::Err> {
+        match s {
+            "http" | "https" => Ok(TrackerScheme::Http),
+            "ws" | "wss" => Ok(TrackerScheme::Websocket),
+            "udp" => Ok(TrackerScheme::UDP),
+            _ => Err(TrackerError::InvalidScheme {
+                scheme: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// Error occurred during parsing a [`Tracker`](crate::tracker::Tracker).
+#[derive(Clone, Debug, PartialEq)]
+pub enum TrackerError {
+    InvalidURL {
+        source: url::ParseError,
+    },
+    InvalidScheme {
+        scheme: String,
+    },
+    /// A `udp` tracker URL had no port.
+    MissingPort {
+        url: String,
+    },
+}
+
+impl std::fmt::Display for TrackerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrackerError::InvalidURL { source } => write!(f, "Invalid URL: {source}"),
+            TrackerError::InvalidScheme { scheme } => write!(f, "Invalid scheme: {scheme}"),
+            TrackerError::MissingPort { url } => write!(f, "Missing UDP tracker port: {url}"),
+        }
+    }
+}
+
+impl std::error::Error for TrackerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TrackerError::InvalidURL { source } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<url::ParseError> for TrackerError {
+    fn from(e: url::ParseError) -> TrackerError {
+        TrackerError::InvalidURL { source: e }
+    }
+}
+
+impl PeerSource {
+    /// Generate a new PeerSource from a given string URL.
+    ///
+    /// Only covers the Tracker variant. Other variants should be
+    /// instantiated directly.
+    pub fn new(url: &str) -> Result<PeerSource, TrackerError> {
+        Ok(Tracker::new(url)?.to_peer_source())
+    }
+
+    /// Generate a new PeerSource from a given parsed URL.
+    ///
+    /// Only covers the Tracker variant. Other variants should be
+    /// instantiated directly.
+    pub fn from_url(url: &Url) -> Result<PeerSource, TrackerError> {
+        Ok(Tracker::from_url(url)?.to_peer_source())
+    }
+
+    pub fn from_tracker(tracker: &Tracker) -> PeerSource {
+        PeerSource::Tracker(tracker.clone())
+    }
+}
+
+impl std::fmt::Display for PeerSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PeerSource::DHT => write!(f, "dht"),
+            PeerSource::PEX => write!(f, "pex"),
+            PeerSource::LSD => write!(f, "lsd"),
+            PeerSource::Tracker(tracker) => write!(f, "{tracker}"),
+        }
+    }
+}
+
+impl FromStr for PeerSource {
+    type Err = TrackerError;
+
+    /// Parses a PeerSource from its canonical string representation. Decentralized
+    /// sources are matched case-insensitively, and anything else is parsed as a tracker URL.
+    fn from_str(s: &str) -> Result<PeerSource, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "dht" => Ok(PeerSource::DHT),
+            "pex" => Ok(PeerSource::PEX),
+            "lsd" => Ok(PeerSource::LSD),
+            _ => PeerSource::new(s),
+        }
+    }
+}
+
+impl From<PeerSource> for String {
+    fn from(source: PeerSource) -> String {
+        source.to_string()
+    }
+}
+
+impl TryFrom<String> for PeerSource {
+    type Error = TrackerError;
+
+    fn try_from(s: String) -> Result<PeerSource, Self::Error> {
+        PeerSource::from_str(&s)
+    }
+}
+
+/// URL params containing the passkey of private trackers.
+const PASSKEY_PARAMS: &[&str] = &["passkey", "authkey", "torrent_pass"];
+
+fn is_passkey_segment(segment: &str) -> bool {
+    segment.len() >= 16
+        && segment.bytes().all(|b| b.is_ascii_alphanumeric())
+        && segment.bytes().any(|b| b.is_ascii_digit())
+}
+
+fn is_i2p_host(host: &str) -> bool {
+    host.to_lowercase().ends_with(".i2p")
+}
+
+impl std::fmt::Debug for Tracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tracker")
+            .field("scheme", &self.scheme)
+            .field("url", &self.redacted().url)
+            .finish()
+    }
+}
+
+impl std::fmt::Display for Tracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.url)
+    }
+}
+
+impl FromStr for Tracker {
+    type Err = TrackerError;
+
+    fn from_str(s: &str) -> Result<Tracker, Self::Err> {
+        Tracker::new(s)
+    }
+}
+
+impl TryFrom<&str> for Tracker {
+    type Error = TrackerError;
+
+    fn try_from(s: &str) -> Result<Tracker, Self::Error> {
+        Tracker::new(s)
+    }
+}
+
+impl Tracker {
+    /// Generate a new Tracker from a given string URL.
+    pub fn new(url: &str) -> Result<Tracker, TrackerError> {
+        Tracker::new_with(url, TrackerParseOptions::default())
+    }
+
+    /// Generate a new Tracker from a given string URL, with custom
+    /// [`TrackerParseOptions`](crate::tracker::TrackerParseOptions).
+    ///
+    /// ```
+    /// use hightorrent::{Tracker, TrackerParseOptions, TrackerScheme};
+    ///
+    /// let url = "gopher://tracker.example/announce";
+    /// assert!(Tracker::new(url).is_err());
+    ///
+    /// let options = TrackerParseOptions { allow_unknown_schemes: true };
+    /// let tracker = Tracker::new_with(url, options).unwrap();
+    /// assert_eq!(tracker.scheme(), &TrackerScheme::Other("gopher".to_string()));
+    /// ```
+    pub fn new_with(url: &str, options: TrackerParseOptions) -> Result<Tracker, TrackerError> {
+        let url = Url::parse(url)?;
+        Tracker::from_url_with(&url, options)
+    }
+
+    /// Generate a new Tracker from a parsed URL.
+    ///
+    /// Will fail if scheme is not "http", "https", "ws", "wss" or "udp". UDP trackers must have a
+    /// port. Their path and query (eg. `/announce.php?passkey=1`) are ignored by BEP-0015 but
+    /// kept, as some trackers expect them as BEP-0041 URL data.
+    pub fn from_url(url: &Url) -> Result<Tracker, TrackerError> {
+        Tracker::from_url_with(url, TrackerParseOptions::default())
+    }
+
+    /// Generate a new Tracker from a parsed URL, with custom
+    /// [`TrackerParseOptions`](crate::tracker::TrackerParseOptions).
+    pub fn from_url_with(url: &Url, options: TrackerParseOptions) -> Result<Tracker, TrackerError> {
+        let scheme = match TrackerScheme::from_str(url.scheme()) {
+            Ok(scheme) => scheme,
+            Err(_) if options.allow_unknown_schemes => {
+                TrackerScheme::Other(url.scheme().to_string())
+            }
+            Err(e) => return Err(e),
+        };
+        let scheme = match url.host_str() {
+            Some(host) if scheme == TrackerScheme::Http && is_i2p_host(host) => TrackerScheme::I2p,
+            _ => scheme,
+        };
+
+        if scheme == TrackerScheme::UDP && url.port().is_none() {
+            return Err(TrackerError::MissingPort {
+                url: url.to_string(),
+            });
+        }
+
+        Ok(Tracker::from_parts(scheme, url.as_str().to_string()))
+    }
+
+    /// Turns a centralized Tracker into a wider PeerSource
+    pub fn to_peer_source(&self) -> PeerSource {
+        PeerSource::from_tracker(self)
+    }
+
+    /// Returns the scrape URL for an HTTP(S) tracker, following the
+    /// [scrape convention](https://www.bittorrent.org/beps/bep_0048.html): if the last
+    /// path segment of the announce URL starts with `announce`, it is replaced with `scrape`
+    /// (eg. `/x/announce.php?passkey=1` becomes `/x/scrape.php?passkey=1`).
+    ///
+    /// Returns None when the announce URL does not follow this convention, in which
+    /// case the tracker does not support scraping. Returns None for non-HTTP trackers,
+    /// which have their own scraping mechanism.
+    pub fn scrape_url(&self) -> Option<Url> {
+        if self.scheme != TrackerScheme::Http {
+            return None;
+        }
+
+        let mut url = Url::parse(&self.url).ok()?;
+        let (dir, last) = url.path().rsplit_once('/')?;
+        let rest = last.strip_prefix("announce")?;
+        let path = format!("{dir}/scrape{rest}");
+        url.set_path(&path);
+        Some(url)
+    }
+}
+
+/// Turn a backend-specific tracker struct into an agnostic [`Tracker`](crate::tracker::Tracker).
+pub trait TryIntoTracker {
+    fn try_into_tracker(&self) -> Result<Tracker, TrackerError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_websocket_trackers() {
+        let secure = Tracker::new("wss://tracker.openwebtorrent.com").unwrap();
+        assert_eq!(secure.scheme(), &TrackerScheme::Websocket);
+        assert!(secure.is_secure());
+
+        let insecure = Tracker::new("ws://tracker.example.com:8000").unwrap();
+        assert_eq!(insecure.scheme(), &TrackerScheme::Websocket);
+        assert!(!insecure.is_secure());
+    }
+
+    #[test]
+    fn can_tell_secure_trackers() {
+        assert!(Tracker::new("https://example.com/announce")
+            .unwrap()
+            .is_secure());
+        assert!(!Tracker::new("http://example.com/announce")
+            .unwrap()
+            .is_secure());
+        assert!(!Tracker::new("udp://example.com:6969/announce")
+            .unwrap()
+            .is_secure());
+    }
+
+    #[test]
+    fn can_access_host_and_port() {
+        let cases = [
+            (
+                "udp://Tracker.Example.com:1337/announce",
+                "tracker.example.com",
+                1337,
+                "/announce",
+            ),
+            ("http://10.0.0.1/announce", "10.0.0.1", 80, "/announce"),
+            (
+                "https://example.com/a/announce",
+                "example.com",
+                443,
+                "/a/announce",
+            ),
+            ("wss://example.com", "example.com", 443, "/"),
+            ("ws://[2001:db8::1]:8000/", "2001:db8::1", 8000, "/"),
+            ("udp://[::1]:6969/announce", "::1", 6969, "/announce"),
+        ];
+        for (url, host, port, path) in cases {
+            let tracker = Tracker::new(url).unwrap();
+            assert_eq!(tracker.host().as_deref(), Some(host), "{url}");
+            assert_eq!(tracker.port(), port, "{url}");
+            assert_eq!(tracker.path(), path, "{url}");
+        }
+
+        let ipv6 = Tracker::new("udp://[::1]:6969").unwrap();
+        assert!(ipv6.host().unwrap().parse::<std::net::IpAddr>().is_ok());
+    }
+
+    #[test]
+    fn validates_udp_trackers() {
+        assert!(Tracker::new("udp://tracker.example.com:6969").is_ok());
+        assert!(Tracker::new("udp://tracker.example.com:6969/announce/").is_ok());
+        assert_eq!(
+            Tracker::new("udp://tracker.example.com/announce").unwrap_err(),
+            TrackerError::MissingPort {
+                url: "udp://tracker.example.com/announce".to_string()
+            }
+        );
+        for url in [
+            "udp://tracker.example.com:6969/announce.php",
+            "udp://tracker.example.com:6969/announce?passkey=abc",
+            "udp://tracker.example.com:6969/#fragment",
+        ] {
+            assert_eq!(Tracker::new(url).unwrap().url(), url);
+        }
+    }
+
+    #[test]
+    fn can_convert_from_str() {
+        let url = "udp://tracker.opentrackr.org:1337/announce";
+        let tracker: Tracker = url.parse().unwrap();
+        assert_eq!(tracker, Tracker::new(url).unwrap());
+        assert_eq!(Tracker::try_from(url).unwrap(), tracker);
+        assert!("tracker.example".parse::<Tracker>().is_err());
+    }
+
+    #[test]
+    fn can_allow_unknown_schemes() {
+        let options = TrackerParseOptions {
+            allow_unknown_schemes: true,
+        };
+        let tracker = Tracker::new_with("ftp://example.com/announce", options).unwrap();
+        assert_eq!(tracker.scheme(), &TrackerScheme::Other("ftp".to_string()));
+        assert_eq!(tracker.url(), "ftp://example.com/announce");
+        assert!(!tracker.is_secure());
+        assert_eq!(tracker, tracker.normalized());
+
+        // Known schemes are still validated
+        assert!(Tracker::new_with("udp://example.com/announce", options).is_err());
+    }
+
+    #[test]
+    fn recognizes_overlay_trackers() {
+        let i2p = Tracker::new("http://tracker2.postman.I2P/announce.php").unwrap();
+        assert_eq!(i2p.scheme(), &TrackerScheme::I2p);
+        assert!(i2p.is_anonymous_overlay());
+
+        let onion = Tracker::new(
+            "udp://example2vqzxtnrmdwbtkc6bp5ruqfbkvv6rutsdrh6bn4tqnvnqmid.onion:6969/announce",
+        )
+        .unwrap();
+        assert_eq!(onion.scheme(), &TrackerScheme::UDP);
+        assert!(onion.is_anonymous_overlay());
+
+        let clearnet = Tracker::new("http://tracker.example.com/announce").unwrap();
+        assert_eq!(clearnet.scheme(), &TrackerScheme::Http);
+        assert!(!clearnet.is_anonymous_overlay());
+    }
+
+    #[test]
+    fn replaces_passkeys() {
+        let tracker =
+            Tracker::new("https://tracker.example/0123456789abcdef0123456789abcdef/announce")
+                .unwrap();
+        assert_eq!(
+            tracker.passkey().as_deref(),
+            Some("0123456789abcdef0123456789abcdef")
+        );
+        let replaced = tracker.with_passkey("fedcba9876543210").unwrap();
+        assert_eq!(
+            replaced.url(),
+            "https://tracker.example/fedcba9876543210/announce"
+        );
+        assert_eq!(
+            tracker.redacted().url(),
+            "https://tracker.example/REDACTED/announce"
+        );
+        assert!(!format!("{tracker:?}").contains("0123456789abcdef"));
+        // Display is not redacted, because it is used for serialization
+        assert_eq!(tracker.to_string(), tracker.url());
+
+        let tracker = Tracker::new("http://tracker.example/announce?info=x&authkey=abc").unwrap();
+        assert_eq!(tracker.passkey().as_deref(), Some("abc"));
+        assert_eq!(
+            tracker.redacted().url(),
+            "http://tracker.example/announce?info=x&authkey=REDACTED"
+        );
+
+        let public = Tracker::new("http://tracker.example/announce").unwrap();
+        assert_eq!(public.passkey(), None);
+        assert_eq!(public.redacted().url(), public.url());
+        assert_eq!(
+            public.with_passkey("abc").unwrap().url(),
+            "http://tracker.example/announce?passkey=abc"
+        );
+
+        let udp = Tracker::new("udp://tracker.example:6969/announce").unwrap();
+        assert_eq!(
+            udp.with_passkey("abc").unwrap().url(),
+            "udp://tracker.example:6969/announce?passkey=abc"
+        );
+    }
+
+    #[test]
+    fn fails_unknown_scheme() {
+        assert_eq!(
+            Tracker::new("ftp://example.com/announce").unwrap_err(),
+            TrackerError::InvalidScheme {
+                scheme: "ftp".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn can_normalize() {
+        let cases = [
+            (
+                "http://Tracker.Example.COM:80/announce/",
+                "http://tracker.example.com/announce",
+            ),
+            ("https://example.com:443/", "https://example.com"),
+            ("https://example.com:8443/", "https://example.com:8443"),
+            ("wss://example.com:443", "wss://example.com"),
+            (
+                "udp://Tracker.Example.com:6969/announce//",
+                "udp://tracker.example.com:6969/announce",
+            ),
+            (
+                "udp://tracker.example.com:6969/",
+                "udp://tracker.example.com:6969",
+            ),
+        ];
+
+        for (url, normalized) in cases {
+            let tracker = Tracker::new(url).unwrap();
+            assert_eq!(tracker.normalized().url(), normalized, "{url}");
+        }
+    }
+
+    #[test]
+    fn compares_normalized() {
+        use std::collections::HashSet;
+
+        let trackers = [
+            Tracker::new("udp://Tracker.Example.com:6969/announce/").unwrap(),
+            Tracker::new("udp://tracker.example.com:6969/announce").unwrap(),
+            Tracker::new("http://tracker.example.com:80/announce").unwrap(),
+            Tracker::new("http://tracker.example.com/announce").unwrap(),
+        ];
+        assert_eq!(trackers[0], trackers[1]);
+        assert_ne!(trackers[1], trackers[2]);
+
+        // The normalized URL is not serialized, but computed again
+        let json = serde_json::to_string(&trackers[0]).unwrap();
+        assert_eq!(
+            json,
+            r#"{"scheme":"UDP","url":"udp://Tracker.Example.com:6969/announce/"}"#
+        );
+        let deserialized: Tracker = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, trackers[1]);
+        assert_eq!(deserialized.url(), trackers[0].url());
+
+        let set: HashSet<Tracker> = trackers.into_iter().collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn peersource_string_roundtrip() {
+        for s in [
+            "dht",
+            "pex",
+            "lsd",
+            "udp://tracker.example.com:6969/announce",
+        ] {
+            let source = PeerSource::from_str(s).unwrap();
+            assert_eq!(source.to_string(), s);
+        }
+        assert_eq!(PeerSource::from_str("DHT").unwrap(), PeerSource::DHT);
+        assert!(PeerSource::from_str("bluetooth").is_err());
+    }
+
+    #[test]
+    fn peersource_serializes_as_string() {
+        let sources = vec![
+            PeerSource::DHT,
+            PeerSource::new("https://tracker.example.com/announce").unwrap(),
+        ];
+        let encoded = bt_bencode::to_vec(&sources).unwrap();
+        assert_eq!(
+            encoded,
+            b"l3:dht36:https://tracker.example.com/announcee".to_vec()
+        );
+        let decoded: Vec<PeerSource> = bt_bencode::from_slice(&encoded).unwrap();
+        assert_eq!(decoded, sources);
+
+        let invalid = b"l7:unknowne";
+        assert!(bt_bencode::from_slice::<Vec<PeerSource>>(invalid).is_err());
+    }
+
+    #[test]
+    fn can_derive_scrape_url() {
+        let cases = [
+            ("http://example.com/announce", "http://example.com/scrape"),
+            (
+                "http://example.com/x/announce",
+                "http://example.com/x/scrape",
+            ),
+            (
+                "https://example.com/announce.php?passkey=1234",
+                "https://example.com/scrape.php?passkey=1234",
+            ),
+            (
+                "http://example.com/announce?x=2/4",
+                "http://example.com/scrape?x=2/4",
+            ),
+            (
+                "http://example.com/announce?x2%0644",
+                "http://example.com/scrape?x2%0644",
+            ),
+        ];
+
+        for (announce, scrape) in cases {
+            let tracker = Tracker::new(announce).unwrap();
+            assert_eq!(tracker.scrape_url().unwrap().as_str(), scrape);
+        }
+    }
+
+    #[test]
+    fn no_scrape_url_outside_convention() {
+        let cases = [
+            "http://example.com/a",
+            "http://example.com/x%064announce",
+            "udp://tracker.opentrackr.org:1337/announce",
+        ];
+
+        for announce in cases {
+            let tracker = Tracker::new(announce).unwrap();
+            assert_eq!(tracker.scrape_url(), None, "{announce}");
+        }
+    }
+}
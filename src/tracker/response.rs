@@ -0,0 +1,429 @@
+//! Parsing of the bencoded responses sent by HTTP trackers to announce
+//! ([BEP-0003](https://www.bittorrent.org/beps/bep_0003.html)) and scrape
+//! ([BEP-0048](https://www.bittorrent.org/beps/bep_0048.html)) requests.
+
+use bt_bencode::{ByteString, Value as BencodeValue};
+use rustc_hex::ToHex;
+
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use crate::bencode::nesting_depth;
+use crate::torrent_file::bencode_i64;
+use crate::{ParseOptions, PeerAddr, TorrentID};
+
+/// Error occurred while parsing an [`AnnounceResponse`](crate::tracker::AnnounceResponse) or a
+/// [`ScrapeResponse`](crate::tracker::ScrapeResponse).
+#[derive(Clone, Debug, PartialEq)]
+pub enum TrackerResponseError {
+    // TODO: bt_bencode::Error is not PartialEq so we store error as String
+    InvalidBencode {
+        reason: String,
+    },
+    /// The tracker rejected the request, with a human-readable `failure reason`.
+    Failure {
+        reason: String,
+    },
+    MissingField {
+        field: String,
+    },
+    InvalidField {
+        field: String,
+    },
+}
+
+impl std::fmt::Display for TrackerResponseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrackerResponseError::InvalidBencode { reason } => {
+                write!(f, "Invalid bencode: {reason}")
+            }
+            TrackerResponseError::Failure { reason } => write!(f, "Tracker failure: {reason}"),
+            TrackerResponseError::MissingField { field } => {
+                write!(f, "Missing field in tracker response: {field}")
+            }
+            TrackerResponseError::InvalidField { field } => {
+                write!(f, "Invalid field in tracker response: {field}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TrackerResponseError {}
+
+type BencodeDict = BTreeMap<ByteString, BencodeValue>;
+
+/// The response of an HTTP tracker to an announce request.
+///
+/// Peers are accepted in the compact form (`peers` as a byte string of 6-byte IPv4 entries,
+/// [BEP-0023](https://www.bittorrent.org/beps/bep_0023.html)), in the dict form (`peers` as a
+/// list of dicts with `ip` and `port`, where `ip` may be a hostname), and as compact IPv6
+/// entries of 18 bytes in `peers6` ([BEP-0007](https://www.bittorrent.org/beps/bep_0007.html)).
+/// Peer IDs of the dict form are ignored, and peers with a zero port are skipped.
+///
+/// When the tracker rejects the announce, the `failure reason` is returned as
+/// [`TrackerResponseError::Failure`](crate::tracker::TrackerResponseError::Failure).
+///
+/// ```
+/// use hightorrent::{AnnounceResponse, PeerAddr, TrackerResponseError};
+///
+/// let body = b"d8:intervali1800e5:peers6:\x0a\x00\x00\x01\x1a\xe1e";
+/// let response = AnnounceResponse::from_slice(body).unwrap();
+/// assert_eq!(response.interval, 1800);
+/// assert_eq!(response.peers, vec![PeerAddr::new("10.0.0.1:6881").unwrap()]);
+///
+/// let body = b"d14:failure reason20:unregistered torrente";
+/// assert_eq!(
+///     AnnounceResponse::from_slice(body).unwrap_err(),
+///     TrackerResponseError::Failure { reason: "unregistered torrent".to_string() }
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnnounceResponse {
+    /// The number of seconds to wait before the next regular announce
+    pub interval: u64,
+    /// The minimum number of seconds to wait before announcing again
+    pub min_interval: Option<u64>,
+    /// An opaque value to send back in the next announces
+    pub tracker_id: Option<Vec<u8>>,
+    /// The number of seeders
+    pub complete: Option<u64>,
+    /// The number of leechers
+    pub incomplete: Option<u64>,
+    /// A warning which does not prevent the announce from succeeding
+    pub warning_message: Option<String>,
+    /// The IP address of the client, as seen by the tracker
+    /// ([BEP-0024](https://www.bittorrent.org/beps/bep_0024.html))
+    pub external_ip: Option<IpAddr>,
+    pub peers: Vec<PeerAddr>,
+}
+
+impl AnnounceResponse {
+    /// Parses the body of an announce response.
+    pub fn from_slice(s: &[u8]) -> Result<AnnounceResponse, TrackerResponseError> {
+        let dict = decode_response(s)?;
+
+        let mut peers = match dict.get("peers".as_bytes()) {
+            None => Vec::new(),
+            Some(BencodeValue::ByteStr(bytes)) => PeerAddr::compact_v4(bytes)
+                .map_err(|_| invalid("peers"))?
+                .map(SocketAddr::from)
+                .filter_map(ip_peer)
+                .collect(),
+            Some(BencodeValue::List(list)) => list
+                .iter()
+                .map(dict_peer)
+                .collect::<Option<Vec<Option<PeerAddr>>>>()
+                .ok_or(invalid("peers"))?
+                .into_iter()
+                .flatten()
+                .collect(),
+            Some(_) => return Err(invalid("peers")),
+        };
+        if let Some(peers6) = dict.get("peers6".as_bytes()) {
+            let peers6 = peers6.as_byte_str().ok_or(invalid("peers6"))?;
+            let peers6 = PeerAddr::compact_v6(peers6).map_err(|_| invalid("peers6"))?;
+            peers.extend(peers6.map(SocketAddr::from).filter_map(ip_peer));
+        }
+
+        let external_ip = match bytes(&dict, "external ip")? {
+            None => None,
+            Some(ip) => Some(ip_from_bytes(ip).ok_or(invalid("external ip"))?),
+        };
+
+        Ok(AnnounceResponse {
+            interval: counter(&dict, "interval")?.ok_or(TrackerResponseError::MissingField {
+                field: "interval".to_string(),
+            })?,
+            min_interval: counter(&dict, "min interval")?,
+            tracker_id: bytes(&dict, "tracker id")?.map(<[u8]>::to_vec),
+            complete: counter(&dict, "complete")?,
+            incomplete: counter(&dict, "incomplete")?,
+            warning_message: bytes(&dict, "warning message")?
+                .map(|message| String::from_utf8_lossy(message).to_string()),
+            external_ip,
+            peers,
+        })
+    }
+}
+
+/// The statistics of a single torrent in a [`ScrapeResponse`](crate::tracker::ScrapeResponse).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScrapeStats {
+    /// The number of seeders
+    pub complete: u64,
+    /// The number of completed downloads
+    pub downloaded: u64,
+    /// The number of leechers
+    pub incomplete: u64,
+}
+
+/// The response of an HTTP tracker to a scrape request, sent to its
+/// [`scrape_url`](crate::tracker::Tracker::scrape_url).
+///
+/// Torrents are keyed by the 20-byte infohash used in the request, which is the
+/// [`TorrentID`](crate::id::TorrentID) of the torrent: v2 torrents are scraped with their
+/// truncated infohash ([BEP-0052](https://www.bittorrent.org/beps/bep_0052.html)).
+///
+/// ```
+/// use hightorrent::{ScrapeResponse, TorrentID};
+///
+/// let mut body = b"d5:filesd20:".to_vec();
+/// body.extend([0xc8, 0x11, 0xb4, 0x16, 0x41, 0xa0, 0x9d, 0x19, 0x2b, 0x8e]);
+/// body.extend([0xd8, 0x1b, 0x14, 0x06, 0x4f, 0xff, 0x55, 0xd8, 0x5c, 0xe3]);
+/// body.extend(b"d8:completei12e10:downloadedi340e10:incompletei3eeee");
+///
+/// let response = ScrapeResponse::from_slice(&body).unwrap();
+/// let id = TorrentID::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap();
+/// assert_eq!(response.files[&id].downloaded, 340);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScrapeResponse {
+    pub files: HashMap<TorrentID, ScrapeStats>,
+    /// The minimum number of seconds to wait before scraping again, from the `flags` dict
+    pub min_request_interval: Option<u64>,
+}
+
+impl ScrapeResponse {
+    /// Parses the body of a scrape response.
+    pub fn from_slice(s: &[u8]) -> Result<ScrapeResponse, TrackerResponseError> {
+        let dict = decode_response(s)?;
+
+        let mut files = HashMap::new();
+        if let Some(entries) = dict.get("files".as_bytes()) {
+            let entries = entries.as_dict().ok_or(invalid("files"))?;
+            for (hash, stats) in entries {
+                if hash.len() != 20 {
+                    return Err(invalid("files"));
+                }
+                // 40 hexadecimal characters are always a valid TorrentID
+                let id = TorrentID::new(hash.to_hex::<String>()).unwrap();
+                let stats = stats.as_dict().ok_or(invalid("files"))?;
+                let stats = ScrapeStats {
+                    complete: counter(stats, "complete")?.unwrap_or(0),
+                    downloaded: counter(stats, "downloaded")?.unwrap_or(0),
+                    incomplete: counter(stats, "incomplete")?.unwrap_or(0),
+                };
+                files.insert(id, stats);
+            }
+        }
+
+        let min_request_interval = match dict.get("flags".as_bytes()) {
+            None => None,
+            Some(flags) => counter(
+                flags.as_dict().ok_or(invalid("flags"))?,
+                "min_request_interval",
+            )?,
+        };
+
+        Ok(ScrapeResponse {
+            files,
+            min_request_interval,
+        })
+    }
+}
+
+/// Decodes the dict of a tracker response, failing when the tracker reported a failure.
+fn decode_response(s: &[u8]) -> Result<BencodeDict, TrackerResponseError> {
+    // Values are decoded recursively, so deeply nested data would overflow the stack
+    let depth = nesting_depth(s);
+    if depth > ParseOptions::DEFAULT_MAX_NESTING_DEPTH {
+        return Err(TrackerResponseError::InvalidBencode {
+            reason: format!("nested too deep: {depth} levels"),
+        });
+    }
+    let dict = match bt_bencode::from_slice(s) {
+        Ok(BencodeValue::Dict(dict)) => dict,
+        Ok(_) => {
+            return Err(TrackerResponseError::InvalidBencode {
+                reason: "response is not a dict".to_string(),
+            })
+        }
+        Err(e) => {
+            return Err(TrackerResponseError::InvalidBencode {
+                reason: e.to_string(),
+            })
+        }
+    };
+
+    if let Some(reason) = bytes(&dict, "failure reason")? {
+        return Err(TrackerResponseError::Failure {
+            reason: String::from_utf8_lossy(reason).to_string(),
+        });
+    }
+    Ok(dict)
+}
+
+fn invalid(field: &str) -> TrackerResponseError {
+    TrackerResponseError::InvalidField {
+        field: field.to_string(),
+    }
+}
+
+fn bytes<'a>(dict: &'a BencodeDict, field: &str) -> Result<Option<&'a [u8]>, TrackerResponseError> {
+    match dict.get(field.as_bytes()) {
+        None => Ok(None),
+        Some(v) => v
+            .as_byte_str()
+            .map(|bytes| Some(bytes.as_slice()))
+            .ok_or(invalid(field)),
+    }
+}
+
+fn counter(dict: &BencodeDict, field: &str) -> Result<Option<u64>, TrackerResponseError> {
+    match dict.get(field.as_bytes()) {
+        None => Ok(None),
+        Some(v) => bencode_i64(v)
+            .and_then(|v| u64::try_from(v).ok())
+            .map(Some)
+            .ok_or(invalid(field)),
+    }
+}
+
+/// Skips compact peers with a zero port.
+fn ip_peer(addr: SocketAddr) -> Option<PeerAddr> {
+    Some(PeerAddr::Ip(addr)).filter(|_| addr.port() != 0)
+}
+
+fn ip_from_bytes(bytes: &[u8]) -> Option<IpAddr> {
+    match bytes.len() {
+        4 => Some(Ipv4Addr::from(<[u8; 4]>::try_from(bytes).ok()?).into()),
+        16 => Some(Ipv6Addr::from(<[u8; 16]>::try_from(bytes).ok()?).into()),
+        _ => None,
+    }
+}
+
+/// Decodes a peer of the dict form. Returns None when the peer is invalid, and Some(None)
+/// when it has a zero port.
+fn dict_peer(peer: &BencodeValue) -> Option<Option<PeerAddr>> {
+    let peer = peer.as_dict()?;
+    let ip = peer.get("ip".as_bytes())?.as_str()?;
+    let port = u16::try_from(bencode_i64(peer.get("port".as_bytes())?)?).ok()?;
+    if port == 0 {
+        return Some(None);
+    }
+    let peer = match ip.parse::<IpAddr>() {
+        Ok(ip) => PeerAddr::Ip(SocketAddr::new(ip, port)),
+        Err(_) => PeerAddr::new(&format!("{ip}:{port}")).ok()?,
+    };
+    Some(Some(peer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PEERS: [u8; 12] = [10, 0, 0, 1, 0x1a, 0xe1, 192, 168, 1, 2, 0, 0];
+    const PEERS6: [u8; 18] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0xc8, 0xd5];
+
+    #[test]
+    fn parses_compact_announce() {
+        let body = [
+            &b"d8:completei5e11:external ip4:"[..],
+            &[203, 0, 113, 7],
+            b"10:incompletei3e8:intervali1800e12:min intervali900e5:peers12:",
+            &PEERS,
+            b"6:peers618:",
+            &PEERS6,
+            b"10:tracker id3:abc15:warning message4:slowe",
+        ]
+        .concat();
+
+        let response = AnnounceResponse::from_slice(&body).unwrap();
+        assert_eq!(response.interval, 1800);
+        assert_eq!(response.min_interval, Some(900));
+        assert_eq!(response.complete, Some(5));
+        assert_eq!(response.incomplete, Some(3));
+        assert_eq!(response.tracker_id, Some(b"abc".to_vec()));
+        assert_eq!(response.warning_message.as_deref(), Some("slow"));
+        assert_eq!(response.external_ip, Some("203.0.113.7".parse().unwrap()));
+        // The peer with a zero port is skipped
+        assert_eq!(
+            response.peers,
+            vec![
+                PeerAddr::new("10.0.0.1:6881").unwrap(),
+                PeerAddr::new("[::1]:51413").unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_dict_peers() {
+        let body = b"d8:intervali60e5:peersld2:ip8:10.0.0.27:peer id20:-XX0001-0123456789ab4:porti6881eed2:ip16:Peer.Example.com4:porti51413eeee";
+        let response = AnnounceResponse::from_slice(body).unwrap();
+        assert_eq!(response.complete, None);
+        assert_eq!(
+            response.peers,
+            vec![
+                PeerAddr::new("10.0.0.2:6881").unwrap(),
+                PeerAddr::new("peer.example.com:51413").unwrap(),
+            ]
+        );
+
+        let body = b"d8:intervali60e5:peersld2:ip4:a b!4:porti6881eeee";
+        assert_eq!(
+            AnnounceResponse::from_slice(body).unwrap_err(),
+            invalid("peers")
+        );
+    }
+
+    #[test]
+    fn fails_invalid_announce() {
+        assert_eq!(
+            AnnounceResponse::from_slice(b"d5:peers0:e").unwrap_err(),
+            TrackerResponseError::MissingField {
+                field: "interval".to_string()
+            }
+        );
+        assert_eq!(
+            AnnounceResponse::from_slice(b"d8:intervali-1ee").unwrap_err(),
+            invalid("interval")
+        );
+        assert_eq!(
+            AnnounceResponse::from_slice(b"d8:intervali60e5:peers5:abcdee").unwrap_err(),
+            invalid("peers")
+        );
+        assert!(matches!(
+            AnnounceResponse::from_slice(b"le").unwrap_err(),
+            TrackerResponseError::InvalidBencode { .. }
+        ));
+        assert!(matches!(
+            AnnounceResponse::from_slice(&[b'l'; 1000]).unwrap_err(),
+            TrackerResponseError::InvalidBencode { .. }
+        ));
+    }
+
+    #[test]
+    fn parses_scrape() {
+        let hash = [0xab; 20];
+        let body = [
+            &b"d5:filesd20:"[..],
+            &hash,
+            b"d8:completei1e10:downloadedi2e10:incompletei3e4:name4:testee",
+            b"5:flagsd20:min_request_intervali3600eee",
+        ]
+        .concat();
+
+        let response = ScrapeResponse::from_slice(&body).unwrap();
+        assert_eq!(response.min_request_interval, Some(3600));
+        assert_eq!(
+            response.files[&TorrentID::new("ab".repeat(20)).unwrap()],
+            ScrapeStats {
+                complete: 1,
+                downloaded: 2,
+                incomplete: 3
+            }
+        );
+
+        assert_eq!(
+            ScrapeResponse::from_slice(b"d5:filesd3:abcdeee").unwrap_err(),
+            invalid("files")
+        );
+        assert_eq!(
+            ScrapeResponse::from_slice(b"d14:failure reason6:bannede").unwrap_err(),
+            TrackerResponseError::Failure {
+                reason: "banned".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,256 @@
+use crate::torrent::tracker_sources;
+use crate::{
+    InfoHash, InfoHashError, Progress, Timestamp, ToTorrent, Torrent, TorrentLimits, Tracker,
+    TrackerError, TryIntoTracker,
+};
+
+/// A torrent, as returned by the Transmission RPC `torrent-get` method.
+///
+/// Only the fields required to build a [`Torrent`](crate::torrent::Torrent) are deserialized,
+/// so make sure to request at least `hashString` and `name`. The infohash is validated during
+/// deserialization.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "RawTransmissionTorrent")]
+pub struct TransmissionTorrent {
+    pub hash: InfoHash,
+    pub name: String,
+    pub download_dir: String,
+    /// Unix timestamp (in seconds) when the torrent was added
+    pub added_date: i64,
+    /// Unix timestamp (in seconds) when the torrent was completed, or 0
+    pub done_date: i64,
+    /// Progress ratio (0-1)
+    pub percent_done: f64,
+    pub total_size: i64,
+    /// Total bytes uploaded (`uploadedEver`)
+    pub uploaded_ever: Option<u64>,
+    /// Total bytes downloaded (`downloadedEver`)
+    pub downloaded_ever: Option<u64>,
+    pub status: TransmissionStatus,
+    pub labels: Vec<String>,
+    pub trackers: Vec<TransmissionTracker>,
+    /// The limits from the `downloadLimit`, `uploadLimit` and `seedRatioLimit` fields, with
+    /// their `downloadLimited`, `uploadLimited` and `seedRatioMode` flags
+    pub limits: TorrentLimits,
+}
+
+/// The status of a [`TransmissionTorrent`](crate::transmission::TransmissionTorrent).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransmissionStatus {
+    Stopped,
+    CheckWait,
+    Check,
+    DownloadWait,
+    Download,
+    SeedWait,
+    Seed,
+    Unknown(i64),
+}
+
+impl From<i64> for TransmissionStatus {
+    fn from(status: i64) -> TransmissionStatus {
+        match status {
+            0 => TransmissionStatus::Stopped,
+            1 => TransmissionStatus::CheckWait,
+            2 => TransmissionStatus::Check,
+            3 => TransmissionStatus::DownloadWait,
+            4 => TransmissionStatus::Download,
+            5 => TransmissionStatus::SeedWait,
+            6 => TransmissionStatus::Seed,
+            other => TransmissionStatus::Unknown(other),
+        }
+    }
+}
+
+impl std::fmt::Display for TransmissionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransmissionStatus::Stopped => write!(f, "stopped"),
+            TransmissionStatus::CheckWait => write!(f, "check-wait"),
+            TransmissionStatus::Check => write!(f, "check"),
+            TransmissionStatus::DownloadWait => write!(f, "download-wait"),
+            TransmissionStatus::Download => write!(f, "download"),
+            TransmissionStatus::SeedWait => write!(f, "seed-wait"),
+            TransmissionStatus::Seed => write!(f, "seed"),
+            TransmissionStatus::Unknown(status) => write!(f, "unknown-{status}"),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTransmissionTorrent {
+    hash_string: String,
+    name: String,
+    #[serde(default)]
+    download_dir: String,
+    #[serde(default)]
+    added_date: i64,
+    #[serde(default)]
+    done_date: i64,
+    #[serde(default)]
+    percent_done: f64,
+    #[serde(default)]
+    total_size: i64,
+    #[serde(default)]
+    uploaded_ever: Option<u64>,
+    #[serde(default)]
+    downloaded_ever: Option<u64>,
+    #[serde(default)]
+    status: i64,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    trackers: Vec<TransmissionTracker>,
+    #[serde(default)]
+    download_limited: bool,
+    #[serde(default)]
+    download_limit: i64,
+    #[serde(default)]
+    upload_limited: bool,
+    #[serde(default)]
+    upload_limit: i64,
+    #[serde(default)]
+    seed_ratio_mode: i64,
+    #[serde(default)]
+    seed_ratio_limit: f64,
+}
+
+impl TryFrom<RawTransmissionTorrent> for TransmissionTorrent {
+    type Error = InfoHashError;
+
+    fn try_from(raw: RawTransmissionTorrent) -> Result<TransmissionTorrent, InfoHashError> {
+        Ok(TransmissionTorrent {
+            hash: InfoHash::new(&raw.hash_string)?,
+            name: raw.name,
+            download_dir: raw.download_dir,
+            added_date: raw.added_date,
+            done_date: raw.done_date,
+            percent_done: raw.percent_done,
+            total_size: raw.total_size,
+            uploaded_ever: raw.uploaded_ever,
+            downloaded_ever: raw.downloaded_ever,
+            status: TransmissionStatus::from(raw.status),
+            labels: raw.labels,
+            trackers: raw.trackers,
+            limits: TorrentLimits::from_transmission(
+                raw.download_limited,
+                raw.download_limit,
+                raw.upload_limited,
+                raw.upload_limit,
+                raw.seed_ratio_mode,
+                raw.seed_ratio_limit,
+            ),
+        })
+    }
+}
+
+impl ToTorrent for TransmissionTorrent {
+    fn to_torrent(&self) -> Torrent {
+        Torrent {
+            name: self.name.clone(),
+            path: self.download_dir.clone(),
+            date_start: Timestamp::from_secs_opt(self.added_date),
+            date_end: Timestamp::from_secs_opt(self.done_date),
+            progress: Progress::from_fraction(
+                self.percent_done,
+                u64::try_from(self.total_size).unwrap_or(0),
+            ),
+            size: self.total_size,
+            uploaded: self.uploaded_ever,
+            downloaded: self.downloaded_ever,
+            state: self.status.to_string(),
+            tags: self.labels.clone(),
+            category: None,
+            sources: tracker_sources(
+                self.trackers
+                    .iter()
+                    .filter_map(|t| t.try_into_tracker().ok()),
+            ),
+            limits: self.limits,
+            id: self.hash.id(),
+            hash: self.hash.clone(),
+        }
+    }
+}
+
+/// A tracker, as returned in the `trackers` field of the Transmission RPC `torrent-get` method.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct TransmissionTracker {
+    pub announce: String,
+    #[serde(default)]
+    pub tier: i64,
+}
+
+impl TryIntoTracker for TransmissionTracker {
+    fn try_into_tracker(&self) -> Result<Tracker, TrackerError> {
+        Tracker::new(&self.announce)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HexDigest;
+
+    #[test]
+    fn can_convert_torrent() {
+        let json = r#"{
+            "addedDate": 1603243361,
+            "doneDate": 0,
+            "downloadDir": "/var/lib/transmission/Downloads",
+            "hashString": "C811B41641A09D192B8ED81B14064FFF55D85CE3",
+            "labels": ["books"],
+            "name": "Goldman, Emma - Essential Works of Anarchism",
+            "percentDone": 0.4213,
+            "status": 4,
+            "totalSize": 4726342,
+            "uploadedEver": 2363171,
+            "downloadedEver": 4726342,
+            "downloadLimited": true,
+            "downloadLimit": 500,
+            "uploadLimit": 100,
+            "seedRatioMode": 0,
+            "seedRatioLimit": 2,
+            "trackers": [
+                {"announce": "udp://tracker.opentrackr.org:1337/announce", "id": 0, "scrape": "", "tier": 0}
+            ]
+        }"#;
+        let transmission: TransmissionTorrent = serde_json::from_str(json).unwrap();
+        assert_eq!(transmission.status, TransmissionStatus::Download);
+        assert!(transmission.trackers[0].try_into_tracker().is_ok());
+
+        let torrent = transmission.to_torrent();
+        assert_eq!(
+            torrent.hash,
+            InfoHash::V1(HexDigest::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap())
+        );
+        assert_eq!(torrent.path, "/var/lib/transmission/Downloads");
+        assert_eq!(torrent.progress, Progress::new(1991208, 4726342));
+        assert_eq!(torrent.progress.to_string(), "42.1%");
+        assert_eq!(torrent.state, "download");
+        assert_eq!(torrent.uploaded, Some(2363171));
+        assert_eq!(torrent.ratio(), Some(0.5));
+        assert!(torrent
+            .uses_tracker(&Tracker::new("udp://tracker.opentrackr.org:1337/announce").unwrap()));
+        assert_eq!(torrent.tags, vec!["books"]);
+        assert_eq!(torrent.limits.download_limit, Some(500_000));
+        assert_eq!(torrent.limits.upload_limit, None);
+        assert_eq!(torrent.limits.ratio_limit, None);
+    }
+
+    #[test]
+    fn negative_size_is_unknown() {
+        let json = r#"{"hashString": "c811b41641a09d192b8ed81b14064fff55d85ce3", "name": "test", "percentDone": 0.996, "totalSize": -1}"#;
+        let torrent = serde_json::from_str::<TransmissionTorrent>(json)
+            .unwrap()
+            .to_torrent();
+        assert_eq!(torrent.progress, Progress::from_percent(99));
+    }
+
+    #[test]
+    fn fails_invalid_hash() {
+        let json = r#"{"hashString": "c811b4", "name": "test"}"#;
+        assert!(serde_json::from_str::<TransmissionTorrent>(json).is_err());
+    }
+}
@@ -0,0 +1,673 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::content::{v1_files, v2_files, RawFile, SafePath};
+use crate::merkle;
+use crate::torrent_file::{bencode_i64, parse_url_list, DecodedTorrent};
+
+/// Minimum piece length for Bittorrent v2 torrents (16 KiB).
+const V2_MIN_PIECE_LENGTH: i64 = 16 * 1024;
+
+/// Maximum length of a single path component on most filesystems.
+const MAX_NAME_LENGTH: usize = 255;
+
+/// The severity of a [`ValidationIssue`](crate::validation::ValidationIssue).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidationSeverity {
+    /// The torrent can be used, but some clients may handle it differently.
+    Warning,
+    /// The torrent is malformed, and most clients will refuse it.
+    Error,
+}
+
+/// A problem found while [`validate`](crate::torrent_file::TorrentFile::validate)-ing a
+/// [`TorrentFile`](crate::torrent_file::TorrentFile).
+///
+/// Paths are represented as `/`-separated strings, with invalid UTF-8 sequences replaced.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationIssue {
+    /// The info dict has an empty name.
+    EmptyName,
+    /// The info dict has no `piece length`.
+    MissingPieceLength,
+    /// The `piece length` is zero or negative.
+    InvalidPieceLength { length: i64 },
+    /// The `piece length` is not a power of two. This is mandatory for Bittorrent v2.
+    PieceLengthNotPowerOfTwo { length: i64 },
+    /// The `piece length` of a Bittorrent v2 torrent is smaller than 16 KiB.
+    PieceLengthTooSmall { length: i64 },
+    /// The Bittorrent v1 info dict has no `pieces`.
+    MissingPieces,
+    /// The Bittorrent v1 `pieces` string is empty although the torrent is not.
+    EmptyPieces,
+    /// The Bittorrent v1 `pieces` string length is not a multiple of 20 bytes.
+    InvalidPiecesLength { length: usize },
+    /// The number of Bittorrent v1 pieces does not match the total size of the torrent.
+    PieceCountMismatch { expected: u64, found: u64 },
+    /// The total size of the Bittorrent v1 files does not fit in 64 bits.
+    TotalSizeOverflow,
+    /// The info dict describes no files at all.
+    NoFiles,
+    /// A Bittorrent v1 info dict has both `length` and `files` fields.
+    AmbiguousFiles,
+    /// A file entry is not properly structured (eg. missing length or path).
+    MalformedFile { path: String },
+    /// A file has a negative size.
+    NegativeFileSize { path: String, size: i64 },
+    /// Two files have the same path.
+    DuplicateFile { path: String },
+    /// A path is not a valid [`SafePath`](crate::content::SafePath): it contains an empty
+    /// component, a `.`/`..` component, a component with a `/`, `\\` or NUL character, or a
+    /// reserved name on Windows.
+    InvalidPath { path: String },
+    /// A path component is longer than 255 bytes, and may be truncated by clients.
+    OverlongName { path: String },
+    /// A web seed (`url-list` or `httpseeds` entry) is not an HTTP(S) URL, and was skipped
+    /// while parsing. The url is empty when the entry is not a string.
+    InvalidWebSeed { url: String },
+}
+
+impl ValidationIssue {
+    /// Returns how serious the issue is.
+    pub fn severity(&self) -> ValidationSeverity {
+        match self {
+            ValidationIssue::DuplicateFile { .. }
+            | ValidationIssue::OverlongName { .. }
+            | ValidationIssue::InvalidWebSeed { .. } => ValidationSeverity::Warning,
+            _ => ValidationSeverity::Error,
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationIssue::EmptyName => write!(f, "Empty torrent name"),
+            ValidationIssue::MissingPieceLength => write!(f, "Missing piece length"),
+            ValidationIssue::InvalidPieceLength { length } => {
+                write!(f, "Invalid piece length: {length}")
+            }
+            ValidationIssue::PieceLengthNotPowerOfTwo { length } => {
+                write!(f, "Piece length is not a power of two: {length}")
+            }
+            ValidationIssue::PieceLengthTooSmall { length } => {
+                write!(f, "Piece length is smaller than 16KiB: {length}")
+            }
+            ValidationIssue::MissingPieces => write!(f, "Missing pieces"),
+            ValidationIssue::EmptyPieces => write!(f, "Empty pieces for a non-empty torrent"),
+            ValidationIssue::InvalidPiecesLength { length } => {
+                write!(f, "Pieces length is not a multiple of 20: {length}")
+            }
+            ValidationIssue::PieceCountMismatch { expected, found } => {
+                write!(f, "Expected {expected} pieces, found {found}")
+            }
+            ValidationIssue::TotalSizeOverflow => write!(f, "Total size overflows 64 bits"),
+            ValidationIssue::NoFiles => write!(f, "No files"),
+            ValidationIssue::AmbiguousFiles => {
+                write!(f, "Both 'length' and 'files' fields are present")
+            }
+            ValidationIssue::MalformedFile { path } => write!(f, "Malformed file: {path}"),
+            ValidationIssue::NegativeFileSize { path, size } => {
+                write!(f, "Negative file size {size}: {path}")
+            }
+            ValidationIssue::DuplicateFile { path } => write!(f, "Duplicate file: {path}"),
+            ValidationIssue::InvalidWebSeed { url } => {
+                write!(f, "Invalid web seed (expected HTTP(S) URL): {url}")
+            }
+            ValidationIssue::InvalidPath { path } => write!(f, "Invalid path: {path}"),
+            ValidationIssue::OverlongName { path } => write!(f, "Overlong file name: {path}"),
+        }
+    }
+}
+
+/// A difference between the v1 and v2 views of a hybrid torrent, as returned by
+/// [`TorrentFile::check_hybrid_consistency`](crate::torrent_file::TorrentFile::check_hybrid_consistency).
+///
+/// Paths start with the torrent name for multi-file torrents, like in
+/// [`ValidationIssue`](crate::validation::ValidationIssue).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HybridMismatch {
+    /// A file is listed in the v2 `file tree`, but not in the v1 `files`.
+    MissingInV1 { path: String },
+    /// A file is listed in the v1 `files`, but not in the v2 `file tree`.
+    MissingInV2 { path: String },
+    /// A file has different sizes in the v1 and v2 views.
+    SizeMismatch {
+        path: String,
+        v1_size: u64,
+        v2_size: u64,
+    },
+}
+
+impl std::fmt::Display for HybridMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HybridMismatch::MissingInV1 { path } => write!(f, "Missing in v1 files: {path}"),
+            HybridMismatch::MissingInV2 { path } => write!(f, "Missing in v2 file tree: {path}"),
+            HybridMismatch::SizeMismatch {
+                path,
+                v1_size,
+                v2_size,
+            } => write!(f, "Size is {v1_size} in v1 but {v2_size} in v2: {path}"),
+        }
+    }
+}
+
+/// The result of [`TorrentFile::validate`](crate::torrent_file::TorrentFile::validate),
+/// listing all the issues found in a torrent.
+///
+/// Parsing a [`TorrentFile`](crate::torrent_file::TorrentFile) only checks that the torrent can
+/// be identified (name and infohash). The ValidationReport goes further, and lets you decide
+/// whether to accept, flag or reject a torrent with sketchy contents.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Returns all the issues found, in the order they were found.
+    pub fn issues(&self) -> &[ValidationIssue] {
+        &self.issues
+    }
+
+    /// Returns the issues with [`Error`](crate::validation::ValidationSeverity::Error) severity.
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|i| i.severity() == ValidationSeverity::Error)
+    }
+
+    /// Returns the issues with [`Warning`](crate::validation::ValidationSeverity::Warning)
+    /// severity.
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|i| i.severity() == ValidationSeverity::Warning)
+    }
+
+    /// Returns true when no issue with [`Error`](crate::validation::ValidationSeverity::Error)
+    /// severity was found. Warnings may still be present.
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Returns true when no issue at all was found.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    fn push(&mut self, issue: ValidationIssue) {
+        self.issues.push(issue);
+    }
+}
+
+fn check_files(files: &[RawFile], report: &mut ValidationReport) {
+    let mut seen: HashSet<&[&[u8]]> = HashSet::new();
+    for file in files {
+        let path = file.display_path();
+
+        match file.length {
+            None => report.push(ValidationIssue::MalformedFile { path: path.clone() }),
+            Some(size) if size < 0 => report.push(ValidationIssue::NegativeFileSize {
+                path: path.clone(),
+                size,
+            }),
+            _ => {}
+        }
+
+        if SafePath::new(file.lossy_components()).is_err() {
+            report.push(ValidationIssue::InvalidPath { path: path.clone() });
+        }
+
+        if file.path.iter().any(|c| c.len() > MAX_NAME_LENGTH) {
+            report.push(ValidationIssue::OverlongName { path: path.clone() });
+        }
+
+        // Padding files are allowed to share a path
+        if !file.attributes.is_padding() && !seen.insert(&file.path) {
+            report.push(ValidationIssue::DuplicateFile { path });
+        }
+    }
+}
+
+pub(crate) fn validate_torrent(torrent: &DecodedTorrent) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let info = &torrent.info;
+    let is_v2 = info.version == Some(2);
+    let has_v1 = info.length.is_some() || info.files.is_some();
+
+    if info.name.is_empty() {
+        report.push(ValidationIssue::EmptyName);
+    }
+
+    for url_list in [&torrent.url_list, &torrent.httpseeds]
+        .into_iter()
+        .flatten()
+    {
+        for url in parse_url_list(url_list).1 {
+            report.push(ValidationIssue::InvalidWebSeed { url });
+        }
+    }
+
+    let piece_length = match info.extra.get("piece length") {
+        None => {
+            report.push(ValidationIssue::MissingPieceLength);
+            None
+        }
+        Some(length) => match bencode_i64(length) {
+            Some(length) if length > 0 => {
+                if length & (length - 1) != 0 {
+                    report.push(ValidationIssue::PieceLengthNotPowerOfTwo { length });
+                }
+                if is_v2 && length < V2_MIN_PIECE_LENGTH {
+                    report.push(ValidationIssue::PieceLengthTooSmall { length });
+                }
+                Some(length)
+            }
+            Some(length) => {
+                report.push(ValidationIssue::InvalidPieceLength { length });
+                None
+            }
+            None => {
+                report.push(ValidationIssue::InvalidPieceLength { length: 0 });
+                None
+            }
+        },
+    };
+
+    if info.length.is_some() && info.files.is_some() {
+        report.push(ValidationIssue::AmbiguousFiles);
+    }
+
+    // Hybrid torrents are checked through both their v1 and v2 views
+    let mut files = Vec::new();
+    let mut malformed = Vec::new();
+    if has_v1 {
+        let v1 = v1_files(&info.name, info.length, info.files.as_ref(), &mut malformed);
+        check_files(&v1, &mut report);
+        files.push(v1);
+    }
+    if is_v2 {
+        if let Some(tree) = &info.file_tree {
+            let v2 = v2_files(&info.name, tree, &mut malformed);
+            check_files(&v2, &mut report);
+            files.push(v2);
+        }
+    }
+    for path in malformed {
+        report.push(ValidationIssue::MalformedFile { path });
+    }
+
+    if files.iter().all(|f| f.is_empty()) {
+        report.push(ValidationIssue::NoFiles);
+    }
+
+    // Bittorrent v1 pieces, which v2-only torrents replace with per-file merkle trees
+    if has_v1 {
+        let total = files[0]
+            .iter()
+            .filter_map(|f| f.length)
+            .filter(|l| *l > 0)
+            .try_fold(0u64, |total, l| total.checked_add(l as u64));
+        if total.is_none() {
+            report.push(ValidationIssue::TotalSizeOverflow);
+        }
+        match info.extra.get("pieces").and_then(|p| p.as_byte_str()) {
+            None => report.push(ValidationIssue::MissingPieces),
+            Some(pieces) if pieces.is_empty() && total != Some(0) => {
+                report.push(ValidationIssue::EmptyPieces)
+            }
+            Some(pieces) if pieces.len() % 20 != 0 => {
+                report.push(ValidationIssue::InvalidPiecesLength {
+                    length: pieces.len(),
+                })
+            }
+            Some(pieces) => {
+                if let (Some(piece_length), Some(total)) = (piece_length, total) {
+                    let piece_length = piece_length as u64;
+                    let expected = total / piece_length + u64::from(total % piece_length != 0);
+                    let found = (pieces.len() / 20) as u64;
+                    if expected != found && !pieces.is_empty() {
+                        report.push(ValidationIssue::PieceCountMismatch { expected, found });
+                    }
+                }
+            }
+        }
+    }
+
+    report
+}
+
+/// Compares the v1 and v2 file lists of a hybrid torrent, ignoring v1 padding files. Torrents
+/// which are not hybrid have no mismatches.
+pub(crate) fn hybrid_mismatches(torrent: &DecodedTorrent) -> Vec<HybridMismatch> {
+    let info = &torrent.info;
+    let tree = match (info.version, &info.file_tree) {
+        (Some(2), Some(tree)) if info.length.is_some() || info.files.is_some() => tree,
+        _ => return Vec::new(),
+    };
+
+    let mut malformed = Vec::new();
+    let size = |file: &RawFile| file.length.unwrap_or(0).max(0) as u64;
+    let v1: Vec<RawFile> = v1_files(&info.name, info.length, info.files.as_ref(), &mut malformed)
+        .into_iter()
+        .filter(|f| !f.attributes.is_padding())
+        .collect();
+    let v2 = v2_files(&info.name, tree, &mut malformed);
+
+    // Paths are matched in a map, because torrents may contain a million files
+    let mut v2_by_path: HashMap<&[&[u8]], &RawFile> = HashMap::new();
+    for file in &v2 {
+        v2_by_path.entry(&file.path).or_insert(file);
+    }
+    let v1_paths: HashSet<&[&[u8]]> = v1.iter().map(|f| f.path.as_slice()).collect();
+
+    let mut mismatches = Vec::new();
+    for file in &v1 {
+        match v2_by_path.get(file.path.as_slice()) {
+            None => mismatches.push(HybridMismatch::MissingInV2 {
+                path: file.display_path(),
+            }),
+            Some(other) if size(other) != size(file) => {
+                mismatches.push(HybridMismatch::SizeMismatch {
+                    path: file.display_path(),
+                    v1_size: size(file),
+                    v2_size: size(other),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    for file in &v2 {
+        if !v1_paths.contains(file.path.as_slice()) {
+            mismatches.push(HybridMismatch::MissingInV1 {
+                path: file.display_path(),
+            });
+        }
+    }
+    mismatches
+}
+
+/// Checks the BEP-0052 `piece layers` of a v2 or hybrid torrent against its file tree: every
+/// file larger than one piece has a layer with one hash per piece, whose merkle root is the
+/// file's pieces root, and there are no other layers. Returns the reason of the first inconsistency found.
+pub(crate) fn check_piece_layers(torrent: &DecodedTorrent) -> Result<(), String> {
+    let info = &torrent.info;
+    let tree = match (info.version, &info.file_tree) {
+        (Some(2), Some(tree)) => tree,
+        _ => return Ok(()),
+    };
+
+    let piece_length = match info.extra.get("piece length").and_then(bencode_i64) {
+        Some(length) if length >= V2_MIN_PIECE_LENGTH && length & (length - 1) == 0 => {
+            length as u64
+        }
+        _ => return Err("invalid piece length".to_string()),
+    };
+
+    let empty = BTreeMap::new();
+    let layers = match torrent.extra.get("piece layers") {
+        None => &empty,
+        Some(layers) => layers
+            .as_dict()
+            .ok_or_else(|| "`piece layers` is not a dict".to_string())?,
+    };
+
+    let mut expected: BTreeSet<&[u8]> = BTreeSet::new();
+    for file in v2_files(&info.name, tree, &mut Vec::new()) {
+        let length = match file.length {
+            Some(length) if length > 0 => length as u64,
+            _ => continue,
+        };
+        let path = file.display_path();
+        let root = match file.pieces_root {
+            Some(root) if root.len() == 32 => root,
+            _ => return Err(format!("invalid pieces root for {path}")),
+        };
+
+        let layer = layers.get(root).map(|l| l.as_byte_str());
+        if length <= piece_length {
+            // Files smaller than a piece only have their pieces root
+            if layer.is_some() {
+                return Err(format!("unexpected piece layer for {path}"));
+            }
+            continue;
+        }
+
+        let hashes = (length + piece_length - 1) / piece_length;
+        match layer {
+            None => return Err(format!("missing piece layer for {path}")),
+            Some(None) => return Err(format!("piece layer for {path} is not a byte string")),
+            Some(Some(layer)) if layer.len() as u64 != hashes * 32 => {
+                return Err(format!(
+                    "piece layer for {path} has {} bytes, expected {}",
+                    layer.len(),
+                    hashes * 32
+                ))
+            }
+            Some(Some(layer)) => {
+                let layer: Vec<merkle::Hash> = layer
+                    .chunks(32)
+                    .map(|hash| hash.try_into().unwrap())
+                    .collect();
+                if merkle::root_from_layer(&layer, piece_length) != root {
+                    return Err(format!(
+                        "piece layer for {path} does not match its pieces root"
+                    ));
+                }
+                expected.insert(root);
+            }
+        }
+    }
+
+    if layers.len() != expected.len() {
+        return Err("piece layer does not match any file".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::TorrentFile;
+
+    use bt_bencode::Value as BencodeValue;
+
+    use super::*;
+
+    fn load(path: &str) -> DecodedTorrent {
+        let slice = std::fs::read(path).unwrap();
+        bt_bencode::from_slice(&slice).unwrap()
+    }
+
+    fn validate(torrent: &DecodedTorrent) -> ValidationReport {
+        let slice = bt_bencode::to_vec(torrent).unwrap();
+        TorrentFile::from_slice(&slice).unwrap().validate()
+    }
+
+    #[test]
+    fn sample_torrents_are_clean() {
+        for path in [
+            "tests/bittorrent-v1-emma-goldman.torrent",
+            "tests/bittorrent-v2-test.torrent",
+            "tests/bittorrent-v2-hybrid-test.torrent",
+        ] {
+            let report = validate(&load(path));
+            assert!(report.is_clean(), "{path}: {:?}", report.issues());
+        }
+    }
+
+    #[test]
+    fn reports_bad_piece_length() {
+        let mut torrent = load("tests/bittorrent-v1-emma-goldman.torrent");
+        torrent
+            .info
+            .extra
+            .insert("piece length".to_string(), BencodeValue::from(0));
+        let report = validate(&torrent);
+        assert!(!report.is_valid());
+        assert!(report
+            .issues()
+            .contains(&ValidationIssue::InvalidPieceLength { length: 0 }));
+
+        torrent
+            .info
+            .extra
+            .insert("piece length".to_string(), BencodeValue::from(131073));
+        let report = validate(&torrent);
+        assert!(report
+            .issues()
+            .contains(&ValidationIssue::PieceLengthNotPowerOfTwo { length: 131073 }));
+    }
+
+    #[test]
+    fn reports_invalid_pieces() {
+        let mut torrent = load("tests/bittorrent-v1-emma-goldman.torrent");
+        torrent
+            .info
+            .extra
+            .insert("pieces".to_string(), BencodeValue::from("abc"));
+        let report = validate(&torrent);
+        assert_eq!(
+            report.issues(),
+            &[ValidationIssue::InvalidPiecesLength { length: 3 }]
+        );
+
+        torrent
+            .info
+            .extra
+            .insert("pieces".to_string(), BencodeValue::from(""));
+        let report = validate(&torrent);
+        assert_eq!(report.issues(), &[ValidationIssue::EmptyPieces]);
+    }
+
+    #[test]
+    fn reports_bad_files() {
+        let mut torrent = load("tests/bittorrent-v1-emma-goldman.torrent");
+        let files = torrent.info.files.as_mut().unwrap();
+        let first = files[0].clone();
+        files.push(first);
+        let mut traversal = files[1].clone();
+        if let BencodeValue::Dict(dict) = &mut traversal {
+            dict.insert(
+                "path".into(),
+                BencodeValue::List(vec!["..".into(), "passwd".into()]),
+            );
+            dict.insert("length".into(), BencodeValue::from(-12));
+        }
+        files.push(traversal);
+
+        let report = validate(&torrent);
+        assert!(!report.is_valid());
+        let first_path = format!(
+            "Goldman, Emma - Essential Works of Anarchism/{}",
+            torrent.info.files.as_ref().unwrap()[0]
+                .get("path")
+                .unwrap()
+                .as_list()
+                .unwrap()
+                .iter()
+                .map(|c| c.as_str().unwrap())
+                .collect::<Vec<_>>()
+                .join("/")
+        );
+        assert!(report.warnings().any(|w| w
+            == &ValidationIssue::DuplicateFile {
+                path: first_path.clone()
+            }));
+        assert!(report.errors().any(|e| e
+            == &ValidationIssue::InvalidPath {
+                path: "Goldman, Emma - Essential Works of Anarchism/../passwd".to_string()
+            }));
+        assert!(report.errors().any(|e| e
+            == &ValidationIssue::NegativeFileSize {
+                path: "Goldman, Emma - Essential Works of Anarchism/../passwd".to_string(),
+                size: -12
+            }));
+    }
+
+    #[test]
+    fn reports_total_size_overflow() {
+        let mut torrent = load("tests/bittorrent-v1-emma-goldman.torrent");
+        for file in torrent.info.files.as_mut().unwrap() {
+            if let BencodeValue::Dict(dict) = file {
+                dict.insert("length".into(), BencodeValue::from(i64::MAX));
+            }
+        }
+
+        let report = validate(&torrent);
+        assert!(report
+            .errors()
+            .any(|e| e == &ValidationIssue::TotalSizeOverflow));
+        assert!(!report
+            .issues()
+            .iter()
+            .any(|i| matches!(i, ValidationIssue::PieceCountMismatch { .. })));
+    }
+
+    #[test]
+    fn hybrid_consistency() {
+        for path in [
+            "tests/bittorrent-v1-emma-goldman.torrent",
+            "tests/bittorrent-v2-test.torrent",
+            "tests/bittorrent-v2-hybrid-test.torrent",
+        ] {
+            let slice = std::fs::read(path).unwrap();
+            let torrent = TorrentFile::from_slice(&slice).unwrap();
+            assert!(torrent.check_hybrid_consistency().is_empty());
+        }
+
+        let mut torrent = load("tests/bittorrent-v2-hybrid-test.torrent");
+        let files = torrent.info.files.as_mut().unwrap();
+        let mut normal = files
+            .iter_mut()
+            .filter(|f| f.get("attr").and_then(|a| a.as_byte_str()).is_none());
+        let resized = normal.next().unwrap();
+        let length = resized.get("length").and_then(bencode_i64).unwrap();
+        resized
+            .as_dict_mut()
+            .unwrap()
+            .insert("length".into(), BencodeValue::from(length + 1));
+        let renamed = normal.next().unwrap();
+        let path = renamed
+            .as_dict_mut()
+            .unwrap()
+            .get_mut("path".as_bytes())
+            .unwrap();
+        let original = path.as_list().unwrap().last().unwrap().clone();
+        path.as_list_mut()
+            .unwrap()
+            .push(BencodeValue::from("renamed"));
+
+        let slice = bt_bencode::to_vec(&torrent).unwrap();
+        let mismatches = TorrentFile::from_slice(&slice)
+            .unwrap()
+            .check_hybrid_consistency();
+        assert_eq!(mismatches.len(), 3);
+        assert!(matches!(
+            &mismatches[0],
+            HybridMismatch::SizeMismatch { v1_size, v2_size, .. } if *v1_size == *v2_size + 1
+        ));
+        assert!(matches!(
+            &mismatches[1],
+            HybridMismatch::MissingInV2 { path } if path.ends_with("/renamed")
+        ));
+        let original = String::from_utf8(original.as_byte_str().unwrap().to_vec()).unwrap();
+        assert!(matches!(
+            &mismatches[2],
+            HybridMismatch::MissingInV1 { path } if path.ends_with(&original)
+        ));
+    }
+
+    #[test]
+    fn reports_small_v2_piece_length() {
+        let mut torrent = load("tests/bittorrent-v2-test.torrent");
+        torrent
+            .info
+            .extra
+            .insert("piece length".to_string(), BencodeValue::from(8192));
+        let report = validate(&torrent);
+        assert_eq!(
+            report.issues(),
+            &[ValidationIssue::PieceLengthTooSmall { length: 8192 }]
+        );
+    }
+}
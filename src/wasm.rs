@@ -0,0 +1,136 @@
+//! JavaScript bindings for web UIs, built with [wasm-bindgen](https://rustwasm.github.io/wasm-bindgen/).
+//!
+//! The bindings wrap the parsing of [`MagnetLink`](crate::magnet::MagnetLink),
+//! [`InfoHash`](crate::hash::InfoHash) and [`TorrentFile`](crate::torrent_file::TorrentFile),
+//! so that user-pasted magnets and uploaded torrents are validated in the browser with the same
+//! rules as on the server. Parsing errors are thrown as JavaScript `Error`s with the message of
+//! the Rust error.
+
+use wasm_bindgen::prelude::*;
+
+use crate::{InfoHash, MagnetLink, TorrentFile};
+
+/// A [`MagnetLink`](crate::magnet::MagnetLink), exported to JavaScript as `MagnetLink`.
+#[wasm_bindgen(js_name = MagnetLink)]
+pub struct WasmMagnetLink(MagnetLink);
+
+#[wasm_bindgen(js_class = MagnetLink)]
+impl WasmMagnetLink {
+    /// Parses a magnet link, throwing an `Error` when it is invalid.
+    #[wasm_bindgen(constructor)]
+    pub fn new(s: &str) -> Result<WasmMagnetLink, JsError> {
+        Ok(WasmMagnetLink(MagnetLink::new(s)?))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.0.name().to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn hash(&self) -> WasmInfoHash {
+        WasmInfoHash(self.0.hash().clone())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id().as_str().to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn trackers(&self) -> Vec<String> {
+        self.0
+            .trackers()
+            .iter()
+            .map(|t| t.url().to_string())
+            .collect()
+    }
+
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_js_string(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+/// An [`InfoHash`](crate::hash::InfoHash), exported to JavaScript as `InfoHash`.
+#[wasm_bindgen(js_name = InfoHash)]
+pub struct WasmInfoHash(InfoHash);
+
+#[wasm_bindgen(js_class = InfoHash)]
+impl WasmInfoHash {
+    /// Parses an infohash, throwing an `Error` when it is invalid.
+    #[wasm_bindgen(constructor)]
+    pub fn new(s: &str) -> Result<WasmInfoHash, JsError> {
+        Ok(WasmInfoHash(InfoHash::new(s)?))
+    }
+
+    /// The infohash v1, if any.
+    #[wasm_bindgen(getter)]
+    pub fn v1(&self) -> Option<String> {
+        self.0.v1().map(String::from)
+    }
+
+    /// The infohash v2, if any.
+    #[wasm_bindgen(getter)]
+    pub fn v2(&self) -> Option<String> {
+        self.0.v2().map(String::from)
+    }
+
+    #[wasm_bindgen(getter, js_name = isHybrid)]
+    pub fn is_hybrid(&self) -> bool {
+        self.0.is_hybrid()
+    }
+
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_js_string(&self) -> String {
+        self.0.as_str().to_string()
+    }
+}
+
+/// A [`TorrentFile`](crate::torrent_file::TorrentFile), exported to JavaScript as `TorrentFile`.
+#[wasm_bindgen(js_name = TorrentFile)]
+pub struct WasmTorrentFile(TorrentFile);
+
+#[wasm_bindgen(js_class = TorrentFile)]
+impl WasmTorrentFile {
+    /// Parses a torrent from a `Uint8Array`, throwing an `Error` when it is invalid.
+    #[wasm_bindgen(constructor)]
+    pub fn new(bytes: &[u8]) -> Result<WasmTorrentFile, JsError> {
+        Ok(WasmTorrentFile(TorrentFile::from_slice(bytes)?))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.0.name().to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn hash(&self) -> WasmInfoHash {
+        WasmInfoHash(InfoHash::from(&self.0))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id().as_str().to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn comment(&self) -> Option<String> {
+        self.0.comment().map(str::to_string)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn trackers(&self) -> Vec<String> {
+        self.0
+            .tracker_tiers()
+            .iter()
+            .map(|t| t.url().to_string())
+            .collect()
+    }
+
+    /// The total size of the files, in bytes.
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> f64 {
+        self.0.files().iter().map(|f| f.size as f64).sum()
+    }
+}
@@ -0,0 +1,400 @@
+use notify::event::{AccessKind, AccessMode, CreateKind, ModifyKind};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+use crate::{MagnetLink, MagnetLinkError, TorrentFile, TorrentFileError, TorrentID};
+
+/// Error occurred while watching a folder with a
+/// [`FolderWatcher`](crate::watch::FolderWatcher).
+#[derive(Clone, Debug, PartialEq)]
+pub enum WatchError {
+    /// The folder or a magnet file could not be read.
+    // std::io::Error is not Clone/PartialEq so we store its kind and message
+    Io {
+        kind: std::io::ErrorKind,
+        reason: String,
+    },
+    /// The torrent file could not be read or parsed.
+    InvalidTorrentFile { source: TorrentFileError },
+    /// The magnet file does not contain a valid magnet link.
+    InvalidMagnetLink { source: MagnetLinkError },
+    /// The folder could not be watched.
+    // notify::Error is not Clone/PartialEq so we store error as String
+    Watcher { reason: String },
+}
+
+impl std::fmt::Display for WatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WatchError::Io { kind: _, reason } => {
+                write!(f, "Failed to read file: {reason}")
+            }
+            WatchError::InvalidTorrentFile { source } => {
+                write!(f, "Invalid torrent file: {source}")
+            }
+            WatchError::InvalidMagnetLink { source } => {
+                write!(f, "Invalid magnet link: {source}")
+            }
+            WatchError::Watcher { reason } => write!(f, "Failed to watch folder: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for WatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WatchError::InvalidTorrentFile { source } => Some(source),
+            WatchError::InvalidMagnetLink { source } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for WatchError {
+    fn from(e: std::io::Error) -> WatchError {
+        WatchError::Io {
+            kind: e.kind(),
+            reason: e.to_string(),
+        }
+    }
+}
+
+impl From<notify::Error> for WatchError {
+    fn from(e: notify::Error) -> WatchError {
+        WatchError::Watcher {
+            reason: e.to_string(),
+        }
+    }
+}
+
+/// An event emitted by a [`FolderWatcher`](crate::watch::FolderWatcher) for a file found in
+/// the watched folder.
+#[derive(Clone, Debug)]
+pub enum WatchEvent {
+    /// A new `.torrent` file was parsed.
+    TorrentFile {
+        path: PathBuf,
+        torrent: Box<TorrentFile>,
+    },
+    /// A new `.magnet` file was parsed.
+    MagnetLink {
+        path: PathBuf,
+        magnet: Box<MagnetLink>,
+    },
+    /// The file describes a torrent which was already found in another file.
+    Duplicate { path: PathBuf, id: TorrentID },
+    /// The file could not be read or parsed.
+    Error { path: PathBuf, error: WatchError },
+}
+
+impl WatchEvent {
+    /// Returns the path of the file which emitted the event.
+    pub fn path(&self) -> &Path {
+        match self {
+            WatchEvent::TorrentFile { path, .. }
+            | WatchEvent::MagnetLink { path, .. }
+            | WatchEvent::Duplicate { path, .. }
+            | WatchEvent::Error { path, .. } => path,
+        }
+    }
+}
+
+/// The state shared between the initial scan and the notify event handler.
+#[derive(Debug)]
+struct WatchState {
+    /// The torrents found so far, with the file they were first found in
+    seen: HashMap<TorrentID, PathBuf>,
+    sender: Sender<WatchEvent>,
+}
+
+impl WatchState {
+    /// Parses a file from the watched folder, and emits the matching event. Files other than
+    /// `.torrent` and `.magnet` are ignored. When `quiet` is set, parsing errors are not
+    /// reported, because the file may still be written to.
+    fn process(&mut self, path: &Path, quiet: bool) {
+        let event = match parse_file(path) {
+            None => return,
+            Some(Err(_)) if quiet => return,
+            Some(Err(error)) => WatchEvent::Error {
+                path: path.to_path_buf(),
+                error,
+            },
+            Some(Ok(event)) => {
+                let id = match &event {
+                    WatchEvent::TorrentFile { torrent, .. } => torrent.id(),
+                    WatchEvent::MagnetLink { magnet, .. } => magnet.id(),
+                    _ => unreachable!(),
+                };
+                match self.seen.get(&id) {
+                    // The same file was written to again
+                    Some(first) if first == path => return,
+                    Some(_) => WatchEvent::Duplicate {
+                        path: path.to_path_buf(),
+                        id,
+                    },
+                    None => {
+                        self.seen.insert(id, path.to_path_buf());
+                        event
+                    }
+                }
+            }
+        };
+        // The receiver may have been dropped, in which case nobody cares about the event
+        let _ = self.sender.send(event);
+    }
+}
+
+/// Parses a `.torrent` or `.magnet` file, returning None for other files.
+fn parse_file(path: &Path) -> Option<Result<WatchEvent, WatchError>> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    let res = match extension.as_str() {
+        "torrent" => TorrentFile::from_path(path)
+            .map(|torrent| WatchEvent::TorrentFile {
+                path: path.to_path_buf(),
+                torrent: Box::new(torrent),
+            })
+            .map_err(|source| WatchError::InvalidTorrentFile { source }),
+        "magnet" => std::fs::read_to_string(path)
+            .map_err(WatchError::from)
+            .and_then(|s| {
+                MagnetLink::new(s.trim()).map_err(|source| WatchError::InvalidMagnetLink { source })
+            })
+            .map(|magnet| WatchEvent::MagnetLink {
+                path: path.to_path_buf(),
+                magnet: Box::new(magnet),
+            }),
+        _ => return None,
+    };
+    Some(res)
+}
+
+/// Watches a folder for new `.torrent` and `.magnet` files, and emits a
+/// [`WatchEvent`](crate::watch::WatchEvent) for each of them over a channel.
+///
+/// Files already in the folder are reported when the FolderWatcher is created. Subfolders are
+/// not watched. Every torrent is reported once by [`TorrentID`](crate::id::TorrentID): a file
+/// describing a torrent which was already found in another file emits a
+/// [`Duplicate`](crate::watch::WatchEvent::Duplicate) event instead.
+///
+/// A file is parsed when it is moved into the folder, or when it is closed after being
+/// written. Writing the file under another name and renaming it once complete, like most
+/// torrent clients and browsers do, avoids reporting partially written files as errors.
+///
+/// The folder is watched until the FolderWatcher is dropped.
+///
+/// ```no_run
+/// use hightorrent::{FolderWatcher, WatchEvent};
+///
+/// let (_watcher, events) = FolderWatcher::new("/home/user/torrents").unwrap();
+/// for event in events {
+///     match event {
+///         WatchEvent::TorrentFile { torrent, .. } => println!("New torrent: {}", torrent.name()),
+///         WatchEvent::MagnetLink { magnet, .. } => println!("New magnet: {}", magnet.id()),
+///         WatchEvent::Duplicate { path, .. } => println!("Duplicate: {}", path.display()),
+///         WatchEvent::Error { path, error } => println!("{}: {error}", path.display()),
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FolderWatcher {
+    dir: PathBuf,
+    state: Arc<Mutex<WatchState>>,
+    // Kept alive to keep watching the folder
+    _watcher: RecommendedWatcher,
+}
+
+impl FolderWatcher {
+    /// Starts watching a folder, returning the FolderWatcher with the receiving end of the
+    /// event channel.
+    pub fn new(dir: impl AsRef<Path>) -> Result<(FolderWatcher, Receiver<WatchEvent>), WatchError> {
+        let dir = dir.as_ref().to_path_buf();
+        let (sender, receiver) = channel();
+        let state = Arc::new(Mutex::new(WatchState {
+            seen: HashMap::new(),
+            sender,
+        }));
+
+        let handler_state = state.clone();
+        let handler_dir = dir.clone();
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+                let event = match res {
+                    Ok(event) => event,
+                    Err(e) => {
+                        let path = e.paths.last().unwrap_or(&handler_dir).clone();
+                        let error = WatchEvent::Error {
+                            path,
+                            error: WatchError::from(e),
+                        };
+                        let _ = handler_state.lock().unwrap().sender.send(error);
+                        return;
+                    }
+                };
+                let quiet = match event.kind {
+                    // The file may be created empty, then written to
+                    EventKind::Create(CreateKind::File | CreateKind::Any) => true,
+                    EventKind::Modify(ModifyKind::Name(_)) => false,
+                    EventKind::Access(AccessKind::Close(AccessMode::Write)) => false,
+                    _ => return,
+                };
+                // A rename within the folder lists the old path first
+                if let Some(path) = event.paths.last() {
+                    handler_state.lock().unwrap().process(path, quiet);
+                }
+            })?;
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+
+        // The folder is scanned after watching starts, so that no file is missed. A file found
+        // by both is reported once as it has the same TorrentID.
+        let watcher = FolderWatcher {
+            dir,
+            state,
+            _watcher: watcher,
+        };
+        watcher.rescan()?;
+        Ok((watcher, receiver))
+    }
+
+    /// Returns the watched folder.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Parses all the files in the folder again, for example after watching was interrupted.
+    /// Files which were already reported emit no event, except for
+    /// [`Duplicate`](crate::watch::WatchEvent::Duplicate)s and errors which are reported again.
+    pub fn rescan(&self) -> Result<(), WatchError> {
+        let mut paths = std::fs::read_dir(&self.dir)
+            .map_err(WatchError::from)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file())
+            .collect::<Vec<PathBuf>>();
+        // Report existing files in a stable order
+        paths.sort();
+
+        let mut state = self.state.lock().unwrap();
+        for path in paths {
+            state.process(&path, false);
+        }
+        Ok(())
+    }
+
+    /// Returns whether a torrent was already found in the folder.
+    pub fn contains(&self, id: &TorrentID) -> bool {
+        self.state.lock().unwrap().seen.contains_key(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hightorrent-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn next(events: &Receiver<WatchEvent>) -> WatchEvent {
+        events.recv_timeout(Duration::from_secs(10)).unwrap()
+    }
+
+    #[test]
+    fn reports_existing_files() {
+        let dir = temp_dir("watch-existing");
+        std::fs::copy(
+            "tests/bittorrent-v1-emma-goldman.torrent",
+            dir.join("a.torrent"),
+        )
+        .unwrap();
+        std::fs::copy(
+            "tests/bittorrent-v1-emma-goldman.magnet",
+            dir.join("b.MAGNET"),
+        )
+        .unwrap();
+        std::fs::write(dir.join("c.magnet"), "magnet:?dn=nohash").unwrap();
+        std::fs::write(dir.join("d.txt"), "ignored").unwrap();
+
+        let (watcher, events) = FolderWatcher::new(&dir).unwrap();
+        let torrent = match next(&events) {
+            WatchEvent::TorrentFile { path, torrent } => {
+                assert_eq!(path, dir.join("a.torrent"));
+                torrent
+            }
+            event => panic!("unexpected event {event:?}"),
+        };
+        assert!(watcher.contains(&torrent.id()));
+        match next(&events) {
+            WatchEvent::Duplicate { path, id } => {
+                assert_eq!(path, dir.join("b.MAGNET"));
+                assert_eq!(id, torrent.id());
+            }
+            event => panic!("unexpected event {event:?}"),
+        }
+        match next(&events) {
+            WatchEvent::Error { path, error } => {
+                assert_eq!(path, dir.join("c.magnet"));
+                assert!(matches!(error, WatchError::InvalidMagnetLink { .. }));
+            }
+            event => panic!("unexpected event {event:?}"),
+        }
+        assert!(events.try_recv().is_err());
+
+        // Only the duplicate and the error are reported again
+        watcher.rescan().unwrap();
+        assert!(matches!(next(&events), WatchEvent::Duplicate { .. }));
+        assert!(matches!(next(&events), WatchEvent::Error { .. }));
+        assert!(events.try_recv().is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reports_new_files() {
+        let dir = temp_dir("watch-new");
+        let (watcher, events) = FolderWatcher::new(&dir).unwrap();
+        assert_eq!(watcher.dir(), dir);
+        assert!(events.try_recv().is_err());
+
+        // Written elsewhere and moved into the folder, like a complete download
+        let tmp = temp_dir("watch-new-tmp");
+        std::fs::copy(
+            "tests/bittorrent-v2-hybrid-test.magnet",
+            tmp.join("hybrid.magnet"),
+        )
+        .unwrap();
+        std::fs::rename(tmp.join("hybrid.magnet"), dir.join("hybrid.magnet")).unwrap();
+        let magnet = match next(&events) {
+            WatchEvent::MagnetLink { path, magnet } => {
+                assert_eq!(path, dir.join("hybrid.magnet"));
+                magnet
+            }
+            event => panic!("unexpected event {event:?}"),
+        };
+
+        // Written in place
+        std::fs::copy(
+            "tests/bittorrent-v2-hybrid-test.torrent",
+            dir.join("hybrid.torrent"),
+        )
+        .unwrap();
+        match next(&events) {
+            WatchEvent::Duplicate { path, id } => {
+                assert_eq!(path, dir.join("hybrid.torrent"));
+                assert_eq!(id, magnet.id());
+            }
+            event => panic!("unexpected event {event:?}"),
+        }
+
+        drop(watcher);
+        std::fs::remove_dir_all(&dir).unwrap();
+        std::fs::remove_dir_all(&tmp).unwrap();
+    }
+}
@@ -0,0 +1,48 @@
+use arbitrary::{Arbitrary, Unstructured};
+use hightorrent::{DecodedTorrent, InfoHash, MagnetLink, TorrentFile, TorrentID, ValidationIssue};
+
+/// Generates values from a deterministic pseudo-random buffer.
+fn generate<T: for<'a> Arbitrary<'a>>(count: usize) -> Vec<T> {
+    let mut state: u32 = 42;
+    let bytes: Vec<u8> = (0..64 * 1024)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (state >> 16) as u8
+        })
+        .collect();
+    let mut u = Unstructured::new(&bytes);
+    (0..count).map(|_| u.arbitrary().unwrap()).collect()
+}
+
+#[test]
+fn infohashes_are_valid() {
+    for hash in generate::<InfoHash>(100) {
+        assert_eq!(InfoHash::new(&hash.to_full_string()).unwrap(), hash);
+    }
+    for id in generate::<TorrentID>(100) {
+        assert_eq!(TorrentID::new(id.as_str()).unwrap(), id);
+    }
+}
+
+#[test]
+fn magnets_roundtrip() {
+    for magnet in generate::<MagnetLink>(100) {
+        let parsed = MagnetLink::new(&magnet.to_string()).unwrap();
+        assert_eq!(parsed.hash(), magnet.hash());
+        assert_eq!(parsed.name(), magnet.name());
+    }
+}
+
+#[test]
+fn torrents_can_be_parsed() {
+    for decoded in generate::<DecodedTorrent>(50) {
+        let slice = bt_bencode::to_vec(&decoded).unwrap();
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        // Only names are arbitrary, so the pieces always match the files
+        assert!(!torrent
+            .validate()
+            .issues()
+            .iter()
+            .any(|i| matches!(i, ValidationIssue::PieceCountMismatch { .. })));
+    }
+}
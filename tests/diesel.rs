@@ -0,0 +1,66 @@
+use diesel::prelude::*;
+use diesel::sql_query;
+use hightorrent::{InfoHash, MagnetLink, TorrentID};
+
+diesel::table! {
+    torrents (id) {
+        id -> Text,
+        hash -> Text,
+        magnet -> Text,
+    }
+}
+
+#[derive(Queryable, Insertable)]
+#[diesel(table_name = torrents)]
+struct Torrent {
+    id: TorrentID,
+    hash: InfoHash,
+    magnet: MagnetLink,
+}
+
+#[test]
+fn can_store_in_sqlite() {
+    let mut conn = SqliteConnection::establish(":memory:").unwrap();
+    sql_query(
+        "CREATE TABLE torrents (id TEXT PRIMARY KEY, hash TEXT NOT NULL, magnet TEXT NOT NULL)",
+    )
+    .execute(&mut conn)
+    .unwrap();
+
+    let magnet_source = std::fs::read_to_string("tests/bittorrent-v2-hybrid-test.magnet").unwrap();
+    let magnet = MagnetLink::new(&magnet_source).unwrap();
+    let torrent = Torrent {
+        id: magnet.id(),
+        hash: magnet.hash().clone(),
+        magnet,
+    };
+
+    diesel::insert_into(torrents::table)
+        .values(&torrent)
+        .execute(&mut conn)
+        .unwrap();
+
+    // Hybrid infohashes are stored with both digests
+    let raw: Vec<String> = torrents::table
+        .select(diesel::dsl::sql::<diesel::sql_types::Text>("hash"))
+        .load(&mut conn)
+        .unwrap();
+    assert_eq!(raw, vec![torrent.hash.to_full_string()]);
+
+    let stored: Torrent = torrents::table
+        .filter(torrents::id.eq(&torrent.id))
+        .first(&mut conn)
+        .unwrap();
+    assert_eq!(stored.id, torrent.id);
+    assert_eq!(stored.hash, torrent.hash);
+    assert_eq!(stored.magnet.to_string(), torrent.magnet.to_string());
+
+    // Invalid values fail to decode
+    sql_query("INSERT INTO torrents (id, hash, magnet) VALUES ('invalid', 'invalid', 'invalid')")
+        .execute(&mut conn)
+        .unwrap();
+    let res = torrents::table
+        .filter(torrents::id.eq("invalid"))
+        .first::<Torrent>(&mut conn);
+    assert!(res.is_err());
+}
@@ -0,0 +1,43 @@
+use hightorrent::{DecodedTorrent, InfoHash, MagnetLink, PeerAddr, TorrentFile, ValidationIssue};
+use proptest::prelude::*;
+use std::net::{SocketAddrV4, SocketAddrV6};
+
+proptest! {
+    #[test]
+    fn infohashes_are_valid(hash in any::<InfoHash>()) {
+        prop_assert_eq!(InfoHash::new(&hash.to_full_string()).unwrap(), hash);
+    }
+
+    #[test]
+    fn magnets_roundtrip(magnet in any::<MagnetLink>()) {
+        let parsed = MagnetLink::new(&magnet.to_string()).unwrap();
+        prop_assert_eq!(parsed.hash(), magnet.hash());
+        prop_assert_eq!(parsed.name(), magnet.name());
+    }
+
+    #[test]
+    fn compact_peers_roundtrip(
+        v4 in proptest::collection::vec(any::<SocketAddrV4>(), 0..20),
+        v6 in proptest::collection::vec(any::<SocketAddrV6>(), 0..20),
+    ) {
+        let bytes = PeerAddr::encode_compact_v4(&v4);
+        prop_assert_eq!(PeerAddr::compact_v4(&bytes).unwrap().collect::<Vec<_>>(), v4);
+        // Flow info and scope IDs are not encoded
+        let v6: Vec<SocketAddrV6> = v6.iter().map(|a| SocketAddrV6::new(*a.ip(), a.port(), 0, 0)).collect();
+        let bytes = PeerAddr::encode_compact_v6(&v6);
+        prop_assert_eq!(PeerAddr::compact_v6(&bytes).unwrap().collect::<Vec<_>>(), v6);
+    }
+
+    #[test]
+    fn torrents_can_be_parsed(decoded in any::<DecodedTorrent>()) {
+        let slice = bt_bencode::to_vec(&decoded).unwrap();
+        let torrent = TorrentFile::from_slice(&slice).unwrap();
+        // Only names are arbitrary, so the pieces always match the files
+        let mismatch = torrent
+            .validate()
+            .issues()
+            .iter()
+            .any(|i| matches!(i, ValidationIssue::PieceCountMismatch { .. }));
+        prop_assert!(!mismatch);
+    }
+}
@@ -0,0 +1,73 @@
+use hightorrent::python_module;
+use pyo3::ffi::c_str;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+fn run(code: &std::ffi::CStr) {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let module = PyModule::new(py, "hightorrent").unwrap();
+        python_module(&module).unwrap();
+        let slice = std::fs::read("tests/bittorrent-v2-hybrid-test.torrent").unwrap();
+        let magnet = std::fs::read_to_string("tests/bittorrent-v1-emma-goldman.magnet").unwrap();
+
+        let globals = PyDict::new(py);
+        globals.set_item("hightorrent", module).unwrap();
+        globals
+            .set_item("torrent", pyo3::types::PyBytes::new(py, &slice))
+            .unwrap();
+        globals.set_item("magnet", magnet.trim()).unwrap();
+        if let Err(e) = py.run(code, Some(&globals), None) {
+            e.print(py);
+            panic!("Python code failed");
+        }
+    });
+}
+
+#[test]
+fn parses_magnet() {
+    run(c_str!(
+        r#"
+link = hightorrent.MagnetLink(magnet)
+assert link.name == "Emma Goldman - Essential Works of Anarchism (16 books)"
+assert str(link.hash) == "c811b41641a09d192b8ed81b14064fff55d85ce3"
+assert link.hash == hightorrent.InfoHash("c811b41641a09d192b8ed81b14064fff55d85ce3")
+assert link.id == hightorrent.TorrentID("c811b41641a09d192b8ed81b14064fff55d85ce3")
+assert len(link.trackers) == 9
+"#
+    ));
+}
+
+#[test]
+fn parses_torrent_file() {
+    run(c_str!(
+        r#"
+parsed = hightorrent.TorrentFile(torrent)
+assert parsed.name == "bittorrent-v1-v2-hybrid-test"
+assert parsed.hash.is_hybrid
+assert parsed.hash.v1 == "631a31dd0a46257d5078c0dee4e66e26f73e42ac"
+assert len(parsed.files) > 0
+assert parsed.validate() == []
+assert parsed.to_bytes() == torrent
+"#
+    ));
+}
+
+#[test]
+fn raises_exceptions() {
+    run(c_str!(
+        r#"
+for parse, error in [
+    (lambda: hightorrent.MagnetLink("magnet:?dn=nohash"), hightorrent.MagnetLinkError),
+    (lambda: hightorrent.TorrentFile(b"not a torrent"), hightorrent.TorrentFileError),
+    (lambda: hightorrent.InfoHash("nothex"), hightorrent.InfoHashError),
+]:
+    try:
+        parse()
+        assert False, "no exception raised"
+    except error as e:
+        assert isinstance(e, hightorrent.HighTorrentError)
+        assert isinstance(e, ValueError)
+"#
+    ));
+}
@@ -0,0 +1,42 @@
+use hightorrent::{InfoHash, MagnetLink, Torrent, TorrentContent, TorrentID, Tracker};
+use schemars::schema_for;
+use serde_json::json;
+
+#[test]
+fn identifiers_are_strings() {
+    assert_eq!(schema_for!(TorrentID).get("type"), Some(&json!("string")));
+    assert_eq!(schema_for!(MagnetLink).get("type"), Some(&json!("string")));
+    assert_eq!(schema_for!(MagnetLink).get("format"), Some(&json!("uri")));
+}
+
+#[test]
+fn infohash_matches_serde() {
+    let schema = schema_for!(InfoHash);
+    let variants = schema.get("oneOf").unwrap().as_array().unwrap();
+    assert_eq!(variants.len(), 3);
+
+    // The schema describes the actual serialized form
+    let hash = InfoHash::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap();
+    let serialized = serde_json::to_value(&hash).unwrap();
+    assert!(variants.iter().any(|v| v["required"] == json!(["V1"])));
+    assert!(serialized.get("V1").is_some());
+}
+
+#[test]
+fn structs_have_properties() {
+    let schema = schema_for!(Torrent);
+    let properties = schema.get("properties").unwrap();
+    for field in ["name", "hash", "id", "progress", "tags"] {
+        assert!(properties.get(field).is_some(), "missing field {field}");
+    }
+
+    let schema = schema_for!(TorrentContent);
+    let properties = schema.get("properties").unwrap();
+    for field in ["path", "size", "attributes", "symlink_target"] {
+        assert!(properties.get(field).is_some(), "missing field {field}");
+    }
+
+    let schema = schema_for!(Tracker);
+    let properties = schema.get("properties").unwrap();
+    assert!(properties.get("url").is_some());
+}
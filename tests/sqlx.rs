@@ -0,0 +1,57 @@
+use hightorrent::{InfoHash, MagnetLink, TorrentFile, TorrentID};
+use sqlx::{Connection, Row, SqliteConnection};
+
+#[tokio::test]
+async fn can_store_in_sqlite() {
+    let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+    sqlx::query(
+        "CREATE TABLE torrents (id TEXT PRIMARY KEY, hash TEXT, magnet TEXT, torrent BLOB)",
+    )
+    .execute(&mut conn)
+    .await
+    .unwrap();
+
+    let slice = std::fs::read("tests/bittorrent-v2-hybrid-test.torrent").unwrap();
+    let torrent = TorrentFile::from_slice(&slice).unwrap();
+    let magnet_source = std::fs::read_to_string("tests/bittorrent-v2-hybrid-test.magnet").unwrap();
+    let magnet = MagnetLink::new(&magnet_source).unwrap();
+    let hash = InfoHash::from(&torrent);
+
+    sqlx::query("INSERT INTO torrents (id, hash, magnet, torrent) VALUES (?, ?, ?, ?)")
+        .bind(torrent.id())
+        .bind(&hash)
+        .bind(&magnet)
+        .bind(&torrent)
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    // Hybrid infohashes are stored with both digests
+    let raw: String = sqlx::query_scalar("SELECT hash FROM torrents")
+        .fetch_one(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(raw, hash.to_full_string());
+
+    let row = sqlx::query("SELECT id, hash, magnet, torrent FROM torrents WHERE id = ?")
+        .bind(torrent.id())
+        .fetch_one(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(row.get::<TorrentID, _>("id"), torrent.id());
+    assert_eq!(row.get::<InfoHash, _>("hash"), hash);
+    assert_eq!(row.get::<MagnetLink, _>("magnet").hash(), &hash);
+    let stored: TorrentFile = row.get("torrent");
+    assert_eq!(stored.as_bytes(), slice.as_slice());
+
+    // Invalid values fail to decode
+    sqlx::query("INSERT INTO torrents (id, hash) VALUES ('invalid', 'invalid')")
+        .execute(&mut conn)
+        .await
+        .unwrap();
+    let row = sqlx::query("SELECT hash FROM torrents WHERE id = 'invalid'")
+        .fetch_one(&mut conn)
+        .await
+        .unwrap();
+    assert!(row.try_get::<InfoHash, _>("hash").is_err());
+}
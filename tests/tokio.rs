@@ -0,0 +1,25 @@
+use hightorrent::{TorrentFile, TorrentFileError};
+
+#[tokio::test]
+async fn can_load_from_path_async() {
+    let torrent = TorrentFile::from_path_async("tests/bittorrent-v2-hybrid-test.torrent")
+        .await
+        .unwrap();
+    assert_eq!(
+        torrent.hash(),
+        TorrentFile::from_path("tests/bittorrent-v2-hybrid-test.torrent")
+            .unwrap()
+            .hash()
+    );
+
+    let err = TorrentFile::from_path_async("tests/missing.torrent")
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        TorrentFileError::Io {
+            kind: std::io::ErrorKind::NotFound,
+            ..
+        }
+    ));
+}
@@ -0,0 +1,39 @@
+// Errors are JavaScript values which only exist on wasm targets, so only successful parsing is
+// tested natively
+use hightorrent::{WasmInfoHash, WasmMagnetLink, WasmTorrentFile};
+
+#[test]
+fn parses_magnet() {
+    let magnet = std::fs::read_to_string("tests/bittorrent-v1-emma-goldman.magnet").unwrap();
+    let magnet = WasmMagnetLink::new(magnet.trim()).unwrap();
+    assert_eq!(
+        magnet.name(),
+        "Emma Goldman - Essential Works of Anarchism (16 books)"
+    );
+    assert_eq!(
+        magnet.hash().to_js_string(),
+        "c811b41641a09d192b8ed81b14064fff55d85ce3"
+    );
+    assert_eq!(magnet.id(), "c811b41641a09d192b8ed81b14064fff55d85ce3");
+    assert_eq!(magnet.trackers().len(), 9);
+}
+
+#[test]
+fn parses_infohash() {
+    let hash = WasmInfoHash::new("c811b41641a09d192b8ed81b14064fff55d85ce3").unwrap();
+    assert_eq!(
+        hash.v1().as_deref(),
+        Some("c811b41641a09d192b8ed81b14064fff55d85ce3")
+    );
+    assert_eq!(hash.v2(), None);
+    assert!(!hash.is_hybrid());
+}
+
+#[test]
+fn parses_torrent_file() {
+    let slice = std::fs::read("tests/bittorrent-v2-hybrid-test.torrent").unwrap();
+    let torrent = WasmTorrentFile::new(&slice).unwrap();
+    assert_eq!(torrent.name(), "bittorrent-v1-v2-hybrid-test");
+    assert!(torrent.hash().is_hybrid());
+    assert!(torrent.size() > 0.0);
+}